@@ -0,0 +1,4 @@
+-- Namespace for Daytona sandbox names created by this instance
+-- Allows several instances to share one Daytona account without colliding
+
+ALTER TABLE swarm_config ADD COLUMN sandbox_name_prefix TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,3 @@
+-- Optional sandbox a task must run in (e.g. one holding build state)
+
+ALTER TABLE swarm_tasks ADD COLUMN pinned_sandbox_id TEXT REFERENCES sandboxes(id) ON DELETE SET NULL;
@@ -0,0 +1,5 @@
+-- Claude CLI options used by the task executor
+-- claude_extra_args is a JSON array of allowlisted flags
+
+ALTER TABLE swarm_config ADD COLUMN claude_model TEXT;
+ALTER TABLE swarm_config ADD COLUMN claude_extra_args TEXT NOT NULL DEFAULT '[]';
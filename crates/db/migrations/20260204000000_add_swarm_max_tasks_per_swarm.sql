@@ -0,0 +1,3 @@
+-- Upper bound on the number of tasks a single swarm may hold
+
+ALTER TABLE swarm_config ADD COLUMN max_tasks_per_swarm INTEGER NOT NULL DEFAULT 10000;
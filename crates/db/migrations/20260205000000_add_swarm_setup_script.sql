@@ -0,0 +1,4 @@
+-- Shell script run in the sandbox workspace before each task of the swarm
+-- NULL or empty means no setup step
+
+ALTER TABLE swarms ADD COLUMN setup_script TEXT;
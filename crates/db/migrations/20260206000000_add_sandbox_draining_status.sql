@@ -0,0 +1,24 @@
+-- Allow sandboxes to be drained: they finish their current task, take no
+-- new ones, and are destroyed once released.
+
+-- 1. Add the replacement column with the wider CHECK
+ALTER TABLE sandboxes
+  ADD COLUMN status_new TEXT NOT NULL DEFAULT 'idle'
+    CHECK (status_new IN ('idle', 'busy', 'draining', 'destroyed'));
+
+-- 2. Copy existing values across
+UPDATE sandboxes
+  SET status_new = status;
+
+-- 3. Drop the index that mentions the old column
+DROP INDEX IF EXISTS idx_sandboxes_status;
+
+-- 4. Remove the old column
+ALTER TABLE sandboxes DROP COLUMN status;
+
+-- 5. Rename the new column back to the canonical name
+ALTER TABLE sandboxes
+  RENAME COLUMN status_new TO status;
+
+-- 6. Re-create the index
+CREATE INDEX idx_sandboxes_status ON sandboxes(status);
@@ -0,0 +1,10 @@
+-- Optional decomposition of large tasks into planned subtasks
+
+ALTER TABLE swarm_tasks ADD COLUMN decompose BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE swarm_tasks ADD COLUMN parent_task_id TEXT REFERENCES swarm_tasks(id) ON DELETE SET NULL;
+ALTER TABLE swarm_tasks ADD COLUMN is_coordinator BOOLEAN NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_swarm_tasks_parent_task_id ON swarm_tasks(parent_task_id);
+
+ALTER TABLE swarm_config ADD COLUMN decompose_enabled BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE swarm_config ADD COLUMN decompose_max_subtasks INTEGER NOT NULL DEFAULT 10;
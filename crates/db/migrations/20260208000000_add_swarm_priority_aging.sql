@@ -0,0 +1,4 @@
+-- Minutes a pending task waits before its effective priority goes up one
+-- level; 0 keeps strict priority ordering
+
+ALTER TABLE swarm_config ADD COLUMN priority_aging_minutes INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,12 @@
+-- Audit log of commands executed in sandboxes (secrets masked before insert)
+
+CREATE TABLE sandbox_commands (
+    id TEXT PRIMARY KEY,
+    daytona_id TEXT NOT NULL,
+    command TEXT NOT NULL,
+    exit_code INTEGER,       -- NULL when the request itself failed
+    duration_ms INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_sandbox_commands_daytona_id ON sandbox_commands(daytona_id, created_at);
@@ -0,0 +1,4 @@
+-- Maximum task dispatches per minute for a swarm (sliding window)
+-- 0 means unlimited
+
+ALTER TABLE swarms ADD COLUMN dispatch_rate_per_minute INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- Skills a swarm may discover and use (JSON array of skill names)
+-- An empty array allows every skill
+
+ALTER TABLE swarms ADD COLUMN allowed_skills TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,11 @@
+-- Human notes on swarm tasks, independent of chat and execution results
+
+CREATE TABLE swarm_task_comments (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL REFERENCES swarm_tasks(id) ON DELETE CASCADE,
+    author TEXT NOT NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_swarm_task_comments_task_id ON swarm_task_comments(task_id, created_at);
@@ -0,0 +1,5 @@
+-- Snapshots tried in order when the default snapshot is unavailable (JSON array)
+ALTER TABLE swarm_config ADD COLUMN pool_fallback_snapshots TEXT NOT NULL DEFAULT '[]';
+
+-- Snapshot a sandbox was actually created from
+ALTER TABLE sandboxes ADD COLUMN snapshot TEXT;
@@ -0,0 +1,2 @@
+-- Optional effort estimate for planning (minutes)
+ALTER TABLE swarm_tasks ADD COLUMN estimate_minutes INTEGER;
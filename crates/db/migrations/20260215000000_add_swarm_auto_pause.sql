@@ -0,0 +1,5 @@
+-- Minutes without tasks or chat after which an idle swarm is paused; 0 disables
+ALTER TABLE swarm_config ADD COLUMN auto_pause_after_minutes INTEGER NOT NULL DEFAULT 0;
+
+-- Set while a swarm is paused by the inactivity check rather than by a user
+ALTER TABLE swarms ADD COLUMN auto_paused_at TIMESTAMP;
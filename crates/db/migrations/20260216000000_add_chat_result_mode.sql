@@ -0,0 +1,2 @@
+-- What is posted to chat when a task completes: 'full', 'summary' or 'none'
+ALTER TABLE swarm_config ADD COLUMN chat_result_mode TEXT NOT NULL DEFAULT 'summary';
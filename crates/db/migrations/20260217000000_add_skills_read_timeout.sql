@@ -0,0 +1,2 @@
+-- Seconds a skill directory scan or read may take before the request fails
+ALTER TABLE swarm_config ADD COLUMN skills_read_timeout_secs INTEGER NOT NULL DEFAULT 5;
@@ -0,0 +1,2 @@
+-- Run every task of the swarm in a newly created sandbox that is destroyed afterwards
+ALTER TABLE swarms ADD COLUMN fresh_sandbox_per_task BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,16 @@
+-- Periodic samples of pool utilization, for charts over time
+
+CREATE TABLE pool_status_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    total INTEGER NOT NULL,      -- live (non-destroyed) sandboxes
+    idle INTEGER NOT NULL,
+    busy INTEGER NOT NULL,
+    draining INTEGER NOT NULL,
+    recorded_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_pool_status_history_recorded_at ON pool_status_history(recorded_at);
+
+-- Seconds between samples (0 disables sampling) and how long samples are kept
+ALTER TABLE swarm_config ADD COLUMN pool_history_interval_secs INTEGER NOT NULL DEFAULT 60;
+ALTER TABLE swarm_config ADD COLUMN pool_history_retention_hours INTEGER NOT NULL DEFAULT 168;
@@ -0,0 +1,30 @@
+-- Optional human approval before tasks are dispatched: tasks of swarms with
+-- require_approval start blocked until someone approves them.
+
+ALTER TABLE swarms ADD COLUMN require_approval BOOLEAN NOT NULL DEFAULT 0;
+
+-- 1. Add the replacement column with the wider CHECK
+ALTER TABLE swarm_tasks
+  ADD COLUMN status_new TEXT NOT NULL DEFAULT 'pending'
+    CHECK (status_new IN ('blocked', 'pending', 'running', 'completed', 'failed', 'cancelled'));
+
+-- 2. Copy existing values across
+UPDATE swarm_tasks
+  SET status_new = status;
+
+-- 3. Drop the index that mentions the old column
+DROP INDEX IF EXISTS idx_swarm_tasks_status;
+
+-- 4. Remove the old column
+ALTER TABLE swarm_tasks DROP COLUMN status;
+
+-- 5. Rename the new column back to the canonical name
+ALTER TABLE swarm_tasks
+  RENAME COLUMN status_new TO status;
+
+-- 6. Re-create the index
+CREATE INDEX idx_swarm_tasks_status ON swarm_tasks(status);
+
+-- Who approved a task and when
+ALTER TABLE swarm_tasks ADD COLUMN approved_by TEXT;
+ALTER TABLE swarm_tasks ADD COLUMN approved_at TIMESTAMP;
@@ -0,0 +1,3 @@
+-- Close WebSocket clients that lag this many times within the window; 0 never closes
+ALTER TABLE swarm_config ADD COLUMN ws_lag_limit INTEGER NOT NULL DEFAULT 3;
+ALTER TABLE swarm_config ADD COLUMN ws_lag_window_secs INTEGER NOT NULL DEFAULT 30;
@@ -0,0 +1,2 @@
+-- Check that a task's CLI: tools exist in the sandbox before running it
+ALTER TABLE swarm_config ADD COLUMN cli_probe_enabled INTEGER NOT NULL DEFAULT 1;
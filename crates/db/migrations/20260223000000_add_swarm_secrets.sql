@@ -0,0 +1,14 @@
+-- Named secrets injected into a task's environment when the task lists them
+
+CREATE TABLE swarm_secrets (
+    id TEXT PRIMARY KEY,
+    swarm_id TEXT NOT NULL REFERENCES swarms(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (swarm_id, name)
+);
+
+-- JSON array of secret names
+ALTER TABLE swarm_tasks ADD COLUMN secrets TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,2 @@
+-- Sandbox path the executor writes a successful task's output to
+ALTER TABLE swarm_tasks ADD COLUMN write_result_to TEXT;
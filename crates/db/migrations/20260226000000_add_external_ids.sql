@@ -0,0 +1,6 @@
+-- Stable ids from imported definitions, so re-importing updates in place
+ALTER TABLE swarms ADD COLUMN external_id TEXT;
+CREATE UNIQUE INDEX idx_swarms_external_id ON swarms(external_id) WHERE external_id IS NOT NULL;
+
+ALTER TABLE swarm_tasks ADD COLUMN external_id TEXT;
+CREATE UNIQUE INDEX idx_swarm_tasks_external_id ON swarm_tasks(swarm_id, external_id) WHERE external_id IS NOT NULL;
@@ -0,0 +1,15 @@
+-- Stored task logs, one row per attempt; logs over the threshold are compressed
+CREATE TABLE swarm_task_logs (
+    id TEXT PRIMARY KEY,
+    task_id TEXT NOT NULL REFERENCES swarm_tasks(id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    encoding TEXT NOT NULL DEFAULT 'none',
+    content BLOB NOT NULL,
+    byte_size INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_swarm_task_logs_task_id ON swarm_task_logs(task_id, attempt);
+
+ALTER TABLE swarm_config ADD COLUMN log_compression TEXT NOT NULL DEFAULT 'zstd';
+ALTER TABLE swarm_config ADD COLUMN log_compression_threshold_bytes INTEGER NOT NULL DEFAULT 65536;
@@ -0,0 +1,2 @@
+-- Execution attempts used by the task's last run, including retries
+ALTER TABLE swarm_tasks ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- Role inferred from the tags of the last task a sandbox ran
+ALTER TABLE sandboxes ADD COLUMN last_role TEXT;
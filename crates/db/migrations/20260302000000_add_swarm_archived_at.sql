@@ -0,0 +1,2 @@
+-- Archived swarms are hidden and never dispatched, but keep their data
+ALTER TABLE swarms ADD COLUMN archived_at TIMESTAMP;
@@ -0,0 +1,2 @@
+-- Per-swarm cap on running tasks; 0 uses the trigger engine's default
+ALTER TABLE swarms ADD COLUMN max_concurrent INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- Variables substituted into task prompts through {{name}} placeholders
+-- prompt_vars is a JSON object of name -> value
+
+ALTER TABLE swarm_config ADD COLUMN prompt_vars TEXT NOT NULL DEFAULT '{}';
@@ -0,0 +1,4 @@
+-- Idle sandboxes kept warm so new tasks skip sandbox creation
+-- 0 disables the warm pool
+
+ALTER TABLE swarm_config ADD COLUMN pool_min_idle INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,9 @@
+-- Chat messages delivered to sandboxes by @mention (@all, an agent role or a sandbox id)
+
+CREATE TABLE swarm_chat_inbox (
+    message_id TEXT NOT NULL REFERENCES swarm_chat(id) ON DELETE CASCADE,
+    sandbox_id TEXT NOT NULL REFERENCES sandboxes(id) ON DELETE CASCADE,
+    PRIMARY KEY (message_id, sandbox_id)
+);
+
+CREATE INDEX idx_swarm_chat_inbox_sandbox_id ON swarm_chat_inbox(sandbox_id);
@@ -0,0 +1,19 @@
+-- Row versions for optimistic concurrency on swarm and task edits
+-- updated_at has second precision, so it can't tell apart two writes in the same second
+ALTER TABLE swarms ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE swarm_tasks ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+
+-- Bump the version on every write that didn't set it itself
+CREATE TRIGGER IF NOT EXISTS trg_swarms_version
+AFTER UPDATE ON swarms
+FOR EACH ROW WHEN NEW.version = OLD.version
+BEGIN
+    UPDATE swarms SET version = OLD.version + 1 WHERE id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_swarm_tasks_version
+AFTER UPDATE ON swarm_tasks
+FOR EACH ROW WHEN NEW.version = OLD.version
+BEGIN
+    UPDATE swarm_tasks SET version = OLD.version + 1 WHERE id = OLD.id;
+END;
@@ -0,0 +1,11 @@
+-- Deleting a sandbox would quietly unpin the tasks waiting on it (ON DELETE SET NULL)
+-- and let them run anywhere; fail them instead, as the engine does for a destroyed sandbox
+CREATE TRIGGER IF NOT EXISTS trg_sandboxes_fail_pinned_tasks
+BEFORE DELETE ON sandboxes
+FOR EACH ROW
+BEGIN
+    UPDATE swarm_tasks
+    SET status = 'failed', error = 'Pinned sandbox was destroyed',
+        completed_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+    WHERE pinned_sandbox_id = OLD.id AND status IN ('pending', 'blocked');
+END;
@@ -0,0 +1,4 @@
+-- Endpoint the trigger engine POSTs task.completed and task.failed events to
+-- NULL sends no events
+
+ALTER TABLE swarm_config ADD COLUMN webhook_url TEXT;
@@ -0,0 +1,4 @@
+-- Collapse runs of identical consecutive lines in live task log streams
+-- Off by default; stored logs are never compacted
+
+ALTER TABLE swarm_config ADD COLUMN log_dedup_enabled INTEGER NOT NULL DEFAULT 0;
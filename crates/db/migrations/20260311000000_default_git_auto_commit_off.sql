@@ -0,0 +1,4 @@
+-- Git auto-commit is opt-in. The column was created defaulting to 1 before
+-- anything read it, so existing installs would start committing (and pushing,
+-- if enabled) after every task; turn it off for them
+UPDATE swarm_config SET git_auto_commit = 0;
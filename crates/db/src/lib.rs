@@ -1,9 +1,12 @@
-use std::{str::FromStr, sync::Arc};
+use std::{path::Path, str::FromStr, sync::Arc, time::Duration};
 
 use sqlx::{
-    Error, Pool, Sqlite, SqlitePool,
+    Error, Pool, Sqlite,
     migrate::MigrateError,
-    sqlite::{SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqlitePoolOptions},
+    sqlite::{
+        SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqlitePoolOptions,
+        SqliteSynchronous,
+    },
 };
 use utils::assets::asset_dir;
 
@@ -67,6 +70,136 @@ async fn run_migrations(pool: &Pool<Sqlite>) -> Result<(), Error> {
     }
 }
 
+/// Per-connection SQLite settings, overridable via environment variables
+///
+/// - `VK_SQLITE_JOURNAL_MODE`: `wal` (default) or `delete`
+/// - `VK_SQLITE_SYNCHRONOUS`: `off`, `normal` (default) or `full`
+/// - `VK_SQLITE_BUSY_TIMEOUT_MS`: how long a writer waits for a lock (default 5000)
+/// - `VK_SQLITE_CACHE_SIZE`: SQLite `cache_size`; negative values are KiB (default -20000)
+///
+/// Foreign keys are always enforced; the schema relies on `ON DELETE CASCADE`.
+#[derive(Debug, Clone)]
+pub struct SqlitePragmas {
+    pub journal_mode: SqliteJournalMode,
+    pub synchronous: SqliteSynchronous,
+    pub busy_timeout: Duration,
+    pub cache_size: i64,
+}
+
+impl Default for SqlitePragmas {
+    fn default() -> Self {
+        Self {
+            journal_mode: SqliteJournalMode::Wal,
+            synchronous: SqliteSynchronous::Normal,
+            busy_timeout: Duration::from_millis(5000),
+            cache_size: -20_000,
+        }
+    }
+}
+
+impl SqlitePragmas {
+    pub fn from_env() -> Self {
+        let mut pragmas = Self::default();
+
+        if let Ok(value) = std::env::var("VK_SQLITE_JOURNAL_MODE") {
+            match SqliteJournalMode::from_str(&value) {
+                Ok(mode) => pragmas.journal_mode = mode,
+                Err(_) => tracing::warn!("Ignoring invalid VK_SQLITE_JOURNAL_MODE: {}", value),
+            }
+        }
+        if let Ok(value) = std::env::var("VK_SQLITE_SYNCHRONOUS") {
+            match SqliteSynchronous::from_str(&value) {
+                Ok(level) => pragmas.synchronous = level,
+                Err(_) => tracing::warn!("Ignoring invalid VK_SQLITE_SYNCHRONOUS: {}", value),
+            }
+        }
+        if let Ok(value) = std::env::var("VK_SQLITE_BUSY_TIMEOUT_MS") {
+            match value.parse::<u64>() {
+                Ok(ms) => pragmas.busy_timeout = Duration::from_millis(ms),
+                Err(_) => tracing::warn!("Ignoring invalid VK_SQLITE_BUSY_TIMEOUT_MS: {}", value),
+            }
+        }
+        if let Ok(value) = std::env::var("VK_SQLITE_CACHE_SIZE") {
+            match value.parse::<i64>() {
+                Ok(size) => pragmas.cache_size = size,
+                Err(_) => tracing::warn!("Ignoring invalid VK_SQLITE_CACHE_SIZE: {}", value),
+            }
+        }
+
+        pragmas
+    }
+
+    pub fn apply(&self, options: SqliteConnectOptions) -> SqliteConnectOptions {
+        options
+            .journal_mode(self.journal_mode)
+            .synchronous(self.synchronous)
+            .busy_timeout(self.busy_timeout)
+            .foreign_keys(true)
+            .pragma("cache_size", self.cache_size.to_string())
+    }
+}
+
+/// Connection pool sizing, overridable via environment variables
+///
+/// - `VK_SQLITE_MAX_CONNECTIONS`: pooled connections (default 10). SQLite has a
+///   single writer even in WAL mode, so more connections only add readers;
+///   raising this rarely helps write-heavy load.
+/// - `VK_SQLITE_ACQUIRE_TIMEOUT_MS`: how long a query waits for a free
+///   connection before failing (default 10000). Requests that time out here
+///   get a 503 instead of hanging.
+#[derive(Debug, Clone)]
+pub struct SqlitePoolSettings {
+    pub max_connections: u32,
+    pub acquire_timeout: Duration,
+}
+
+impl Default for SqlitePoolSettings {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            acquire_timeout: Duration::from_millis(10_000),
+        }
+    }
+}
+
+impl SqlitePoolSettings {
+    pub fn from_env() -> Self {
+        let mut settings = Self::default();
+
+        if let Ok(value) = std::env::var("VK_SQLITE_MAX_CONNECTIONS") {
+            match value.parse::<u32>() {
+                Ok(max) if max > 0 => settings.max_connections = max,
+                _ => tracing::warn!("Ignoring invalid VK_SQLITE_MAX_CONNECTIONS: {}", value),
+            }
+        }
+        if let Ok(value) = std::env::var("VK_SQLITE_ACQUIRE_TIMEOUT_MS") {
+            match value.parse::<u64>() {
+                Ok(ms) if ms > 0 => settings.acquire_timeout = Duration::from_millis(ms),
+                _ => tracing::warn!("Ignoring invalid VK_SQLITE_ACQUIRE_TIMEOUT_MS: {}", value),
+            }
+        }
+
+        settings
+    }
+
+    pub fn apply(&self, options: SqlitePoolOptions) -> SqlitePoolOptions {
+        options
+            .max_connections(self.max_connections)
+            .acquire_timeout(self.acquire_timeout)
+    }
+}
+
+fn pool_options() -> SqlitePoolOptions {
+    SqlitePoolSettings::from_env().apply(SqlitePoolOptions::new())
+}
+
+fn connect_options(path: &Path) -> Result<SqliteConnectOptions, Error> {
+    let database_url = format!("sqlite://{}", path.to_string_lossy());
+    let options = SqliteConnectOptions::from_str(&database_url)?.create_if_missing(true);
+
+    Ok(SqlitePragmas::from_env().apply(options))
+}
+
 #[derive(Clone)]
 pub struct DBService {
     pub pool: Pool<Sqlite>,
@@ -74,14 +207,12 @@ pub struct DBService {
 
 impl DBService {
     pub async fn new() -> Result<DBService, Error> {
-        let database_url = format!(
-            "sqlite://{}",
-            asset_dir().join("db.sqlite").to_string_lossy()
-        );
-        let options = SqliteConnectOptions::from_str(&database_url)?
-            .create_if_missing(true)
-            .journal_mode(SqliteJournalMode::Delete);
-        let pool = SqlitePool::connect_with(options).await?;
+        Self::open(&asset_dir().join("db.sqlite")).await
+    }
+
+    /// Open the database at `path`, creating it if missing, and migrate it
+    pub async fn open(path: &Path) -> Result<DBService, Error> {
+        let pool = pool_options().connect_with(connect_options(path)?).await?;
         run_migrations(&pool).await?;
         Ok(DBService { pool })
     }
@@ -110,16 +241,10 @@ impl DBService {
             + Sync
             + 'static,
     {
-        let database_url = format!(
-            "sqlite://{}",
-            asset_dir().join("db.sqlite").to_string_lossy()
-        );
-        let options = SqliteConnectOptions::from_str(&database_url)?
-            .create_if_missing(true)
-            .journal_mode(SqliteJournalMode::Delete);
+        let options = connect_options(&asset_dir().join("db.sqlite"))?;
 
         let pool = if let Some(hook) = after_connect {
-            SqlitePoolOptions::new()
+            pool_options()
                 .after_connect(move |conn, _meta| {
                     let hook = hook.clone();
                     Box::pin(async move {
@@ -130,7 +255,7 @@ impl DBService {
                 .connect_with(options)
                 .await?
         } else {
-            SqlitePool::connect_with(options).await?
+            pool_options().connect_with(options).await?
         };
 
         run_migrations(&pool).await?;
@@ -4,17 +4,43 @@ pub mod execution_process_logs;
 pub mod execution_process_repo_state;
 pub mod image;
 pub mod merge;
+pub mod pool_status_history;
 pub mod project;
 pub mod project_repo;
 pub mod repo;
 pub mod sandbox;
+pub mod sandbox_command;
 pub mod scratch;
 pub mod session;
 pub mod swarm;
 pub mod swarm_chat;
 pub mod swarm_config;
+pub mod swarm_secret;
 pub mod swarm_task;
+pub mod swarm_task_comment;
+pub mod swarm_task_log;
 pub mod tag;
 pub mod task;
+pub mod webhook_dead_letter;
 pub mod workspace;
 pub mod workspace_repo;
+
+use thiserror::Error;
+
+/// Failure of an update that may carry an `expected_version` precondition
+#[derive(Debug, Error)]
+pub enum UpdateError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    /// The row changed after the caller read it; their edit would overwrite that change
+    #[error("Modified since it was read; reload and try again")]
+    Conflict,
+}
+
+/// `LIKE` pattern matching `text` anywhere, with wildcards in `text` escaped
+///
+/// Use with `ESCAPE '\\'`.
+pub(crate) fn contains_pattern(text: &str) -> String {
+    let escaped = text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
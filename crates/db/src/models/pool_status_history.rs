@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use ts_rs::TS;
+
+/// Most samples returned by a single history query
+const MAX_HISTORY_SAMPLES: i64 = 10_000;
+
+/// A point-in-time sample of pool utilization
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct PoolStatusSample {
+    pub id: i64,
+    /// Live (non-destroyed) sandboxes
+    pub total: i64,
+    pub idle: i64,
+    pub busy: i64,
+    pub draining: i64,
+    #[ts(type = "Date")]
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreatePoolStatusSample {
+    pub total: i64,
+    pub idle: i64,
+    pub busy: i64,
+    pub draining: i64,
+}
+
+impl PoolStatusSample {
+    fn from_row(row: sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            total: row.try_get("total")?,
+            idle: row.try_get("idle")?,
+            busy: row.try_get("busy")?,
+            draining: row.try_get("draining")?,
+            recorded_at: row.try_get("recorded_at")?,
+        })
+    }
+
+    pub async fn create(pool: &SqlitePool, data: &CreatePoolStatusSample) -> Result<Self, sqlx::Error> {
+        let row = sqlx::query(
+            "INSERT INTO pool_status_history (total, idle, busy, draining)
+             VALUES ($1, $2, $3, $4)
+             RETURNING id, total, idle, busy, draining, recorded_at"
+        )
+        .bind(data.total)
+        .bind(data.idle)
+        .bind(data.busy)
+        .bind(data.draining)
+        .fetch_one(pool)
+        .await?;
+
+        Self::from_row(row)
+    }
+
+    /// Samples recorded within `[from, to]`, oldest first
+    ///
+    /// Either bound may be omitted. At most the first 10,000 samples of the
+    /// range are returned.
+    pub async fn find_range(
+        pool: &SqlitePool,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, total, idle, busy, draining, recorded_at
+             FROM pool_status_history
+             WHERE ($1 IS NULL OR julianday(recorded_at) >= julianday($1))
+               AND ($2 IS NULL OR julianday(recorded_at) <= julianday($2))
+             ORDER BY recorded_at ASC, id ASC
+             LIMIT $3"
+        )
+        .bind(from)
+        .bind(to)
+        .bind(MAX_HISTORY_SAMPLES)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    /// Delete samples recorded before `cutoff`
+    pub async fn delete_older_than(pool: &SqlitePool, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM pool_status_history WHERE julianday(recorded_at) < julianday($1)")
+            .bind(cutoff)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Row, SqlitePool, Type};
+use sqlx::{Executor, FromRow, Row, Sqlite, SqlitePool, Type};
 use strum_macros::{Display, EnumString};
 use ts_rs::TS;
 use uuid::Uuid;
@@ -13,6 +13,8 @@ pub enum SandboxStatus {
     #[default]
     Idle,
     Busy,
+    /// Finishing its current task; takes no new work and is destroyed once released
+    Draining,
     Destroyed,
 }
 
@@ -23,16 +25,22 @@ pub struct Sandbox {
     pub swarm_id: Option<Uuid>,
     pub status: SandboxStatus,
     pub current_task_id: Option<Uuid>,
+    /// Snapshot the sandbox was created from, if known
+    pub snapshot: Option<String>,
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
     #[ts(type = "Date | null")]
     pub last_used_at: Option<DateTime<Utc>>,
+    /// Agent role of the last task dispatched to the sandbox
+    pub last_role: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, TS)]
 pub struct CreateSandbox {
     pub daytona_id: String,
     pub swarm_id: Option<Uuid>,
+    #[serde(default)]
+    pub snapshot: Option<String>,
 }
 
 impl Sandbox {
@@ -46,14 +54,16 @@ impl Sandbox {
             swarm_id: row.try_get("swarm_id")?,
             status,
             current_task_id: row.try_get("current_task_id")?,
+            snapshot: row.try_get("snapshot")?,
             created_at: row.try_get("created_at")?,
             last_used_at: row.try_get("last_used_at")?,
+            last_role: row.try_get("last_role")?,
         })
     }
 
     pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         let rows = sqlx::query(
-            "SELECT id, daytona_id, swarm_id, status, current_task_id, created_at, last_used_at
+            "SELECT id, daytona_id, swarm_id, status, current_task_id, snapshot, created_at, last_used_at, last_role
              FROM sandboxes
              ORDER BY created_at DESC"
         )
@@ -65,7 +75,7 @@ impl Sandbox {
 
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         let row = sqlx::query(
-            "SELECT id, daytona_id, swarm_id, status, current_task_id, created_at, last_used_at
+            "SELECT id, daytona_id, swarm_id, status, current_task_id, snapshot, created_at, last_used_at, last_role
              FROM sandboxes
              WHERE id = $1"
         )
@@ -78,7 +88,7 @@ impl Sandbox {
 
     pub async fn find_by_daytona_id(pool: &SqlitePool, daytona_id: &str) -> Result<Option<Self>, sqlx::Error> {
         let row = sqlx::query(
-            "SELECT id, daytona_id, swarm_id, status, current_task_id, created_at, last_used_at
+            "SELECT id, daytona_id, swarm_id, status, current_task_id, snapshot, created_at, last_used_at, last_role
              FROM sandboxes
              WHERE daytona_id = $1"
         )
@@ -91,7 +101,7 @@ impl Sandbox {
 
     pub async fn find_idle(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         let rows = sqlx::query(
-            "SELECT id, daytona_id, swarm_id, status, current_task_id, created_at, last_used_at
+            "SELECT id, daytona_id, swarm_id, status, current_task_id, snapshot, created_at, last_used_at, last_role
              FROM sandboxes
              WHERE status = 'idle'
              ORDER BY last_used_at ASC"
@@ -102,9 +112,29 @@ impl Sandbox {
         rows.into_iter().map(Self::from_row).collect()
     }
 
+    /// Idle sandboxes no waiting task is pinned to, least recently used first
+    ///
+    /// These are the ones free for any task; a pinned sandbox is kept for its task.
+    pub async fn find_idle_unpinned(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, daytona_id, swarm_id, status, current_task_id, snapshot, created_at, last_used_at, last_role
+             FROM sandboxes
+             WHERE status = 'idle'
+               AND id NOT IN (
+                   SELECT pinned_sandbox_id FROM swarm_tasks
+                   WHERE pinned_sandbox_id IS NOT NULL AND status IN ('pending', 'blocked')
+               )
+             ORDER BY last_used_at ASC"
+        )
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
     pub async fn find_busy(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         let rows = sqlx::query(
-            "SELECT id, daytona_id, swarm_id, status, current_task_id, created_at, last_used_at
+            "SELECT id, daytona_id, swarm_id, status, current_task_id, snapshot, created_at, last_used_at, last_role
              FROM sandboxes
              WHERE status = 'busy'
              ORDER BY created_at DESC"
@@ -115,6 +145,35 @@ impl Sandbox {
         rows.into_iter().map(Self::from_row).collect()
     }
 
+    /// Sandboxes of a swarm that have not been destroyed
+    pub async fn find_active_by_swarm_id(pool: &SqlitePool, swarm_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, daytona_id, swarm_id, status, current_task_id, snapshot, created_at, last_used_at, last_role
+             FROM sandboxes
+             WHERE swarm_id = $1 AND status != 'destroyed'
+             ORDER BY created_at ASC"
+        )
+        .bind(swarm_id)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    /// Draining sandboxes that no longer hold a task and are ready to be destroyed
+    pub async fn find_drained(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, daytona_id, swarm_id, status, current_task_id, snapshot, created_at, last_used_at, last_role
+             FROM sandboxes
+             WHERE status = 'draining' AND current_task_id IS NULL
+             ORDER BY created_at DESC"
+        )
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
     pub async fn count_active(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
         let row = sqlx::query("SELECT COUNT(*) as count FROM sandboxes WHERE status != 'destroyed'")
             .fetch_one(pool)
@@ -125,13 +184,14 @@ impl Sandbox {
 
     pub async fn create(pool: &SqlitePool, data: &CreateSandbox, sandbox_id: Uuid) -> Result<Self, sqlx::Error> {
         let row = sqlx::query(
-            "INSERT INTO sandboxes (id, daytona_id, swarm_id)
-             VALUES ($1, $2, $3)
-             RETURNING id, daytona_id, swarm_id, status, current_task_id, created_at, last_used_at"
+            "INSERT INTO sandboxes (id, daytona_id, swarm_id, snapshot)
+             VALUES ($1, $2, $3, $4)
+             RETURNING id, daytona_id, swarm_id, status, current_task_id, snapshot, created_at, last_used_at, last_role"
         )
         .bind(sandbox_id)
         .bind(&data.daytona_id)
         .bind(data.swarm_id)
+        .bind(&data.snapshot)
         .fetch_one(pool)
         .await?;
 
@@ -157,18 +217,110 @@ impl Sandbox {
         Ok(())
     }
 
+    /// Record the agent role of the task the sandbox is running
+    pub async fn set_last_role(pool: &SqlitePool, id: Uuid, role: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE sandboxes SET last_role = $2 WHERE id = $1")
+            .bind(id)
+            .bind(role)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Clear the sandbox's task; a draining sandbox stays draining so it can be destroyed
+    ///
+    /// A destroyed sandbox is never brought back to idle.
     pub async fn release_task(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
-        sqlx::query("UPDATE sandboxes SET current_task_id = NULL, status = 'idle', last_used_at = CURRENT_TIMESTAMP WHERE id = $1")
+        sqlx::query(
+            "UPDATE sandboxes
+             SET current_task_id = NULL,
+                 status = CASE WHEN status = 'draining' THEN 'draining' ELSE 'idle' END,
+                 last_used_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND status != 'destroyed'"
+        )
             .bind(id)
             .execute(pool)
             .await?;
         Ok(())
     }
 
-    pub async fn mark_destroyed(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+    /// Release the sandbox only while it is still running the given task
+    ///
+    /// Returns false if the sandbox was already handed back, e.g. because the
+    /// task was reassigned, and may now be running something else.
+    pub async fn release_task_if_current(pool: &SqlitePool, id: Uuid, task_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE sandboxes
+             SET current_task_id = NULL,
+                 status = CASE WHEN status = 'draining' THEN 'draining' ELSE 'idle' END,
+                 last_used_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND current_task_id = $2 AND status != 'destroyed'"
+        )
+        .bind(id)
+        .bind(task_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Assign a task only if the sandbox is still idle and unassigned
+    ///
+    /// Returns false if another caller claimed the sandbox first.
+    pub async fn try_assign_task<'e, E>(executor: E, id: Uuid, task_id: Uuid) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query(
+            "UPDATE sandboxes SET current_task_id = $2, status = 'busy', last_used_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND status = 'idle' AND current_task_id IS NULL"
+        )
+        .bind(id)
+        .bind(task_id)
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Release whichever sandbox is currently running the given task
+    ///
+    /// Returns the number of sandboxes released (0 if none held the task).
+    pub async fn release_by_task_id<'e, E>(executor: E, task_id: Uuid) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query(
+            "UPDATE sandboxes
+             SET current_task_id = NULL,
+                 status = CASE WHEN status = 'draining' THEN 'draining' ELSE 'idle' END,
+                 last_used_at = CURRENT_TIMESTAMP
+             WHERE current_task_id = $1 AND status IN ('busy', 'draining')"
+        )
+        .bind(task_id)
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Stop a live sandbox from taking new tasks
+    ///
+    /// Returns false if the sandbox is already draining or destroyed.
+    pub async fn mark_draining(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE sandboxes SET status = 'draining' WHERE id = $1 AND status IN ('idle', 'busy')"
+        )
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn mark_destroyed<'e, E>(executor: E, id: Uuid) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         sqlx::query("UPDATE sandboxes SET status = 'destroyed', current_task_id = NULL WHERE id = $1")
             .bind(id)
-            .execute(pool)
+            .execute(executor)
             .await?;
         Ok(())
     }
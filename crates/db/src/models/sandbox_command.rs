@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A command executed in a sandbox, recorded for auditing
+///
+/// `command` is stored with secrets already masked.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct SandboxCommand {
+    pub id: Uuid,
+    pub daytona_id: String,
+    pub command: String,
+    /// None when the command never reached the sandbox
+    pub exit_code: Option<i32>,
+    pub duration_ms: i64,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateSandboxCommand {
+    pub daytona_id: String,
+    pub command: String,
+    pub exit_code: Option<i32>,
+    pub duration_ms: i64,
+}
+
+impl SandboxCommand {
+    fn from_row(row: sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            daytona_id: row.try_get("daytona_id")?,
+            command: row.try_get("command")?,
+            exit_code: row.try_get("exit_code")?,
+            duration_ms: row.try_get("duration_ms")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+
+    /// Most recent commands run in a sandbox, newest first
+    pub async fn find_by_daytona_id(
+        pool: &SqlitePool,
+        daytona_id: &str,
+        limit: Option<i32>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let limit = limit.unwrap_or(100).min(500);
+        let rows = sqlx::query(
+            "SELECT id, daytona_id, command, exit_code, duration_ms, created_at
+             FROM sandbox_commands
+             WHERE daytona_id = $1
+             ORDER BY created_at DESC, rowid DESC
+             LIMIT $2"
+        )
+        .bind(daytona_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    pub async fn create(pool: &SqlitePool, data: &CreateSandboxCommand) -> Result<Self, sqlx::Error> {
+        let row = sqlx::query(
+            "INSERT INTO sandbox_commands (id, daytona_id, command, exit_code, duration_ms)
+             VALUES ($1, $2, $3, $4, $5)
+             RETURNING id, daytona_id, command, exit_code, duration_ms, created_at"
+        )
+        .bind(Uuid::new_v4())
+        .bind(&data.daytona_id)
+        .bind(&data.command)
+        .bind(data.exit_code)
+        .bind(data.duration_ms)
+        .fetch_one(pool)
+        .await?;
+
+        Self::from_row(row)
+    }
+}
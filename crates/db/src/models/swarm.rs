@@ -1,10 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Row, SqlitePool, Type};
+use sqlx::{Executor, FromRow, Row, Sqlite, SqlitePool, Type};
 use strum_macros::{Display, EnumString};
 use ts_rs::TS;
 use uuid::Uuid;
 
+use super::UpdateError;
+
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS, EnumString, Display, Default)]
 #[sqlx(type_name = "swarm_status", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
@@ -16,34 +18,135 @@ pub enum SwarmStatus {
     Stopped,
 }
 
-#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Default, FromRow, Serialize, Deserialize, TS)]
 pub struct Swarm {
     pub id: Uuid,
     pub name: String,
     pub description: Option<String>,
     pub status: SwarmStatus,
     pub project_id: Option<Uuid>,
+    /// Shell script run in the workspace before each task; empty means none
+    pub setup_script: Option<String>,
+    /// Maximum task dispatches per minute; 0 means unlimited
+    pub dispatch_rate_per_minute: i32,
+    /// Skills this swarm may discover and use; empty means all
+    pub allowed_skills: Vec<String>,
+    /// Run each task in a newly created sandbox and destroy it afterwards
+    /// instead of reusing pooled sandboxes
+    pub fresh_sandbox_per_task: bool,
+    /// New tasks start blocked and are only dispatched once approved
+    pub require_approval: bool,
+    /// Maximum tasks running at once; 0 uses the trigger engine's default
+    #[serde(default)]
+    pub max_concurrent: i32,
+    /// Set while the swarm is paused for inactivity; a new task resumes it
+    #[ts(type = "Date | null")]
+    pub auto_paused_at: Option<DateTime<Utc>>,
+    /// Id from an imported definition; importing it again updates this swarm
+    pub external_id: Option<String>,
+    /// Set while the swarm is archived: hidden from listings and never dispatched
+    #[ts(type = "Date | null")]
+    pub archived_at: Option<DateTime<Utc>>,
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
     #[ts(type = "Date")]
     pub updated_at: DateTime<Utc>,
+    /// Bumped on every write; pass it back as `expected_version` to detect concurrent edits
+    #[ts(type = "number")]
+    pub version: i64,
 }
 
 #[derive(Debug, Clone, Deserialize, TS)]
+#[serde(deny_unknown_fields)]
 pub struct CreateSwarm {
     pub name: String,
     pub description: Option<String>,
     pub project_id: Option<Uuid>,
+    pub setup_script: Option<String>,
+    pub dispatch_rate_per_minute: Option<i32>,
+    pub allowed_skills: Option<Vec<String>>,
+    pub fresh_sandbox_per_task: Option<bool>,
+    pub require_approval: Option<bool>,
+    pub max_concurrent: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, TS)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateSwarm {
     pub name: Option<String>,
     pub description: Option<String>,
     pub status: Option<SwarmStatus>,
+    pub setup_script: Option<String>,
+    pub dispatch_rate_per_minute: Option<i32>,
+    pub allowed_skills: Option<Vec<String>>,
+    pub fresh_sandbox_per_task: Option<bool>,
+    pub require_approval: Option<bool>,
+    pub max_concurrent: Option<i32>,
+    /// The `version` the client last saw; the update is refused if the swarm changed since
+    #[ts(type = "number | null")]
+    pub expected_version: Option<i64>,
+}
+
+fn skills_json(skills: &[String]) -> String {
+    serde_json::to_string(skills).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Column a swarm list is ordered by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwarmSortKey {
+    #[default]
+    CreatedAt,
+    Name,
+    UpdatedAt,
+}
+
+impl SwarmSortKey {
+    /// Only these fixed expressions are ever interpolated into the query
+    fn column(self) -> &'static str {
+        match self {
+            Self::CreatedAt => "created_at",
+            Self::Name => "name COLLATE NOCASE",
+            Self::UpdatedAt => "updated_at",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    fn keyword(self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+}
+
+/// Filters for `Swarm::find_paginated`; unset fields match every swarm
+#[derive(Debug, Clone, Default)]
+pub struct SwarmFilters {
+    pub status: Option<SwarmStatus>,
+    pub include_archived: bool,
+    pub sort: SwarmSortKey,
+    pub order: SortOrder,
+    /// None returns every match
+    pub limit: Option<i64>,
+    pub offset: i64,
 }
 
 impl Swarm {
+    /// Whether `skill` is usable by this swarm; an empty allowlist allows all
+    pub fn allows_skill(&self, skill: &str) -> bool {
+        self.allowed_skills.is_empty() || self.allowed_skills.iter().any(|s| s == skill)
+    }
+
     fn from_row(row: sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
         let status_str: String = row.try_get("status")?;
         let status = status_str.parse::<SwarmStatus>().unwrap_or_else(|_| {
@@ -54,20 +157,88 @@ impl Swarm {
             SwarmStatus::default()
         });
 
+        let allowed_skills: Vec<String> = row
+            .try_get::<Option<String>, _>("allowed_skills")?
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+
         Ok(Self {
             id: row.try_get("id")?,
             name: row.try_get("name")?,
             description: row.try_get("description")?,
             status,
             project_id: row.try_get("project_id")?,
+            setup_script: row.try_get("setup_script")?,
+            dispatch_rate_per_minute: row.try_get("dispatch_rate_per_minute")?,
+            allowed_skills,
+            fresh_sandbox_per_task: row.try_get("fresh_sandbox_per_task")?,
+            require_approval: row.try_get("require_approval")?,
+            max_concurrent: row.try_get("max_concurrent")?,
+            auto_paused_at: row.try_get("auto_paused_at")?,
+            external_id: row.try_get("external_id")?,
+            archived_at: row.try_get("archived_at")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
+            version: row.try_get("version")?,
         })
     }
 
+    /// All swarms that are not archived
     pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         let rows = sqlx::query(
-            "SELECT id, name, description, status, project_id, created_at, updated_at
+            "SELECT id, name, description, status, project_id, setup_script, dispatch_rate_per_minute, allowed_skills, fresh_sandbox_per_task, require_approval, max_concurrent, auto_paused_at, external_id, archived_at, created_at, updated_at, version
+             FROM swarms
+             WHERE archived_at IS NULL
+             ORDER BY created_at DESC"
+        )
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    /// One page of swarms matching `filters`, with the total number of matches
+    ///
+    /// Rows with equal sort values are kept in insertion order, so pages
+    /// never overlap.
+    pub async fn find_paginated(pool: &SqlitePool, filters: &SwarmFilters) -> Result<(Vec<Self>, i64), sqlx::Error> {
+        const FILTER: &str = "($1 IS NULL OR status = $1) AND ($2 OR archived_at IS NULL)";
+
+        let status = filters.status.as_ref().map(|s| s.to_string());
+
+        let total = sqlx::query(&format!("SELECT COUNT(*) as count FROM swarms WHERE {}", FILTER))
+            .bind(&status)
+            .bind(filters.include_archived)
+            .fetch_one(pool)
+            .await?
+            .try_get::<i64, _>("count")?;
+
+        let order = filters.order.keyword();
+        let rows = sqlx::query(&format!(
+            "SELECT id, name, description, status, project_id, setup_script, dispatch_rate_per_minute, allowed_skills, fresh_sandbox_per_task, require_approval, max_concurrent, auto_paused_at, external_id, archived_at, created_at, updated_at, version
+             FROM swarms
+             WHERE {}
+             ORDER BY {} {}, rowid {}
+             LIMIT $3 OFFSET $4",
+            FILTER,
+            filters.sort.column(),
+            order,
+            order
+        ))
+        .bind(&status)
+        .bind(filters.include_archived)
+        .bind(filters.limit.unwrap_or(-1))
+        .bind(filters.offset)
+        .fetch_all(pool)
+        .await?;
+
+        let swarms = rows.into_iter().map(Self::from_row).collect::<Result<_, _>>()?;
+        Ok((swarms, total))
+    }
+
+    pub async fn find_all_including_archived(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, name, description, status, project_id, setup_script, dispatch_rate_per_minute, allowed_skills, fresh_sandbox_per_task, require_approval, max_concurrent, auto_paused_at, external_id, archived_at, created_at, updated_at, version
              FROM swarms
              ORDER BY created_at DESC"
         )
@@ -79,7 +250,7 @@ impl Swarm {
 
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         let row = sqlx::query(
-            "SELECT id, name, description, status, project_id, created_at, updated_at
+            "SELECT id, name, description, status, project_id, setup_script, dispatch_rate_per_minute, allowed_skills, fresh_sandbox_per_task, require_approval, max_concurrent, auto_paused_at, external_id, archived_at, created_at, updated_at, version
              FROM swarms
              WHERE id = $1"
         )
@@ -90,9 +261,22 @@ impl Swarm {
         row.map(Self::from_row).transpose()
     }
 
+    pub async fn find_by_external_id(pool: &SqlitePool, external_id: &str) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT id, name, description, status, project_id, setup_script, dispatch_rate_per_minute, allowed_skills, fresh_sandbox_per_task, require_approval, max_concurrent, auto_paused_at, external_id, archived_at, created_at, updated_at, version
+             FROM swarms
+             WHERE external_id = $1"
+        )
+        .bind(external_id)
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(Self::from_row).transpose()
+    }
+
     pub async fn find_by_project_id(pool: &SqlitePool, project_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
         let rows = sqlx::query(
-            "SELECT id, name, description, status, project_id, created_at, updated_at
+            "SELECT id, name, description, status, project_id, setup_script, dispatch_rate_per_minute, allowed_skills, fresh_sandbox_per_task, require_approval, max_concurrent, auto_paused_at, external_id, archived_at, created_at, updated_at, version
              FROM swarms
              WHERE project_id = $1
              ORDER BY created_at DESC"
@@ -106,9 +290,9 @@ impl Swarm {
 
     pub async fn find_active(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         let rows = sqlx::query(
-            "SELECT id, name, description, status, project_id, created_at, updated_at
+            "SELECT id, name, description, status, project_id, setup_script, dispatch_rate_per_minute, allowed_skills, fresh_sandbox_per_task, require_approval, max_concurrent, auto_paused_at, external_id, archived_at, created_at, updated_at, version
              FROM swarms
-             WHERE status = 'active'
+             WHERE status = 'active' AND archived_at IS NULL
              ORDER BY created_at DESC"
         )
         .fetch_all(pool)
@@ -117,23 +301,77 @@ impl Swarm {
         rows.into_iter().map(Self::from_row).collect()
     }
 
-    pub async fn create(pool: &SqlitePool, data: &CreateSwarm, swarm_id: Uuid) -> Result<Self, sqlx::Error> {
+    pub async fn create<'e, E>(executor: E, data: &CreateSwarm, swarm_id: Uuid) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         let row = sqlx::query(
-            "INSERT INTO swarms (id, name, description, project_id)
-             VALUES ($1, $2, $3, $4)
-             RETURNING id, name, description, status, project_id, created_at, updated_at"
+            "INSERT INTO swarms (id, name, description, project_id, setup_script, dispatch_rate_per_minute, allowed_skills, fresh_sandbox_per_task, require_approval, max_concurrent)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+             RETURNING id, name, description, status, project_id, setup_script, dispatch_rate_per_minute, allowed_skills, fresh_sandbox_per_task, require_approval, max_concurrent, auto_paused_at, external_id, archived_at, created_at, updated_at, version"
         )
         .bind(swarm_id)
         .bind(&data.name)
         .bind(&data.description)
         .bind(data.project_id)
-        .fetch_one(pool)
+        .bind(&data.setup_script)
+        .bind(data.dispatch_rate_per_minute.unwrap_or(0))
+        .bind(skills_json(data.allowed_skills.as_deref().unwrap_or_default()))
+        .bind(data.fresh_sandbox_per_task.unwrap_or(false))
+        .bind(data.require_approval.unwrap_or(false))
+        .bind(data.max_concurrent.unwrap_or(0))
+        .fetch_one(executor)
         .await?;
 
         Self::from_row(row)
     }
 
-    pub async fn update(pool: &SqlitePool, id: Uuid, data: &UpdateSwarm) -> Result<Self, sqlx::Error> {
+    pub async fn set_external_id<'e, E>(executor: E, id: Uuid, external_id: &str) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query("UPDATE swarms SET external_id = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1")
+            .bind(id)
+            .bind(external_id)
+            .execute(executor)
+            .await?;
+        Ok(())
+    }
+
+    /// Overwrite the swarm's settings with `data`, leaving its status alone
+    ///
+    /// Unlike `update`, fields missing from `data` are reset to their defaults.
+    pub async fn replace_definition<'e, E>(executor: E, id: Uuid, data: &CreateSwarm) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query(
+            "UPDATE swarms
+             SET name = $2, description = $3, project_id = $4, setup_script = $5,
+                 dispatch_rate_per_minute = $6, allowed_skills = $7, fresh_sandbox_per_task = $8,
+                 require_approval = $9, max_concurrent = $10, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1"
+        )
+        .bind(id)
+        .bind(&data.name)
+        .bind(&data.description)
+        .bind(data.project_id)
+        .bind(&data.setup_script)
+        .bind(data.dispatch_rate_per_minute.unwrap_or(0))
+        .bind(skills_json(data.allowed_skills.as_deref().unwrap_or_default()))
+        .bind(data.fresh_sandbox_per_task.unwrap_or(false))
+        .bind(data.require_approval.unwrap_or(false))
+        .bind(data.max_concurrent.unwrap_or(0))
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Apply `data` to a swarm, leaving unset fields unchanged
+    ///
+    /// With `expected_version` the write only happens if the swarm is still at
+    /// that version, and `UpdateError::Conflict` is returned otherwise.
+    pub async fn update(pool: &SqlitePool, id: Uuid, data: &UpdateSwarm) -> Result<Self, UpdateError> {
         let existing = Self::find_by_id(pool, id)
             .await?
             .ok_or(sqlx::Error::RowNotFound)?;
@@ -142,33 +380,147 @@ impl Swarm {
         let description = data.description.clone().or(existing.description);
         let status = data.status.clone().unwrap_or(existing.status);
         let status_str = status.to_string();
+        let setup_script = data.setup_script.clone().or(existing.setup_script);
+        let dispatch_rate_per_minute = data
+            .dispatch_rate_per_minute
+            .unwrap_or(existing.dispatch_rate_per_minute);
+        let allowed_skills = data.allowed_skills.clone().unwrap_or(existing.allowed_skills);
+        let fresh_sandbox_per_task = data
+            .fresh_sandbox_per_task
+            .unwrap_or(existing.fresh_sandbox_per_task);
+        let require_approval = data.require_approval.unwrap_or(existing.require_approval);
+        let max_concurrent = data.max_concurrent.unwrap_or(existing.max_concurrent);
 
+        // An explicit status change supersedes an auto-pause
         let row = sqlx::query(
             "UPDATE swarms
-             SET name = $2, description = $3, status = $4, updated_at = CURRENT_TIMESTAMP
-             WHERE id = $1
-             RETURNING id, name, description, status, project_id, created_at, updated_at"
+             SET name = $2, description = $3, status = $4, setup_script = $5,
+                 dispatch_rate_per_minute = $6, allowed_skills = $7, fresh_sandbox_per_task = $9,
+                 require_approval = $10, max_concurrent = $11,
+                 auto_paused_at = CASE WHEN $8 THEN NULL ELSE auto_paused_at END,
+                 updated_at = CURRENT_TIMESTAMP, version = version + 1
+             WHERE id = $1 AND ($12 IS NULL OR version = $12)
+             RETURNING id, name, description, status, project_id, setup_script, dispatch_rate_per_minute, allowed_skills, fresh_sandbox_per_task, require_approval, max_concurrent, auto_paused_at, external_id, archived_at, created_at, updated_at, version"
         )
         .bind(id)
         .bind(&name)
         .bind(&description)
         .bind(&status_str)
-        .fetch_one(pool)
+        .bind(&setup_script)
+        .bind(dispatch_rate_per_minute)
+        .bind(skills_json(&allowed_skills))
+        .bind(data.status.is_some())
+        .bind(fresh_sandbox_per_task)
+        .bind(require_approval)
+        .bind(max_concurrent)
+        .bind(data.expected_version)
+        .fetch_optional(pool)
         .await?;
 
-        Self::from_row(row)
+        match row {
+            Some(row) => Ok(Self::from_row(row)?),
+            None if data.expected_version.is_some() => Err(UpdateError::Conflict),
+            None => Err(sqlx::Error::RowNotFound.into()),
+        }
     }
 
-    pub async fn update_status(pool: &SqlitePool, id: Uuid, status: SwarmStatus) -> Result<(), sqlx::Error> {
+    pub async fn update_status<'e, E>(executor: E, id: Uuid, status: SwarmStatus) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         let status_str = status.to_string();
-        sqlx::query("UPDATE swarms SET status = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1")
-            .bind(id)
-            .bind(&status_str)
-            .execute(pool)
-            .await?;
+        sqlx::query(
+            "UPDATE swarms SET status = $2, auto_paused_at = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = $1",
+        )
+        .bind(id)
+        .bind(&status_str)
+        .execute(executor)
+        .await?;
         Ok(())
     }
 
+    /// Active, unarchived swarms with no pending or running tasks and no task, chat or
+    /// swarm update since `cutoff`
+    pub async fn find_idle_since(pool: &SqlitePool, cutoff: DateTime<Utc>) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, name, description, status, project_id, setup_script, dispatch_rate_per_minute, allowed_skills, fresh_sandbox_per_task, require_approval, max_concurrent, auto_paused_at, external_id, archived_at, created_at, updated_at, version
+             FROM swarms s
+             WHERE status = 'active'
+               AND archived_at IS NULL
+               AND NOT EXISTS (
+                   SELECT 1 FROM swarm_tasks t
+                   WHERE t.swarm_id = s.id AND t.status IN ('pending', 'running')
+               )
+               AND MAX(
+                   julianday(s.updated_at),
+                   COALESCE((SELECT MAX(julianday(t.updated_at)) FROM swarm_tasks t WHERE t.swarm_id = s.id), 0),
+                   COALESCE((SELECT MAX(julianday(c.created_at)) FROM swarm_chat c WHERE c.swarm_id = s.id), 0)
+               ) < julianday($1)"
+        )
+        .bind(cutoff)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    /// Pause an active swarm for inactivity; false if it is no longer active or
+    /// has picked up work since it was found idle
+    pub async fn auto_pause(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE swarms
+             SET status = 'paused', auto_paused_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1
+               AND status = 'active'
+               AND NOT EXISTS (
+                   SELECT 1 FROM swarm_tasks
+                   WHERE swarm_id = $1 AND status IN ('pending', 'running')
+               )"
+        )
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Reactivate the swarm if it was auto-paused; user pauses are left alone
+    pub async fn resume_if_auto_paused(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE swarms
+             SET status = 'active', auto_paused_at = NULL, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND status = 'paused' AND auto_paused_at IS NOT NULL"
+        )
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Hide the swarm from listings and the trigger engine; false if it was
+    /// already archived
+    pub async fn archive(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE swarms SET archived_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND archived_at IS NULL"
+        )
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Restore an archived swarm; false if it was not archived
+    pub async fn unarchive(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE swarms SET archived_at = NULL, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND archived_at IS NOT NULL"
+        )
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
         let result = sqlx::query("DELETE FROM swarms WHERE id = $1")
             .bind(id)
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Row, SqlitePool, Type};
+use sqlx::{Executor, FromRow, Row, Sqlite, SqlitePool, Type};
 use strum_macros::{Display, EnumString};
 use ts_rs::TS;
 use uuid::Uuid;
@@ -15,6 +15,65 @@ pub enum SenderType {
     Sandbox,
 }
 
+/// Metadata attached to chat messages
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct MessageMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sandbox_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typing: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_response: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_required: Option<String>,
+}
+
+impl MessageMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_task(mut self, task_id: Uuid) -> Self {
+        self.task_id = Some(task_id);
+        self
+    }
+
+    pub fn with_sandbox(mut self, sandbox_id: Uuid) -> Self {
+        self.sandbox_id = Some(sandbox_id);
+        self
+    }
+
+    pub fn with_error(mut self, error: String) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    pub fn with_role(mut self, role: String) -> Self {
+        self.role = Some(role);
+        self
+    }
+
+    pub fn as_typing(mut self) -> Self {
+        self.typing = Some(true);
+        self
+    }
+
+    pub fn as_agent_response(mut self) -> Self {
+        self.agent_response = Some(true);
+        self
+    }
+
+    pub fn to_json(&self) -> Option<String> {
+        serde_json::to_string(self).ok()
+    }
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct SwarmChat {
     pub id: Uuid,
@@ -22,7 +81,12 @@ pub struct SwarmChat {
     pub sender_type: SenderType,
     pub sender_id: Option<String>,
     pub message: String,
+    /// Raw metadata JSON as stored
     pub metadata: Option<String>,
+    /// `metadata` parsed into its known fields; None when absent or malformed
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub parsed_metadata: Option<MessageMetadata>,
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
 }
@@ -40,6 +104,10 @@ impl SwarmChat {
     fn from_row(row: sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
         let sender_type_str: String = row.try_get("sender_type")?;
         let sender_type = sender_type_str.parse::<SenderType>().unwrap_or(SenderType::System);
+        let metadata: Option<String> = row.try_get("metadata")?;
+        let parsed_metadata = metadata
+            .as_deref()
+            .and_then(|raw| serde_json::from_str::<MessageMetadata>(raw).ok());
 
         Ok(Self {
             id: row.try_get("id")?,
@@ -47,7 +115,8 @@ impl SwarmChat {
             sender_type,
             sender_id: row.try_get("sender_id")?,
             message: row.try_get("message")?,
-            metadata: row.try_get("metadata")?,
+            metadata,
+            parsed_metadata,
             created_at: row.try_get("created_at")?,
         })
     }
@@ -56,16 +125,191 @@ impl SwarmChat {
         pool: &SqlitePool,
         swarm_id: Uuid,
         limit: Option<i32>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        Self::find_by_swarm_id_since(pool, swarm_id, None, limit).await
+    }
+
+    /// Newest messages posted strictly after `since` (if given), newest first
+    ///
+    /// `since` is applied before `limit`, so the limit only counts messages
+    /// that match it.
+    pub async fn find_by_swarm_id_since(
+        pool: &SqlitePool,
+        swarm_id: Uuid,
+        since: Option<DateTime<Utc>>,
+        limit: Option<i32>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let (messages, _) = Self::find_page(pool, swarm_id, since, None, limit).await?;
+        Ok(messages)
+    }
+
+    /// One page of a swarm's messages, newest first
+    ///
+    /// `before` is the id of the oldest message of the previous page; only
+    /// messages posted before it are returned. Messages with the same
+    /// timestamp are kept in insertion order, so paging never skips or repeats
+    /// one. Also returns whether older messages remain.
+    pub async fn find_page(
+        pool: &SqlitePool,
+        swarm_id: Uuid,
+        since: Option<DateTime<Utc>>,
+        before: Option<Uuid>,
+        limit: Option<i32>,
+    ) -> Result<(Vec<Self>, bool), sqlx::Error> {
+        let limit = limit.unwrap_or(100).clamp(1, 500);
+        let rows = sqlx::query(
+            "SELECT id, swarm_id, sender_type, sender_id, message, metadata, created_at
+             FROM swarm_chat
+             WHERE swarm_id = $1 AND ($2 IS NULL OR julianday(created_at) > julianday($2))
+               AND ($3 IS NULL OR (julianday(created_at), rowid) <
+                   (SELECT julianday(created_at), rowid FROM swarm_chat WHERE id = $3))
+             ORDER BY created_at DESC, rowid DESC
+             LIMIT $4"
+        )
+        .bind(swarm_id)
+        .bind(since)
+        .bind(before)
+        // One extra row tells whether another page exists
+        .bind(limit + 1)
+        .fetch_all(pool)
+        .await?;
+
+        let has_more = rows.len() > limit as usize;
+        let messages = rows
+            .into_iter()
+            .take(limit as usize)
+            .map(Self::from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((messages, has_more))
+    }
+
+    /// The oldest `limit` messages posted after `since` and after the message
+    /// `after` (each if given), oldest first
+    ///
+    /// For clients polling for new messages. Timestamps have second precision,
+    /// so resuming from the last message's id, not its `created_at`, is what
+    /// walks forward without gaps: messages with the same timestamp are kept in
+    /// insertion order, as in `find_page`. Also returns whether more follow.
+    pub async fn find_after(
+        pool: &SqlitePool,
+        swarm_id: Uuid,
+        since: Option<DateTime<Utc>>,
+        after: Option<Uuid>,
+        limit: Option<i32>,
+    ) -> Result<(Vec<Self>, bool), sqlx::Error> {
+        let limit = limit.unwrap_or(100).clamp(1, 500);
+        let rows = sqlx::query(
+            "SELECT id, swarm_id, sender_type, sender_id, message, metadata, created_at
+             FROM swarm_chat
+             WHERE swarm_id = $1 AND ($2 IS NULL OR julianday(created_at) > julianday($2))
+               AND ($3 IS NULL OR (julianday(created_at), rowid) >
+                   (SELECT julianday(created_at), rowid FROM swarm_chat WHERE id = $3))
+             ORDER BY created_at ASC, rowid ASC
+             LIMIT $4"
+        )
+        .bind(swarm_id)
+        .bind(since)
+        .bind(after)
+        .bind(limit + 1)
+        .fetch_all(pool)
+        .await?;
+
+        let has_more = rows.len() > limit as usize;
+        let messages = rows
+            .into_iter()
+            .take(limit as usize)
+            .map(Self::from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((messages, has_more))
+    }
+
+    /// Messages posted at or after `since`, oldest first
+    pub async fn find_since(
+        pool: &SqlitePool,
+        swarm_id: Uuid,
+        since: DateTime<Utc>,
+        limit: Option<i32>,
     ) -> Result<Vec<Self>, sqlx::Error> {
         let limit = limit.unwrap_or(100).min(500);
+        let rows = sqlx::query(
+            "SELECT id, swarm_id, sender_type, sender_id, message, metadata, created_at
+             FROM swarm_chat
+             WHERE swarm_id = $1 AND julianday(created_at) >= julianday($2)
+             ORDER BY created_at ASC
+             LIMIT $3"
+        )
+        .bind(swarm_id)
+        .bind(since)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    /// Every message in a swarm, oldest first
+    pub async fn find_all_by_swarm_id(pool: &SqlitePool, swarm_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
         let rows = sqlx::query(
             "SELECT id, swarm_id, sender_type, sender_id, message, metadata, created_at
              FROM swarm_chat
              WHERE swarm_id = $1
-             ORDER BY created_at DESC
-             LIMIT $2"
+             ORDER BY created_at ASC"
         )
         .bind(swarm_id)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    /// Messages of a swarm containing `query`, newest first
+    ///
+    /// Matching is a case-insensitive (for ASCII) substring match; `%` and `_`
+    /// in `query` match literally. At most 100 messages are returned.
+    pub async fn search(
+        pool: &SqlitePool,
+        swarm_id: Uuid,
+        query: &str,
+        limit: Option<i32>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let limit = limit.unwrap_or(50).clamp(1, 100);
+        let rows = sqlx::query(
+            "SELECT id, swarm_id, sender_type, sender_id, message, metadata, created_at
+             FROM swarm_chat
+             WHERE swarm_id = $1 AND message LIKE $2 ESCAPE '\\'
+             ORDER BY created_at DESC, rowid DESC
+             LIMIT $3"
+        )
+        .bind(swarm_id)
+        .bind(super::contains_pattern(query))
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    /// Messages delivered to a sandbox's inbox after `since` (if given), oldest first
+    ///
+    /// Resuming from the last message returned walks forward without gaps, as
+    /// with `find_after`.
+    pub async fn find_inbox(
+        pool: &SqlitePool,
+        sandbox_id: Uuid,
+        since: Option<DateTime<Utc>>,
+        limit: Option<i32>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let limit = limit.unwrap_or(100).clamp(1, 500);
+        let rows = sqlx::query(
+            "SELECT c.id, c.swarm_id, c.sender_type, c.sender_id, c.message, c.metadata, c.created_at
+             FROM swarm_chat c
+             JOIN swarm_chat_inbox i ON i.message_id = c.id
+             WHERE i.sandbox_id = $1 AND ($2 IS NULL OR julianday(c.created_at) > julianday($2))
+             ORDER BY c.created_at ASC, c.rowid ASC
+             LIMIT $3"
+        )
+        .bind(sandbox_id)
+        .bind(since)
         .bind(limit)
         .fetch_all(pool)
         .await?;
@@ -73,6 +317,22 @@ impl SwarmChat {
         rows.into_iter().map(Self::from_row).collect()
     }
 
+    /// Put a message in the inbox of each given sandbox
+    pub async fn deliver(pool: &SqlitePool, message_id: Uuid, sandbox_ids: &[Uuid]) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        for sandbox_id in sandbox_ids {
+            sqlx::query(
+                "INSERT OR IGNORE INTO swarm_chat_inbox (message_id, sandbox_id)
+                 VALUES ($1, $2)"
+            )
+            .bind(message_id)
+            .bind(sandbox_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
         let row = sqlx::query(
             "SELECT id, swarm_id, sender_type, sender_id, message, metadata, created_at
@@ -106,10 +366,36 @@ impl SwarmChat {
         Self::from_row(row)
     }
 
-    pub async fn delete_by_swarm_id(pool: &SqlitePool, swarm_id: Uuid) -> Result<u64, sqlx::Error> {
+    /// Insert a message as given, keeping its original timestamp
+    pub async fn restore<'e, E>(executor: E, message: &SwarmChat) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let row = sqlx::query(
+            "INSERT INTO swarm_chat (id, swarm_id, sender_type, sender_id, message, metadata, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING id, swarm_id, sender_type, sender_id, message, metadata, created_at"
+        )
+        .bind(message.id)
+        .bind(message.swarm_id)
+        .bind(message.sender_type.to_string())
+        .bind(&message.sender_id)
+        .bind(&message.message)
+        .bind(&message.metadata)
+        .bind(message.created_at)
+        .fetch_one(executor)
+        .await?;
+
+        Self::from_row(row)
+    }
+
+    pub async fn delete_by_swarm_id<'e, E>(executor: E, swarm_id: Uuid) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         let result = sqlx::query("DELETE FROM swarm_chat WHERE swarm_id = $1")
             .bind(swarm_id)
-            .execute(pool)
+            .execute(executor)
             .await?;
         Ok(result.rows_affected())
     }
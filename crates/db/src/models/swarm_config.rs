@@ -1,8 +1,37 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{Row, SqlitePool};
+use strum_macros::{Display, EnumString};
 use ts_rs::TS;
 
+/// How much of a completed task's result is posted to the swarm chat
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, TS, EnumString, Display, Default)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ChatResultMode {
+    /// The whole result
+    Full,
+    /// The SUMMARY section of the output, or a truncated result without one
+    #[default]
+    Summary,
+    /// No completion message
+    None,
+}
+
+/// How stored task logs are compressed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, TS, EnumString, Display, Default)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum LogCompression {
+    /// Stored as plain text
+    None,
+    Gzip,
+    #[default]
+    Zstd,
+}
+
 /// Swarm configuration stored in database
 /// Secrets (api keys, tokens) are NOT serialized to frontend
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -17,14 +46,31 @@ pub struct SwarmConfig {
     // Pool
     pub pool_max_sandboxes: i32,
     pub pool_idle_timeout_minutes: i32,
+    /// Idle sandboxes kept warm and spared from idle cleanup; 0 disables the warm pool
+    pub pool_min_idle: i32,
     pub pool_default_snapshot: String,
+    /// Tried in order when the default snapshot is not found
+    pub pool_fallback_snapshots: Vec<String>,
+    pub sandbox_name_prefix: String,
+    /// Seconds between pool utilization samples; 0 disables sampling
+    pub pool_history_interval_secs: i32,
+    /// Hours pool utilization samples are kept
+    pub pool_history_retention_hours: i32,
 
     // Claude
     #[serde(skip_serializing)]
     pub anthropic_api_key: Option<String>,
+    pub claude_model: Option<String>,
+    pub claude_extra_args: Vec<String>,
+    /// Check the task's `CLI:` tools are installed before running Claude
+    pub cli_probe_enabled: bool,
+    /// Values for `{{name}}` placeholders in task prompts
+    pub prompt_vars: HashMap<String, String>,
 
     // Skills
     pub skills_path: String,
+    /// Seconds a skill scan or read may take; guards against hung network mounts
+    pub skills_read_timeout_secs: i32,
 
     // Git
     pub git_auto_commit: bool,
@@ -37,6 +83,37 @@ pub struct SwarmConfig {
     pub trigger_poll_interval_seconds: i32,
     pub trigger_execution_timeout_minutes: i32,
     pub trigger_max_retries: i32,
+    /// Minutes of waiting per one-level priority bump; 0 disables aging
+    pub priority_aging_minutes: i32,
+    /// Minutes without tasks or chat before an idle swarm is paused; 0 disables
+    pub auto_pause_after_minutes: i32,
+    /// What the trigger engine posts to chat when a task completes
+    pub chat_result_mode: ChatResultMode,
+
+    // Limits
+    pub max_tasks_per_swarm: i32,
+
+    // Decomposition
+    pub decompose_enabled: bool,
+    pub decompose_max_subtasks: i32,
+
+    // Streaming
+    /// Lag events within `ws_lag_window_secs` before a WebSocket client is
+    /// disconnected; 0 never disconnects
+    pub ws_lag_limit: i32,
+    pub ws_lag_window_secs: i32,
+
+    // Logs
+    /// Algorithm for stored task logs at or above the threshold
+    pub log_compression: LogCompression,
+    /// Smaller task logs are stored uncompressed
+    pub log_compression_threshold_bytes: i32,
+    /// Collapse runs of identical lines in live log streams into one summary line
+    pub log_dedup_enabled: bool,
+
+    // Webhooks
+    /// Endpoint POSTed task.completed and task.failed events; None sends none
+    pub webhook_url: Option<String>,
 
     #[ts(type = "Date")]
     pub updated_at: DateTime<Utc>,
@@ -44,6 +121,7 @@ pub struct SwarmConfig {
 
 /// DTO for updating config (accepts secrets)
 #[derive(Debug, Clone, Deserialize, TS)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateSwarmConfig {
     // Daytona
     pub daytona_api_url: Option<String>,
@@ -52,13 +130,24 @@ pub struct UpdateSwarmConfig {
     // Pool
     pub pool_max_sandboxes: Option<i32>,
     pub pool_idle_timeout_minutes: Option<i32>,
+    pub pool_min_idle: Option<i32>,
     pub pool_default_snapshot: Option<String>,
+    pub pool_fallback_snapshots: Option<Vec<String>>,
+    pub sandbox_name_prefix: Option<String>,
+    pub pool_history_interval_secs: Option<i32>,
+    pub pool_history_retention_hours: Option<i32>,
 
     // Claude
     pub anthropic_api_key: Option<String>,
+    /// An empty string clears the model, so the CLI default is used
+    pub claude_model: Option<String>,
+    pub claude_extra_args: Option<Vec<String>>,
+    pub cli_probe_enabled: Option<bool>,
+    pub prompt_vars: Option<HashMap<String, String>>,
 
     // Skills
     pub skills_path: Option<String>,
+    pub skills_read_timeout_secs: Option<i32>,
 
     // Git
     pub git_auto_commit: Option<bool>,
@@ -70,6 +159,29 @@ pub struct UpdateSwarmConfig {
     pub trigger_poll_interval_seconds: Option<i32>,
     pub trigger_execution_timeout_minutes: Option<i32>,
     pub trigger_max_retries: Option<i32>,
+    pub priority_aging_minutes: Option<i32>,
+    pub auto_pause_after_minutes: Option<i32>,
+    pub chat_result_mode: Option<ChatResultMode>,
+
+    // Limits
+    pub max_tasks_per_swarm: Option<i32>,
+
+    // Decomposition
+    pub decompose_enabled: Option<bool>,
+    pub decompose_max_subtasks: Option<i32>,
+
+    // Streaming
+    pub ws_lag_limit: Option<i32>,
+    pub ws_lag_window_secs: Option<i32>,
+
+    // Logs
+    pub log_compression: Option<LogCompression>,
+    pub log_compression_threshold_bytes: Option<i32>,
+    pub log_dedup_enabled: Option<bool>,
+
+    // Webhooks
+    /// An empty string clears the webhook
+    pub webhook_url: Option<String>,
 }
 
 /// Response that includes masked secrets info for display
@@ -85,9 +197,12 @@ pub struct SwarmConfigWithMaskedSecrets {
 
 impl SwarmConfig {
     fn from_row(row: sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
-        let git_auto_commit: i32 = row.try_get("git_auto_commit").unwrap_or(1);
+        let git_auto_commit: i32 = row.try_get("git_auto_commit").unwrap_or(0);
         let git_auto_push: i32 = row.try_get("git_auto_push").unwrap_or(0);
         let trigger_enabled: i32 = row.try_get("trigger_enabled").unwrap_or(1);
+        let decompose_enabled: i32 = row.try_get("decompose_enabled").unwrap_or(0);
+        let cli_probe_enabled: i32 = row.try_get("cli_probe_enabled").unwrap_or(1);
+        let log_dedup_enabled: i32 = row.try_get("log_dedup_enabled").unwrap_or(0);
 
         Ok(Self {
             id: row.try_get::<Option<String>, _>("id")?.unwrap_or_else(|| "default".to_string()),
@@ -95,9 +210,28 @@ impl SwarmConfig {
             daytona_api_key: row.try_get("daytona_api_key")?,
             pool_max_sandboxes: row.try_get::<Option<i32>, _>("pool_max_sandboxes")?.unwrap_or(5),
             pool_idle_timeout_minutes: row.try_get::<Option<i32>, _>("pool_idle_timeout_minutes")?.unwrap_or(10),
+            pool_min_idle: row.try_get::<Option<i32>, _>("pool_min_idle")?.unwrap_or(0),
             pool_default_snapshot: row.try_get::<Option<String>, _>("pool_default_snapshot")?.unwrap_or_else(|| "swarm-lite-v1".to_string()),
+            pool_fallback_snapshots: row
+                .try_get::<Option<String>, _>("pool_fallback_snapshots")?
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
+            sandbox_name_prefix: row.try_get::<Option<String>, _>("sandbox_name_prefix")?.unwrap_or_default(),
+            pool_history_interval_secs: row.try_get::<Option<i32>, _>("pool_history_interval_secs")?.unwrap_or(60),
+            pool_history_retention_hours: row.try_get::<Option<i32>, _>("pool_history_retention_hours")?.unwrap_or(168),
             anthropic_api_key: row.try_get("anthropic_api_key")?,
+            claude_model: row.try_get("claude_model")?,
+            claude_extra_args: row
+                .try_get::<Option<String>, _>("claude_extra_args")?
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
+            cli_probe_enabled: cli_probe_enabled != 0,
+            prompt_vars: row
+                .try_get::<Option<String>, _>("prompt_vars")?
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
             skills_path: row.try_get::<Option<String>, _>("skills_path")?.unwrap_or_else(|| "/root/.claude/skills".to_string()),
+            skills_read_timeout_secs: row.try_get::<Option<i32>, _>("skills_read_timeout_secs")?.unwrap_or(5),
             git_auto_commit: git_auto_commit != 0,
             git_auto_push: git_auto_push != 0,
             git_token: row.try_get("git_token")?,
@@ -105,6 +239,26 @@ impl SwarmConfig {
             trigger_poll_interval_seconds: row.try_get::<Option<i32>, _>("trigger_poll_interval_seconds")?.unwrap_or(5),
             trigger_execution_timeout_minutes: row.try_get::<Option<i32>, _>("trigger_execution_timeout_minutes")?.unwrap_or(10),
             trigger_max_retries: row.try_get::<Option<i32>, _>("trigger_max_retries")?.unwrap_or(3),
+            priority_aging_minutes: row.try_get::<Option<i32>, _>("priority_aging_minutes")?.unwrap_or(0),
+            auto_pause_after_minutes: row.try_get::<Option<i32>, _>("auto_pause_after_minutes")?.unwrap_or(0),
+            chat_result_mode: row
+                .try_get::<Option<String>, _>("chat_result_mode")?
+                .and_then(|s| s.parse().ok())
+                .unwrap_or_default(),
+            max_tasks_per_swarm: row.try_get::<Option<i32>, _>("max_tasks_per_swarm")?.unwrap_or(10000),
+            decompose_enabled: decompose_enabled != 0,
+            decompose_max_subtasks: row.try_get::<Option<i32>, _>("decompose_max_subtasks")?.unwrap_or(10),
+            ws_lag_limit: row.try_get::<Option<i32>, _>("ws_lag_limit")?.unwrap_or(3),
+            ws_lag_window_secs: row.try_get::<Option<i32>, _>("ws_lag_window_secs")?.unwrap_or(30),
+            log_compression: row
+                .try_get::<Option<String>, _>("log_compression")?
+                .and_then(|s| s.parse().ok())
+                .unwrap_or_default(),
+            log_compression_threshold_bytes: row
+                .try_get::<Option<i32>, _>("log_compression_threshold_bytes")?
+                .unwrap_or(65536),
+            log_dedup_enabled: log_dedup_enabled != 0,
+            webhook_url: row.try_get("webhook_url")?,
             updated_at: row.try_get("updated_at")?,
         })
     }
@@ -112,10 +266,16 @@ impl SwarmConfig {
     pub async fn get(pool: &SqlitePool) -> Result<Self, sqlx::Error> {
         let row = sqlx::query(
             "SELECT id, daytona_api_url, daytona_api_key, pool_max_sandboxes,
-                    pool_idle_timeout_minutes, pool_default_snapshot, anthropic_api_key,
-                    skills_path, git_auto_commit, git_auto_push, git_token, trigger_enabled,
+                    pool_idle_timeout_minutes, pool_default_snapshot, sandbox_name_prefix, anthropic_api_key,
+                    claude_model, claude_extra_args, skills_path, git_auto_commit, git_auto_push, git_token, trigger_enabled,
                     trigger_poll_interval_seconds, trigger_execution_timeout_minutes,
-                    trigger_max_retries, updated_at
+                    trigger_max_retries, max_tasks_per_swarm, decompose_enabled,
+                    decompose_max_subtasks, priority_aging_minutes, pool_fallback_snapshots,
+                    auto_pause_after_minutes, chat_result_mode, skills_read_timeout_secs,
+                    pool_history_interval_secs, pool_history_retention_hours, ws_lag_limit,
+                    ws_lag_window_secs, cli_probe_enabled, log_compression,
+                    log_compression_threshold_bytes, prompt_vars, pool_min_idle, webhook_url, log_dedup_enabled,
+                    updated_at
              FROM swarm_config
              WHERE id = 'default'"
         )
@@ -132,8 +292,19 @@ impl SwarmConfig {
         let daytona_api_key = data.daytona_api_key.clone().or(existing.daytona_api_key);
         let pool_max_sandboxes = data.pool_max_sandboxes.unwrap_or(existing.pool_max_sandboxes);
         let pool_idle_timeout_minutes = data.pool_idle_timeout_minutes.unwrap_or(existing.pool_idle_timeout_minutes);
+        let pool_min_idle = data.pool_min_idle.unwrap_or(existing.pool_min_idle);
         let pool_default_snapshot = data.pool_default_snapshot.clone().unwrap_or(existing.pool_default_snapshot);
+        let pool_fallback_snapshots = data.pool_fallback_snapshots.clone().unwrap_or(existing.pool_fallback_snapshots);
+        let pool_fallback_snapshots_json = serde_json::to_string(&pool_fallback_snapshots).unwrap_or_else(|_| "[]".to_string());
+        let sandbox_name_prefix = data.sandbox_name_prefix.clone().unwrap_or(existing.sandbox_name_prefix);
         let anthropic_api_key = data.anthropic_api_key.clone().or(existing.anthropic_api_key);
+        let claude_model = match data.claude_model.as_deref() {
+            Some("") => None,
+            Some(model) => Some(model.to_string()),
+            None => existing.claude_model,
+        };
+        let claude_extra_args = data.claude_extra_args.clone().unwrap_or(existing.claude_extra_args);
+        let claude_extra_args_json = serde_json::to_string(&claude_extra_args).unwrap_or_else(|_| "[]".to_string());
         let skills_path = data.skills_path.clone().unwrap_or(existing.skills_path);
         let git_auto_commit = data.git_auto_commit.unwrap_or(existing.git_auto_commit);
         let git_auto_push = data.git_auto_push.unwrap_or(existing.git_auto_push);
@@ -142,11 +313,38 @@ impl SwarmConfig {
         let trigger_poll_interval_seconds = data.trigger_poll_interval_seconds.unwrap_or(existing.trigger_poll_interval_seconds);
         let trigger_execution_timeout_minutes = data.trigger_execution_timeout_minutes.unwrap_or(existing.trigger_execution_timeout_minutes);
         let trigger_max_retries = data.trigger_max_retries.unwrap_or(existing.trigger_max_retries);
+        let max_tasks_per_swarm = data.max_tasks_per_swarm.unwrap_or(existing.max_tasks_per_swarm);
+        let decompose_enabled = data.decompose_enabled.unwrap_or(existing.decompose_enabled);
+        let decompose_max_subtasks = data.decompose_max_subtasks.unwrap_or(existing.decompose_max_subtasks);
+        let priority_aging_minutes = data.priority_aging_minutes.unwrap_or(existing.priority_aging_minutes);
+        let auto_pause_after_minutes = data.auto_pause_after_minutes.unwrap_or(existing.auto_pause_after_minutes);
+        let chat_result_mode = data.chat_result_mode.unwrap_or(existing.chat_result_mode);
+        let skills_read_timeout_secs = data.skills_read_timeout_secs.unwrap_or(existing.skills_read_timeout_secs);
+        let pool_history_interval_secs = data.pool_history_interval_secs.unwrap_or(existing.pool_history_interval_secs);
+        let pool_history_retention_hours = data.pool_history_retention_hours.unwrap_or(existing.pool_history_retention_hours);
+        let ws_lag_limit = data.ws_lag_limit.unwrap_or(existing.ws_lag_limit);
+        let ws_lag_window_secs = data.ws_lag_window_secs.unwrap_or(existing.ws_lag_window_secs);
+        let cli_probe_enabled = data.cli_probe_enabled.unwrap_or(existing.cli_probe_enabled);
+        let prompt_vars = data.prompt_vars.clone().unwrap_or(existing.prompt_vars);
+        let prompt_vars_json = serde_json::to_string(&prompt_vars).unwrap_or_else(|_| "{}".to_string());
+        let log_compression = data.log_compression.unwrap_or(existing.log_compression);
+        let log_compression_threshold_bytes = data
+            .log_compression_threshold_bytes
+            .unwrap_or(existing.log_compression_threshold_bytes);
+        let log_dedup_enabled = data.log_dedup_enabled.unwrap_or(existing.log_dedup_enabled);
+        let webhook_url = match data.webhook_url.as_deref() {
+            Some("") => None,
+            Some(url) => Some(url.to_string()),
+            None => existing.webhook_url,
+        };
 
         // SQLite booleans
         let git_auto_commit_int: i32 = if git_auto_commit { 1 } else { 0 };
         let git_auto_push_int: i32 = if git_auto_push { 1 } else { 0 };
         let trigger_enabled_int: i32 = if trigger_enabled { 1 } else { 0 };
+        let decompose_enabled_int: i32 = if decompose_enabled { 1 } else { 0 };
+        let cli_probe_enabled_int: i32 = if cli_probe_enabled { 1 } else { 0 };
+        let log_dedup_enabled_int: i32 = if log_dedup_enabled { 1 } else { 0 };
 
         sqlx::query(
             "UPDATE swarm_config SET
@@ -164,6 +362,28 @@ impl SwarmConfig {
                 trigger_poll_interval_seconds = $12,
                 trigger_execution_timeout_minutes = $13,
                 trigger_max_retries = $14,
+                sandbox_name_prefix = $15,
+                claude_model = $16,
+                claude_extra_args = $17,
+                max_tasks_per_swarm = $18,
+                decompose_enabled = $19,
+                decompose_max_subtasks = $20,
+                priority_aging_minutes = $21,
+                pool_fallback_snapshots = $22,
+                auto_pause_after_minutes = $23,
+                chat_result_mode = $24,
+                skills_read_timeout_secs = $25,
+                pool_history_interval_secs = $26,
+                pool_history_retention_hours = $27,
+                ws_lag_limit = $28,
+                ws_lag_window_secs = $29,
+                cli_probe_enabled = $30,
+                log_compression = $31,
+                log_compression_threshold_bytes = $32,
+                prompt_vars = $33,
+                pool_min_idle = $34,
+                webhook_url = $35,
+                log_dedup_enabled = $36,
                 updated_at = CURRENT_TIMESTAMP
             WHERE id = 'default'"
         )
@@ -181,6 +401,28 @@ impl SwarmConfig {
         .bind(trigger_poll_interval_seconds)
         .bind(trigger_execution_timeout_minutes)
         .bind(trigger_max_retries)
+        .bind(&sandbox_name_prefix)
+        .bind(&claude_model)
+        .bind(&claude_extra_args_json)
+        .bind(max_tasks_per_swarm)
+        .bind(decompose_enabled_int)
+        .bind(decompose_max_subtasks)
+        .bind(priority_aging_minutes)
+        .bind(&pool_fallback_snapshots_json)
+        .bind(auto_pause_after_minutes)
+        .bind(chat_result_mode.to_string())
+        .bind(skills_read_timeout_secs)
+        .bind(pool_history_interval_secs)
+        .bind(pool_history_retention_hours)
+        .bind(ws_lag_limit)
+        .bind(ws_lag_window_secs)
+        .bind(cli_probe_enabled_int)
+        .bind(log_compression.to_string())
+        .bind(log_compression_threshold_bytes)
+        .bind(&prompt_vars_json)
+        .bind(pool_min_idle)
+        .bind(&webhook_url)
+        .bind(log_dedup_enabled_int)
         .execute(pool)
         .await?;
 
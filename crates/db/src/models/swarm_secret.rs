@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Row, Sqlite, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A named secret a swarm's tasks can request in their environment
+/// The value is write-only and never serialized.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct SwarmSecret {
+    pub id: Uuid,
+    pub swarm_id: Uuid,
+    pub name: String,
+    #[serde(skip_serializing)]
+    #[ts(skip)]
+    pub value: String,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(deny_unknown_fields)]
+pub struct SetSwarmSecret {
+    pub value: String,
+}
+
+impl SwarmSecret {
+    fn from_row(row: sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            swarm_id: row.try_get("swarm_id")?,
+            name: row.try_get("name")?,
+            value: row.try_get("value")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+
+    /// Secrets of a swarm, by name
+    pub async fn find_by_swarm_id(pool: &SqlitePool, swarm_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, swarm_id, name, value, created_at, updated_at
+             FROM swarm_secrets
+             WHERE swarm_id = $1
+             ORDER BY name ASC"
+        )
+        .bind(swarm_id)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    /// Create a secret, or replace the value of an existing one with the same name
+    pub async fn upsert(
+        pool: &SqlitePool,
+        swarm_id: Uuid,
+        name: &str,
+        data: &SetSwarmSecret,
+    ) -> Result<Self, sqlx::Error> {
+        let row = sqlx::query(
+            "INSERT INTO swarm_secrets (id, swarm_id, name, value)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (swarm_id, name)
+             DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+             RETURNING id, swarm_id, name, value, created_at, updated_at"
+        )
+        .bind(Uuid::new_v4())
+        .bind(swarm_id)
+        .bind(name)
+        .bind(&data.value)
+        .fetch_one(pool)
+        .await?;
+
+        Self::from_row(row)
+    }
+
+    /// Delete a secret; returns false if the swarm has no secret by that name
+    pub async fn delete(pool: &SqlitePool, swarm_id: Uuid, name: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM swarm_secrets WHERE swarm_id = $1 AND name = $2")
+            .bind(swarm_id)
+            .bind(name)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete_by_swarm_id<'e, E>(executor: E, swarm_id: Uuid) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query("DELETE FROM swarm_secrets WHERE swarm_id = $1")
+            .bind(swarm_id)
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Values of the named secrets of a swarm, keyed by name
+    ///
+    /// Names the swarm has no secret for are absent from the map.
+    pub async fn resolve(
+        pool: &SqlitePool,
+        swarm_id: Uuid,
+        names: &[String],
+    ) -> Result<HashMap<String, String>, sqlx::Error> {
+        let mut values = HashMap::new();
+        for secret in Self::find_by_swarm_id(pool, swarm_id).await? {
+            if names.contains(&secret.name) {
+                values.insert(secret.name, secret.value);
+            }
+        }
+        Ok(values)
+    }
+}
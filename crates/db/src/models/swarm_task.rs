@@ -1,15 +1,21 @@
+use std::collections::{HashMap, HashSet};
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{Row, SqlitePool, Type};
+use sqlx::{Executor, Row, Sqlite, SqlitePool, Type};
 use strum_macros::{Display, EnumString};
 use ts_rs::TS;
 use uuid::Uuid;
 
+use super::UpdateError;
+
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS, EnumString, Display, Default)]
 #[sqlx(type_name = "swarm_task_status", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 #[strum(serialize_all = "lowercase")]
 pub enum SwarmTaskStatus {
+    /// Awaiting approval before it may be dispatched
+    Blocked,
     #[default]
     Pending,
     Running,
@@ -18,6 +24,13 @@ pub enum SwarmTaskStatus {
     Cancelled,
 }
 
+impl SwarmTaskStatus {
+    /// Whether the task has finished and will not run again unless retried
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
 #[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS, EnumString, Display, Default)]
 #[sqlx(type_name = "task_priority", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
@@ -30,7 +43,7 @@ pub enum TaskPriority {
     Urgent,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
 pub struct SwarmTask {
     pub id: Uuid,
     pub swarm_id: Uuid,
@@ -52,18 +65,66 @@ pub struct SwarmTask {
     pub created_at: DateTime<Utc>,
     #[ts(type = "Date")]
     pub updated_at: DateTime<Utc>,
+    /// Sandbox this task must run in (waits for it instead of taking any idle one)
+    pub pinned_sandbox_id: Option<Uuid>,
+    /// Run a planning pass that splits this task into subtasks instead of executing it
+    pub decompose: bool,
+    /// Coordinator task this subtask was planned from
+    pub parent_task_id: Option<Uuid>,
+    /// Set once the task has been decomposed; it completes when its subtasks do
+    pub is_coordinator: bool,
+    /// Expected effort in minutes, for planning
+    pub estimate_minutes: Option<i32>,
+    /// Who approved the task for dispatch, from the approver's `X-User-Id`
+    pub approved_by: Option<String>,
+    #[ts(type = "Date | null")]
+    pub approved_at: Option<DateTime<Utc>>,
+    /// Names of swarm secrets injected into the task's environment
+    pub secrets: Vec<String>,
+    /// Sandbox path the output is also written to after a successful run
+    pub write_result_to: Option<String>,
+    /// Id from an imported definition; importing it again updates this task
+    pub external_id: Option<String>,
+    /// Execution attempts of the last run, including retries
+    #[serde(default)]
+    pub attempts: i32,
+    /// Bumped on every write; pass it back as `expected_version` to detect concurrent edits
+    #[serde(default)]
+    #[ts(type = "number")]
+    pub version: i64,
 }
 
 #[derive(Debug, Clone, Deserialize, TS)]
+#[serde(deny_unknown_fields)]
 pub struct CreateSwarmTask {
     pub title: String,
     pub description: Option<String>,
     pub priority: Option<TaskPriority>,
     pub depends_on: Option<Vec<Uuid>>,
     pub tags: Option<Vec<String>>,
+    pub pinned_sandbox_id: Option<Uuid>,
+    pub decompose: Option<bool>,
+    pub estimate_minutes: Option<i32>,
+    pub secrets: Option<Vec<String>>,
+    pub write_result_to: Option<String>,
+}
+
+/// Filters for `SwarmTask::search`; unset fields match every task
+#[derive(Debug, Clone, Default)]
+pub struct SwarmTaskFilters {
+    pub status: Option<SwarmTaskStatus>,
+    pub priority: Option<TaskPriority>,
+    /// Only tasks whose `tags` contain this value
+    pub tag: Option<String>,
+    /// Case-insensitive substring of the title or description
+    pub text: Option<String>,
+    /// None returns every match
+    pub limit: Option<i64>,
+    pub offset: i64,
 }
 
 #[derive(Debug, Clone, Deserialize, TS)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateSwarmTask {
     pub title: Option<String>,
     pub description: Option<String>,
@@ -75,6 +136,12 @@ pub struct UpdateSwarmTask {
     pub result: Option<String>,
     pub error: Option<String>,
     pub tags: Option<Vec<String>>,
+    pub estimate_minutes: Option<i32>,
+    pub secrets: Option<Vec<String>>,
+    pub write_result_to: Option<String>,
+    /// The `version` the client last saw; the update is refused if the task changed since
+    #[ts(type = "number | null")]
+    pub expected_version: Option<i64>,
 }
 
 impl SwarmTask {
@@ -99,6 +166,11 @@ impl SwarmTask {
             .and_then(|s| serde_json::from_str(&s).ok())
             .unwrap_or_default();
 
+        let secrets: Vec<String> = row
+            .try_get::<Option<String>, _>("secrets")?
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+
         Ok(Self {
             id: row.try_get("id")?,
             swarm_id: row.try_get("swarm_id")?,
@@ -116,6 +188,18 @@ impl SwarmTask {
             completed_at: row.try_get("completed_at")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
+            pinned_sandbox_id: row.try_get("pinned_sandbox_id")?,
+            decompose: row.try_get::<i32, _>("decompose")? != 0,
+            parent_task_id: row.try_get("parent_task_id")?,
+            is_coordinator: row.try_get::<i32, _>("is_coordinator")? != 0,
+            estimate_minutes: row.try_get("estimate_minutes")?,
+            approved_by: row.try_get("approved_by")?,
+            approved_at: row.try_get("approved_at")?,
+            secrets,
+            write_result_to: row.try_get("write_result_to")?,
+            external_id: row.try_get("external_id")?,
+            attempts: row.try_get("attempts")?,
+            version: row.try_get("version")?,
         })
     }
 
@@ -123,7 +207,9 @@ impl SwarmTask {
         let rows = sqlx::query(
             "SELECT id, swarm_id, title, description, status, priority, sandbox_id,
                     depends_on, triggers_after, result, error, tags,
-                    started_at, completed_at, created_at, updated_at
+                    started_at, completed_at, created_at, updated_at,
+                    pinned_sandbox_id, decompose, parent_task_id, is_coordinator, estimate_minutes,
+                    approved_by, approved_at, secrets, write_result_to, external_id, attempts, version
              FROM swarm_tasks
              ORDER BY created_at DESC"
         )
@@ -137,7 +223,9 @@ impl SwarmTask {
         let row = sqlx::query(
             "SELECT id, swarm_id, title, description, status, priority, sandbox_id,
                     depends_on, triggers_after, result, error, tags,
-                    started_at, completed_at, created_at, updated_at
+                    started_at, completed_at, created_at, updated_at,
+                    pinned_sandbox_id, decompose, parent_task_id, is_coordinator, estimate_minutes,
+                    approved_by, approved_at, secrets, write_result_to, external_id, attempts, version
              FROM swarm_tasks
              WHERE id = $1"
         )
@@ -148,7 +236,32 @@ impl SwarmTask {
         row.map(Self::from_row).transpose()
     }
 
+    pub async fn find_by_external_id(
+        pool: &SqlitePool,
+        swarm_id: Uuid,
+        external_id: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT id, swarm_id, title, description, status, priority, sandbox_id,
+                    depends_on, triggers_after, result, error, tags,
+                    started_at, completed_at, created_at, updated_at,
+                    pinned_sandbox_id, decompose, parent_task_id, is_coordinator, estimate_minutes,
+                    approved_by, approved_at, secrets, write_result_to, external_id, attempts, version
+             FROM swarm_tasks
+             WHERE swarm_id = $1 AND external_id = $2"
+        )
+        .bind(swarm_id)
+        .bind(external_id)
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(Self::from_row).transpose()
+    }
+
     /// Find multiple tasks by their IDs in a single query (avoids N+1)
+    ///
+    /// Rows come back in no particular order; use `find_by_ids_ordered` when
+    /// the order of `ids` matters.
     pub async fn find_by_ids(pool: &SqlitePool, ids: &[Uuid]) -> Result<Vec<Self>, sqlx::Error> {
         if ids.is_empty() {
             return Ok(Vec::new());
@@ -161,7 +274,9 @@ impl SwarmTask {
         let query = format!(
             "SELECT id, swarm_id, title, description, status, priority, sandbox_id,
                     depends_on, triggers_after, result, error, tags,
-                    started_at, completed_at, created_at, updated_at
+                    started_at, completed_at, created_at, updated_at,
+                    pinned_sandbox_id, decompose, parent_task_id, is_coordinator, estimate_minutes,
+                    approved_by, approved_at, secrets, write_result_to, external_id, attempts, version
              FROM swarm_tasks
              WHERE id IN ({})",
             placeholders_str
@@ -176,11 +291,25 @@ impl SwarmTask {
         rows.into_iter().map(Self::from_row).collect()
     }
 
+    /// Like `find_by_ids`, but in the order of `ids`; unknown ids are skipped
+    /// and repeated ids return the task once
+    pub async fn find_by_ids_ordered(pool: &SqlitePool, ids: &[Uuid]) -> Result<Vec<Self>, sqlx::Error> {
+        let mut by_id: HashMap<Uuid, Self> = Self::find_by_ids(pool, ids)
+            .await?
+            .into_iter()
+            .map(|t| (t.id, t))
+            .collect();
+
+        Ok(ids.iter().filter_map(|id| by_id.remove(id)).collect())
+    }
+
     pub async fn find_by_swarm_id(pool: &SqlitePool, swarm_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
         let rows = sqlx::query(
             "SELECT id, swarm_id, title, description, status, priority, sandbox_id,
                     depends_on, triggers_after, result, error, tags,
-                    started_at, completed_at, created_at, updated_at
+                    started_at, completed_at, created_at, updated_at,
+                    pinned_sandbox_id, decompose, parent_task_id, is_coordinator, estimate_minutes,
+                    approved_by, approved_at, secrets, write_result_to, external_id, attempts, version
              FROM swarm_tasks
              WHERE swarm_id = $1
              ORDER BY created_at DESC"
@@ -192,30 +321,170 @@ impl SwarmTask {
         rows.into_iter().map(Self::from_row).collect()
     }
 
-    pub async fn find_pending_by_swarm_id(pool: &SqlitePool, swarm_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+    /// Tasks of a swarm matching `filters`, newest first, with the total number of matches
+    pub async fn search(
+        pool: &SqlitePool,
+        swarm_id: Uuid,
+        filters: &SwarmTaskFilters,
+    ) -> Result<(Vec<Self>, i64), sqlx::Error> {
+        const FILTER: &str = "swarm_id = $1
+               AND ($2 IS NULL OR status = $2)
+               AND ($3 IS NULL OR priority = $3)
+               AND ($4 IS NULL OR EXISTS (SELECT 1 FROM json_each(swarm_tasks.tags) WHERE json_each.value = $4))
+               AND ($5 IS NULL OR title LIKE $5 ESCAPE '\\' OR description LIKE $5 ESCAPE '\\')";
+
+        let status = filters.status.as_ref().map(|s| s.to_string());
+        let priority = filters.priority.as_ref().map(|p| p.to_string());
+        let pattern = filters.text.as_deref().map(super::contains_pattern);
+
+        let total = sqlx::query(&format!("SELECT COUNT(*) as count FROM swarm_tasks WHERE {}", FILTER))
+            .bind(swarm_id)
+            .bind(&status)
+            .bind(&priority)
+            .bind(&filters.tag)
+            .bind(&pattern)
+            .fetch_one(pool)
+            .await?
+            .try_get::<i64, _>("count")?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT id, swarm_id, title, description, status, priority, sandbox_id,
+                    depends_on, triggers_after, result, error, tags,
+                    started_at, completed_at, created_at, updated_at,
+                    pinned_sandbox_id, decompose, parent_task_id, is_coordinator, estimate_minutes,
+                    approved_by, approved_at, secrets, write_result_to, external_id, attempts, version
+             FROM swarm_tasks
+             WHERE {}
+             ORDER BY created_at DESC, rowid DESC
+             LIMIT $6 OFFSET $7",
+            FILTER
+        ))
+        .bind(swarm_id)
+        .bind(&status)
+        .bind(&priority)
+        .bind(&filters.tag)
+        .bind(&pattern)
+        .bind(filters.limit.unwrap_or(-1))
+        .bind(filters.offset)
+        .fetch_all(pool)
+        .await?;
+
+        let tasks = rows.into_iter().map(Self::from_row).collect::<Result<_, _>>()?;
+        Ok((tasks, total))
+    }
+
+    /// Tasks of a swarm updated at or after `since`, oldest change first
+    ///
+    /// Timestamps are stored with second precision, so the comparison is
+    /// inclusive; callers polling with a cursor may see a task twice.
+    pub async fn find_updated_since(
+        pool: &SqlitePool,
+        swarm_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, swarm_id, title, description, status, priority, sandbox_id,
+                    depends_on, triggers_after, result, error, tags,
+                    started_at, completed_at, created_at, updated_at,
+                    pinned_sandbox_id, decompose, parent_task_id, is_coordinator, estimate_minutes,
+                    approved_by, approved_at, secrets, write_result_to, external_id, attempts, version
+             FROM swarm_tasks
+             WHERE swarm_id = $1 AND julianday(updated_at) >= julianday($2)
+             ORDER BY updated_at ASC"
+        )
+        .bind(swarm_id)
+        .bind(since)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    /// Pending tasks in dispatch order: priority first, then oldest first
+    ///
+    /// With `aging_minutes > 0`, every full window a task has been waiting
+    /// raises its effective priority one level (capped at urgent), so old
+    /// low-priority tasks are not starved by a steady stream of newer ones.
+    pub async fn find_pending_by_swarm_id(
+        pool: &SqlitePool,
+        swarm_id: Uuid,
+        aging_minutes: i32,
+    ) -> Result<Vec<Self>, sqlx::Error> {
         let rows = sqlx::query(
             "SELECT id, swarm_id, title, description, status, priority, sandbox_id,
                     depends_on, triggers_after, result, error, tags,
-                    started_at, completed_at, created_at, updated_at
+                    started_at, completed_at, created_at, updated_at,
+                    pinned_sandbox_id, decompose, parent_task_id, is_coordinator, estimate_minutes,
+                    approved_by, approved_at, secrets, write_result_to, external_id, attempts, version
              FROM swarm_tasks
              WHERE swarm_id = $1 AND status = 'pending'
              ORDER BY
-                CASE priority
-                    WHEN 'urgent' THEN 1
-                    WHEN 'high' THEN 2
-                    WHEN 'medium' THEN 3
-                    WHEN 'low' THEN 4
-                END,
+                MAX(
+                    1,
+                    CASE priority
+                        WHEN 'urgent' THEN 1
+                        WHEN 'high' THEN 2
+                        WHEN 'medium' THEN 3
+                        WHEN 'low' THEN 4
+                    END
+                    - CASE
+                        WHEN $2 > 0 THEN CAST((julianday('now') - julianday(created_at)) * 1440 / $2 AS INTEGER)
+                        ELSE 0
+                    END
+                ),
                 created_at ASC"
         )
         .bind(swarm_id)
+        .bind(aging_minutes)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    /// Running tasks that started before `cutoff`, oldest first
+    pub async fn find_running_started_before(
+        pool: &SqlitePool,
+        cutoff: DateTime<Utc>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, swarm_id, title, description, status, priority, sandbox_id,
+                    depends_on, triggers_after, result, error, tags,
+                    started_at, completed_at, created_at, updated_at,
+                    pinned_sandbox_id, decompose, parent_task_id, is_coordinator, estimate_minutes,
+                    approved_by, approved_at, secrets, write_result_to, external_id, attempts, version
+             FROM swarm_tasks
+             WHERE status = 'running' AND julianday(started_at) < julianday($1)
+             ORDER BY started_at ASC"
+        )
+        .bind(cutoff)
         .fetch_all(pool)
         .await?;
 
         rows.into_iter().map(Self::from_row).collect()
     }
 
-    pub async fn create(pool: &SqlitePool, swarm_id: Uuid, data: &CreateSwarmTask, task_id: Uuid) -> Result<Self, sqlx::Error> {
+    /// 1-based position of each pending task in dispatch order
+    ///
+    /// Uses the same ordering as `find_pending_by_swarm_id`; dependencies are
+    /// not considered, so a task waiting on one keeps its place in line.
+    pub async fn queue_positions(
+        pool: &SqlitePool,
+        swarm_id: Uuid,
+        aging_minutes: i32,
+    ) -> Result<HashMap<Uuid, usize>, sqlx::Error> {
+        let pending = Self::find_pending_by_swarm_id(pool, swarm_id, aging_minutes).await?;
+        Ok(pending
+            .iter()
+            .enumerate()
+            .map(|(i, task)| (task.id, i + 1))
+            .collect())
+    }
+
+    pub async fn create<'e, E>(executor: E, swarm_id: Uuid, data: &CreateSwarmTask, task_id: Uuid) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         let priority = data.priority.clone().unwrap_or_default();
         let priority_str = priority.to_string();
 
@@ -226,12 +495,20 @@ impl SwarmTask {
             .map(|v| serde_json::to_string(v).unwrap_or_else(|_| "[]".to_string()))
             .unwrap_or_else(|| "[]".to_string());
 
+        let secrets_json = data.secrets.as_ref()
+            .map(|v| serde_json::to_string(v).unwrap_or_else(|_| "[]".to_string()))
+            .unwrap_or_else(|| "[]".to_string());
+
         let row = sqlx::query(
-            "INSERT INTO swarm_tasks (id, swarm_id, title, description, priority, depends_on, tags)
-             VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "INSERT INTO swarm_tasks (id, swarm_id, title, description, status, priority, depends_on, tags, pinned_sandbox_id, decompose, estimate_minutes, secrets, write_result_to)
+             VALUES ($1, $2, $3, $4,
+                     COALESCE((SELECT CASE WHEN require_approval THEN 'blocked' END FROM swarms WHERE id = $2), 'pending'),
+                     $5, $6, $7, $8, $9, $10, $11, $12)
              RETURNING id, swarm_id, title, description, status, priority, sandbox_id,
                        depends_on, triggers_after, result, error, tags,
-                       started_at, completed_at, created_at, updated_at"
+                       started_at, completed_at, created_at, updated_at,
+                       pinned_sandbox_id, decompose, parent_task_id, is_coordinator, estimate_minutes,
+                       approved_by, approved_at, secrets, write_result_to, external_id, attempts, version"
         )
         .bind(task_id)
         .bind(swarm_id)
@@ -240,13 +517,172 @@ impl SwarmTask {
         .bind(&priority_str)
         .bind(&depends_on_json)
         .bind(&tags_json)
-        .fetch_one(pool)
+        .bind(data.pinned_sandbox_id)
+        .bind(data.decompose.unwrap_or(false))
+        .bind(data.estimate_minutes)
+        .bind(&secrets_json)
+        .bind(&data.write_result_to)
+        .fetch_one(executor)
+        .await?;
+
+        Self::from_row(row)
+    }
+
+    /// Insert a task exactly as given, keeping its status and timestamps
+    ///
+    /// Used when importing a swarm bundle. `parent_task_id` is not written, as
+    /// the parent may not exist yet; set it afterwards with `set_parent_task`.
+    pub async fn restore<'e, E>(executor: E, task: &SwarmTask) -> Result<Self, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let to_json = |v: &[Uuid]| serde_json::to_string(v).unwrap_or_else(|_| "[]".to_string());
+        let depends_on_json = task.depends_on.as_deref().map(to_json);
+        let triggers_json = task.triggers_after.as_deref().map(to_json);
+        let tags_json = serde_json::to_string(&task.tags).unwrap_or_else(|_| "[]".to_string());
+        let secrets_json = serde_json::to_string(&task.secrets).unwrap_or_else(|_| "[]".to_string());
+
+        let row = sqlx::query(
+            "INSERT INTO swarm_tasks (id, swarm_id, title, description, status, priority, sandbox_id,
+                                      depends_on, triggers_after, result, error, tags,
+                                      started_at, completed_at, created_at, updated_at,
+                                      pinned_sandbox_id, decompose, is_coordinator, estimate_minutes,
+                                      approved_by, approved_at, secrets, write_result_to, external_id)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12,
+                     $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25)
+             RETURNING id, swarm_id, title, description, status, priority, sandbox_id,
+                       depends_on, triggers_after, result, error, tags,
+                       started_at, completed_at, created_at, updated_at,
+                       pinned_sandbox_id, decompose, parent_task_id, is_coordinator, estimate_minutes,
+                       approved_by, approved_at, secrets, write_result_to, external_id, attempts, version"
+        )
+        .bind(task.id)
+        .bind(task.swarm_id)
+        .bind(&task.title)
+        .bind(&task.description)
+        .bind(task.status.to_string())
+        .bind(task.priority.to_string())
+        .bind(&task.sandbox_id)
+        .bind(&depends_on_json)
+        .bind(&triggers_json)
+        .bind(&task.result)
+        .bind(&task.error)
+        .bind(&tags_json)
+        .bind(task.started_at)
+        .bind(task.completed_at)
+        .bind(task.created_at)
+        .bind(task.updated_at)
+        .bind(task.pinned_sandbox_id)
+        .bind(task.decompose)
+        .bind(task.is_coordinator)
+        .bind(task.estimate_minutes)
+        .bind(&task.approved_by)
+        .bind(task.approved_at)
+        .bind(&secrets_json)
+        .bind(&task.write_result_to)
+        .bind(&task.external_id)
+        .fetch_one(executor)
         .await?;
 
         Self::from_row(row)
     }
 
-    pub async fn update(pool: &SqlitePool, id: Uuid, data: &UpdateSwarmTask) -> Result<Self, sqlx::Error> {
+    /// Overwrite a task's definition with `task`'s, keeping its run state
+    ///
+    /// Status, sandbox, result and timestamps are left as they are.
+    pub async fn replace_definition<'e, E>(executor: E, id: Uuid, task: &SwarmTask) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let to_json = |v: &[Uuid]| serde_json::to_string(v).unwrap_or_else(|_| "[]".to_string());
+        let depends_on_json = task.depends_on.as_deref().map(to_json);
+        let triggers_json = task.triggers_after.as_deref().map(to_json);
+        let tags_json = serde_json::to_string(&task.tags).unwrap_or_else(|_| "[]".to_string());
+        let secrets_json = serde_json::to_string(&task.secrets).unwrap_or_else(|_| "[]".to_string());
+
+        sqlx::query(
+            "UPDATE swarm_tasks
+             SET title = $2, description = $3, priority = $4, depends_on = $5, triggers_after = $6,
+                 tags = $7, decompose = $8, estimate_minutes = $9, secrets = $10, write_result_to = $11,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1"
+        )
+        .bind(id)
+        .bind(&task.title)
+        .bind(&task.description)
+        .bind(task.priority.to_string())
+        .bind(&depends_on_json)
+        .bind(&triggers_json)
+        .bind(&tags_json)
+        .bind(task.decompose)
+        .bind(task.estimate_minutes)
+        .bind(&secrets_json)
+        .bind(&task.write_result_to)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Replace the list of tasks whose completion triggers this one
+    pub async fn set_triggers_after<'e, E>(executor: E, id: Uuid, triggers_after: &[Uuid]) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let triggers_json = serde_json::to_string(triggers_after).unwrap_or_else(|_| "[]".to_string());
+
+        sqlx::query(
+            "UPDATE swarm_tasks SET triggers_after = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1"
+        )
+        .bind(id)
+        .bind(&triggers_json)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Record the coordinator task a subtask was planned from
+    pub async fn set_parent_task<'e, E>(executor: E, id: Uuid, parent_task_id: Uuid) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query(
+            "UPDATE swarm_tasks SET parent_task_id = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1"
+        )
+        .bind(id)
+        .bind(parent_task_id)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Turn a decomposed task into a coordinator waiting on its subtasks
+    ///
+    /// The task goes back to pending with `depends_on` set to the subtasks, so
+    /// anything depending on it keeps waiting until the whole plan is done.
+    pub async fn mark_coordinator<'e, E>(executor: E, id: Uuid, subtask_ids: &[Uuid]) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let depends_on_json = serde_json::to_string(subtask_ids).unwrap_or_else(|_| "[]".to_string());
+
+        sqlx::query(
+            "UPDATE swarm_tasks
+             SET is_coordinator = 1, decompose = 0, depends_on = $2, status = 'pending',
+                 sandbox_id = NULL, started_at = NULL, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1"
+        )
+        .bind(id)
+        .bind(&depends_on_json)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Apply `data` to a task, leaving unset fields unchanged
+    ///
+    /// With `expected_version` the write only happens if the task is still at
+    /// that version, and `UpdateError::Conflict` is returned otherwise.
+    pub async fn update(pool: &SqlitePool, id: Uuid, data: &UpdateSwarmTask) -> Result<Self, UpdateError> {
         let existing = Self::find_by_id(pool, id)
             .await?
             .ok_or(sqlx::Error::RowNotFound)?;
@@ -260,6 +696,8 @@ impl SwarmTask {
         let sandbox_id = data.sandbox_id.clone().or(existing.sandbox_id);
         let result = data.result.clone().or(existing.result);
         let error = data.error.clone().or(existing.error);
+        let estimate_minutes = data.estimate_minutes.or(existing.estimate_minutes);
+        let write_result_to = data.write_result_to.clone().or(existing.write_result_to);
 
         let depends_on_json = data.depends_on.as_ref()
             .map(|v| serde_json::to_string(v).unwrap_or_else(|_| "[]".to_string()))
@@ -273,15 +711,23 @@ impl SwarmTask {
             .map(|v| serde_json::to_string(v).unwrap_or_else(|_| "[]".to_string()))
             .unwrap_or_else(|| serde_json::to_string(&existing.tags).unwrap_or_else(|_| "[]".to_string()));
 
+        let secrets_json = data.secrets.as_ref()
+            .map(|v| serde_json::to_string(v).unwrap_or_else(|_| "[]".to_string()))
+            .unwrap_or_else(|| serde_json::to_string(&existing.secrets).unwrap_or_else(|_| "[]".to_string()));
+
         let row = sqlx::query(
             "UPDATE swarm_tasks
              SET title = $2, description = $3, status = $4, priority = $5,
                  sandbox_id = $6, depends_on = $7, triggers_after = $8,
-                 result = $9, error = $10, tags = $11, updated_at = CURRENT_TIMESTAMP
-             WHERE id = $1
+                 result = $9, error = $10, tags = $11, estimate_minutes = $12,
+                 secrets = $13, write_result_to = $14, updated_at = CURRENT_TIMESTAMP,
+                 version = version + 1
+             WHERE id = $1 AND ($15 IS NULL OR version = $15)
              RETURNING id, swarm_id, title, description, status, priority, sandbox_id,
                        depends_on, triggers_after, result, error, tags,
-                       started_at, completed_at, created_at, updated_at"
+                       started_at, completed_at, created_at, updated_at,
+                       pinned_sandbox_id, decompose, parent_task_id, is_coordinator, estimate_minutes,
+                       approved_by, approved_at, secrets, write_result_to, external_id, attempts, version"
         )
         .bind(id)
         .bind(&title)
@@ -294,10 +740,18 @@ impl SwarmTask {
         .bind(&result)
         .bind(&error)
         .bind(&tags_json)
-        .fetch_one(pool)
+        .bind(estimate_minutes)
+        .bind(&secrets_json)
+        .bind(&write_result_to)
+        .bind(data.expected_version)
+        .fetch_optional(pool)
         .await?;
 
-        Self::from_row(row)
+        match row {
+            Some(row) => Ok(Self::from_row(row)?),
+            None if data.expected_version.is_some() => Err(UpdateError::Conflict),
+            None => Err(sqlx::Error::RowNotFound.into()),
+        }
     }
 
     pub async fn update_status(pool: &SqlitePool, id: Uuid, status: SwarmTaskStatus) -> Result<(), sqlx::Error> {
@@ -380,10 +834,13 @@ impl SwarmTask {
         Ok(result.rows_affected())
     }
 
-    pub async fn delete_by_swarm_id(pool: &SqlitePool, swarm_id: Uuid) -> Result<u64, sqlx::Error> {
+    pub async fn delete_by_swarm_id<'e, E>(executor: E, swarm_id: Uuid) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         let result = sqlx::query("DELETE FROM swarm_tasks WHERE swarm_id = $1")
             .bind(swarm_id)
-            .execute(pool)
+            .execute(executor)
             .await?;
         Ok(result.rows_affected())
     }
@@ -402,6 +859,62 @@ impl SwarmTask {
         Ok(())
     }
 
+    /// Move a running task back to pending so it is dispatched again
+    ///
+    /// Only applies while the task is still running. Returns whether the task was requeued.
+    pub async fn requeue<'e, E>(executor: E, id: Uuid) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query(
+            "UPDATE swarm_tasks
+             SET status = 'pending', sandbox_id = NULL, started_at = NULL,
+                 error = NULL, result = NULL, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND status = 'running'"
+        )
+        .bind(id)
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Move a running task back to pending, pinned to another sandbox
+    ///
+    /// Only applies while the task is still running, so a task that finished
+    /// concurrently is left alone. Returns whether the task was requeued.
+    pub async fn requeue_on_sandbox<'e, E>(executor: E, id: Uuid, sandbox_id: Uuid) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query(
+            "UPDATE swarm_tasks
+             SET status = 'pending', sandbox_id = NULL, pinned_sandbox_id = $2, started_at = NULL,
+                 error = NULL, result = NULL, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND status = 'running'"
+        )
+        .bind(id)
+        .bind(sandbox_id)
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Mark a task cancelled unless it has already finished
+    ///
+    /// The sandbox stays assigned so a running task can still be stopped.
+    /// Returns whether the task was cancelled.
+    pub async fn cancel(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE swarm_tasks
+             SET status = 'cancelled', completed_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND status NOT IN ('completed', 'failed', 'cancelled')"
+        )
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Complete a task - set status to completed, save result, set completed_at
     pub async fn complete_task(pool: &SqlitePool, id: Uuid, result: Option<&str>) -> Result<(), sqlx::Error> {
         sqlx::query(
@@ -430,6 +943,78 @@ impl SwarmTask {
         Ok(())
     }
 
+    /// Fail a task only if it is still running
+    ///
+    /// Returns whether the task was failed; a task that finished or was
+    /// cancelled in the meantime keeps its status.
+    pub async fn fail_if_running(pool: &SqlitePool, id: Uuid, error: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE swarm_tasks
+             SET status = 'failed', error = $2, completed_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND status = 'running'"
+        )
+        .bind(id)
+        .bind(error)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Complete a task only if it is still running in the given sandbox
+    ///
+    /// Returns whether the task was completed; a task that was cancelled,
+    /// failed or reassigned while the run finished keeps its status.
+    pub async fn complete_if_running_on(
+        pool: &SqlitePool,
+        id: Uuid,
+        sandbox_id: &str,
+        result: Option<&str>,
+    ) -> Result<bool, sqlx::Error> {
+        let rows = sqlx::query(
+            "UPDATE swarm_tasks
+             SET status = 'completed', result = $3, completed_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND status = 'running' AND sandbox_id = $2"
+        )
+        .bind(id)
+        .bind(sandbox_id)
+        .bind(result)
+        .execute(pool)
+        .await?;
+        Ok(rows.rows_affected() > 0)
+    }
+
+    /// Fail a task only if it is still running in the given sandbox
+    ///
+    /// Returns whether the task was failed, as with `complete_if_running_on`.
+    pub async fn fail_if_running_on(
+        pool: &SqlitePool,
+        id: Uuid,
+        sandbox_id: &str,
+        error: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE swarm_tasks
+             SET status = 'failed', error = $3, completed_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND status = 'running' AND sandbox_id = $2"
+        )
+        .bind(id)
+        .bind(sandbox_id)
+        .bind(error)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record how many execution attempts a run took
+    pub async fn set_attempts(pool: &SqlitePool, id: Uuid, attempts: i32) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE swarm_tasks SET attempts = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1")
+            .bind(id)
+            .bind(attempts)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
     /// Release sandbox from task - clear sandbox_id
     pub async fn release_sandbox(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
         sqlx::query(
@@ -441,19 +1026,45 @@ impl SwarmTask {
         Ok(())
     }
 
+    /// Clear the task's sandbox only if it is still the given one
+    ///
+    /// Used when a run winds down, so a task that was reassigned and
+    /// started elsewhere in the meantime keeps its new sandbox.
+    pub async fn release_sandbox_if_assigned(pool: &SqlitePool, id: Uuid, sandbox_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE swarm_tasks SET sandbox_id = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = $1 AND sandbox_id = $2"
+        )
+        .bind(id)
+        .bind(sandbox_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
     /// Check if all task dependencies are complete
+    ///
+    /// Tasks listed in `triggers_after` count as dependencies too: a chained
+    /// task starts only once every task it follows has completed.
     /// Uses a single query to fetch all dependencies (avoids N+1 problem)
     pub async fn are_dependencies_complete(pool: &SqlitePool, task: &SwarmTask) -> Result<bool, sqlx::Error> {
-        let depends_on = match &task.depends_on {
-            Some(deps) if !deps.is_empty() => deps,
-            _ => return Ok(true),
-        };
+        let mut dependencies: Vec<Uuid> = task
+            .depends_on
+            .iter()
+            .chain(&task.triggers_after)
+            .flatten()
+            .copied()
+            .collect();
+        dependencies.sort();
+        dependencies.dedup();
+        if dependencies.is_empty() {
+            return Ok(true);
+        }
 
         // Fetch all dependency tasks in a single query
-        let dep_tasks = Self::find_by_ids(pool, depends_on).await?;
+        let dep_tasks = Self::find_by_ids(pool, &dependencies).await?;
 
         // If we didn't find all dependencies, some are missing - consider incomplete
-        if dep_tasks.len() != depends_on.len() {
+        if dep_tasks.len() != dependencies.len() {
             return Ok(false);
         }
 
@@ -461,29 +1072,141 @@ impl SwarmTask {
         Ok(dep_tasks.iter().all(|t| t.status == SwarmTaskStatus::Completed))
     }
 
+    /// Whether giving `task_id` the upstream tasks `new_deps` would close a dependency cycle
+    ///
+    /// `new_deps` replaces the task's current `depends_on` and `triggers_after`
+    /// entries; both kinds of edge hold a task back, so a cycle through either
+    /// leaves its tasks waiting forever. A task that is new has no downstream
+    /// tasks yet and can only close a cycle by depending on itself.
+    pub async fn would_create_cycle(
+        pool: &SqlitePool,
+        swarm_id: Uuid,
+        task_id: Uuid,
+        new_deps: &[Uuid],
+    ) -> Result<bool, sqlx::Error> {
+        let rows = sqlx::query("SELECT id, depends_on, triggers_after FROM swarm_tasks WHERE swarm_id = $1")
+            .bind(swarm_id)
+            .fetch_all(pool)
+            .await?;
+
+        let mut upstream: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for row in rows {
+            let id: Uuid = row.try_get("id")?;
+            let mut deps = Vec::new();
+            for column in ["depends_on", "triggers_after"] {
+                if let Some(ids) = row
+                    .try_get::<Option<String>, _>(column)?
+                    .and_then(|s| serde_json::from_str::<Vec<Uuid>>(&s).ok())
+                {
+                    deps.extend(ids);
+                }
+            }
+            upstream.insert(id, deps);
+        }
+        upstream.insert(task_id, new_deps.to_vec());
+
+        // Depth-first walk upstream from the task; reaching it again closes a cycle
+        let mut visited = HashSet::new();
+        let mut stack: Vec<Uuid> = new_deps.to_vec();
+        while let Some(id) = stack.pop() {
+            if id == task_id {
+                return Ok(true);
+            }
+            if !visited.insert(id) {
+                continue;
+            }
+            if let Some(deps) = upstream.get(&id) {
+                stack.extend(deps.iter().copied());
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Tasks whose `triggers_after` lists `task_id`
+    pub async fn find_triggered_by(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, swarm_id, title, description, status, priority, sandbox_id,
+                    depends_on, triggers_after, result, error, tags,
+                    started_at, completed_at, created_at, updated_at,
+                    pinned_sandbox_id, decompose, parent_task_id, is_coordinator, estimate_minutes,
+                    approved_by, approved_at, secrets, write_result_to, external_id, attempts, version
+             FROM swarm_tasks
+             WHERE triggers_after IS NOT NULL
+               AND EXISTS (SELECT 1 FROM json_each(swarm_tasks.triggers_after) WHERE json_each.value = $1)
+             ORDER BY created_at ASC"
+        )
+        .bind(task_id.to_string())
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    /// Approve a blocked task, moving it to pending so it can be dispatched
+    ///
+    /// Returns false if the task was not blocked.
+    pub async fn approve(pool: &SqlitePool, id: Uuid, approved_by: Option<&str>) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE swarm_tasks
+             SET status = 'pending', approved_by = $2, approved_at = CURRENT_TIMESTAMP,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1 AND status = 'blocked'"
+        )
+        .bind(id)
+        .bind(approved_by)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Retry a failed task - reset status to pending, clear error/result/sandbox
-    pub async fn retry_task(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+    ///
+    /// A task that was never approved goes back to blocked if its swarm
+    /// requires approval.
+    pub async fn retry_task<'e, E>(executor: E, id: Uuid) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         sqlx::query(
             "UPDATE swarm_tasks
-             SET status = 'pending', sandbox_id = NULL, error = NULL, result = NULL,
+             SET status = CASE
+                     WHEN approved_at IS NULL
+                          AND (SELECT require_approval FROM swarms WHERE id = swarm_tasks.swarm_id)
+                     THEN 'blocked' ELSE 'pending'
+                 END,
+                 sandbox_id = NULL, error = NULL, result = NULL,
                  started_at = NULL, completed_at = NULL, updated_at = CURRENT_TIMESTAMP
              WHERE id = $1"
         )
         .bind(id)
-        .execute(pool)
+        .execute(executor)
         .await?;
         Ok(())
     }
 
+    /// Count all tasks in a swarm
+    pub async fn count_by_swarm_id(pool: &SqlitePool, swarm_id: Uuid) -> Result<i64, sqlx::Error> {
+        let row = sqlx::query("SELECT COUNT(*) as count FROM swarm_tasks WHERE swarm_id = $1")
+            .bind(swarm_id)
+            .fetch_one(pool)
+            .await?;
+
+        row.try_get::<i64, _>("count")
+    }
+
     /// Count tasks by status for a swarm
     pub async fn count_by_status(pool: &SqlitePool, swarm_id: Uuid) -> Result<TaskStatusCounts, sqlx::Error> {
         let row = sqlx::query(
             "SELECT
+                COUNT(CASE WHEN status = 'blocked' THEN 1 END) as blocked,
                 COUNT(CASE WHEN status = 'pending' THEN 1 END) as pending,
                 COUNT(CASE WHEN status = 'running' THEN 1 END) as running,
                 COUNT(CASE WHEN status = 'completed' THEN 1 END) as completed,
                 COUNT(CASE WHEN status = 'failed' THEN 1 END) as failed,
-                COUNT(CASE WHEN status = 'cancelled' THEN 1 END) as cancelled
+                COUNT(CASE WHEN status = 'cancelled' THEN 1 END) as cancelled,
+                COALESCE(SUM(CASE WHEN status IN ('pending', 'running') THEN estimate_minutes END), 0)
+                    as remaining_estimate_minutes
              FROM swarm_tasks
              WHERE swarm_id = $1"
         )
@@ -492,11 +1215,13 @@ impl SwarmTask {
         .await?;
 
         Ok(TaskStatusCounts {
+            blocked: row.try_get::<i64, _>("blocked")? as usize,
             pending: row.try_get::<i64, _>("pending")? as usize,
             running: row.try_get::<i64, _>("running")? as usize,
             completed: row.try_get::<i64, _>("completed")? as usize,
             failed: row.try_get::<i64, _>("failed")? as usize,
             cancelled: row.try_get::<i64, _>("cancelled")? as usize,
+            remaining_estimate_minutes: row.try_get("remaining_estimate_minutes")?,
         })
     }
 }
@@ -504,9 +1229,12 @@ impl SwarmTask {
 /// Task status counts for a swarm
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct TaskStatusCounts {
+    pub blocked: usize,
     pub pending: usize,
     pub running: usize,
     pub completed: usize,
     pub failed: usize,
     pub cancelled: usize,
+    /// Sum of estimates of pending and running tasks; unestimated tasks count as 0
+    pub remaining_estimate_minutes: i64,
 }
@@ -0,0 +1,71 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A human note left on a swarm task
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct SwarmTaskComment {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub author: String,
+    pub body: String,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(deny_unknown_fields)]
+pub struct CreateSwarmTaskComment {
+    pub author: String,
+    pub body: String,
+}
+
+impl SwarmTaskComment {
+    fn from_row(row: sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            task_id: row.try_get("task_id")?,
+            author: row.try_get("author")?,
+            body: row.try_get("body")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+
+    /// Comments on a task, oldest first
+    pub async fn find_by_task_id(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, task_id, author, body, created_at
+             FROM swarm_task_comments
+             WHERE task_id = $1
+             ORDER BY created_at ASC, rowid ASC"
+        )
+        .bind(task_id)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        data: &CreateSwarmTaskComment,
+        comment_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let row = sqlx::query(
+            "INSERT INTO swarm_task_comments (id, task_id, author, body)
+             VALUES ($1, $2, $3, $4)
+             RETURNING id, task_id, author, body, created_at"
+        )
+        .bind(comment_id)
+        .bind(task_id)
+        .bind(&data.author)
+        .bind(&data.body)
+        .fetch_one(pool)
+        .await?;
+
+        Self::from_row(row)
+    }
+}
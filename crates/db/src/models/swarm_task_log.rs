@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use super::swarm_config::LogCompression;
+
+/// One attempt's log as stored, possibly compressed
+#[derive(Debug, Clone)]
+pub struct SwarmTaskLog {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub attempt: i32,
+    /// How `content` is compressed
+    pub encoding: LogCompression,
+    pub content: Vec<u8>,
+    /// Size of the uncompressed log
+    pub byte_size: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateSwarmTaskLog {
+    pub task_id: Uuid,
+    pub attempt: i32,
+    pub encoding: LogCompression,
+    pub content: Vec<u8>,
+    pub byte_size: i64,
+}
+
+impl SwarmTaskLog {
+    fn from_row(row: sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        let encoding_str: String = row.try_get("encoding")?;
+        let encoding = encoding_str
+            .parse::<LogCompression>()
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            task_id: row.try_get("task_id")?,
+            attempt: row.try_get("attempt")?,
+            encoding,
+            content: row.try_get("content")?,
+            byte_size: row.try_get("byte_size")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+
+    pub async fn create(pool: &SqlitePool, data: &CreateSwarmTaskLog) -> Result<Self, sqlx::Error> {
+        let row = sqlx::query(
+            "INSERT INTO swarm_task_logs (id, task_id, attempt, encoding, content, byte_size)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             RETURNING id, task_id, attempt, encoding, content, byte_size, created_at"
+        )
+        .bind(Uuid::new_v4())
+        .bind(data.task_id)
+        .bind(data.attempt)
+        .bind(data.encoding.to_string())
+        .bind(&data.content)
+        .bind(data.byte_size)
+        .fetch_one(pool)
+        .await?;
+
+        Self::from_row(row)
+    }
+
+    /// All stored logs of a task, oldest attempt first
+    pub async fn find_by_task_id(pool: &SqlitePool, task_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, task_id, attempt, encoding, content, byte_size, created_at
+             FROM swarm_task_logs
+             WHERE task_id = $1
+             ORDER BY attempt ASC, created_at ASC"
+        )
+        .bind(task_id)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A webhook event that failed every delivery attempt
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct WebhookDeadLetter {
+    pub id: Uuid,
+    pub url: String,
+    pub event: String,
+    /// JSON body that was sent
+    pub payload: String,
+    /// Delivery attempts so far, including redeliveries
+    pub attempts: i32,
+    pub last_error: String,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateWebhookDeadLetter {
+    pub url: String,
+    pub event: String,
+    pub payload: String,
+    pub attempts: i32,
+    pub last_error: String,
+}
+
+impl WebhookDeadLetter {
+    fn from_row(row: sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            url: row.try_get("url")?,
+            event: row.try_get("event")?,
+            payload: row.try_get("payload")?,
+            attempts: row.try_get("attempts")?,
+            last_error: row.try_get("last_error")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+
+    pub async fn create(pool: &SqlitePool, data: &CreateWebhookDeadLetter) -> Result<Self, sqlx::Error> {
+        let row = sqlx::query(
+            "INSERT INTO webhook_dead_letters (id, url, event, payload, attempts, last_error)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             RETURNING id, url, event, payload, attempts, last_error, created_at, updated_at"
+        )
+        .bind(Uuid::new_v4())
+        .bind(&data.url)
+        .bind(&data.event)
+        .bind(&data.payload)
+        .bind(data.attempts)
+        .bind(&data.last_error)
+        .fetch_one(pool)
+        .await?;
+
+        Self::from_row(row)
+    }
+
+    /// Most recent dead letters first
+    pub async fn find_recent(pool: &SqlitePool, limit: i64) -> Result<Vec<Self>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, url, event, payload, attempts, last_error, created_at, updated_at
+             FROM webhook_dead_letters
+             ORDER BY created_at DESC, rowid DESC
+             LIMIT $1"
+        )
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(Self::from_row).collect()
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT id, url, event, payload, attempts, last_error, created_at, updated_at
+             FROM webhook_dead_letters
+             WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(Self::from_row).transpose()
+    }
+
+    /// Record another failed redelivery
+    pub async fn record_failure(
+        pool: &SqlitePool,
+        id: Uuid,
+        attempts: i32,
+        last_error: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let row = sqlx::query(
+            "UPDATE webhook_dead_letters
+             SET attempts = attempts + $2, last_error = $3, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1
+             RETURNING id, url, event, payload, attempts, last_error, created_at, updated_at"
+        )
+        .bind(id)
+        .bind(attempts)
+        .bind(last_error)
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(Self::from_row).transpose()
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM webhook_dead_letters WHERE id = $1")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
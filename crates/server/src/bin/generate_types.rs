@@ -62,6 +62,7 @@ fn generate_types_content() -> String {
         utils::diff::Diff::decl(),
         utils::diff::DiffChangeKind::decl(),
         utils::response::ApiResponse::<()>::decl(),
+        utils::response::ErrorCode::decl(),
         utils::api::oauth::LoginStatus::decl(),
         utils::api::oauth::ProfileResponse::decl(),
         utils::api::oauth::ProviderProfile::decl(),
@@ -224,23 +225,50 @@ fn generate_types_content() -> String {
         db::models::swarm_config::SwarmConfig::decl(),
         db::models::swarm_config::SwarmConfigWithMaskedSecrets::decl(),
         db::models::swarm_config::UpdateSwarmConfig::decl(),
+        db::models::swarm_config::ChatResultMode::decl(),
+        db::models::swarm_config::LogCompression::decl(),
         db::models::swarm_chat::SwarmChat::decl(),
         db::models::swarm_chat::SenderType::decl(),
         db::models::swarm_chat::CreateSwarmChat::decl(),
+        db::models::swarm_chat::MessageMetadata::decl(),
         db::models::sandbox::Sandbox::decl(),
         db::models::sandbox::SandboxStatus::decl(),
         db::models::sandbox::CreateSandbox::decl(),
+        db::models::sandbox_command::SandboxCommand::decl(),
+        db::models::pool_status_history::PoolStatusSample::decl(),
         db::models::swarm_task::SwarmTask::decl(),
         db::models::swarm_task::SwarmTaskStatus::decl(),
         db::models::swarm_task::TaskPriority::decl(),
         db::models::swarm_task::CreateSwarmTask::decl(),
         db::models::swarm_task::UpdateSwarmTask::decl(),
         db::models::swarm_task::TaskStatusCounts::decl(),
+        db::models::swarm_task_comment::SwarmTaskComment::decl(),
+        db::models::swarm_task_comment::CreateSwarmTaskComment::decl(),
+        db::models::swarm_secret::SwarmSecret::decl(),
+        db::models::swarm_secret::SetSwarmSecret::decl(),
+        db::models::webhook_dead_letter::WebhookDeadLetter::decl(),
         services::services::swarm::PoolStatus::decl(),
         services::services::swarm::PoolConfig::decl(),
         services::services::swarm::PoolStats::decl(),
         services::services::swarm::SandboxInfo::decl(),
-        services::services::swarm::MessageMetadata::decl(),
+        services::services::swarm::LogSource::decl(),
+        services::services::swarm::LogLevel::decl(),
+        services::services::swarm::LogEntry::decl(),
+        services::services::swarm::LogEnd::decl(),
+        services::services::swarm::ChatBroadcastMessage::decl(),
+        services::services::swarm::ChatMessageData::decl(),
+        services::services::swarm::PoolStatusUpdate::decl(),
+        services::services::swarm::TaskPlan::decl(),
+        services::services::swarm::PlannedSkill::decl(),
+        services::services::swarm::PlannedCli::decl(),
+        services::services::swarm::TaskLog::decl(),
+        services::services::swarm::TriggerStats::decl(),
+        server::routes::swarm::ws::WsMessage::decl(),
+        server::routes::swarm::ws::WsErrorDetail::decl(),
+        server::routes::swarm::chat::ChatPage::decl(),
+        server::routes::swarm::trigger::TriggerStatsResponse::decl(),
+        server::routes::swarm::pool::SandboxLive::decl(),
+        server::routes::swarm::tasks::TaskStats::decl(),
     ];
 
     let body = decls
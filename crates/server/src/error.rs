@@ -1,5 +1,6 @@
 use axum::{
     Json,
+    extract::rejection::JsonRejection,
     extract::multipart::MultipartError,
     http::StatusCode,
     response::{IntoResponse, Response},
@@ -7,7 +8,7 @@ use axum::{
 use db::models::{
     execution_process::ExecutionProcessError, project::ProjectError,
     project_repo::ProjectRepoError, repo::RepoError, scratch::ScratchError, session::SessionError,
-    workspace::WorkspaceError,
+    workspace::WorkspaceError, UpdateError,
 };
 use deployment::{DeploymentError, RemoteClientNotConfigured};
 use executors::{command::CommandBuildError, executors::ExecutorError};
@@ -22,10 +23,11 @@ use services::services::{
     project::ProjectServiceError,
     remote_client::RemoteClientError,
     repo::RepoError as RepoServiceError,
+    swarm::{DaytonaError, WebhookError},
     worktree_manager::WorktreeError,
 };
 use thiserror::Error;
-use utils::response::ApiResponse;
+use utils::response::{ApiResponse, ErrorCode};
 
 #[derive(Debug, Error, ts_rs::TS)]
 #[ts(type = "string")]
@@ -68,6 +70,10 @@ pub enum ApiError {
     EditorOpen(#[from] EditorOpenError),
     #[error(transparent)]
     RemoteClient(#[from] RemoteClientError),
+    #[error(transparent)]
+    Daytona(#[from] DaytonaError),
+    #[error(transparent)]
+    Webhook(#[from] WebhookError),
     #[error("Unauthorized")]
     Unauthorized,
     #[error("Bad request: {0}")]
@@ -76,10 +82,49 @@ pub enum ApiError {
     Conflict(String),
     #[error("Forbidden: {0}")]
     Forbidden(String),
+    #[error("Timeout: {0}")]
+    Timeout(String),
     #[error(transparent)]
     CommandBuilder(#[from] CommandBuildError),
     #[error(transparent)]
     Pty(#[from] PtyError),
+    /// Another error tagged with a machine-readable code; its status and message are kept
+    #[error("{1}")]
+    WithCode(ErrorCode, Box<ApiError>),
+}
+
+impl ApiError {
+    /// Tag the error with a machine-readable code for the response body
+    pub fn with_code(self, code: ErrorCode) -> Self {
+        ApiError::WithCode(code, Box::new(self))
+    }
+
+    /// Message of a bad request, with or without a code
+    pub fn bad_request_message(&self) -> Option<&str> {
+        match self {
+            ApiError::BadRequest(msg) => Some(msg),
+            ApiError::WithCode(_, inner) => inner.bad_request_message(),
+            _ => None,
+        }
+    }
+}
+
+/// Code for errors that don't carry a specific one
+fn default_code(status: StatusCode) -> ErrorCode {
+    match status {
+        StatusCode::BAD_REQUEST => ErrorCode::BadRequest,
+        StatusCode::UNAUTHORIZED => ErrorCode::Unauthorized,
+        StatusCode::FORBIDDEN => ErrorCode::Forbidden,
+        StatusCode::NOT_FOUND => ErrorCode::NotFound,
+        StatusCode::CONFLICT => ErrorCode::Conflict,
+        StatusCode::GONE => ErrorCode::Gone,
+        StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+        StatusCode::GATEWAY_TIMEOUT => ErrorCode::Timeout,
+        StatusCode::SERVICE_UNAVAILABLE => ErrorCode::ServiceUnavailable,
+        StatusCode::BAD_GATEWAY => ErrorCode::UpstreamError,
+        status if status.is_client_error() => ErrorCode::BadRequest,
+        _ => ErrorCode::InternalError,
+    }
 }
 
 impl From<&'static str> for ApiError {
@@ -94,6 +139,14 @@ impl From<Git2Error> for ApiError {
     }
 }
 
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        // Keeps serde's detail, e.g. "unknown field `pool_max_sandbox`, expected one of ..."
+        ApiError::BadRequest(format!("Invalid request body: {}", rejection.body_text()))
+            .with_code(ErrorCode::InvalidRequestBody)
+    }
+}
+
 impl From<RemoteClientNotConfigured> for ApiError {
     fn from(_: RemoteClientNotConfigured) -> Self {
         ApiError::BadRequest("Remote client not configured".to_string())
@@ -102,6 +155,20 @@ impl From<RemoteClientNotConfigured> for ApiError {
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
+        // The outermost code wins
+        let mut code = None;
+        let mut error = self;
+        while let ApiError::WithCode(tag, inner) = error {
+            code = code.or(Some(tag));
+            error = *inner;
+        }
+        error.respond(code)
+    }
+}
+
+impl ApiError {
+    /// Answer an error whose code tag has already been unwrapped
+    fn respond(self, code: Option<ErrorCode>) -> Response {
         let (status_code, error_type) = match &self {
             ApiError::Project(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ProjectError"),
             ApiError::Repo(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ProjectRepoError"),
@@ -127,6 +194,10 @@ impl IntoResponse for ApiError {
             ApiError::Container(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ContainerError"),
             ApiError::Executor(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ExecutorError"),
             ApiError::CommandBuilder(_) => (StatusCode::INTERNAL_SERVER_ERROR, "CommandBuildError"),
+            // Pool exhaustion is transient; tell the client to retry instead of a 500
+            ApiError::Database(sqlx::Error::PoolTimedOut) => {
+                (StatusCode::SERVICE_UNAVAILABLE, "DatabaseError")
+            }
             ApiError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DatabaseError"),
             ApiError::Worktree(_) => (StatusCode::INTERNAL_SERVER_ERROR, "WorktreeError"),
             ApiError::Config(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ConfigError"),
@@ -176,15 +247,26 @@ impl IntoResponse for ApiError {
                     (StatusCode::BAD_REQUEST, "RemoteClientError")
                 }
             },
+            ApiError::Daytona(err) => match err {
+                DaytonaError::Timeout(_) => (StatusCode::GATEWAY_TIMEOUT, "DaytonaError"),
+                DaytonaError::SandboxNotFound(_) => (StatusCode::NOT_FOUND, "DaytonaError"),
+                DaytonaError::Config(_) | DaytonaError::CommandRejected(_) => {
+                    (StatusCode::BAD_REQUEST, "DaytonaError")
+                }
+                _ => (StatusCode::BAD_GATEWAY, "DaytonaError"),
+            },
+            ApiError::Webhook(_) => (StatusCode::BAD_GATEWAY, "WebhookError"),
             ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized"),
             ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, "BadRequest"),
             ApiError::Conflict(_) => (StatusCode::CONFLICT, "ConflictError"),
             ApiError::Forbidden(_) => (StatusCode::FORBIDDEN, "ForbiddenError"),
+            ApiError::Timeout(_) => (StatusCode::GATEWAY_TIMEOUT, "TimeoutError"),
             ApiError::Pty(err) => match err {
                 PtyError::SessionNotFound(_) => (StatusCode::NOT_FOUND, "PtyError"),
                 PtyError::SessionClosed => (StatusCode::GONE, "PtyError"),
                 _ => (StatusCode::INTERNAL_SERVER_ERROR, "PtyError"),
             },
+            ApiError::WithCode(..) => unreachable!("codes are unwrapped by into_response"),
         };
 
         let error_message = match &self {
@@ -259,17 +341,31 @@ impl IntoResponse for ApiError {
                 RemoteClientError::Serde(_) => "Unexpected response from remote service.".to_string(),
                 RemoteClientError::Url(_) => "Remote service URL is invalid.".to_string(),
             },
+            ApiError::Database(sqlx::Error::PoolTimedOut) => {
+                "Database is busy. Please try again.".to_string()
+            }
             ApiError::Unauthorized => "Unauthorized. Please sign in again.".to_string(),
             ApiError::BadRequest(msg) => msg.clone(),
             ApiError::Conflict(msg) => msg.clone(),
             ApiError::Forbidden(msg) => msg.clone(),
+            ApiError::Timeout(msg) => msg.clone(),
             _ => format!("{}: {}", error_type, self),
         };
-        let response = ApiResponse::<()>::error(&error_message);
+        let code = code.unwrap_or_else(|| default_code(status_code));
+        let response = ApiResponse::<()>::error_with_code(&error_message, code);
         (status_code, Json(response)).into_response()
     }
 }
 
+impl From<UpdateError> for ApiError {
+    fn from(err: UpdateError) -> Self {
+        match err {
+            UpdateError::Database(db_err) => ApiError::Database(db_err),
+            conflict @ UpdateError::Conflict => ApiError::Conflict(conflict.to_string()),
+        }
+    }
+}
+
 impl From<ProjectServiceError> for ApiError {
     fn from(err: ProjectServiceError) -> Self {
         match err {
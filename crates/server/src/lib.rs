@@ -5,20 +5,36 @@ pub mod routes;
 
 use std::sync::Arc;
 
-use services::services::swarm::BroadcastManager;
+use routes::swarm::pool::LiveStateCache;
+use services::services::swarm::{BroadcastManager, TriggerEngine};
 use sqlx::SqlitePool;
+use tokio::sync::Semaphore;
 
 // #[cfg(feature = "cloud")]
 // type DeploymentImpl = vibe_kanban_cloud::deployment::CloudDeployment;
 // #[cfg(not(feature = "cloud"))]
 pub type DeploymentImpl = local_deployment::LocalDeployment;
 
+/// Sandboxes that may be created in Daytona at the same time
+pub const MAX_CONCURRENT_SANDBOX_CREATIONS: usize = 4;
+
+/// Skill directory scans that may run on the blocking pool at the same time
+pub const MAX_CONCURRENT_SKILL_SCANS: usize = 4;
+
 /// Application state for swarm routes
 #[derive(Clone)]
 pub struct AppState {
     pub db_pool: SqlitePool,
     /// Broadcast manager for WebSocket streams
     pub broadcast: Arc<BroadcastManager>,
+    /// Throttles sandbox creation so bursts don't overwhelm Daytona
+    pub sandbox_creation: Arc<Semaphore>,
+    /// Caps skill filesystem scans so request bursts can't exhaust the blocking pool
+    pub skill_scans: Arc<Semaphore>,
+    /// Recent live sandbox lookups from Daytona
+    pub live_states: Arc<LiveStateCache>,
+    /// Trigger engine dispatching swarm tasks, if one runs in this process
+    pub trigger: Option<Arc<TriggerEngine>>,
 }
 
 impl AppState {
@@ -26,11 +42,28 @@ impl AppState {
         Self {
             db_pool,
             broadcast: Arc::new(BroadcastManager::new()),
+            sandbox_creation: Arc::new(Semaphore::new(MAX_CONCURRENT_SANDBOX_CREATIONS)),
+            skill_scans: Arc::new(Semaphore::new(MAX_CONCURRENT_SKILL_SCANS)),
+            live_states: Arc::new(LiveStateCache::new()),
+            trigger: None,
         }
     }
 
     /// Create with a custom broadcast manager
     pub fn with_broadcast(db_pool: SqlitePool, broadcast: Arc<BroadcastManager>) -> Self {
-        Self { db_pool, broadcast }
+        Self {
+            db_pool,
+            broadcast,
+            sandbox_creation: Arc::new(Semaphore::new(MAX_CONCURRENT_SANDBOX_CREATIONS)),
+            skill_scans: Arc::new(Semaphore::new(MAX_CONCURRENT_SKILL_SCANS)),
+            live_states: Arc::new(LiveStateCache::new()),
+            trigger: None,
+        }
+    }
+
+    /// Attach the trigger engine running in this process
+    pub fn with_trigger_engine(mut self, engine: Arc<TriggerEngine>) -> Self {
+        self.trigger = Some(engine);
+        self
     }
 }
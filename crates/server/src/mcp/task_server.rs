@@ -3,6 +3,8 @@ use std::{future::Future, str::FromStr};
 use db::models::{
     project::Project,
     repo::Repo,
+    swarm_chat::SwarmChat,
+    swarm_task::{SwarmTask, TaskPriority},
     tag::Tag,
     task::{CreateTask, Task, TaskStatus, TaskWithAttemptStatus, UpdateTask},
     workspace::{Workspace, WorkspaceContext},
@@ -41,6 +43,37 @@ pub struct CreateTaskResponse {
     pub task_id: String,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CreateSwarmTaskRequest {
+    #[schemars(description = "The ID of the swarm to enqueue the task in. This is required!")]
+    pub swarm_id: Uuid,
+    #[schemars(description = "The title of the task")]
+    pub title: String,
+    #[schemars(description = "Optional description of the task; this is the prompt the agent runs")]
+    pub description: Option<String>,
+    #[schemars(description = "Optional priority: 'low', 'medium', 'high', 'urgent' (default: 'medium')")]
+    pub priority: Option<String>,
+    #[schemars(description = "Optional tags, e.g. 'frontend' or 'qa'; they pick the agent role")]
+    pub tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct PostSwarmMessageRequest {
+    #[schemars(description = "The ID of the swarm whose chat to post in. This is required!")]
+    pub swarm_id: Uuid,
+    #[schemars(description = "The ID of the sandbox you are running in. This is required!")]
+    pub sandbox_id: Uuid,
+    #[schemars(description = "The message to post, e.g. a progress report")]
+    pub message: String,
+    #[schemars(description = "Optional agent role to show with the message, e.g. 'backend'")]
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct PostSwarmMessageResponse {
+    pub message_id: String,
+}
+
 #[derive(Debug, Serialize, schemars::JsonSchema)]
 pub struct ProjectSummary {
     #[schemars(description = "The unique identifier of the project")]
@@ -618,6 +651,78 @@ impl TaskServer {
         })
     }
 
+    #[tool(
+        description = "Create a task in a swarm and enqueue it for an agent to run. Use it to split your work into subtasks. `swarm_id` and `title` are required."
+    )]
+    async fn create_swarm_task(
+        &self,
+        Parameters(CreateSwarmTaskRequest {
+            swarm_id,
+            title,
+            description,
+            priority,
+            tags,
+        }): Parameters<CreateSwarmTaskRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let priority = match priority {
+            Some(ref priority_str) => match TaskPriority::from_str(priority_str) {
+                Ok(p) => Some(p),
+                Err(_) => {
+                    return Self::err(
+                        "Invalid priority. Valid values: 'low', 'medium', 'high', 'urgent'".to_string(),
+                        Some(priority_str.to_string()),
+                    );
+                }
+            },
+            None => None,
+        };
+
+        // The swarm route checks the swarm exists and validates the task as for any client
+        let url = self.url(&format!("/api/swarms/{}/tasks", swarm_id));
+        let payload = serde_json::json!({
+            "title": title,
+            "description": description,
+            "priority": priority,
+            "tags": tags,
+        });
+        let task: SwarmTask = match self.send_json(self.client.post(&url).json(&payload)).await {
+            Ok(t) => t,
+            Err(e) => return Ok(e),
+        };
+
+        TaskServer::success(&CreateTaskResponse {
+            task_id: task.id.to_string(),
+        })
+    }
+
+    #[tool(
+        description = "Post a message to a swarm's chat, e.g. to report progress mid-task. It is shown to everyone watching the chat. `swarm_id`, `sandbox_id` and `message` are required."
+    )]
+    async fn post_swarm_message(
+        &self,
+        Parameters(PostSwarmMessageRequest {
+            swarm_id,
+            sandbox_id,
+            message,
+            role,
+        }): Parameters<PostSwarmMessageRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let url = self.url(&format!("/api/swarms/{}/chat/sandbox", swarm_id));
+        let payload = serde_json::json!({
+            "sandbox_id": sandbox_id,
+            "message": message,
+            "role": role,
+        });
+        let chat: SwarmChat = match self.send_json(self.client.post(&url).json(&payload)).await {
+            Ok(c) => c,
+            Err(e) => return Ok(e),
+        };
+
+        TaskServer::success(&PostSwarmMessageResponse {
+            message_id: chat.id.to_string(),
+        })
+    }
+
     #[tool(description = "List all the available projects")]
     async fn list_projects(&self) -> Result<CallToolResult, ErrorData> {
         let url = self.url("/api/projects");
@@ -1004,7 +1109,7 @@ impl TaskServer {
 #[tool_handler]
 impl ServerHandler for TaskServer {
     fn get_info(&self) -> ServerInfo {
-        let mut instruction = "A task and project management server. If you need to create or update tickets or tasks then use these tools. Most of them absolutely require that you pass the `project_id` of the project that you are currently working on. You can get project ids by using `list projects`. Call `list_tasks` to fetch the `task_ids` of all the tasks in a project. TOOLS: 'list_projects', 'list_tasks', 'create_task', 'start_workspace_session', 'get_task', 'update_task', 'delete_task', 'list_repos', 'get_repo', 'update_setup_script', 'update_cleanup_script', 'update_dev_server_script'. Make sure to pass `project_id`, `task_id`, or `repo_id` where required. You can use list tools to get the available ids.".to_string();
+        let mut instruction = "A task and project management server. If you need to create or update tickets or tasks then use these tools. Most of them absolutely require that you pass the `project_id` of the project that you are currently working on. You can get project ids by using `list projects`. Call `list_tasks` to fetch the `task_ids` of all the tasks in a project. TOOLS: 'list_projects', 'list_tasks', 'create_task', 'create_swarm_task', 'post_swarm_message', 'start_workspace_session', 'get_task', 'update_task', 'delete_task', 'list_repos', 'get_repo', 'update_setup_script', 'update_cleanup_script', 'update_dev_server_script'. Make sure to pass `project_id`, `swarm_id`, `task_id`, or `repo_id` where required. You can use list tools to get the available ids.".to_string();
         if self.context.is_some() {
             let context_instruction = "Use 'get_context' to fetch project/task/workspace metadata for the active Vibe Kanban workspace session when available.";
             instruction = format!("{} {}", context_instruction, instruction);
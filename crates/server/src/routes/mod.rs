@@ -1,8 +1,12 @@
+use std::sync::Arc;
+
 use axum::{
     Router,
     routing::{IntoMakeService, get},
 };
+use db::models::swarm_config::SwarmConfig;
 use deployment::Deployment;
+use services::services::swarm::PoolHistorySampler;
 use tower_http::validate_request::ValidateRequestHeaderLayer;
 
 use crate::{AppState, DeploymentImpl, middleware};
@@ -33,6 +37,18 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
     // Create AppState for swarm routes
     let app_state = AppState::new(deployment.db().pool.clone());
 
+    // Record pool utilization for GET /pool/history
+    Arc::new(PoolHistorySampler::new(app_state.db_pool.clone())).start();
+
+    // Live log streams follow the configured dedup setting
+    let (db_pool, logs) = (app_state.db_pool.clone(), app_state.broadcast.logs.clone());
+    tokio::spawn(async move {
+        match SwarmConfig::get(&db_pool).await {
+            Ok(config) => logs.set_dedup(config.log_dedup_enabled),
+            Err(e) => tracing::warn!(error = %e, "Failed to load log dedup setting"),
+        }
+    });
+
     // Create routers with different middleware layers
     let base_routes = Router::new()
         .route("/health", get(health::health_check))
@@ -0,0 +1,150 @@
+//! Admin / Maintenance Routes
+
+use std::time::Instant;
+
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    response::Json as ResponseJson,
+    routing::{get, post},
+};
+use db::models::webhook_dead_letter::WebhookDeadLetter;
+use serde::{Deserialize, Serialize};
+use services::services::swarm::{WebhookDeliverer, WebhookRetryConfig};
+use sqlx::{ConnectOptions, Connection, SqliteConnection};
+use tokio::sync::Mutex;
+use ts_rs::TS;
+use utils::response::{ApiResponse, ErrorCode};
+use uuid::Uuid;
+
+use crate::{AppState, error::ApiError};
+
+/// Guards against concurrent maintenance runs
+static MAINTENANCE_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct MaintenanceResponse {
+    pub success: bool,
+    pub size_before_bytes: i64,
+    pub size_after_bytes: i64,
+    pub reclaimed_bytes: i64,
+    pub duration_ms: u64,
+}
+
+/// Current on-disk size of the database (page_count * page_size)
+async fn database_size(conn: &mut SqliteConnection) -> Result<i64, sqlx::Error> {
+    let page_count: i64 = sqlx::query_scalar("PRAGMA page_count")
+        .fetch_one(&mut *conn)
+        .await?;
+    let page_size: i64 = sqlx::query_scalar("PRAGMA page_size")
+        .fetch_one(&mut *conn)
+        .await?;
+
+    Ok(page_count * page_size)
+}
+
+/// Run VACUUM and ANALYZE on the database
+///
+/// VACUUM rewrites the whole database file and holds an exclusive lock while
+/// doing so, so other readers and writers will block until it finishes. On a
+/// large database this can take several seconds; run it during quiet periods.
+/// The run opens its own connection, so it never takes one of the pool's
+/// connections away from other requests. Only one maintenance run is allowed
+/// at a time.
+pub async fn run_db_maintenance(
+    State(state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<MaintenanceResponse>>, ApiError> {
+    let _guard = MAINTENANCE_LOCK
+        .try_lock()
+        .map_err(|_| ApiError::Conflict("Database maintenance already in progress".to_string()))?;
+
+    let started = Instant::now();
+
+    // VACUUM cannot run inside a transaction, so the whole run uses one
+    // connection, opened with the pool's settings but outside the pool
+    let mut conn = state.db_pool.connect_options().connect().await?;
+
+    let size_before = database_size(&mut conn).await?;
+
+    sqlx::query("VACUUM").execute(&mut conn).await?;
+    sqlx::query("ANALYZE").execute(&mut conn).await?;
+
+    let size_after = database_size(&mut conn).await?;
+    conn.close().await?;
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    tracing::info!(
+        size_before = size_before,
+        size_after = size_after,
+        duration_ms = duration_ms,
+        "Database maintenance completed"
+    );
+
+    Ok(ResponseJson(ApiResponse::success(MaintenanceResponse {
+        success: true,
+        size_before_bytes: size_before,
+        size_after_bytes: size_after,
+        reclaimed_bytes: (size_before - size_after).max(0),
+        duration_ms,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterQuery {
+    /// Most dead letters to return (1-500, default 100)
+    pub limit: Option<i64>,
+}
+
+/// GET /api/admin/webhooks/dead-letters - Undelivered webhook events, newest first
+pub async fn list_dead_letters(
+    State(state): State<AppState>,
+    Query(query): Query<DeadLetterQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<WebhookDeadLetter>>>, ApiError> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+    let letters = WebhookDeadLetter::find_recent(&state.db_pool, limit).await?;
+    Ok(ResponseJson(ApiResponse::success(letters)))
+}
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct RedeliverResponse {
+    pub delivered: bool,
+    /// The dead letter with its updated attempt count and error; absent once delivered
+    pub dead_letter: Option<WebhookDeadLetter>,
+}
+
+/// POST /api/admin/webhooks/dead-letters/redeliver/:id - Send a dead letter again
+///
+/// Makes one attempt, so the request returns within a single webhook timeout.
+/// A delivered event is removed from the dead-letter log; otherwise its
+/// attempt count and last error are updated.
+pub async fn redeliver_dead_letter(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<RedeliverResponse>>, ApiError> {
+    let letter = WebhookDeadLetter::find_by_id(&state.db_pool, id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Dead letter not found".to_string())
+            .with_code(ErrorCode::DeadLetterNotFound))?;
+
+    let deliverer = WebhookDeliverer::new(WebhookRetryConfig::default())?;
+    let dead_letter = deliverer.redeliver(&state.db_pool, &letter).await?;
+
+    tracing::info!(
+        dead_letter_id = %id,
+        event = %letter.event,
+        delivered = dead_letter.is_none(),
+        "Redelivered webhook dead letter"
+    );
+
+    Ok(ResponseJson(ApiResponse::success(RedeliverResponse {
+        delivered: dead_letter.is_none(),
+        dead_letter,
+    })))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/admin/db/maintenance", post(run_db_maintenance))
+        .route("/admin/webhooks/dead-letters", get(list_dead_letters))
+        .route("/admin/webhooks/dead-letters/redeliver/{id}", post(redeliver_dead_letter))
+}
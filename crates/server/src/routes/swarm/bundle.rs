@@ -0,0 +1,338 @@
+//! Swarm Export / Import
+//!
+//! A bundle is the swarm, its tasks and its chat in one JSON document. Importing
+//! one creates a new swarm with fresh ids, remembering the bundle's ids as
+//! `external_id`s; importing the same bundle again updates that swarm in place.
+//! Sandboxes and secret values are not part of a bundle.
+
+use std::collections::{HashMap, HashSet};
+
+use axum::{Extension, extract::State, response::Json as ResponseJson};
+use db::models::{
+    swarm::{CreateSwarm, Swarm, SwarmStatus},
+    swarm_chat::SwarmChat,
+    swarm_config::SwarmConfig,
+    swarm_task::{SwarmTask, SwarmTaskStatus},
+};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utils::response::{ApiResponse, ErrorCode};
+use uuid::Uuid;
+
+use super::{
+    StrictJson, secrets::validate_task_secrets, tasks::nodes_in_cycles, tasks::validate_write_result_to,
+    validate_allowed_skills, validate_dispatch_rate, validate_max_concurrent, validate_setup_script,
+};
+use crate::{AppState, error::ApiError};
+
+/// Bundle format written by this version
+const BUNDLE_VERSION: u32 = 1;
+
+/// Longest accepted external id
+const MAX_EXTERNAL_ID_LEN: usize = 255;
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct SwarmBundle {
+    pub version: u32,
+    pub swarm: Swarm,
+    pub tasks: Vec<SwarmTask>,
+    /// Oldest first
+    pub chat: Vec<SwarmChat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ImportSwarmResponse {
+    pub swarm: Swarm,
+    /// False if an existing swarm with the same external id was updated
+    pub created: bool,
+    /// Task id in the bundle -> id of the created or updated task
+    pub task_ids: HashMap<Uuid, Uuid>,
+}
+
+/// GET /api/swarms/:id/export - The swarm, its tasks and its chat as one bundle
+pub async fn export_swarm(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<SwarmBundle>>, ApiError> {
+    let tasks = SwarmTask::find_by_swarm_id(&state.db_pool, swarm.id).await?;
+    let chat = SwarmChat::find_all_by_swarm_id(&state.db_pool, swarm.id).await?;
+
+    Ok(ResponseJson(ApiResponse::success(SwarmBundle {
+        version: BUNDLE_VERSION,
+        swarm,
+        tasks,
+        chat,
+    })))
+}
+
+/// POST /api/swarms/import - Recreate a swarm from an exported bundle
+///
+/// Every id is replaced and task references are rewired to the new ids. The
+/// swarm is created paused; running tasks come back as pending since their
+/// sandboxes are not carried over. Secrets referenced by tasks must be set
+/// again on the new swarm.
+///
+/// The swarm and each task are keyed by their `external_id`, or their id in the
+/// bundle if they have none. When a swarm with that key already exists, its
+/// settings and the definitions of matching tasks are overwritten, tasks new to
+/// the bundle are added, and run state and chat are left alone, so importing
+/// the same bundle twice changes nothing.
+pub async fn import_swarm(
+    State(state): State<AppState>,
+    StrictJson(bundle): StrictJson<SwarmBundle>,
+) -> Result<ResponseJson<ApiResponse<ImportSwarmResponse>>, ApiError> {
+    if bundle.version != BUNDLE_VERSION {
+        return Err(ApiError::BadRequest(format!(
+            "Unsupported bundle version {} (expected {})",
+            bundle.version, BUNDLE_VERSION
+        )));
+    }
+
+    let source = &bundle.swarm;
+    if source.name.len() > 255 {
+        return Err(ApiError::BadRequest("Name too long (max 255 chars)".to_string())
+            .with_code(ErrorCode::ValidationNameTooLong));
+    }
+    if let Some(ref desc) = source.description
+        && desc.len() > 5000
+    {
+        return Err(ApiError::BadRequest("Description too long (max 5000 chars)".to_string())
+            .with_code(ErrorCode::ValidationDescriptionTooLong));
+    }
+    validate_setup_script(source.setup_script.as_deref())?;
+    validate_dispatch_rate(Some(source.dispatch_rate_per_minute))?;
+    validate_max_concurrent(Some(source.max_concurrent))?;
+    validate_allowed_skills(Some(&source.allowed_skills))?;
+
+    let swarm_external_id = external_id_or(source.external_id.as_deref(), source.id)?;
+    let existing = Swarm::find_by_external_id(&state.db_pool, &swarm_external_id).await?;
+    let existing_tasks: HashMap<String, Uuid> = match existing {
+        Some(ref swarm) => SwarmTask::find_by_swarm_id(&state.db_pool, swarm.id)
+            .await?
+            .into_iter()
+            .filter_map(|task| Some((task.external_id?, task.id)))
+            .collect(),
+        None => HashMap::new(),
+    };
+
+    let task_ids = remap_task_ids(&bundle.tasks, &existing_tasks)?;
+    let updated_ids: HashSet<Uuid> = existing_tasks.into_values().collect();
+    let added = task_ids.values().filter(|id| !updated_ids.contains(id)).count();
+
+    let config = SwarmConfig::get(&state.db_pool).await?;
+    let current = match existing {
+        Some(ref swarm) => SwarmTask::count_by_swarm_id(&state.db_pool, swarm.id).await?,
+        None => 0,
+    };
+    if current + added as i64 > i64::from(config.max_tasks_per_swarm) {
+        return Err(ApiError::BadRequest(format!(
+            "Too many tasks (max {})",
+            config.max_tasks_per_swarm
+        ))
+        .with_code(ErrorCode::TaskLimitReached));
+    }
+    let tasks = bundle
+        .tasks
+        .iter()
+        .map(|task| remap_task(task, &task_ids))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Dependencies were acyclic when exported, but a bundle may have been edited
+    let index: HashMap<Uuid, usize> = tasks.iter().enumerate().map(|(i, t)| (t.id, i)).collect();
+    let mut adjacency = vec![Vec::new(); tasks.len()];
+    for (i, task) in tasks.iter().enumerate() {
+        for upstream in task.depends_on.iter().chain(&task.triggers_after).flatten() {
+            adjacency[index[upstream]].push(i);
+        }
+    }
+    if let Some(i) = nodes_in_cycles(&adjacency).iter().position(|&cyclic| cyclic) {
+        return Err(ApiError::BadRequest(format!(
+            "Task '{}' is part of a dependency cycle",
+            bundle.tasks[i].title
+        ))
+        .with_code(ErrorCode::DependencyCycle));
+    }
+
+    // Keep the project link only if the project exists here
+    let project_id = match source.project_id {
+        Some(id) => sqlx::query_scalar::<_, Uuid>("SELECT id FROM projects WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&state.db_pool)
+            .await?,
+        None => None,
+    };
+
+    let data = CreateSwarm {
+        name: source.name.clone(),
+        description: source.description.clone(),
+        project_id,
+        setup_script: source.setup_script.clone(),
+        dispatch_rate_per_minute: Some(source.dispatch_rate_per_minute),
+        allowed_skills: Some(source.allowed_skills.clone()),
+        fresh_sandbox_per_task: Some(source.fresh_sandbox_per_task),
+        require_approval: Some(source.require_approval),
+        max_concurrent: Some(source.max_concurrent),
+    };
+
+    let mut tx = state.db_pool.begin().await?;
+
+    let swarm_id = match existing {
+        Some(ref swarm) => {
+            Swarm::replace_definition(&mut *tx, swarm.id, &data).await?;
+            swarm.id
+        }
+        None => {
+            let swarm_id = Uuid::new_v4();
+            Swarm::create(&mut *tx, &data, swarm_id).await?;
+            Swarm::update_status(&mut *tx, swarm_id, SwarmStatus::Paused).await?;
+            Swarm::set_external_id(&mut *tx, swarm_id, &swarm_external_id).await?;
+            swarm_id
+        }
+    };
+
+    for task in &tasks {
+        if updated_ids.contains(&task.id) {
+            SwarmTask::replace_definition(&mut *tx, task.id, task).await?;
+        } else {
+            SwarmTask::restore(&mut *tx, &SwarmTask { swarm_id, ..task.clone() }).await?;
+        }
+    }
+    for task in &tasks {
+        if let Some(parent_task_id) = task.parent_task_id {
+            SwarmTask::set_parent_task(&mut *tx, task.id, parent_task_id).await?;
+        }
+    }
+
+    // Chat is history rather than definition; it is only copied into a new swarm
+    let chat = if existing.is_none() { bundle.chat.as_slice() } else { &[] };
+    for message in chat {
+        let message = SwarmChat {
+            id: Uuid::new_v4(),
+            swarm_id,
+            metadata: message.metadata.as_deref().map(|raw| remap_metadata(raw, &task_ids)),
+            ..message.clone()
+        };
+        SwarmChat::restore(&mut *tx, &message).await?;
+    }
+
+    tx.commit().await?;
+
+    let swarm = Swarm::find_by_id(&state.db_pool, swarm_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Swarm not found".to_string())
+            .with_code(ErrorCode::SwarmNotFound))?;
+
+    tracing::info!(
+        "Imported swarm '{}' as {} ({} tasks added, {} updated, {} messages)",
+        swarm.name,
+        swarm.id,
+        added,
+        tasks.len() - added,
+        chat.len()
+    );
+
+    Ok(ResponseJson(ApiResponse::success(ImportSwarmResponse {
+        swarm,
+        created: existing.is_none(),
+        task_ids,
+    })))
+}
+
+/// The explicit external id if there is one, otherwise the bundle id
+fn external_id_or(external_id: Option<&str>, id: Uuid) -> Result<String, ApiError> {
+    match external_id {
+        Some(external_id) if external_id.trim().is_empty() => {
+            Err(ApiError::BadRequest("External id must not be empty".to_string()))
+        }
+        Some(external_id) if external_id.len() > MAX_EXTERNAL_ID_LEN => Err(ApiError::BadRequest(
+            format!("External id too long (max {} chars)", MAX_EXTERNAL_ID_LEN),
+        )),
+        Some(external_id) => Ok(external_id.to_string()),
+        None => Ok(id.to_string()),
+    }
+}
+
+/// Target id for every task in the bundle: the existing task with the same
+/// external id, or a fresh one
+fn remap_task_ids(
+    tasks: &[SwarmTask],
+    existing: &HashMap<String, Uuid>,
+) -> Result<HashMap<Uuid, Uuid>, ApiError> {
+    let mut ids = HashMap::with_capacity(tasks.len());
+    let mut seen = HashSet::with_capacity(tasks.len());
+    for task in tasks {
+        let external_id = external_id_or(task.external_id.as_deref(), task.id)?;
+        let target = existing.get(&external_id).copied().unwrap_or_else(Uuid::new_v4);
+        if !seen.insert(external_id) || ids.insert(task.id, target).is_some() {
+            return Err(ApiError::BadRequest(format!(
+                "Task '{}' has the same id or external id as another task",
+                task.title
+            )));
+        }
+    }
+    Ok(ids)
+}
+
+/// Copy of `task` under its new id, with references rewired and sandbox state dropped
+fn remap_task(task: &SwarmTask, ids: &HashMap<Uuid, Uuid>) -> Result<SwarmTask, ApiError> {
+    if task.title.len() > 255 {
+        return Err(ApiError::BadRequest("Title too long (max 255 chars)".to_string())
+            .with_code(ErrorCode::ValidationTitleTooLong));
+    }
+    if let Some(ref desc) = task.description
+        && desc.len() > 10000
+    {
+        return Err(ApiError::BadRequest("Description too long (max 10000 chars)".to_string())
+            .with_code(ErrorCode::ValidationDescriptionTooLong));
+    }
+    validate_task_secrets(Some(&task.secrets))?;
+    validate_write_result_to(task.write_result_to.as_deref())?;
+
+    let resolve = |id: &Uuid| {
+        ids.get(id).copied().ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Task '{}' references task {} which is not in the bundle",
+                task.title, id
+            ))
+        })
+    };
+    let resolve_all = |list: &Option<Vec<Uuid>>| {
+        list.as_ref()
+            .map(|ids| ids.iter().map(resolve).collect::<Result<Vec<_>, _>>())
+            .transpose()
+    };
+
+    let running = task.status == SwarmTaskStatus::Running;
+    Ok(SwarmTask {
+        id: ids[&task.id],
+        depends_on: resolve_all(&task.depends_on)?,
+        triggers_after: resolve_all(&task.triggers_after)?,
+        parent_task_id: task.parent_task_id.as_ref().map(resolve).transpose()?,
+        status: if running { SwarmTaskStatus::Pending } else { task.status.clone() },
+        started_at: if running { None } else { task.started_at },
+        sandbox_id: None,
+        pinned_sandbox_id: None,
+        external_id: Some(external_id_or(task.external_id.as_deref(), task.id)?),
+        ..task.clone()
+    })
+}
+
+/// Point a message's `task_id` at the imported task and drop its `sandbox_id`
+///
+/// Metadata that is not a JSON object is kept as is.
+fn remap_metadata(raw: &str, ids: &HashMap<Uuid, Uuid>) -> String {
+    let Ok(serde_json::Value::Object(mut metadata)) = serde_json::from_str(raw) else {
+        return raw.to_string();
+    };
+
+    metadata.remove("sandbox_id");
+    if let Some(task_id) = metadata.get("task_id").and_then(|v| v.as_str()).and_then(|s| s.parse().ok())
+    {
+        match ids.get(&task_id) {
+            Some(new_id) => metadata.insert("task_id".to_string(), new_id.to_string().into()),
+            None => metadata.remove("task_id"),
+        };
+    }
+
+    serde_json::Value::Object(metadata).to_string()
+}
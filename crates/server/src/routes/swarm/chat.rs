@@ -4,22 +4,52 @@ use axum::{
     Extension, Json, Router,
     extract::{Query, State},
     response::Json as ResponseJson,
-    routing::get,
+    routing::{get, post},
 };
+use chrono::{DateTime, Utc};
 use db::models::{
     swarm::Swarm,
     swarm_chat::{CreateSwarmChat, SenderType, SwarmChat},
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use services::services::swarm::{ChatError, ChatService, MAX_MESSAGE_LEN};
 use ts_rs::TS;
-use utils::response::ApiResponse;
+use utils::response::{ApiResponse, ErrorCode};
 use uuid::Uuid;
 
 use crate::{AppState, error::ApiError};
 
 #[derive(Debug, Deserialize)]
 pub struct ChatQuery {
+    /// Most messages to return (1-500, default 100)
     pub limit: Option<i32>,
+    /// Only messages posted after this time; applied before `limit`. Without
+    /// `before`, switches to polling order: the oldest matches, oldest first
+    pub since: Option<DateTime<Utc>>,
+    /// Only messages older than this one; pass the previous page's `next_cursor`
+    pub before: Option<Uuid>,
+    /// Only messages newer than this one, oldest first; pass the last message
+    /// received to poll for new ones
+    pub after: Option<Uuid>,
+}
+
+/// Longest accepted search query
+const MAX_SEARCH_QUERY_CHARS: usize = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct ChatSearchQuery {
+    /// Text to look for in message bodies
+    pub q: String,
+    /// Most messages to return (1-100, default 50)
+    pub limit: Option<i32>,
+}
+
+/// A page of chat messages, newest first (oldest first when polling with `since`)
+#[derive(Debug, Serialize, TS)]
+pub struct ChatPage {
+    pub messages: Vec<SwarmChat>,
+    /// Id to pass as `before` for the next, older page; null on the last page
+    pub next_cursor: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize, TS)]
@@ -30,18 +60,112 @@ pub struct PostMessageRequest {
     pub metadata: Option<String>,
 }
 
+/// Longest agent role name shown on a sandbox message
+const MAX_ROLE_CHARS: usize = 100;
+
+#[derive(Debug, Deserialize, TS)]
+pub struct PostSandboxMessageRequest {
+    pub sandbox_id: Uuid,
+    pub message: String,
+    /// Agent role shown alongside the message
+    pub role: Option<String>,
+}
+
+fn chat_error(e: ChatError) -> ApiError {
+    match e {
+        ChatError::Database(e) => ApiError::Database(e),
+        other => ApiError::BadRequest(other.to_string()),
+    }
+}
+
+fn validate_message(message: &str) -> Result<(), ApiError> {
+    if message.len() > MAX_MESSAGE_LEN {
+        return Err(ApiError::BadRequest(format!(
+            "Message too long (max {} chars)",
+            MAX_MESSAGE_LEN
+        ))
+        .with_code(ErrorCode::ValidationMessageTooLong));
+    }
+    Ok(())
+}
+
+/// GET /api/swarms/:id/chat - Recent messages, newest first, paged with `before`
+///
+/// Clients that cannot keep the chat WebSocket open poll with `after` set to
+/// the last message's id and get newer messages oldest first, ready to
+/// append; `since` alone starts polling from a point in time. A full page
+/// means more are waiting; there is no `next_cursor` in this mode.
 pub async fn get_messages(
     Extension(swarm): Extension<Swarm>,
     State(state): State<AppState>,
     Query(query): Query<ChatQuery>,
-) -> Result<ResponseJson<ApiResponse<Vec<SwarmChat>>>, ApiError> {
-    let messages = SwarmChat::find_by_swarm_id(
+) -> Result<ResponseJson<ApiResponse<ChatPage>>, ApiError> {
+    if let Some(limit) = query.limit
+        && !(1..=500).contains(&limit)
+    {
+        return Err(ApiError::BadRequest("limit must be between 1 and 500".to_string()));
+    }
+
+    if query.before.is_some() && query.after.is_some() {
+        return Err(ApiError::BadRequest("before and after cannot be combined".to_string()));
+    }
+
+    for cursor in [query.before, query.after].into_iter().flatten() {
+        let message = SwarmChat::find_by_id(&state.db_pool, cursor).await?;
+        if message.is_none_or(|m| m.swarm_id != swarm.id) {
+            return Err(ApiError::BadRequest("Message not found".to_string())
+                .with_code(ErrorCode::MessageNotFound));
+        }
+    }
+
+    if query.after.is_some() || (query.since.is_some() && query.before.is_none()) {
+        let (messages, _) =
+            SwarmChat::find_after(&state.db_pool, swarm.id, query.since, query.after, query.limit).await?;
+        return Ok(ResponseJson(ApiResponse::success(ChatPage {
+            messages,
+            next_cursor: None,
+        })));
+    }
+
+    let (messages, has_more) = SwarmChat::find_page(
         &state.db_pool,
         swarm.id,
+        query.since,
+        query.before,
         query.limit,
     )
     .await?;
 
+    let next_cursor = if has_more { messages.last().map(|m| m.id) } else { None };
+    Ok(ResponseJson(ApiResponse::success(ChatPage { messages, next_cursor })))
+}
+
+/// GET /api/swarms/:id/chat/search - Messages containing `q`, newest first
+///
+/// Each result's id can be passed as `before` to the chat listing to load the
+/// messages around it.
+pub async fn search_messages(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+    Query(query): Query<ChatSearchQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<SwarmChat>>>, ApiError> {
+    let text = query.q.trim();
+    if text.is_empty() {
+        return Err(ApiError::BadRequest("q must not be empty".to_string()));
+    }
+    if text.chars().count() > MAX_SEARCH_QUERY_CHARS {
+        return Err(ApiError::BadRequest(format!(
+            "q too long (max {} chars)",
+            MAX_SEARCH_QUERY_CHARS
+        )));
+    }
+    if let Some(limit) = query.limit
+        && !(1..=100).contains(&limit)
+    {
+        return Err(ApiError::BadRequest("limit must be between 1 and 100".to_string()));
+    }
+
+    let messages = SwarmChat::search(&state.db_pool, swarm.id, text, query.limit).await?;
     Ok(ResponseJson(ApiResponse::success(messages)))
 }
 
@@ -50,10 +174,7 @@ pub async fn post_message(
     State(state): State<AppState>,
     Json(payload): Json<PostMessageRequest>,
 ) -> Result<ResponseJson<ApiResponse<SwarmChat>>, ApiError> {
-    // Validate message size
-    if payload.message.len() > 10000 {
-        return Err(ApiError::BadRequest("Message too long (max 10000 chars)".to_string()));
-    }
+    validate_message(&payload.message)?;
     if let Some(ref metadata) = payload.metadata {
         if metadata.len() > 5000 {
             return Err(ApiError::BadRequest("Metadata too long (max 5000 chars)".to_string()));
@@ -71,13 +192,63 @@ pub async fn post_message(
     };
 
     let message = SwarmChat::create(&state.db_pool, &create_data, message_id).await?;
+    ChatService::new()
+        .route_mentions(&state.db_pool, &message)
+        .await
+        .map_err(chat_error)?;
 
     tracing::debug!("Posted message {} to swarm {}", message.id, swarm.id);
 
     Ok(ResponseJson(ApiResponse::success(message)))
 }
 
+/// POST /api/swarms/:id/chat/sandbox - Post an agent's message from its sandbox
+///
+/// Unlike a plain post, the message is also pushed to chat WebSocket
+/// subscribers, so workers can report progress while a task runs.
+pub async fn post_sandbox_message(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+    Json(payload): Json<PostSandboxMessageRequest>,
+) -> Result<ResponseJson<ApiResponse<SwarmChat>>, ApiError> {
+    if payload.message.trim().is_empty() {
+        return Err(ApiError::BadRequest("Message must not be empty".to_string()));
+    }
+    validate_message(&payload.message)?;
+    if let Some(ref role) = payload.role
+        && (role.trim().is_empty() || role.chars().count() > MAX_ROLE_CHARS)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Role must be 1-{} chars",
+            MAX_ROLE_CHARS
+        )));
+    }
+
+    let message = ChatService::new()
+        .post_sandbox_message_with_broadcast(
+            &state.db_pool,
+            &state.broadcast.chat,
+            swarm.id,
+            payload.sandbox_id,
+            payload.message,
+            payload.role,
+        )
+        .await
+        .map_err(chat_error)?;
+
+    tracing::debug!(
+        "Sandbox {} posted message {} to swarm {}",
+        payload.sandbox_id,
+        message.id,
+        swarm.id
+    );
+
+    Ok(ResponseJson(ApiResponse::success(message)))
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/chat", get(get_messages).post(post_message))
+        .route("/chat/sandbox", post(post_sandbox_message))
+        .route("/chat/search", get(search_messages))
 }
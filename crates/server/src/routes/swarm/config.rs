@@ -1,18 +1,47 @@
 //! Swarm Configuration Routes
 
 use axum::{
-    Json, Router,
+    Router,
     extract::State,
     response::Json as ResponseJson,
     routing::{get, post},
 };
 use db::models::swarm_config::{SwarmConfig, SwarmConfigWithMaskedSecrets, UpdateSwarmConfig};
 use serde::{Deserialize, Serialize};
+use services::services::swarm::{
+    validate_claude_extra_args, validate_claude_model, validate_prompt_vars, MAX_DECOMPOSE_SUBTASKS,
+};
 use ts_rs::TS;
 use utils::response::ApiResponse;
 
+use super::StrictJson;
 use crate::{AppState, error::ApiError};
 
+/// Most fallback snapshots that may be configured
+const MAX_FALLBACK_SNAPSHOTS: usize = 10;
+
+/// Longest configurable inactivity before auto-pause (30 days)
+const MAX_AUTO_PAUSE_MINUTES: i32 = 43_200;
+
+/// Longest configurable skill read timeout
+const MAX_SKILLS_READ_TIMEOUT_SECS: i32 = 60;
+
+/// Most lag events a WebSocket client may be allowed before disconnecting
+const MAX_WS_LAG_LIMIT: i32 = 100;
+
+/// Longest window over which WebSocket lag events are counted (1 hour)
+const MAX_WS_LAG_WINDOW_SECS: i32 = 3_600;
+
+/// Pool history sampling interval bounds when enabled (10s to 1 day)
+const MIN_POOL_HISTORY_INTERVAL_SECS: i32 = 10;
+const MAX_POOL_HISTORY_INTERVAL_SECS: i32 = 86_400;
+
+/// Longest pool history retention (1 year)
+const MAX_POOL_HISTORY_RETENTION_HOURS: i32 = 8_760;
+
+/// Largest log compression threshold (64 MiB)
+const MAX_LOG_COMPRESSION_THRESHOLD_BYTES: i32 = 64 * 1024 * 1024;
+
 #[derive(Debug, Serialize, Deserialize, TS)]
 pub struct TestConnectionResponse {
     pub success: bool,
@@ -37,7 +66,7 @@ pub async fn get_config(
 
 pub async fn update_config(
     State(state): State<AppState>,
-    Json(payload): Json<UpdateSwarmConfig>,
+    StrictJson(payload): StrictJson<UpdateSwarmConfig>,
 ) -> Result<ResponseJson<ApiResponse<SwarmConfigWithMaskedSecrets>>, ApiError> {
     // Validate input sizes
     if let Some(ref url) = payload.daytona_api_url {
@@ -45,6 +74,16 @@ pub async fn update_config(
             return Err(ApiError::BadRequest("Daytona API URL too long (max 500 chars)".to_string()));
         }
     }
+    if let Some(ref url) = payload.webhook_url
+        && !url.is_empty()
+    {
+        if url.len() > 500 {
+            return Err(ApiError::BadRequest("Webhook URL too long (max 500 chars)".to_string()));
+        }
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(ApiError::BadRequest("Webhook URL must start with http:// or https://".to_string()));
+        }
+    }
     if let Some(ref path) = payload.skills_path {
         if path.len() > 500 {
             return Err(ApiError::BadRequest("Skills path too long (max 500 chars)".to_string()));
@@ -55,10 +94,143 @@ pub async fn update_config(
             return Err(ApiError::BadRequest("Snapshot name too long (max 255 chars)".to_string()));
         }
     }
+    if let Some(ref snapshots) = payload.pool_fallback_snapshots {
+        if snapshots.len() > MAX_FALLBACK_SNAPSHOTS {
+            return Err(ApiError::BadRequest(format!(
+                "Too many fallback snapshots (max {})",
+                MAX_FALLBACK_SNAPSHOTS
+            )));
+        }
+        if snapshots.iter().any(|s| s.trim().is_empty() || s.len() > 255) {
+            return Err(ApiError::BadRequest(
+                "Fallback snapshot names must be 1-255 chars".to_string(),
+            ));
+        }
+    }
+    if let Some(ref prefix) = payload.sandbox_name_prefix {
+        if prefix.len() > 32 {
+            return Err(ApiError::BadRequest("Sandbox name prefix too long (max 32 chars)".to_string()));
+        }
+        if !prefix.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(ApiError::BadRequest(
+                "Sandbox name prefix may only contain letters, digits, '-' and '_'".to_string(),
+            ));
+        }
+    }
+
+    if let Some(max_tasks) = payload.max_tasks_per_swarm
+        && max_tasks < 1
+    {
+        return Err(ApiError::BadRequest("Max tasks per swarm must be at least 1".to_string()));
+    }
+
+    if let Some(aging) = payload.priority_aging_minutes
+        && aging < 0
+    {
+        return Err(ApiError::BadRequest("Priority aging minutes cannot be negative".to_string()));
+    }
+
+    if let Some(secs) = payload.skills_read_timeout_secs
+        && !(1..=MAX_SKILLS_READ_TIMEOUT_SECS).contains(&secs)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Skills read timeout must be between 1 and {} seconds",
+            MAX_SKILLS_READ_TIMEOUT_SECS
+        )));
+    }
+
+    if let Some(secs) = payload.pool_history_interval_secs
+        && secs != 0
+        && !(MIN_POOL_HISTORY_INTERVAL_SECS..=MAX_POOL_HISTORY_INTERVAL_SECS).contains(&secs)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Pool history interval must be 0 (off) or between {} and {} seconds",
+            MIN_POOL_HISTORY_INTERVAL_SECS, MAX_POOL_HISTORY_INTERVAL_SECS
+        )));
+    }
+
+    if let Some(hours) = payload.pool_history_retention_hours
+        && !(1..=MAX_POOL_HISTORY_RETENTION_HOURS).contains(&hours)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Pool history retention must be between 1 and {} hours",
+            MAX_POOL_HISTORY_RETENTION_HOURS
+        )));
+    }
+
+    if let Some(limit) = payload.ws_lag_limit
+        && !(0..=MAX_WS_LAG_LIMIT).contains(&limit)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "WebSocket lag limit must be between 0 and {}",
+            MAX_WS_LAG_LIMIT
+        )));
+    }
+
+    if let Some(secs) = payload.ws_lag_window_secs
+        && !(1..=MAX_WS_LAG_WINDOW_SECS).contains(&secs)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "WebSocket lag window must be between 1 and {} seconds",
+            MAX_WS_LAG_WINDOW_SECS
+        )));
+    }
+
+    if let Some(bytes) = payload.log_compression_threshold_bytes
+        && !(0..=MAX_LOG_COMPRESSION_THRESHOLD_BYTES).contains(&bytes)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Log compression threshold must be between 0 and {} bytes",
+            MAX_LOG_COMPRESSION_THRESHOLD_BYTES
+        )));
+    }
+
+    if let Some(minutes) = payload.auto_pause_after_minutes
+        && !(0..=MAX_AUTO_PAUSE_MINUTES).contains(&minutes)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Auto-pause minutes must be between 0 and {}",
+            MAX_AUTO_PAUSE_MINUTES
+        )));
+    }
+
+    if let Some(max_subtasks) = payload.decompose_max_subtasks
+        && !(1..=MAX_DECOMPOSE_SUBTASKS).contains(&max_subtasks)
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Max subtasks must be between 1 and {}",
+            MAX_DECOMPOSE_SUBTASKS
+        )));
+    }
+
+    if payload.pool_min_idle.is_some() || payload.pool_max_sandboxes.is_some() {
+        let existing = SwarmConfig::get(&state.db_pool).await?;
+        let min_idle = payload.pool_min_idle.unwrap_or(existing.pool_min_idle);
+        let max_sandboxes = payload.pool_max_sandboxes.unwrap_or(existing.pool_max_sandboxes);
+        if !(0..=max_sandboxes.max(0)).contains(&min_idle) {
+            return Err(ApiError::BadRequest(format!(
+                "Pool min idle must be between 0 and the pool max of {}",
+                max_sandboxes
+            )));
+        }
+    }
+
+    if let Some(ref model) = payload.claude_model
+        && !model.is_empty()
+    {
+        validate_claude_model(model).map_err(ApiError::BadRequest)?;
+    }
+    if let Some(ref args) = payload.claude_extra_args {
+        validate_claude_extra_args(args).map_err(ApiError::BadRequest)?;
+    }
+    if let Some(ref vars) = payload.prompt_vars {
+        validate_prompt_vars(vars).map_err(ApiError::BadRequest)?;
+    }
 
     SwarmConfig::update(&state.db_pool, &payload).await?;
 
     let config = SwarmConfig::get_with_masked_secrets(&state.db_pool).await?;
+    state.broadcast.logs.set_dedup(config.config.log_dedup_enabled);
 
     tracing::info!("Updated swarm configuration");
 
@@ -103,13 +275,18 @@ pub async fn get_status(
     let config = SwarmConfig::get(pool).await?;
     let sandbox_count = db::models::sandbox::Sandbox::count_active(pool).await?;
 
-    let skills_count = if let Some(skills_path) = super::skills::find_skills_dir(&config.skills_path) {
-        std::fs::read_dir(&skills_path)
-            .map(|entries| entries.flatten().filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false)).count())
-            .unwrap_or(0)
-    } else {
-        0
-    };
+    let skills_path = config.skills_path.clone();
+    let timeout = super::skills::skill_read_timeout(&config);
+    let skills_count = super::skills::run_skill_scan(&state, timeout, move || {
+        let count = match super::skills::find_skills_dir(&skills_path) {
+            Some(dir) => std::fs::read_dir(&dir)
+                .map(|entries| entries.flatten().filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false)).count())
+                .unwrap_or(0),
+            None => 0,
+        };
+        Ok(count)
+    })
+    .await?;
 
     let daytona_connected = config.daytona_api_url.is_some() && config.daytona_api_key.is_some();
 
@@ -0,0 +1,141 @@
+//! Swarm Events Long-Poll Route
+//!
+//! Fallback for clients that cannot keep a WebSocket open: a single request
+//! waits up to `wait` seconds for task, chat or pool changes and returns them
+//! together with a cursor for the next poll.
+
+use std::time::Duration;
+
+use axum::{
+    Extension, Router,
+    extract::{Query, State},
+    response::Json as ResponseJson,
+    routing::get,
+};
+use chrono::{DateTime, SubsecRound, Utc};
+use db::models::{swarm::Swarm, swarm_chat::SwarmChat, swarm_task::SwarmTask};
+use serde::{Deserialize, Serialize};
+use services::services::swarm::PoolStatusUpdate;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Instant;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{AppState, error::ApiError};
+
+/// Longest a single poll may wait; larger `wait` values are clamped
+pub const MAX_EVENTS_WAIT_SECS: u64 = 30;
+
+/// How often the task and chat tables are re-read while waiting
+///
+/// Task updates have no broadcast channel, so this is how they are noticed.
+const EVENTS_RECHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Most chat messages returned by one poll
+const EVENTS_MESSAGE_LIMIT: i32 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    /// Cursor from the previous response; omit to only see changes from now on
+    pub since: Option<DateTime<Utc>>,
+    /// Seconds to wait for a change (default 0, max 30)
+    #[serde(default)]
+    pub wait: u64,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct SwarmEvents {
+    /// Tasks created or updated since the cursor, oldest change first
+    pub tasks: Vec<SwarmTask>,
+    /// Chat messages posted since the cursor, oldest first
+    pub messages: Vec<SwarmChat>,
+    /// Pool updates for this swarm observed while the request was waiting
+    pub pool: Vec<PoolStatusUpdate>,
+    /// Pass as `since` on the next poll
+    #[ts(type = "Date")]
+    pub cursor: DateTime<Utc>,
+}
+
+impl SwarmEvents {
+    fn is_empty(&self) -> bool {
+        self.tasks.is_empty() && self.messages.is_empty() && self.pool.is_empty()
+    }
+}
+
+/// GET /api/swarms/:id/events - Long-poll for task, chat and pool changes
+///
+/// Returns as soon as anything changed since `since`, or an empty result once
+/// `wait` seconds (capped at [`MAX_EVENTS_WAIT_SECS`]) have passed.
+/// Timestamps have second precision and `since` is inclusive, so an item
+/// changed in the same second as the cursor may be returned twice; clients
+/// should de-duplicate by id.
+pub async fn poll_events(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> Result<ResponseJson<ApiResponse<SwarmEvents>>, ApiError> {
+    let deadline = Instant::now() + Duration::from_secs(query.wait.min(MAX_EVENTS_WAIT_SECS));
+    let since = query.since.unwrap_or_else(Utc::now).trunc_subsecs(0);
+
+    // Subscribe before the first read so nothing published in between is missed
+    let mut chat_rx = state.broadcast.chat.subscribe_chat(swarm.id).await;
+    let mut pool_rx = state.broadcast.pool.subscribe();
+    let mut pool_updates = Vec::new();
+
+    loop {
+        // Taken before reading, so changes made during the reads are seen next time
+        let mut cursor = Utc::now().trunc_subsecs(0);
+
+        let tasks = SwarmTask::find_updated_since(&state.db_pool, swarm.id, since).await?;
+        let messages =
+            SwarmChat::find_since(&state.db_pool, swarm.id, since, Some(EVENTS_MESSAGE_LIMIT))
+                .await?;
+
+        // A full page may have more behind it; resume from the last one returned
+        if messages.len() == EVENTS_MESSAGE_LIMIT as usize
+            && let Some(last) = messages.last()
+        {
+            cursor = cursor.min(last.created_at);
+        }
+
+        let events = SwarmEvents {
+            tasks,
+            messages,
+            pool: std::mem::take(&mut pool_updates),
+            cursor,
+        };
+
+        if !events.is_empty() || Instant::now() >= deadline {
+            return Ok(ResponseJson(ApiResponse::success(events)));
+        }
+
+        // Wait for a broadcast relevant to this swarm or the next re-read
+        let recheck_at = (Instant::now() + EVENTS_RECHECK_INTERVAL).min(deadline);
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep_until(recheck_at) => break,
+                event = chat_rx.recv() => {
+                    if let Err(RecvError::Closed) = event {
+                        tokio::time::sleep_until(recheck_at).await;
+                    }
+                    break;
+                }
+                update = pool_rx.recv() => match update {
+                    Ok(update) if update.belongs_to_swarm(swarm.id) => {
+                        pool_updates.push(update);
+                        break;
+                    }
+                    Err(RecvError::Closed) => {
+                        tokio::time::sleep_until(recheck_at).await;
+                        break;
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/events", get(poll_events))
+}
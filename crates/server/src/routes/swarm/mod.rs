@@ -2,36 +2,54 @@
 //!
 //! This module contains all routes for the Swarm subsystem including:
 //! - Swarm CRUD operations
+//! - Export and import of whole swarms as JSON bundles
 //! - Swarm task management
 //! - Chat messaging
+//! - Write-only secrets injected into task environments
+//! - Long-poll change feed for clients without WebSockets
 //! - Pool (sandbox) management
 //! - Skills discovery
 //! - Configuration
+//! - Trigger engine stats
+//! - Database maintenance and webhook dead letters
 //! - WebSocket streaming for logs and chat
 
+pub mod admin;
+pub mod bundle;
 pub mod chat;
 pub mod config;
+pub mod events;
 pub mod pool;
+pub mod secrets;
 pub mod skills;
 pub mod tasks;
 #[cfg(test)]
 mod tests;
+pub mod trigger;
 pub mod ws;
 
 use axum::{
     Extension, Json, Router,
-    extract::{Path, State},
+    extract::{FromRequest, Path, Query, State},
     middleware::from_fn_with_state,
     response::Json as ResponseJson,
     routing::{get, post},
 };
-use db::models::swarm::{CreateSwarm, Swarm, SwarmStatus, UpdateSwarm};
+use db::models::{
+    sandbox::{Sandbox, SandboxStatus},
+    swarm::{CreateSwarm, SortOrder, Swarm, SwarmFilters, SwarmSortKey, SwarmStatus, UpdateSwarm},
+    swarm_chat::SwarmChat,
+    swarm_secret::SwarmSecret,
+    swarm_task::SwarmTask,
+};
 use serde::{Deserialize, Serialize};
+use services::services::swarm::{DaytonaError, PoolStatusUpdate};
 use sqlx;
 use ts_rs::TS;
-use utils::response::ApiResponse;
+use utils::response::{ApiResponse, ErrorCode};
 use uuid::Uuid;
 
+use tasks::{MAX_LIST_LIMIT, TOTAL_COUNT_HEADER};
 use crate::{AppState, error::ApiError};
 
 /// Path params struct for routes with only swarm_id
@@ -48,6 +66,15 @@ struct SwarmTaskPath {
     task_id: Uuid,
 }
 
+/// JSON body extractor that reports bad bodies as `ApiError::BadRequest`
+///
+/// Plain `Json` answers with a text/plain 422, which hides which field was
+/// wrong; combined with `deny_unknown_fields` on the DTOs this turns a
+/// misspelled key into a 400 naming it.
+#[derive(FromRequest)]
+#[from_request(via(Json), rejection(ApiError))]
+pub struct StrictJson<T>(pub T);
+
 /// Middleware to load swarm by ID from path parameter (single swarm_id)
 async fn load_swarm_middleware(
     State(state): State<AppState>,
@@ -57,7 +84,8 @@ async fn load_swarm_middleware(
 ) -> Result<axum::response::Response, ApiError> {
     let swarm = Swarm::find_by_id(&state.db_pool, params.swarm_id)
         .await?
-        .ok_or_else(|| ApiError::BadRequest("Swarm not found".to_string()))?;
+        .ok_or_else(|| ApiError::BadRequest("Swarm not found".to_string())
+            .with_code(ErrorCode::SwarmNotFound))?;
 
     request.extensions_mut().insert(swarm);
     Ok(next.run(request).await)
@@ -72,7 +100,8 @@ async fn load_swarm_middleware_with_task(
 ) -> Result<axum::response::Response, ApiError> {
     let swarm = Swarm::find_by_id(&state.db_pool, params.swarm_id)
         .await?
-        .ok_or_else(|| ApiError::BadRequest("Swarm not found".to_string()))?;
+        .ok_or_else(|| ApiError::BadRequest("Swarm not found".to_string())
+            .with_code(ErrorCode::SwarmNotFound))?;
 
     request.extensions_mut().insert(swarm);
     Ok(next.run(request).await)
@@ -82,28 +111,130 @@ async fn load_swarm_middleware_with_task(
 // Swarm CRUD Handlers
 // ============================================================================
 
-/// GET /api/swarms - List all swarms
+fn validate_setup_script(script: Option<&str>) -> Result<(), ApiError> {
+    if let Some(script) = script
+        && script.len() > 20000
+    {
+        return Err(ApiError::BadRequest("Setup script too long (max 20000 chars)".to_string()));
+    }
+    Ok(())
+}
+
+fn validate_dispatch_rate(rate: Option<i32>) -> Result<(), ApiError> {
+    if let Some(rate) = rate
+        && !(0..=10000).contains(&rate)
+    {
+        return Err(ApiError::BadRequest(
+            "dispatch_rate_per_minute must be between 0 and 10000".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_max_concurrent(limit: Option<i32>) -> Result<(), ApiError> {
+    if let Some(limit) = limit
+        && !(0..=1000).contains(&limit)
+    {
+        return Err(ApiError::BadRequest("max_concurrent must be between 0 and 1000".to_string()));
+    }
+    Ok(())
+}
+
+fn validate_allowed_skills(skills: Option<&[String]>) -> Result<(), ApiError> {
+    let Some(skills) = skills else {
+        return Ok(());
+    };
+    if skills.len() > 200 {
+        return Err(ApiError::BadRequest("Too many allowed skills (max 200)".to_string()));
+    }
+    if let Some(name) = skills.iter().find(|name| !skills::is_valid_skill_name(name)) {
+        return Err(ApiError::BadRequest(format!("Invalid skill name: {}", name))
+            .with_code(ErrorCode::ValidationInvalidSkillName));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListSwarmsQuery {
+    /// Only the swarm imported under this external id
+    pub external_id: Option<String>,
+    /// Also list archived swarms
+    #[serde(default)]
+    pub include_archived: bool,
+    pub status: Option<SwarmStatus>,
+    #[serde(default)]
+    pub sort: SwarmSortKey,
+    #[serde(default)]
+    pub order: SortOrder,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// GET /api/swarms - List swarms, newest first, hiding archived ones unless asked
+///
+/// `sort` is `created_at`, `name` or `updated_at` and `order` is `asc` or
+/// `desc`. Without `limit` every swarm is returned. The number of matching
+/// swarms is sent in the `X-Total-Count` header so clients can page.
 pub async fn list_swarms(
     State(state): State<AppState>,
-) -> Result<ResponseJson<ApiResponse<Vec<Swarm>>>, ApiError> {
-    let swarms = Swarm::find_all(&state.db_pool).await?;
-    Ok(ResponseJson(ApiResponse::success(swarms)))
+    Query(query): Query<ListSwarmsQuery>,
+) -> Result<([(&'static str, String); 1], ResponseJson<ApiResponse<Vec<Swarm>>>), ApiError> {
+    if let Some(limit) = query.limit
+        && !(1..=MAX_LIST_LIMIT).contains(&limit)
+    {
+        return Err(ApiError::BadRequest(format!("limit must be between 1 and {}", MAX_LIST_LIMIT)));
+    }
+    if query.offset.is_some_and(|offset| offset < 0) {
+        return Err(ApiError::BadRequest("offset must not be negative".to_string()));
+    }
+
+    let (swarms, total) = match query.external_id {
+        Some(external_id) => {
+            let swarms: Vec<Swarm> = Swarm::find_by_external_id(&state.db_pool, &external_id)
+                .await?
+                .into_iter()
+                .collect();
+            let total = swarms.len() as i64;
+            (swarms, total)
+        }
+        None => {
+            let filters = SwarmFilters {
+                status: query.status,
+                include_archived: query.include_archived,
+                sort: query.sort,
+                order: query.order,
+                limit: query.limit,
+                offset: query.offset.unwrap_or(0),
+            };
+            Swarm::find_paginated(&state.db_pool, &filters).await?
+        }
+    };
+    Ok((
+        [(TOTAL_COUNT_HEADER, total.to_string())],
+        ResponseJson(ApiResponse::success(swarms)),
+    ))
 }
 
 /// POST /api/swarms - Create a new swarm
 pub async fn create_swarm(
     State(state): State<AppState>,
-    Json(payload): Json<CreateSwarm>,
+    StrictJson(payload): StrictJson<CreateSwarm>,
 ) -> Result<ResponseJson<ApiResponse<Swarm>>, ApiError> {
     // Validate input sizes
     if payload.name.len() > 255 {
-        return Err(ApiError::BadRequest("Name too long (max 255 chars)".to_string()));
+        return Err(ApiError::BadRequest("Name too long (max 255 chars)".to_string())
+            .with_code(ErrorCode::ValidationNameTooLong));
     }
     if let Some(ref desc) = payload.description {
         if desc.len() > 5000 {
-            return Err(ApiError::BadRequest("Description too long (max 5000 chars)".to_string()));
+            return Err(ApiError::BadRequest("Description too long (max 5000 chars)".to_string())
+                .with_code(ErrorCode::ValidationDescriptionTooLong));
         }
     }
+    validate_setup_script(payload.setup_script.as_deref())?;
+    validate_dispatch_rate(payload.dispatch_rate_per_minute)?;
+    validate_max_concurrent(payload.max_concurrent)?;
+    validate_allowed_skills(payload.allowed_skills.as_deref())?;
 
     let swarm_id = Uuid::new_v4();
     let swarm = Swarm::create(&state.db_pool, &payload, swarm_id).await?;
@@ -124,19 +255,25 @@ pub async fn get_swarm(
 pub async fn update_swarm(
     Extension(existing): Extension<Swarm>,
     State(state): State<AppState>,
-    Json(payload): Json<UpdateSwarm>,
+    StrictJson(payload): StrictJson<UpdateSwarm>,
 ) -> Result<ResponseJson<ApiResponse<Swarm>>, ApiError> {
     // Validate input sizes
     if let Some(ref name) = payload.name {
         if name.len() > 255 {
-            return Err(ApiError::BadRequest("Name too long (max 255 chars)".to_string()));
+            return Err(ApiError::BadRequest("Name too long (max 255 chars)".to_string())
+                .with_code(ErrorCode::ValidationNameTooLong));
         }
     }
     if let Some(ref desc) = payload.description {
         if desc.len() > 5000 {
-            return Err(ApiError::BadRequest("Description too long (max 5000 chars)".to_string()));
+            return Err(ApiError::BadRequest("Description too long (max 5000 chars)".to_string())
+                .with_code(ErrorCode::ValidationDescriptionTooLong));
         }
     }
+    validate_setup_script(payload.setup_script.as_deref())?;
+    validate_dispatch_rate(payload.dispatch_rate_per_minute)?;
+    validate_max_concurrent(payload.max_concurrent)?;
+    validate_allowed_skills(payload.allowed_skills.as_deref())?;
 
     let swarm = Swarm::update(&state.db_pool, existing.id, &payload).await?;
     Ok(ResponseJson(ApiResponse::success(swarm)))
@@ -156,6 +293,8 @@ pub async fn delete_swarm(
         .execute(&mut *tx)
         .await?;
 
+    SwarmSecret::delete_by_swarm_id(&mut *tx, swarm.id).await?;
+
     // Delete the swarm within transaction
     let result = sqlx::query("DELETE FROM swarms WHERE id = $1")
         .bind(swarm.id)
@@ -163,12 +302,16 @@ pub async fn delete_swarm(
         .await?;
 
     if result.rows_affected() == 0 {
-        return Err(ApiError::BadRequest("Swarm not found".to_string()));
+        return Err(ApiError::BadRequest("Swarm not found".to_string())
+            .with_code(ErrorCode::SwarmNotFound));
     }
 
     // Commit transaction - both operations succeed atomically
     tx.commit().await?;
 
+    // End open chat streams so their clients stop reconnecting
+    state.broadcast.chat.close_channel(swarm.id).await;
+
     tracing::info!("Deleted swarm {} ({})", swarm.name, swarm.id);
 
     Ok(ResponseJson(ApiResponse::success(DeleteResponse {
@@ -181,6 +324,99 @@ pub struct DeleteResponse {
     pub deleted: bool,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ResetQuery {
+    /// Reset even while tasks are running
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// What a swarm reset cleared
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct ResetSwarmResponse {
+    pub tasks_deleted: u64,
+    pub messages_deleted: u64,
+    pub sandboxes_destroyed: u64,
+}
+
+/// POST /api/swarms/:id/reset - Clear a swarm's tasks, chat and sandboxes
+///
+/// The swarm itself, its id and its settings are kept. Refused while tasks
+/// are running unless `?force=true`; forced resets leave the trigger engine
+/// to stop executions whose task has disappeared. Each sandbox is deleted from
+/// Daytona before it is marked destroyed; one whose delete fails is left
+/// draining for the trigger engine's drained-sandbox sweep.
+pub async fn reset_swarm(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+    Query(query): Query<ResetQuery>,
+) -> Result<ResponseJson<ApiResponse<ResetSwarmResponse>>, ApiError> {
+    if !query.force {
+        let counts = SwarmTask::count_by_status(&state.db_pool, swarm.id).await?;
+        if counts.running > 0 {
+            return Err(ApiError::Conflict(format!(
+                "Swarm has {} running task(s); pass force=true to reset anyway",
+                counts.running
+            ))
+            .with_code(ErrorCode::InvalidSwarmState));
+        }
+    }
+
+    let sandboxes_destroyed = destroy_swarm_sandboxes(&state, swarm.id).await?;
+
+    let mut tx = state.db_pool.begin().await?;
+    let tasks_deleted = SwarmTask::delete_by_swarm_id(&mut *tx, swarm.id).await?;
+    let messages_deleted = SwarmChat::delete_by_swarm_id(&mut *tx, swarm.id).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        "Reset swarm {} ({}): {} tasks, {} messages, {} sandboxes",
+        swarm.name,
+        swarm.id,
+        tasks_deleted,
+        messages_deleted,
+        sandboxes_destroyed
+    );
+
+    Ok(ResponseJson(ApiResponse::success(ResetSwarmResponse {
+        tasks_deleted,
+        messages_deleted,
+        sandboxes_destroyed,
+    })))
+}
+
+/// Delete a swarm's live sandboxes from Daytona, returning how many were destroyed
+async fn destroy_swarm_sandboxes(state: &AppState, swarm_id: Uuid) -> Result<u64, ApiError> {
+    let sandboxes = Sandbox::find_active_by_swarm_id(&state.db_pool, swarm_id).await?;
+    if sandboxes.is_empty() {
+        return Ok(0);
+    }
+
+    let client = pool::daytona_client(state).await?;
+    let mut destroyed = 0;
+    for sandbox in sandboxes {
+        match client.delete_sandbox(&sandbox.daytona_id).await {
+            // Already gone from Daytona
+            Ok(()) | Err(DaytonaError::SandboxNotFound(_)) => {}
+            Err(e) => {
+                tracing::warn!(sandbox_id = %sandbox.id, error = %e, "Failed to delete sandbox during reset, leaving it draining");
+                Sandbox::mark_draining(&state.db_pool, sandbox.id).await?;
+                Sandbox::release_task(&state.db_pool, sandbox.id).await?;
+                continue;
+            }
+        }
+
+        Sandbox::mark_destroyed(&state.db_pool, sandbox.id).await?;
+        state.broadcast.pool.publish(
+            PoolStatusUpdate::new(sandbox.id.to_string(), SandboxStatus::Destroyed.to_string())
+                .with_swarm(swarm_id.to_string()),
+        );
+        destroyed += 1;
+    }
+
+    Ok(destroyed)
+}
+
 // ============================================================================
 // Swarm Lifecycle Handlers
 // ============================================================================
@@ -191,14 +427,16 @@ pub async fn pause_swarm(
     State(state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<Swarm>>, ApiError> {
     if swarm.status == SwarmStatus::Paused {
-        return Err(ApiError::BadRequest("Swarm is already paused".to_string()));
+        return Err(ApiError::BadRequest("Swarm is already paused".to_string())
+            .with_code(ErrorCode::InvalidSwarmState));
     }
 
     Swarm::update_status(&state.db_pool, swarm.id, SwarmStatus::Paused).await?;
 
     let updated = Swarm::find_by_id(&state.db_pool, swarm.id)
         .await?
-        .ok_or_else(|| ApiError::BadRequest("Swarm not found".to_string()))?;
+        .ok_or_else(|| ApiError::BadRequest("Swarm not found".to_string())
+            .with_code(ErrorCode::SwarmNotFound))?;
 
     tracing::info!("Paused swarm {} ({})", swarm.name, swarm.id);
 
@@ -211,20 +449,65 @@ pub async fn resume_swarm(
     State(state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<Swarm>>, ApiError> {
     if swarm.status == SwarmStatus::Active {
-        return Err(ApiError::BadRequest("Swarm is already active".to_string()));
+        return Err(ApiError::BadRequest("Swarm is already active".to_string())
+            .with_code(ErrorCode::InvalidSwarmState));
     }
 
     Swarm::update_status(&state.db_pool, swarm.id, SwarmStatus::Active).await?;
 
     let updated = Swarm::find_by_id(&state.db_pool, swarm.id)
         .await?
-        .ok_or_else(|| ApiError::BadRequest("Swarm not found".to_string()))?;
+        .ok_or_else(|| ApiError::BadRequest("Swarm not found".to_string())
+            .with_code(ErrorCode::SwarmNotFound))?;
 
     tracing::info!("Resumed swarm {} ({})", swarm.name, swarm.id);
 
     Ok(ResponseJson(ApiResponse::success(updated)))
 }
 
+/// POST /api/swarms/:id/archive - Hide a swarm without deleting its data
+///
+/// Archived swarms are left out of listings and never dispatched; use
+/// DELETE to remove a swarm permanently.
+pub async fn archive_swarm(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Swarm>>, ApiError> {
+    if !Swarm::archive(&state.db_pool, swarm.id).await? {
+        return Err(ApiError::BadRequest("Swarm is already archived".to_string())
+            .with_code(ErrorCode::InvalidSwarmState));
+    }
+
+    let updated = Swarm::find_by_id(&state.db_pool, swarm.id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Swarm not found".to_string())
+            .with_code(ErrorCode::SwarmNotFound))?;
+
+    tracing::info!("Archived swarm {} ({})", swarm.name, swarm.id);
+
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
+/// POST /api/swarms/:id/unarchive - Restore an archived swarm
+pub async fn unarchive_swarm(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Swarm>>, ApiError> {
+    if !Swarm::unarchive(&state.db_pool, swarm.id).await? {
+        return Err(ApiError::BadRequest("Swarm is not archived".to_string())
+            .with_code(ErrorCode::InvalidSwarmState));
+    }
+
+    let updated = Swarm::find_by_id(&state.db_pool, swarm.id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Swarm not found".to_string())
+            .with_code(ErrorCode::SwarmNotFound))?;
+
+    tracing::info!("Unarchived swarm {} ({})", swarm.name, swarm.id);
+
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
 // ============================================================================
 // Router
 // ============================================================================
@@ -235,8 +518,22 @@ pub fn router(state: &AppState) -> Router<AppState> {
         .route("/", get(get_swarm).put(update_swarm).delete(delete_swarm))
         .route("/pause", post(pause_swarm))
         .route("/resume", post(resume_swarm))
+        .route("/archive", post(archive_swarm))
+        .route("/unarchive", post(unarchive_swarm))
+        .route("/reset", post(reset_swarm))
+        .route("/export", get(bundle::export_swarm))
         .route("/tasks", get(tasks::list_tasks).post(tasks::create_task))
+        .route("/tasks/graph", get(tasks::get_task_graph))
+        .route("/tasks/stats", get(tasks::get_task_stats))
+        .route("/tasks/batch", post(tasks::batch_create_tasks))
+        .route("/tasks/import", post(tasks::import_tasks))
+        .route("/tasks/retry-failed", post(tasks::retry_failed_tasks))
+        .route("/skills", get(skills::list_swarm_skills))
+        .route("/skills/{name}", get(skills::get_swarm_skill))
+        .route("/pool/warmup", post(pool::warmup_pool))
         .merge(chat::router())
+        .merge(events::router())
+        .merge(secrets::router())
         .layer(from_fn_with_state(state.clone(), load_swarm_middleware));
 
     // Routes with both swarm_id and task_id
@@ -246,6 +543,8 @@ pub fn router(state: &AppState) -> Router<AppState> {
     // Main swarms router
     let swarms_router = Router::new()
         .route("/", get(list_swarms).post(create_swarm))
+        .route("/import", post(bundle::import_swarm))
+        .merge(trigger::router())
         .nest("/{swarm_id}", swarm_id_only_router)
         .nest("/{swarm_id}/tasks/{task_id}", task_routes);
 
@@ -255,5 +554,6 @@ pub fn router(state: &AppState) -> Router<AppState> {
         .merge(pool::router())
         .merge(skills::router())
         .merge(config::router())
+        .merge(admin::router())
         .merge(ws::router())
 }
@@ -1,27 +1,118 @@
 //! Pool (Sandbox) Management Routes
 
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
 use axum::{
-    Router,
-    extract::{Path, State},
-    response::Json as ResponseJson,
+    Extension, Router,
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Json as ResponseJson, Response},
     routing::{get, post},
 };
-use db::models::sandbox::{Sandbox, SandboxStatus};
+use chrono::{DateTime, Utc};
+use db::models::pool_status_history::PoolStatusSample;
+use db::models::sandbox::{CreateSandbox, Sandbox, SandboxStatus};
+use db::models::sandbox_command::SandboxCommand;
+use db::models::swarm::Swarm;
+use db::models::swarm_chat::SwarmChat;
+use db::models::swarm_config::SwarmConfig;
+use futures_util::future::join_all;
 use serde::{Deserialize, Serialize};
+use services::services::swarm::{
+    CommandHook, DaytonaClient, DaytonaConfig, DaytonaError, FileEntry, PoolStatusUpdate, PreviewUrl,
+    validate_workspace_file,
+};
 use ts_rs::TS;
-use utils::response::ApiResponse;
+use utils::response::{ApiResponse, ErrorCode};
 use uuid::Uuid;
 
+use super::StrictJson;
 use crate::{AppState, error::ApiError};
 
+/// Most sandboxes a single warmup request may create
+pub const MAX_WARMUP_COUNT: u32 = 20;
+
+/// How long a live Daytona lookup is reused before asking again
+pub const LIVE_STATE_TTL: Duration = Duration::from_secs(5);
+
 #[derive(Debug, Serialize, Deserialize, TS)]
 pub struct PoolStatus {
     pub total: i64,
     pub idle: usize,
     pub busy: usize,
+    pub draining: usize,
     pub sandboxes: Vec<Sandbox>,
 }
 
+/// Sandbox record plus, on request, its live state in Daytona
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct SandboxDetail {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub sandbox: Sandbox,
+    /// Daytona runtime state (e.g. started, stopped, error); only set with `?refresh=true`
+    pub state: Option<String>,
+}
+
+/// Sandbox record plus its live state in Daytona
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct SandboxLive {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub sandbox: Sandbox,
+    /// Daytona runtime state (e.g. started, stopped, error)
+    pub state: Option<String>,
+    /// Daytona no longer knows the sandbox, though our record says it exists
+    pub destroyed_remotely: bool,
+    /// When Daytona was asked; None for sandboxes destroyed here, which are never looked up
+    #[ts(type = "Date | null")]
+    pub fetched_at: Option<DateTime<Utc>>,
+}
+
+/// What Daytona last said about a sandbox
+#[derive(Debug, Clone)]
+struct RemoteState {
+    state: Option<String>,
+    destroyed_remotely: bool,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Recent live lookups by sandbox id, so a polling dashboard doesn't hit
+/// Daytona on every request
+#[derive(Debug, Default)]
+pub struct LiveStateCache {
+    entries: Mutex<HashMap<Uuid, (Instant, RemoteState)>>,
+}
+
+impl LiveStateCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn get(&self, sandbox_id: Uuid, now: Instant) -> Option<RemoteState> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(&sandbox_id)
+            .filter(|(at, _)| now.duration_since(*at) < LIVE_STATE_TTL)
+            .map(|(_, state)| state.clone())
+    }
+
+    fn insert(&self, sandbox_id: Uuid, state: RemoteState, now: Instant) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, (at, _)| now.duration_since(*at) < LIVE_STATE_TTL);
+        entries.insert(sandbox_id, (now, state));
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SandboxQuery {
+    /// Ask Daytona for the sandbox's live state instead of returning cached data only
+    #[serde(default)]
+    pub refresh: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, TS)]
 pub struct CleanupResponse {
     pub success: bool,
@@ -35,6 +126,62 @@ pub struct DestroyResponse {
     pub sandbox_id: Uuid,
 }
 
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct DrainResponse {
+    pub success: bool,
+    pub sandbox_id: Uuid,
+    pub status: SandboxStatus,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PreviewQuery {
+    /// Lifetime in seconds of a signed URL; omit for the plain preview URL
+    pub ttl: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InboxQuery {
+    /// Only messages posted after this time; pass the last message's `created_at`
+    pub since: Option<DateTime<Utc>>,
+    /// Most messages to return (1-500, default 100)
+    pub limit: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FilesQuery {
+    /// Directory to list inside the sandbox
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[serde(deny_unknown_fields)]
+pub struct WarmupRequest {
+    /// Sandboxes to create; fewer are created if the pool fills up
+    pub count: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct WarmupResponse {
+    pub requested: u32,
+    /// Sandboxes created and registered idle for the swarm
+    pub created: Vec<Sandbox>,
+    /// Errors from creations that failed; the others are still returned
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PoolHistoryQuery {
+    /// Earliest sample time to include (RFC 3339)
+    pub from: Option<DateTime<Utc>>,
+    /// Latest sample time to include (RFC 3339)
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommandsQuery {
+    pub limit: Option<i32>,
+}
+
 pub async fn get_pool_status(
     State(state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<PoolStatus>>, ApiError> {
@@ -53,23 +200,110 @@ pub async fn get_pool_status(
         .filter(|s| s.status == SandboxStatus::Busy)
         .count();
 
+    let draining_count = sandboxes
+        .iter()
+        .filter(|s| s.status == SandboxStatus::Draining)
+        .count();
+
     Ok(ResponseJson(ApiResponse::success(PoolStatus {
         total,
         idle: idle_count,
         busy: busy_count,
+        draining: draining_count,
         sandboxes,
     })))
 }
 
+/// Build a Daytona client from the stored swarm config
+pub(super) async fn daytona_client(state: &AppState) -> Result<DaytonaClient, ApiError> {
+    let config = SwarmConfig::get(&state.db_pool).await?;
+    let daytona_config = DaytonaConfig::from_swarm_config(&config)
+        .ok_or_else(|| ApiError::BadRequest("Daytona is not configured".to_string())
+            .with_code(ErrorCode::DaytonaNotConfigured))?;
+
+    Ok(DaytonaClient::new(daytona_config)?
+        .with_command_hook(CommandHook::persist_to(state.db_pool.clone())))
+}
+
+/// GET /api/pool/:sandbox_id - Sandbox record, optionally with its live Daytona state
+///
+/// Our status is bookkeeping only; `?refresh=true` also fetches the runtime
+/// state from Daytona so divergence (our "idle", Daytona "stopped") is visible.
+/// Destroyed sandboxes are never looked up.
 pub async fn get_sandbox(
     State(state): State<AppState>,
     Path(sandbox_id): Path<Uuid>,
-) -> Result<ResponseJson<ApiResponse<Sandbox>>, ApiError> {
+    Query(query): Query<SandboxQuery>,
+) -> Result<ResponseJson<ApiResponse<SandboxDetail>>, ApiError> {
+    let sandbox = Sandbox::find_by_id(&state.db_pool, sandbox_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Sandbox not found".to_string())
+            .with_code(ErrorCode::SandboxNotFound))?;
+
+    let live_state = if query.refresh && sandbox.status != SandboxStatus::Destroyed {
+        let client = daytona_client(&state).await?;
+        client.get_sandbox(&sandbox.daytona_id).await?.state
+    } else {
+        None
+    };
+
+    Ok(ResponseJson(ApiResponse::success(SandboxDetail {
+        sandbox,
+        state: live_state,
+    })))
+}
+
+/// GET /api/pool/:sandbox_id/live - Sandbox record with its live Daytona state
+///
+/// Lookups are reused for `LIVE_STATE_TTL`. A sandbox that Daytona no longer
+/// knows is reported with `destroyed_remotely` rather than as an error, so the
+/// UI can reconcile it.
+pub async fn get_sandbox_live(
+    State(state): State<AppState>,
+    Path(sandbox_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<SandboxLive>>, ApiError> {
     let sandbox = Sandbox::find_by_id(&state.db_pool, sandbox_id)
         .await?
-        .ok_or_else(|| ApiError::BadRequest("Sandbox not found".to_string()))?;
+        .ok_or_else(|| ApiError::BadRequest("Sandbox not found".to_string())
+            .with_code(ErrorCode::SandboxNotFound))?;
+
+    if sandbox.status == SandboxStatus::Destroyed {
+        return Ok(ResponseJson(ApiResponse::success(SandboxLive {
+            sandbox,
+            state: None,
+            destroyed_remotely: false,
+            fetched_at: None,
+        })));
+    }
 
-    Ok(ResponseJson(ApiResponse::success(sandbox)))
+    let remote = match state.live_states.get(sandbox_id, Instant::now()) {
+        Some(remote) => remote,
+        None => {
+            let client = daytona_client(&state).await?;
+            let remote = match client.get_sandbox(&sandbox.daytona_id).await {
+                Ok(live) => RemoteState {
+                    state: live.state,
+                    destroyed_remotely: false,
+                    fetched_at: Utc::now(),
+                },
+                Err(DaytonaError::SandboxNotFound(_)) => RemoteState {
+                    state: None,
+                    destroyed_remotely: true,
+                    fetched_at: Utc::now(),
+                },
+                Err(e) => return Err(e.into()),
+            };
+            state.live_states.insert(sandbox_id, remote.clone(), Instant::now());
+            remote
+        }
+    };
+
+    Ok(ResponseJson(ApiResponse::success(SandboxLive {
+        sandbox,
+        state: remote.state,
+        destroyed_remotely: remote.destroyed_remotely,
+        fetched_at: Some(remote.fetched_at),
+    })))
 }
 
 pub async fn destroy_sandbox(
@@ -80,7 +314,8 @@ pub async fn destroy_sandbox(
 
     let sandbox = Sandbox::find_by_id(pool, sandbox_id)
         .await?
-        .ok_or_else(|| ApiError::BadRequest("Sandbox not found".to_string()))?;
+        .ok_or_else(|| ApiError::BadRequest("Sandbox not found".to_string())
+            .with_code(ErrorCode::SandboxNotFound))?;
 
     Sandbox::mark_destroyed(pool, sandbox.id).await?;
 
@@ -92,6 +327,71 @@ pub async fn destroy_sandbox(
     })))
 }
 
+/// POST /api/pool/:sandbox_id/drain - Destroy a sandbox once its current task finishes
+///
+/// A draining sandbox takes no new tasks. An idle sandbox has nothing to
+/// finish and is deleted from Daytona immediately; a busy one, or an idle one
+/// whose delete fails, is destroyed by the trigger engine's drained-sandbox
+/// sweep once its task releases it.
+pub async fn drain_sandbox(
+    State(state): State<AppState>,
+    Path(sandbox_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<DrainResponse>>, ApiError> {
+    let pool = &state.db_pool;
+
+    let sandbox = Sandbox::find_by_id(pool, sandbox_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Sandbox not found".to_string())
+            .with_code(ErrorCode::SandboxNotFound))?;
+
+    if !Sandbox::mark_draining(pool, sandbox.id).await? {
+        return Err(ApiError::Conflict(format!("Sandbox is already {}", sandbox.status))
+            .with_code(ErrorCode::InvalidSandboxState));
+    }
+
+    // Re-read after the update so a task released in between is not missed
+    let sandbox = Sandbox::find_by_id(pool, sandbox_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Sandbox not found".to_string())
+            .with_code(ErrorCode::SandboxNotFound))?;
+
+    let status = if sandbox.current_task_id.is_none() {
+        let deleted = match daytona_client(&state).await {
+            Ok(client) => match client.delete_sandbox(&sandbox.daytona_id).await {
+                // Already gone from Daytona
+                Ok(()) | Err(DaytonaError::SandboxNotFound(_)) => Ok(()),
+                Err(e) => Err(ApiError::from(e)),
+            },
+            Err(e) => Err(e),
+        };
+        match deleted {
+            Ok(()) => {
+                Sandbox::mark_destroyed(pool, sandbox.id).await?;
+                let mut update = PoolStatusUpdate::new(sandbox.id.to_string(), SandboxStatus::Destroyed.to_string());
+                if let Some(swarm_id) = sandbox.swarm_id {
+                    update = update.with_swarm(swarm_id.to_string());
+                }
+                state.broadcast.pool.publish(update);
+                SandboxStatus::Destroyed
+            }
+            Err(e) => {
+                tracing::warn!(sandbox_id = %sandbox.id, error = %e, "Failed to delete drained sandbox, leaving it draining");
+                SandboxStatus::Draining
+            }
+        }
+    } else {
+        SandboxStatus::Draining
+    };
+
+    tracing::info!("Draining sandbox {} (daytona_id: {}): {}", sandbox.id, sandbox.daytona_id, status);
+
+    Ok(ResponseJson(ApiResponse::success(DrainResponse {
+        success: true,
+        sandbox_id,
+        status,
+    })))
+}
+
 pub async fn cleanup_pool(
     State(state): State<AppState>,
 ) -> Result<ResponseJson<ApiResponse<CleanupResponse>>, ApiError> {
@@ -115,9 +415,294 @@ pub async fn cleanup_pool(
     })))
 }
 
+/// GET /api/pool/:sandbox_id/commands - Commands executed in a sandbox, newest first
+///
+/// Commands are recorded with secrets masked. `?limit=N` defaults to 100
+/// (max 500).
+pub async fn get_sandbox_commands(
+    State(state): State<AppState>,
+    Path(sandbox_id): Path<Uuid>,
+    Query(query): Query<CommandsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<SandboxCommand>>>, ApiError> {
+    let sandbox = Sandbox::find_by_id(&state.db_pool, sandbox_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Sandbox not found".to_string())
+            .with_code(ErrorCode::SandboxNotFound))?;
+
+    let commands =
+        SandboxCommand::find_by_daytona_id(&state.db_pool, &sandbox.daytona_id, query.limit)
+            .await?;
+
+    Ok(ResponseJson(ApiResponse::success(commands)))
+}
+
+/// GET /api/pool/:sandbox_id/inbox - Chat messages that @mention a sandbox, oldest first
+///
+/// Agents poll with `since` set to the last message's `created_at` to pick up
+/// new directed instructions. See `ChatService::route_mentions` for which
+/// mentions reach a sandbox.
+pub async fn get_sandbox_inbox(
+    State(state): State<AppState>,
+    Path(sandbox_id): Path<Uuid>,
+    Query(query): Query<InboxQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<SwarmChat>>>, ApiError> {
+    if let Some(limit) = query.limit
+        && !(1..=500).contains(&limit)
+    {
+        return Err(ApiError::BadRequest("limit must be between 1 and 500".to_string()));
+    }
+
+    let sandbox = Sandbox::find_by_id(&state.db_pool, sandbox_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Sandbox not found".to_string())
+            .with_code(ErrorCode::SandboxNotFound))?;
+
+    let messages = SwarmChat::find_inbox(&state.db_pool, sandbox.id, query.since, query.limit).await?;
+
+    Ok(ResponseJson(ApiResponse::success(messages)))
+}
+
+/// GET /api/pool/:sandbox_id/files?path= - Directory listing from a sandbox
+pub async fn list_sandbox_files(
+    State(state): State<AppState>,
+    Path(sandbox_id): Path<Uuid>,
+    Query(query): Query<FilesQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<FileEntry>>>, ApiError> {
+    if query.path.trim().is_empty() || query.path.len() > 4096 {
+        return Err(ApiError::BadRequest("path must be between 1 and 4096 chars".to_string()));
+    }
+
+    let sandbox = Sandbox::find_by_id(&state.db_pool, sandbox_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Sandbox not found".to_string())
+            .with_code(ErrorCode::SandboxNotFound))?;
+
+    if sandbox.status == SandboxStatus::Destroyed {
+        return Err(ApiError::BadRequest("Sandbox has been destroyed".to_string())
+            .with_code(ErrorCode::InvalidSandboxState));
+    }
+
+    let client = daytona_client(&state).await?;
+    let entries = client.list_files(&sandbox.daytona_id, &query.path).await?;
+
+    Ok(ResponseJson(ApiResponse::success(entries)))
+}
+
+/// GET /api/pool/:sandbox_id/files/download?path= - A file from a sandbox's workspace
+///
+/// The body is the file as stored, so binary artifacts such as images and
+/// archives survive, with a content type guessed from the extension. Only
+/// files under the workspace may be fetched.
+pub async fn download_sandbox_file(
+    State(state): State<AppState>,
+    Path(sandbox_id): Path<Uuid>,
+    Query(query): Query<FilesQuery>,
+) -> Result<Response, ApiError> {
+    validate_workspace_file(&query.path, "path").map_err(ApiError::BadRequest)?;
+
+    let sandbox = Sandbox::find_by_id(&state.db_pool, sandbox_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Sandbox not found".to_string())
+            .with_code(ErrorCode::SandboxNotFound))?;
+
+    if sandbox.status == SandboxStatus::Destroyed {
+        return Err(ApiError::BadRequest("Sandbox has been destroyed".to_string())
+            .with_code(ErrorCode::InvalidSandboxState));
+    }
+
+    let client = daytona_client(&state).await?;
+    let bytes = client.read_file_bytes(&sandbox.daytona_id, &query.path).await?;
+
+    let content_type = mime_guess::from_path(&query.path).first_or_octet_stream().to_string();
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CONTENT_DISPOSITION, attachment_disposition(&query.path)),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+/// `Content-Disposition` naming the file after the last path segment
+///
+/// Characters that cannot appear in a quoted header value are replaced.
+fn attachment_disposition(path: &str) -> String {
+    let name: String = path
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            let allowed = c == ' ' || (c.is_ascii_graphic() && c != '"' && c != '\\');
+            if allowed { c } else { '_' }
+        })
+        .collect();
+    format!("attachment; filename=\"{}\"", name)
+}
+
+/// GET /api/pool/:sandbox_id/preview/:port - Preview URL for a port exposed by a sandbox
+///
+/// With `?ttl=N` the URL is signed by Daytona and expires after N seconds.
+/// Without it the plain preview URL is returned, which is not access-limited;
+/// check `signed`/`fallback` in the response before sharing it.
+pub async fn get_preview_url(
+    State(state): State<AppState>,
+    Path((sandbox_id, port)): Path<(Uuid, u16)>,
+    Query(query): Query<PreviewQuery>,
+) -> Result<ResponseJson<ApiResponse<PreviewUrl>>, ApiError> {
+    let sandbox = Sandbox::find_by_id(&state.db_pool, sandbox_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Sandbox not found".to_string())
+            .with_code(ErrorCode::SandboxNotFound))?;
+
+    if sandbox.status == SandboxStatus::Destroyed {
+        return Err(ApiError::BadRequest("Sandbox has been destroyed".to_string())
+            .with_code(ErrorCode::InvalidSandboxState));
+    }
+
+    let client = daytona_client(&state).await?;
+
+    let preview = match query.ttl {
+        Some(ttl) => client.get_signed_preview_url(&sandbox.daytona_id, port, ttl).await?,
+        None => client.get_preview_url(&sandbox.daytona_id, port).await?,
+    };
+
+    Ok(ResponseJson(ApiResponse::success(preview)))
+}
+
+/// POST /api/swarms/:id/pool/warmup - Pre-create idle sandboxes for a swarm
+///
+/// Creates up to `count` sandboxes so the swarm's first tasks don't wait on
+/// Daytona. Creation goes through the shared creation semaphore and stops
+/// once the pool reaches `pool_max_sandboxes`; a full pool is a 409.
+pub async fn warmup_pool(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+    StrictJson(payload): StrictJson<WarmupRequest>,
+) -> Result<ResponseJson<ApiResponse<WarmupResponse>>, ApiError> {
+    if payload.count == 0 || payload.count > MAX_WARMUP_COUNT {
+        return Err(ApiError::BadRequest(format!(
+            "count must be between 1 and {}",
+            MAX_WARMUP_COUNT
+        )));
+    }
+
+    let max_sandboxes = i64::from(SwarmConfig::get(&state.db_pool).await?.pool_max_sandboxes);
+    let available = max_sandboxes - Sandbox::count_active(&state.db_pool).await?;
+    if available <= 0 {
+        return Err(ApiError::Conflict(format!(
+            "Pool is at capacity (max: {})",
+            max_sandboxes
+        ))
+        .with_code(ErrorCode::PoolAtCapacity));
+    }
+
+    let client = daytona_client(&state).await?;
+    let wanted = i64::from(payload.count).min(available);
+
+    let results = join_all(
+        (0..wanted).map(|_| create_warm_sandbox(&state, &client, swarm.id, max_sandboxes)),
+    )
+    .await;
+
+    let mut created = Vec::new();
+    let mut errors = Vec::new();
+    for result in results {
+        match result {
+            Ok(Some(sandbox)) => created.push(sandbox),
+            Ok(None) => {}
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+
+    tracing::info!(
+        swarm_id = %swarm.id,
+        requested = payload.count,
+        created = created.len(),
+        failed = errors.len(),
+        "Pool warmup finished"
+    );
+
+    Ok(ResponseJson(ApiResponse::success(WarmupResponse {
+        requested: payload.count,
+        created,
+        errors,
+    })))
+}
+
+/// Create and register one idle sandbox, or None if the pool filled up meanwhile
+async fn create_warm_sandbox(
+    state: &AppState,
+    client: &DaytonaClient,
+    swarm_id: Uuid,
+    max_sandboxes: i64,
+) -> Result<Option<Sandbox>, ApiError> {
+    let _permit = state
+        .sandbox_creation
+        .acquire()
+        .await
+        .map_err(|_| ApiError::Conflict("Sandbox creation is shutting down".to_string()))?;
+
+    // Other creations may have filled the pool while this one waited
+    if Sandbox::count_active(&state.db_pool).await? >= max_sandboxes {
+        return Ok(None);
+    }
+
+    let remote = client.create_sandbox_from_snapshot(None).await?;
+    let data = CreateSandbox {
+        daytona_id: remote.id.clone(),
+        swarm_id: Some(swarm_id),
+        snapshot: remote.snapshot.clone(),
+    };
+
+    let sandbox = match Sandbox::create(&state.db_pool, &data, Uuid::new_v4()).await {
+        Ok(sandbox) => sandbox,
+        Err(e) => {
+            // Don't leave an untracked sandbox running in Daytona
+            if let Err(delete_err) = client.delete_sandbox(&remote.id).await {
+                tracing::warn!(daytona_id = %remote.id, "Failed to delete unregistered sandbox: {}", delete_err);
+            }
+            return Err(e.into());
+        }
+    };
+
+    state.broadcast.pool.publish(
+        PoolStatusUpdate::new(sandbox.id.to_string(), sandbox.status.to_string())
+            .with_swarm(swarm_id.to_string()),
+    );
+
+    Ok(Some(sandbox))
+}
+
+/// Recorded pool utilization samples, oldest first
+pub async fn get_pool_history(
+    State(state): State<AppState>,
+    Query(query): Query<PoolHistoryQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<PoolStatusSample>>>, ApiError> {
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from > to
+    {
+        return Err(ApiError::BadRequest(
+            "'from' must not be later than 'to'".to_string(),
+        ));
+    }
+
+    let samples = PoolStatusSample::find_range(&state.db_pool, query.from, query.to).await?;
+    Ok(ResponseJson(ApiResponse::success(samples)))
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/pool", get(get_pool_status))
         .route("/pool/cleanup", post(cleanup_pool))
+        .route("/pool/history", get(get_pool_history))
         .route("/pool/{sandbox_id}", get(get_sandbox).delete(destroy_sandbox))
+        .route("/pool/{sandbox_id}/live", get(get_sandbox_live))
+        .route("/pool/{sandbox_id}/drain", post(drain_sandbox))
+        .route("/pool/{sandbox_id}/commands", get(get_sandbox_commands))
+        .route("/pool/{sandbox_id}/files", get(list_sandbox_files))
+        .route("/pool/{sandbox_id}/files/download", get(download_sandbox_file))
+        .route("/pool/{sandbox_id}/inbox", get(get_sandbox_inbox))
+        .route("/pool/{sandbox_id}/preview/{port}", get(get_preview_url))
 }
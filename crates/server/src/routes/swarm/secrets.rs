@@ -0,0 +1,113 @@
+//! Swarm Secret Routes
+//!
+//! Secrets are write-only: responses carry names and timestamps, never values.
+
+use axum::{
+    Extension, Router,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+    routing::{get, put},
+};
+use db::models::{
+    swarm::Swarm,
+    swarm_secret::{SetSwarmSecret, SwarmSecret},
+};
+use utils::response::{ApiResponse, ErrorCode};
+use uuid::Uuid;
+
+use super::{DeleteResponse, StrictJson};
+use crate::{AppState, error::ApiError};
+
+/// Longest accepted secret name
+const MAX_SECRET_NAME_LEN: usize = 128;
+
+/// Largest accepted secret value (64 KiB)
+const MAX_SECRET_VALUE_LEN: usize = 64 * 1024;
+
+/// Most secrets a single task may reference
+const MAX_TASK_SECRETS: usize = 20;
+
+/// Secret names become environment variable names, so they follow the same rules
+pub(super) fn validate_secret_name(name: &str) -> Result<(), ApiError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_SECRET_NAME_LEN
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(ApiError::BadRequest(format!(
+            "Invalid secret name '{}': use letters, digits and underscores, not starting with a digit (max {} chars)",
+            name, MAX_SECRET_NAME_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// Check the secret names a task refers to
+///
+/// Names are not required to exist yet; a task whose secret is missing when it
+/// runs fails with an error naming it.
+pub(super) fn validate_task_secrets(secrets: Option<&[String]>) -> Result<(), ApiError> {
+    let Some(secrets) = secrets else {
+        return Ok(());
+    };
+    if secrets.len() > MAX_TASK_SECRETS {
+        return Err(ApiError::BadRequest(format!(
+            "Too many secrets (max {})",
+            MAX_TASK_SECRETS
+        )));
+    }
+    secrets.iter().try_for_each(|name| validate_secret_name(name))
+}
+
+/// GET /api/swarms/:id/secrets - Names of the swarm's secrets
+pub async fn list_secrets(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<SwarmSecret>>>, ApiError> {
+    let secrets = SwarmSecret::find_by_swarm_id(&state.db_pool, swarm.id).await?;
+    Ok(ResponseJson(ApiResponse::success(secrets)))
+}
+
+/// PUT /api/swarms/:id/secrets/:name - Create or replace a secret
+pub async fn set_secret(
+    Extension(swarm): Extension<Swarm>,
+    Path((_swarm_id, name)): Path<(Uuid, String)>,
+    State(state): State<AppState>,
+    StrictJson(payload): StrictJson<SetSwarmSecret>,
+) -> Result<ResponseJson<ApiResponse<SwarmSecret>>, ApiError> {
+    validate_secret_name(&name)?;
+    if payload.value.is_empty() || payload.value.len() > MAX_SECRET_VALUE_LEN {
+        return Err(ApiError::BadRequest(format!(
+            "Secret value must be 1-{} bytes",
+            MAX_SECRET_VALUE_LEN
+        )));
+    }
+
+    let secret = SwarmSecret::upsert(&state.db_pool, swarm.id, &name, &payload).await?;
+
+    tracing::info!("Set secret '{}' in swarm {}", name, swarm.id);
+
+    Ok(ResponseJson(ApiResponse::success(secret)))
+}
+
+/// DELETE /api/swarms/:id/secrets/:name - Remove a secret
+pub async fn delete_secret(
+    Extension(swarm): Extension<Swarm>,
+    Path((_swarm_id, name)): Path<(Uuid, String)>,
+    State(state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<DeleteResponse>>, ApiError> {
+    if !SwarmSecret::delete(&state.db_pool, swarm.id, &name).await? {
+        return Err(ApiError::BadRequest("Secret not found".to_string())
+            .with_code(ErrorCode::SecretNotFound));
+    }
+
+    tracing::info!("Deleted secret '{}' from swarm {}", name, swarm.id);
+
+    Ok(ResponseJson(ApiResponse::success(DeleteResponse { deleted: true })))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/secrets", get(list_secrets))
+        .route("/secrets/{name}", put(set_secret).delete(delete_secret))
+}
@@ -1,20 +1,34 @@
 //! Skills Discovery Routes
 
+use std::io::Read;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use axum::{
-    Router,
+    Extension, Router,
     extract::{Path, Query, State},
     response::Json as ResponseJson,
-    routing::get,
+    routing::{get, post},
 };
+use db::models::swarm::Swarm;
 use db::models::swarm_config::SwarmConfig;
 use serde::{Deserialize, Serialize};
+use services::services::swarm::{
+    SKILL_FILE, SkillFrontmatter, plan_skill_preview, split_skill_frontmatter,
+};
 use ts_rs::TS;
-use utils::response::ApiResponse;
+use utils::response::{ApiResponse, ErrorCode};
+use uuid::Uuid;
 
+use super::StrictJson;
 use crate::{AppState, error::ApiError};
 
+/// Largest SKILL.md returned by the detail endpoint
+pub const MAX_SKILL_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Bytes of SKILL.md read to describe a skill in listings
+const SKILL_PREVIEW_BYTES: u64 = 16 * 1024;
+
 #[derive(Debug, Serialize, Deserialize, TS)]
 pub struct Skill {
     pub name: String,
@@ -23,6 +37,13 @@ pub struct Skill {
     pub path: String,
     pub has_skill_file: bool,
     pub description: String,
+    /// Name declared in the skill's frontmatter; `name` is the directory name
+    pub display_name: Option<String>,
+    pub version: Option<String>,
+    /// Tags declared in the skill's frontmatter
+    pub tags: Vec<String>,
+    /// Working directory declared in the skill's frontmatter
+    pub workdir: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -37,6 +58,38 @@ pub struct SkillDetail {
     pub path: String,
     pub content: String,
     pub files: Vec<String>,
+    /// Working directory declared in the skill's frontmatter
+    pub workdir: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[serde(deny_unknown_fields)]
+pub struct CreateSkillRequest {
+    /// Directory name of the skill
+    pub name: String,
+    /// SKILL.md markdown
+    pub content: String,
+    /// Replace the SKILL.md of an existing skill instead of rejecting the request
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[serde(deny_unknown_fields)]
+pub struct SkillPreviewRequest {
+    pub title: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Prompt a task using the skill would be sent, with the skill's instructions
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct SkillPreview {
+    pub prompt: String,
+    pub workdir: String,
+    /// The skill's SKILL.md
+    pub skill_content: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,37 +113,109 @@ pub fn find_skills_dir(config_path: &str) -> Option<PathBuf> {
     None
 }
 
-fn read_skill_description(skill_path: &PathBuf) -> String {
-    let skill_file = skill_path.join("SKILL.md");
+/// Frontmatter and short description of a skill
+///
+/// The description comes from the frontmatter, else the first text line of
+/// SKILL.md, truncated to 100 chars.
+fn read_skill_metadata(skill_path: &PathBuf) -> (SkillFrontmatter, String) {
+    let skill_file = skill_path.join(SKILL_FILE);
+
+    // Only the head is needed; a huge file must not stall the listing
+    let mut head = Vec::new();
+    let Ok(_) = std::fs::File::open(&skill_file)
+        .and_then(|file| file.take(SKILL_PREVIEW_BYTES).read_to_end(&mut head))
+    else {
+        return (SkillFrontmatter::default(), String::new());
+    };
+    let content = String::from_utf8_lossy(&head);
+
+    let (frontmatter, body) = split_skill_frontmatter(&content);
+    let description = frontmatter
+        .description
+        .as_deref()
+        .or_else(|| {
+            body.lines()
+                .find(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        })
+        .map(|line| line.trim().chars().take(100).collect())
+        .unwrap_or_default();
+
+    (frontmatter, description)
+}
 
-    if !skill_file.exists() {
-        return String::new();
-    }
+/// Whether `name` is a plain skill directory name (no path components)
+pub fn is_valid_skill_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 255
+        && !name.contains("..")
+        && !name.contains('/')
+        && !name.contains('\\')
+}
 
-    match std::fs::read_to_string(&skill_file) {
-        Ok(content) => {
-            content
-                .lines()
-                .find(|line| !line.trim().is_empty() && !line.starts_with('#'))
-                .map(|line| line.trim().chars().take(100).collect())
-                .unwrap_or_default()
-        }
-        Err(_) => String::new(),
-    }
+/// Configured time limit for skill filesystem work
+pub(super) fn skill_read_timeout(config: &SwarmConfig) -> Duration {
+    Duration::from_secs(config.skills_read_timeout_secs.max(1) as u64)
 }
 
-pub async fn list_skills(
-    State(state): State<AppState>,
-    Query(query): Query<SearchQuery>,
-) -> Result<ResponseJson<ApiResponse<SkillsListResponse>>, ApiError> {
+/// Run blocking skill filesystem work off the async runtime
+///
+/// Waits for a slot in `state.skill_scans` first, so a burst of requests
+/// queues here instead of tying up every blocking thread. The slot is held
+/// until the work finishes, even if the request is dropped meanwhile.
+///
+/// Fails with a timeout error when waiting plus the work take longer than
+/// `timeout`, e.g. on a hung network mount. The blocking work itself cannot
+/// be interrupted and keeps its slot until it returns.
+pub(super) async fn run_skill_scan<T, F>(state: &AppState, timeout: Duration, scan: F) -> Result<T, ApiError>
+where
+    F: FnOnce() -> Result<T, ApiError> + Send + 'static,
+    T: Send + 'static,
+{
+    let work = async {
+        let permit = state
+            .skill_scans
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| ApiError::Io(std::io::Error::other("Skill scans are shutting down")))?;
+
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            scan()
+        })
+        .await
+        .map_err(|e| ApiError::Io(std::io::Error::other(format!("Skill scan failed: {}", e))))?
+    };
+
+    tokio::time::timeout(timeout, work).await.map_err(|_| {
+        ApiError::Timeout(format!(
+            "Reading the skills directory timed out after {}s",
+            timeout.as_secs()
+        ))
+    })?
+}
+
+/// List skills in the configured directory, keeping those `allowed` accepts
+async fn collect_skills(
+    state: &AppState,
+    query: SearchQuery,
+    allowed: impl Fn(&str) -> bool + Send + 'static,
+) -> Result<SkillsListResponse, ApiError> {
     let config = SwarmConfig::get(&state.db_pool).await?;
-    let skills_dir = find_skills_dir(&config.skills_path);
+    let timeout = skill_read_timeout(&config);
+    run_skill_scan(state, timeout, move || scan_skills(&config.skills_path, &query, allowed)).await
+}
 
-    let Some(skills_path) = skills_dir else {
-        return Ok(ResponseJson(ApiResponse::success(SkillsListResponse {
+fn scan_skills(
+    config_path: &str,
+    query: &SearchQuery,
+    allowed: impl Fn(&str) -> bool,
+) -> Result<SkillsListResponse, ApiError> {
+    let Some(skills_path) = find_skills_dir(config_path) else {
+        return Ok(SkillsListResponse {
             skills: vec![],
             total: 0,
-        })));
+        });
     };
 
     let entries = std::fs::read_dir(&skills_path)
@@ -104,15 +229,22 @@ pub async fn list_skills(
         }
 
         let name = entry.file_name().to_string_lossy().to_string();
+        if !allowed(&name) {
+            continue;
+        }
+
         let entry_path = entry.path();
-        let skill_file = entry_path.join("SKILL.md");
+        let skill_file = entry_path.join(SKILL_FILE);
         let has_skill_file = skill_file.exists();
-        let description = read_skill_description(&entry_path);
+        let (frontmatter, description) = read_skill_metadata(&entry_path);
 
         if let Some(ref search) = query.q {
             let search_lower = search.to_lowercase();
-            if !name.to_lowercase().contains(&search_lower)
-                && !description.to_lowercase().contains(&search_lower)
+            let matches = |text: &str| text.to_lowercase().contains(&search_lower);
+            if !matches(&name)
+                && !matches(&description)
+                && !frontmatter.name.as_deref().is_some_and(matches)
+                && !frontmatter.tags.iter().any(|tag| matches(tag))
             {
                 continue;
             }
@@ -124,6 +256,10 @@ pub async fn list_skills(
             path: entry_path.to_string_lossy().to_string(),
             has_skill_file,
             description,
+            display_name: frontmatter.name,
+            version: frontmatter.version,
+            tags: frontmatter.tags,
+            workdir: frontmatter.workdir,
         });
     }
 
@@ -131,23 +267,44 @@ pub async fn list_skills(
 
     let total = skills.len();
 
-    Ok(ResponseJson(ApiResponse::success(SkillsListResponse {
-        skills,
-        total,
-    })))
+    Ok(SkillsListResponse { skills, total })
 }
 
-pub async fn get_skill(
+/// GET /api/skills - All skills, unfiltered
+pub async fn list_skills(
     State(state): State<AppState>,
-    Path(name): Path<String>,
-) -> Result<ResponseJson<ApiResponse<SkillDetail>>, ApiError> {
+    Query(query): Query<SearchQuery>,
+) -> Result<ResponseJson<ApiResponse<SkillsListResponse>>, ApiError> {
+    let skills = collect_skills(&state, query, |_| true).await?;
+    Ok(ResponseJson(ApiResponse::success(skills)))
+}
+
+/// GET /api/swarms/:id/skills - Skills in the swarm's allowlist
+pub async fn list_swarm_skills(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+) -> Result<ResponseJson<ApiResponse<SkillsListResponse>>, ApiError> {
+    let skills = collect_skills(&state, query, move |name| swarm.allows_skill(name)).await?;
+    Ok(ResponseJson(ApiResponse::success(skills)))
+}
+
+/// Read a skill's SKILL.md and file list
+async fn load_skill(state: &AppState, name: String) -> Result<SkillDetail, ApiError> {
     let config = SwarmConfig::get(&state.db_pool).await?;
-    let skills_dir = find_skills_dir(&config.skills_path)
-        .ok_or_else(|| ApiError::BadRequest("Skills directory not found".to_string()))?;
+    let timeout = skill_read_timeout(&config);
+    run_skill_scan(state, timeout, move || read_skill_detail(&config.skills_path, name)).await
+}
+
+fn read_skill_detail(config_path: &str, name: String) -> Result<SkillDetail, ApiError> {
+    let skills_dir = find_skills_dir(config_path)
+        .ok_or_else(|| ApiError::BadRequest("Skills directory not found".to_string())
+            .with_code(ErrorCode::SkillsDirectoryNotFound))?;
 
     // Security: Validate skill name to prevent path traversal attacks
-    if name.contains("..") || name.contains('/') || name.contains('\\') {
-        return Err(ApiError::BadRequest("Invalid skill name".to_string()));
+    if !is_valid_skill_name(&name) {
+        return Err(ApiError::BadRequest("Invalid skill name".to_string())
+            .with_code(ErrorCode::ValidationInvalidSkillName));
     }
 
     let skill_path = skills_dir.join(&name);
@@ -165,16 +322,27 @@ pub async fn get_skill(
 
     let canonical_skill_path = skill_path.canonicalize().map_err(|_| {
         ApiError::BadRequest(format!("Skill not found: {}", name))
+            .with_code(ErrorCode::SkillNotFound)
     })?;
 
     if !canonical_skill_path.starts_with(&canonical_skills_dir) {
-        return Err(ApiError::BadRequest("Invalid skill name".to_string()));
+        return Err(ApiError::BadRequest("Invalid skill name".to_string())
+            .with_code(ErrorCode::ValidationInvalidSkillName));
     }
 
-    let skill_file = canonical_skill_path.join("SKILL.md");
+    let skill_file = canonical_skill_path.join(SKILL_FILE);
 
     if !skill_file.exists() {
-        return Err(ApiError::BadRequest(format!("Skill not found: {}", name)));
+        return Err(ApiError::BadRequest(format!("Skill not found: {}", name))
+            .with_code(ErrorCode::SkillNotFound));
+    }
+
+    let size = std::fs::metadata(&skill_file).map_err(ApiError::Io)?.len();
+    if size > MAX_SKILL_FILE_BYTES {
+        return Err(ApiError::BadRequest(format!(
+            "{} is too large ({} bytes, max {})",
+            SKILL_FILE, size, MAX_SKILL_FILE_BYTES
+        )));
     }
 
     let content = std::fs::read_to_string(&skill_file)
@@ -186,16 +354,215 @@ pub async fn get_skill(
         .map(|e| e.file_name().to_string_lossy().to_string())
         .collect();
 
-    Ok(ResponseJson(ApiResponse::success(SkillDetail {
+    let workdir = split_skill_frontmatter(&content).0.workdir;
+
+    Ok(SkillDetail {
         name,
         path: canonical_skill_path.to_string_lossy().to_string(),
         content,
         files,
-    })))
+        workdir,
+    })
+}
+
+/// Write SKILL.md of skill `name`, creating the skill (and the skills directory) if needed
+fn install_skill(config_path: &str, request: CreateSkillRequest) -> Result<SkillDetail, ApiError> {
+    let skills_dir = match find_skills_dir(config_path) {
+        Some(dir) => dir,
+        None => {
+            std::fs::create_dir_all(config_path).map_err(ApiError::Io)?;
+            PathBuf::from(config_path)
+        }
+    };
+    let canonical_skills_dir = skills_dir.canonicalize().map_err(ApiError::Io)?;
+    let skill_path = canonical_skills_dir.join(&request.name);
+
+    match std::fs::symlink_metadata(&skill_path) {
+        Ok(_) if !request.overwrite => {
+            return Err(ApiError::Conflict(format!("Skill already exists: {}", request.name))
+                .with_code(ErrorCode::SkillAlreadyExists));
+        }
+        Ok(_) => {
+            // Security: an existing entry may be a symlink; only write inside the skills directory
+            let canonical_skill_path = skill_path.canonicalize().map_err(|_| {
+                ApiError::BadRequest(format!("Skill is not a directory: {}", request.name))
+            })?;
+            if !canonical_skill_path.starts_with(&canonical_skills_dir) || !canonical_skill_path.is_dir() {
+                return Err(ApiError::BadRequest("Invalid skill name".to_string())
+                    .with_code(ErrorCode::ValidationInvalidSkillName));
+            }
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            std::fs::create_dir(&skill_path).map_err(ApiError::Io)?;
+        }
+        Err(e) => return Err(ApiError::Io(e)),
+    }
+
+    // Write beside the file and rename, so readers never see a partial SKILL.md
+    // and a symlinked SKILL.md is replaced rather than followed
+    let staged = skill_path.join(format!(".{}.{}.tmp", SKILL_FILE, Uuid::new_v4()));
+    std::fs::write(&staged, &request.content).map_err(ApiError::Io)?;
+    if let Err(e) = std::fs::rename(&staged, skill_path.join(SKILL_FILE)) {
+        let _ = std::fs::remove_file(&staged);
+        return Err(ApiError::Io(e));
+    }
+
+    tracing::info!(skill = %request.name, overwrite = request.overwrite, "Installed skill");
+    read_skill_detail(canonical_skills_dir.to_string_lossy().as_ref(), request.name)
+}
+
+/// Remove skill `name` from the skills directory
+///
+/// A skill that is a symlink has only the link removed, never its target.
+fn remove_skill(config_path: &str, name: &str) -> Result<(), ApiError> {
+    let skills_dir = find_skills_dir(config_path)
+        .ok_or_else(|| ApiError::BadRequest("Skills directory not found".to_string())
+            .with_code(ErrorCode::SkillsDirectoryNotFound))?;
+    let skill_path = skills_dir.join(name);
+
+    let metadata = std::fs::symlink_metadata(&skill_path)
+        .map_err(|_| ApiError::BadRequest(format!("Skill not found: {}", name))
+            .with_code(ErrorCode::SkillNotFound))?;
+
+    if metadata.file_type().is_symlink() {
+        std::fs::remove_file(&skill_path).map_err(ApiError::Io)?;
+    } else if metadata.is_dir() {
+        std::fs::remove_dir_all(&skill_path).map_err(ApiError::Io)?;
+    } else {
+        return Err(ApiError::BadRequest(format!("Skill not found: {}", name))
+            .with_code(ErrorCode::SkillNotFound));
+    }
+
+    tracing::info!(skill = %name, "Removed skill");
+    Ok(())
+}
+
+/// POST /api/skills - Install a skill from its SKILL.md
+///
+/// Existing skills are rejected with 409 unless `overwrite` is set.
+pub async fn create_skill(
+    State(state): State<AppState>,
+    StrictJson(payload): StrictJson<CreateSkillRequest>,
+) -> Result<ResponseJson<ApiResponse<SkillDetail>>, ApiError> {
+    // Security: Validate skill name to prevent path traversal attacks
+    if !is_valid_skill_name(&payload.name) || payload.name.starts_with('.') {
+        return Err(ApiError::BadRequest("Invalid skill name".to_string())
+            .with_code(ErrorCode::ValidationInvalidSkillName));
+    }
+    if payload.content.len() as u64 > MAX_SKILL_FILE_BYTES {
+        return Err(ApiError::BadRequest(format!(
+            "{} is too large ({} bytes, max {})",
+            SKILL_FILE,
+            payload.content.len(),
+            MAX_SKILL_FILE_BYTES
+        )));
+    }
+
+    let config = SwarmConfig::get(&state.db_pool).await?;
+    let timeout = skill_read_timeout(&config);
+    let skill = run_skill_scan(&state, timeout, move || install_skill(&config.skills_path, payload)).await?;
+
+    Ok(ResponseJson(ApiResponse::success(skill)))
+}
+
+/// DELETE /api/skills/:name - Remove a skill directory
+pub async fn delete_skill(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    // Security: Validate skill name to prevent path traversal attacks
+    if !is_valid_skill_name(&name) || name.starts_with('.') {
+        return Err(ApiError::BadRequest("Invalid skill name".to_string())
+            .with_code(ErrorCode::ValidationInvalidSkillName));
+    }
+
+    let config = SwarmConfig::get(&state.db_pool).await?;
+    let timeout = skill_read_timeout(&config);
+    run_skill_scan(&state, timeout, move || remove_skill(&config.skills_path, &name)).await?;
+
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// GET /api/skills/:name - Any skill, unfiltered
+pub async fn get_skill(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<ResponseJson<ApiResponse<SkillDetail>>, ApiError> {
+    Ok(ResponseJson(ApiResponse::success(load_skill(&state, name).await?)))
+}
+
+/// GET /api/swarms/:id/skills/:name - A skill, if the swarm's allowlist permits it
+///
+/// Skills outside the allowlist are reported as not found.
+pub async fn get_swarm_skill(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+    Path((_swarm_id, name)): Path<(Uuid, String)>,
+) -> Result<ResponseJson<ApiResponse<SkillDetail>>, ApiError> {
+    if !swarm.allows_skill(&name) {
+        return Err(ApiError::BadRequest(format!("Skill not found: {}", name))
+            .with_code(ErrorCode::SkillNotFound));
+    }
+    Ok(ResponseJson(ApiResponse::success(load_skill(&state, name).await?)))
+}
+
+/// POST /api/skills/:name/preview - Prompt a task using the skill would get
+///
+/// Renders the executor prompt for a transient task referencing the skill;
+/// nothing is stored or run.
+pub async fn preview_skill(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    StrictJson(payload): StrictJson<SkillPreviewRequest>,
+) -> Result<ResponseJson<ApiResponse<SkillPreview>>, ApiError> {
+    // Same limits as task creation
+    if payload.title.trim().is_empty() {
+        return Err(ApiError::BadRequest("Title must not be empty".to_string())
+            .with_code(ErrorCode::ValidationTitleEmpty));
+    }
+    if payload.title.len() > 255 {
+        return Err(ApiError::BadRequest("Title too long (max 255 chars)".to_string())
+            .with_code(ErrorCode::ValidationTitleTooLong));
+    }
+    if payload.description.as_ref().is_some_and(|d| d.len() > 10000) {
+        return Err(ApiError::BadRequest("Description too long (max 10000 chars)".to_string())
+            .with_code(ErrorCode::ValidationDescriptionTooLong));
+    }
+    if payload.tags.len() > 50 {
+        return Err(ApiError::BadRequest("Too many tags (max 50)".to_string())
+            .with_code(ErrorCode::ValidationTooManyTags));
+    }
+    if payload.tags.iter().any(|t| t.len() > 100) {
+        return Err(ApiError::BadRequest("Tag too long (max 100 chars)".to_string())
+            .with_code(ErrorCode::ValidationTagTooLong));
+    }
+
+    let config = SwarmConfig::get(&state.db_pool).await?;
+    let timeout = skill_read_timeout(&config);
+    let preview = run_skill_scan(&state, timeout, move || {
+        let skill = read_skill_detail(&config.skills_path, name)?;
+        let plan = plan_skill_preview(
+            &config.skills_path,
+            &config.prompt_vars,
+            &skill.name,
+            &payload.title,
+            payload.description.as_deref(),
+            payload.tags,
+        );
+        Ok(SkillPreview {
+            prompt: plan.prompt,
+            workdir: plan.workdir,
+            skill_content: skill.content,
+        })
+    })
+    .await?;
+
+    Ok(ResponseJson(ApiResponse::success(preview)))
 }
 
 pub fn router() -> Router<AppState> {
     Router::new()
-        .route("/skills", get(list_skills))
-        .route("/skills/{name}", get(get_skill))
+        .route("/skills", get(list_skills).post(create_skill))
+        .route("/skills/{name}", get(get_skill).delete(delete_skill))
+        .route("/skills/{name}/preview", post(preview_skill))
 }
@@ -1,57 +1,545 @@
 //! Swarm Task Routes
 
+use std::collections::HashMap;
+
 use axum::{
-    Extension, Json, Router,
-    extract::{Path, State},
+    Extension, Router,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
     response::Json as ResponseJson,
     routing::{get, post},
 };
+use db::models::sandbox::{Sandbox, SandboxStatus};
 use db::models::swarm::Swarm;
-use db::models::swarm_task::{SwarmTask, SwarmTaskStatus, CreateSwarmTask, UpdateSwarmTask};
-use utils::response::ApiResponse;
+use db::models::swarm_config::SwarmConfig;
+use db::models::swarm_task::{
+    SwarmTask, SwarmTaskFilters, SwarmTaskStatus, CreateSwarmTask, TaskPriority, TaskStatusCounts, UpdateSwarmTask,
+};
+use db::models::swarm_task_comment::{CreateSwarmTaskComment, SwarmTaskComment};
+use db::models::swarm_task_log::SwarmTaskLog;
+use serde::{Deserialize, Serialize};
+use services::services::swarm::{
+    kill_task_processes, plan_task, validate_result_path, LogEnd, TaskLog, TaskPlan,
+};
+use sqlx::error::ErrorKind;
+use ts_rs::TS;
+use utils::response::{ApiResponse, ErrorCode};
 use uuid::Uuid;
 
+use super::StrictJson;
+use super::pool::daytona_client;
+use super::secrets::validate_task_secrets;
+use super::skills::{run_skill_scan, skill_read_timeout};
 use crate::{AppState, error::ApiError};
 
+/// Task plus, while it is pending, its place in the dispatch queue
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct SwarmTaskDetail {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub task: SwarmTask,
+    /// 1 for the next task to dispatch; only set for pending tasks
+    pub queue_position: Option<usize>,
+    /// Sandbox the task is assigned to; only set with `?expand=sandbox` and
+    /// while the sandbox has not been destroyed
+    pub sandbox: Option<Sandbox>,
+}
+
+impl SwarmTaskDetail {
+    fn new(task: SwarmTask, positions: &HashMap<Uuid, usize>) -> Self {
+        let queue_position = match task.status {
+            SwarmTaskStatus::Pending => positions.get(&task.id).copied(),
+            _ => None,
+        };
+        Self {
+            task,
+            queue_position,
+            sandbox: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskQuery {
+    /// Comma-separated related records to include; only `sandbox` is supported
+    pub expand: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TaskGraphNode {
+    pub id: Uuid,
+    pub title: String,
+    pub status: SwarmTaskStatus,
+    /// True if this task is part of a dependency cycle
+    pub in_cycle: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskGraphEdgeKind {
+    Depends,
+    Triggers,
+}
+
+/// Edge from a prerequisite task to the task that waits on it
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TaskGraphEdge {
+    pub from: Uuid,
+    pub to: Uuid,
+    pub kind: TaskGraphEdgeKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TaskGraph {
+    pub nodes: Vec<TaskGraphNode>,
+    pub edges: Vec<TaskGraphEdge>,
+    pub has_cycles: bool,
+}
+
+/// Task counts by status, for a board summary without listing every task
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TaskStats {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub counts: TaskStatusCounts,
+    pub total: usize,
+    /// Completed tasks as a percentage of all tasks; 0 for a swarm without tasks
+    pub completion_percentage: f64,
+}
+
+impl TaskStats {
+    fn new(counts: TaskStatusCounts) -> Self {
+        let total = counts.blocked + counts.pending + counts.running + counts.completed + counts.failed + counts.cancelled;
+        let completion_percentage = if total == 0 {
+            0.0
+        } else {
+            counts.completed as f64 * 100.0 / total as f64
+        };
+        Self { counts, total, completion_percentage }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(deny_unknown_fields)]
+pub struct ReassignTaskRequest {
+    pub sandbox_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct RetryFailedResponse {
+    /// Failed tasks reset to pending (or blocked, if still awaiting approval)
+    pub requeued: usize,
+    /// Failed tasks left alone because a dependency has not completed
+    pub skipped: Vec<Uuid>,
+}
+
+/// Maximum number of tasks accepted in a single import document
+const MAX_IMPORT_TASKS: usize = 500;
+
+/// A task in an import document; other entries refer to it by `name`
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct ImportTaskSpec {
+    pub name: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Option<TaskPriority>,
+    /// Names of tasks in the same document that must complete first
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Names of tasks in the same document whose completion triggers this one
+    #[serde(default)]
+    pub triggers_after: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct ImportTasksDocument {
+    pub tasks: Vec<ImportTaskSpec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ImportTasksResponse {
+    pub tasks: Vec<SwarmTask>,
+    /// Local task name -> id of the created task
+    pub ids: HashMap<String, Uuid>,
+}
+
+/// Maximum number of tasks accepted in a single batch create
+const MAX_BATCH_TASKS: usize = 100;
+
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(deny_unknown_fields)]
+pub struct BatchCreateTasks {
+    pub tasks: Vec<CreateSwarmTask>,
+    /// Placeholder id -> position in `tasks`; `depends_on` entries naming a
+    /// placeholder are replaced with the id of the task created for it
+    #[serde(default)]
+    pub keys: HashMap<Uuid, usize>,
+}
+
+/// Why one entry of a batch request was rejected
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct BatchItemError {
+    /// Position of the entry in the request, starting at 0
+    pub index: usize,
+    /// The entry's local `name` (for batch creation, its key or title)
+    pub name: String,
+    pub message: String,
+}
+
+impl BatchItemError {
+    fn new(index: usize, name: &str, message: impl Into<String>) -> Self {
+        Self {
+            index,
+            name: name.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Batch endpoints answer 400 with the rejected entries in `error_data`
+type BatchResponse<T> = (StatusCode, ResponseJson<ApiResponse<T, Vec<BatchItemError>>>);
+
+fn batch_rejected<T>(errors: Vec<BatchItemError>) -> BatchResponse<T> {
+    (
+        StatusCode::BAD_REQUEST,
+        ResponseJson(ApiResponse::error_with_data(errors)),
+    )
+}
+
+/// Attribute a failed insert to the entry being written
+///
+/// Constraint violations are the entry's fault and become an item error;
+/// anything else (locks, I/O) is a server error.
+fn item_error_from_db(
+    index: usize,
+    name: &str,
+    error: sqlx::Error,
+) -> Result<BatchItemError, ApiError> {
+    let kind = error.as_database_error().map(|e| e.kind());
+    match kind {
+        Some(
+            ErrorKind::UniqueViolation
+            | ErrorKind::ForeignKeyViolation
+            | ErrorKind::NotNullViolation
+            | ErrorKind::CheckViolation,
+        ) => Ok(BatchItemError::new(index, name, error.to_string())),
+        _ => {
+            tracing::error!(index, name = %name, "Batch insert failed: {}", error);
+            Err(ApiError::Database(error))
+        }
+    }
+}
+
+/// Reject the request if adding `adding` tasks would exceed the swarm's task limit
+async fn ensure_task_capacity(state: &AppState, swarm_id: Uuid, adding: usize) -> Result<(), ApiError> {
+    let config = SwarmConfig::get(&state.db_pool).await?;
+    let current = SwarmTask::count_by_swarm_id(&state.db_pool, swarm_id).await?;
+
+    if current + adding as i64 > i64::from(config.max_tasks_per_swarm) {
+        return Err(ApiError::Conflict(format!(
+            "Swarm task limit reached ({} of {} tasks)",
+            current, config.max_tasks_per_swarm
+        ))
+        .with_code(ErrorCode::TaskLimitReached));
+    }
+
+    Ok(())
+}
+
+/// Estimates are capped at one week
+fn validate_estimate_minutes(estimate: Option<i32>) -> Result<(), ApiError> {
+    if let Some(estimate) = estimate && !(0..=10080).contains(&estimate) {
+        return Err(ApiError::BadRequest("estimate_minutes must be between 0 and 10080".to_string()));
+    }
+    Ok(())
+}
+
+/// Results may only be written to files inside the sandbox workspace
+pub(super) fn validate_write_result_to(path: Option<&str>) -> Result<(), ApiError> {
+    path.map_or(Ok(()), |path| validate_result_path(path).map_err(ApiError::BadRequest))
+}
+
+/// Header carrying the number of tasks matching a list query, before `limit`/`offset`
+pub const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
+/// Largest page a list endpoint returns
+pub(super) const MAX_LIST_LIMIT: i64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct ListTasksQuery {
+    /// Only the task imported under this external id
+    pub external_id: Option<String>,
+    pub status: Option<SwarmTaskStatus>,
+    pub priority: Option<TaskPriority>,
+    pub tag: Option<String>,
+    /// Free text matched against title and description
+    pub q: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// GET /api/swarms/:id/tasks - List tasks, newest first
+///
+/// Without filters or `limit` every task is returned. The number of matching
+/// tasks is sent in the `X-Total-Count` header so clients can page.
 pub async fn list_tasks(
     Extension(swarm): Extension<Swarm>,
     State(state): State<AppState>,
-) -> Result<ResponseJson<ApiResponse<Vec<SwarmTask>>>, ApiError> {
+    Query(query): Query<ListTasksQuery>,
+) -> Result<([(&'static str, String); 1], ResponseJson<ApiResponse<Vec<SwarmTaskDetail>>>), ApiError> {
+    if let Some(limit) = query.limit && !(1..=MAX_LIST_LIMIT).contains(&limit) {
+        return Err(ApiError::BadRequest(format!("limit must be between 1 and {}", MAX_LIST_LIMIT)));
+    }
+    if query.offset.is_some_and(|offset| offset < 0) {
+        return Err(ApiError::BadRequest("offset must not be negative".to_string()));
+    }
+
+    let (tasks, total) = match query.external_id {
+        Some(external_id) => {
+            let tasks: Vec<SwarmTask> = SwarmTask::find_by_external_id(&state.db_pool, swarm.id, &external_id)
+                .await?
+                .into_iter()
+                .collect();
+            let total = tasks.len() as i64;
+            (tasks, total)
+        }
+        None => {
+            let filters = SwarmTaskFilters {
+                status: query.status,
+                priority: query.priority,
+                tag: query.tag,
+                text: query.q.filter(|q| !q.trim().is_empty()),
+                limit: query.limit,
+                offset: query.offset.unwrap_or(0),
+            };
+            SwarmTask::search(&state.db_pool, swarm.id, &filters).await?
+        }
+    };
+    let positions = queue_positions(&state, swarm.id).await?;
+
+    let tasks = tasks
+        .into_iter()
+        .map(|task| SwarmTaskDetail::new(task, &positions))
+        .collect();
+    Ok((
+        [(TOTAL_COUNT_HEADER, total.to_string())],
+        ResponseJson(ApiResponse::success(tasks)),
+    ))
+}
+
+/// Dispatch order of the swarm's pending tasks, with the configured priority aging
+async fn queue_positions(state: &AppState, swarm_id: Uuid) -> Result<HashMap<Uuid, usize>, ApiError> {
+    let config = SwarmConfig::get(&state.db_pool).await?;
+    Ok(SwarmTask::queue_positions(&state.db_pool, swarm_id, config.priority_aging_minutes).await?)
+}
+
+pub async fn get_task_graph(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<TaskGraph>>, ApiError> {
     let tasks = SwarmTask::find_by_swarm_id(&state.db_pool, swarm.id)
         .await
         ?;
 
-    Ok(ResponseJson(ApiResponse::success(tasks)))
+    Ok(ResponseJson(ApiResponse::success(build_task_graph(&tasks))))
 }
 
-pub async fn create_task(
+/// GET /api/swarms/:id/tasks/stats - Task counts by status
+pub async fn get_task_stats(
     Extension(swarm): Extension<Swarm>,
     State(state): State<AppState>,
-    Json(payload): Json<CreateSwarmTask>,
-) -> Result<ResponseJson<ApiResponse<SwarmTask>>, ApiError> {
+) -> Result<ResponseJson<ApiResponse<TaskStats>>, ApiError> {
+    let counts = SwarmTask::count_by_status(&state.db_pool, swarm.id).await?;
+
+    Ok(ResponseJson(ApiResponse::success(TaskStats::new(counts))))
+}
+
+/// Build the dependency graph for a set of tasks
+///
+/// Edges pointing at tasks outside the set (e.g. deleted dependencies) are dropped.
+fn build_task_graph(tasks: &[SwarmTask]) -> TaskGraph {
+    let index: HashMap<Uuid, usize> = tasks.iter().enumerate().map(|(i, t)| (t.id, i)).collect();
+
+    let mut edges = Vec::new();
+    for task in tasks {
+        let upstream = [
+            (task.depends_on.as_deref(), TaskGraphEdgeKind::Depends),
+            (task.triggers_after.as_deref(), TaskGraphEdgeKind::Triggers),
+        ];
+        for (ids, kind) in upstream {
+            for from in ids.unwrap_or_default() {
+                if index.contains_key(from) {
+                    edges.push(TaskGraphEdge { from: *from, to: task.id, kind });
+                }
+            }
+        }
+    }
+
+    let mut adjacency = vec![Vec::new(); tasks.len()];
+    for edge in &edges {
+        adjacency[index[&edge.from]].push(index[&edge.to]);
+    }
+    let in_cycle = nodes_in_cycles(&adjacency);
+
+    let nodes = tasks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| TaskGraphNode {
+            id: t.id,
+            title: t.title.clone(),
+            status: t.status.clone(),
+            in_cycle: in_cycle[i],
+        })
+        .collect();
+
+    TaskGraph {
+        nodes,
+        edges,
+        has_cycles: in_cycle.iter().any(|c| *c),
+    }
+}
+
+/// Mark every node that belongs to a cycle (iterative Tarjan SCC)
+///
+/// A node is in a cycle if its strongly connected component has more than
+/// one node, or it has an edge to itself.
+pub(super) fn nodes_in_cycles(adjacency: &[Vec<usize>]) -> Vec<bool> {
+    let n = adjacency.len();
+    let mut index = vec![usize::MAX; n];
+    let mut lowlink = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut in_cycle = vec![false; n];
+    let mut next_index = 0;
+
+    for root in 0..n {
+        if index[root] != usize::MAX {
+            continue;
+        }
+
+        // (node, next neighbour position)
+        let mut call_stack = vec![(root, 0)];
+        index[root] = next_index;
+        lowlink[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&(node, pos)) = call_stack.last() {
+            if let Some(&next) = adjacency[node].get(pos) {
+                let top = call_stack.len() - 1;
+                call_stack[top].1 += 1;
+                if index[next] == usize::MAX {
+                    index[next] = next_index;
+                    lowlink[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    call_stack.push((next, 0));
+                } else if on_stack[next] {
+                    lowlink[node] = lowlink[node].min(index[next]);
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[node]);
+            }
+
+            if lowlink[node] == index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                let cyclic = component.len() > 1 || adjacency[node].contains(&node);
+                for member in component {
+                    in_cycle[member] = cyclic;
+                }
+            }
+        }
+    }
+
+    in_cycle
+}
+
+/// Checks shared by single and batch task creation
+async fn validate_new_task(state: &AppState, swarm_id: Uuid, payload: &CreateSwarmTask) -> Result<(), ApiError> {
     // Validate input sizes
     if payload.title.len() > 255 {
-        return Err(ApiError::BadRequest("Title too long (max 255 chars)".to_string()));
+        return Err(ApiError::BadRequest("Title too long (max 255 chars)".to_string())
+            .with_code(ErrorCode::ValidationTitleTooLong));
     }
     if let Some(ref desc) = payload.description {
         if desc.len() > 10000 {
-            return Err(ApiError::BadRequest("Description too long (max 10000 chars)".to_string()));
+            return Err(ApiError::BadRequest("Description too long (max 10000 chars)".to_string())
+                .with_code(ErrorCode::ValidationDescriptionTooLong));
         }
     }
     if let Some(ref deps) = payload.depends_on {
         if deps.len() > 20 {
-            return Err(ApiError::BadRequest("Too many dependencies (max 20)".to_string()));
+            return Err(ApiError::BadRequest("Too many dependencies (max 20)".to_string())
+                .with_code(ErrorCode::ValidationTooManyDependencies));
         }
     }
     if let Some(ref tags) = payload.tags {
         if tags.len() > 50 {
-            return Err(ApiError::BadRequest("Too many tags (max 50)".to_string()));
+            return Err(ApiError::BadRequest("Too many tags (max 50)".to_string())
+                .with_code(ErrorCode::ValidationTooManyTags));
         }
         if tags.iter().any(|t| t.len() > 100) {
-            return Err(ApiError::BadRequest("Tag too long (max 100 chars)".to_string()));
+            return Err(ApiError::BadRequest("Tag too long (max 100 chars)".to_string())
+                .with_code(ErrorCode::ValidationTagTooLong));
+        }
+    }
+    validate_estimate_minutes(payload.estimate_minutes)?;
+    validate_task_secrets(payload.secrets.as_deref())?;
+    validate_write_result_to(payload.write_result_to.as_deref())?;
+    if let Some(pinned_id) = payload.pinned_sandbox_id {
+        let sandbox = Sandbox::find_by_id(&state.db_pool, pinned_id)
+            .await?
+            .ok_or_else(|| ApiError::BadRequest("Pinned sandbox not found".to_string())
+                .with_code(ErrorCode::SandboxNotFound))?;
+
+        if sandbox.swarm_id != Some(swarm_id) {
+            return Err(ApiError::BadRequest("Pinned sandbox does not belong to this swarm".to_string())
+                .with_code(ErrorCode::SandboxNotInSwarm));
+        }
+        if sandbox.status == SandboxStatus::Destroyed {
+            return Err(ApiError::BadRequest("Pinned sandbox has been destroyed".to_string())
+                .with_code(ErrorCode::InvalidSandboxState));
+        }
+        if sandbox.status == SandboxStatus::Draining {
+            return Err(ApiError::BadRequest("Pinned sandbox is draining".to_string())
+                .with_code(ErrorCode::InvalidSandboxState));
+        }
+    }
+
+    if payload.decompose == Some(true) {
+        let config = SwarmConfig::get(&state.db_pool).await?;
+        if !config.decompose_enabled {
+            return Err(ApiError::BadRequest("Task decomposition is disabled".to_string())
+                .with_code(ErrorCode::DecompositionDisabled));
         }
     }
 
+    Ok(())
+}
+
+pub async fn create_task(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+    StrictJson(payload): StrictJson<CreateSwarmTask>,
+) -> Result<ResponseJson<ApiResponse<SwarmTask>>, ApiError> {
+    validate_new_task(&state, swarm.id, &payload).await?;
+    ensure_task_capacity(&state, swarm.id, 1).await?;
+
     let task_id = Uuid::new_v4();
 
     let task = SwarmTask::create(&state.db_pool, swarm.id, &payload, task_id)
@@ -60,41 +548,431 @@ pub async fn create_task(
 
     tracing::info!("Created swarm task '{}' in swarm {}", task.title, swarm.id);
 
+    if Swarm::resume_if_auto_paused(&state.db_pool, swarm.id).await? {
+        tracing::info!("Resumed auto-paused swarm {} for new task", swarm.id);
+    }
+
     Ok(ResponseJson(ApiResponse::success(task)))
 }
 
+/// POST /api/swarms/:id/tasks/batch - Create several tasks in one transaction
+///
+/// Returns the created tasks in request order. If any entry is invalid nothing
+/// is created and every rejected entry is listed.
+pub async fn batch_create_tasks(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+    StrictJson(payload): StrictJson<BatchCreateTasks>,
+) -> Result<BatchResponse<Vec<SwarmTask>>, ApiError> {
+    let BatchCreateTasks { tasks, keys } = payload;
+    if tasks.is_empty() {
+        return Err(ApiError::BadRequest("Batch contains no tasks".to_string()));
+    }
+    if tasks.len() > MAX_BATCH_TASKS {
+        return Err(ApiError::BadRequest(format!(
+            "Too many tasks (max {})",
+            MAX_BATCH_TASKS
+        )));
+    }
+
+    let mut names: Vec<String> = tasks.iter().map(|t| t.title.clone()).collect();
+    for (key, &i) in &keys {
+        let Some(name) = names.get_mut(i) else {
+            return Err(ApiError::BadRequest(format!(
+                "Key {} refers to entry {}, but the batch has {} tasks",
+                key,
+                i,
+                tasks.len()
+            )));
+        };
+        *name = key.to_string();
+    }
+
+    // Validate every entry up front so the caller sees all problems at once
+    let mut errors = Vec::new();
+    let mut adjacency = vec![Vec::new(); tasks.len()];
+    for (i, task) in tasks.iter().enumerate() {
+        match validate_new_task(&state, swarm.id, task).await {
+            Ok(()) => {}
+            Err(e) => match e.bad_request_message() {
+                Some(message) => errors.push(BatchItemError::new(i, &names[i], message)),
+                None => return Err(e),
+            },
+        }
+
+        for upstream in task.depends_on.iter().flatten() {
+            if let Some(&from) = keys.get(upstream) {
+                adjacency[from].push(i);
+            }
+        }
+    }
+
+    for (i, cyclic) in nodes_in_cycles(&adjacency).into_iter().enumerate() {
+        if cyclic {
+            errors.push(BatchItemError::new(i, &names[i], "Task dependencies form a cycle"));
+        }
+    }
+
+    if !errors.is_empty() {
+        errors.sort_by_key(|e| e.index);
+        return Ok(batch_rejected(errors));
+    }
+
+    ensure_task_capacity(&state, swarm.id, tasks.len()).await?;
+
+    let ids: Vec<Uuid> = tasks.iter().map(|_| Uuid::new_v4()).collect();
+    let resolve = |id: &Uuid| keys.get(id).map_or(*id, |&i| ids[i]);
+
+    let mut tx = state.db_pool.begin().await?;
+
+    for (i, task) in tasks.iter().enumerate() {
+        let data = CreateSwarmTask {
+            depends_on: task.depends_on.as_ref().map(|deps| deps.iter().map(resolve).collect()),
+            ..task.clone()
+        };
+
+        // The transaction rolls back when dropped, so nothing is left behind
+        if let Err(e) = SwarmTask::create(&mut *tx, swarm.id, &data, ids[i]).await {
+            return match item_error_from_db(i, &names[i], e) {
+                Ok(item_error) => Ok(batch_rejected(vec![item_error])),
+                Err(e) => Err(e),
+            };
+        }
+    }
+
+    tx.commit().await?;
+
+    if Swarm::resume_if_auto_paused(&state.db_pool, swarm.id).await? {
+        tracing::info!("Resumed auto-paused swarm {} for new tasks", swarm.id);
+    }
+
+    let created = SwarmTask::find_by_ids_ordered(&state.db_pool, &ids).await?;
+
+    tracing::info!("Created {} tasks in swarm {}", created.len(), swarm.id);
+
+    Ok((StatusCode::OK, ResponseJson(ApiResponse::success(created))))
+}
+
+/// POST /api/swarms/:id/tasks/import - Create a set of tasks from a YAML or JSON document
+///
+/// The body is parsed as YAML when the Content-Type mentions yaml, JSON otherwise.
+/// Tasks reference each other by `name`; the whole document is rejected if a
+/// reference is unknown or the references form a cycle.
+pub async fn import_tasks(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<BatchResponse<ImportTasksResponse>, ApiError> {
+    let is_yaml = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.contains("yaml"));
+
+    let document: ImportTasksDocument = if is_yaml {
+        serde_yaml::from_str(&body)
+            .map_err(|e| ApiError::BadRequest(format!("Invalid import document: {}", e)))?
+    } else {
+        serde_json::from_str(&body)
+            .map_err(|e| ApiError::BadRequest(format!("Invalid import document: {}", e)))?
+    };
+
+    let specs = document.tasks;
+    if specs.is_empty() {
+        return Err(ApiError::BadRequest("Import document contains no tasks".to_string()));
+    }
+    if specs.len() > MAX_IMPORT_TASKS {
+        return Err(ApiError::BadRequest(format!(
+            "Too many tasks (max {})",
+            MAX_IMPORT_TASKS
+        )));
+    }
+
+    // Validate every entry up front so the caller sees all problems at once
+    let mut errors = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for (i, spec) in specs.iter().enumerate() {
+        if spec.name.trim().is_empty() {
+            errors.push(BatchItemError::new(i, &spec.name, "Task name must not be empty"));
+        } else if index.insert(spec.name.as_str(), i).is_some() {
+            errors.push(BatchItemError::new(i, &spec.name, "Duplicate task name"));
+        }
+    }
+
+    // Same limits as create_task, plus reference resolution
+    let mut adjacency = vec![Vec::new(); specs.len()];
+    for (i, spec) in specs.iter().enumerate() {
+        if spec.title.len() > 255 {
+            errors.push(BatchItemError::new(i, &spec.name, "Title too long (max 255 chars)"));
+        }
+        if let Some(ref desc) = spec.description
+            && desc.len() > 10000
+        {
+            errors.push(BatchItemError::new(i, &spec.name, "Description too long (max 10000 chars)"));
+        }
+        if spec.depends_on.len() > 20 {
+            errors.push(BatchItemError::new(i, &spec.name, "Too many dependencies (max 20)"));
+        }
+        if spec.tags.len() > 50 {
+            errors.push(BatchItemError::new(i, &spec.name, "Too many tags (max 50)"));
+        }
+        if spec.tags.iter().any(|t| t.len() > 100) {
+            errors.push(BatchItemError::new(i, &spec.name, "Tag too long (max 100 chars)"));
+        }
+
+        for upstream in spec.depends_on.iter().chain(&spec.triggers_after) {
+            match index.get(upstream.as_str()) {
+                Some(&from) => adjacency[from].push(i),
+                None => errors.push(BatchItemError::new(
+                    i,
+                    &spec.name,
+                    format!("References unknown task '{}'", upstream),
+                )),
+            }
+        }
+    }
+
+    for (i, cyclic) in nodes_in_cycles(&adjacency).into_iter().enumerate() {
+        if cyclic {
+            errors.push(BatchItemError::new(i, &specs[i].name, "Task references form a cycle"));
+        }
+    }
+
+    if !errors.is_empty() {
+        errors.sort_by_key(|e| e.index);
+        return Ok(batch_rejected(errors));
+    }
+
+    ensure_task_capacity(&state, swarm.id, specs.len()).await?;
+
+    let ids: HashMap<String, Uuid> = specs
+        .iter()
+        .map(|spec| (spec.name.clone(), Uuid::new_v4()))
+        .collect();
+    let resolve = |names: &[String]| -> Vec<Uuid> { names.iter().map(|n| ids[n]).collect() };
+
+    let mut tx = state.db_pool.begin().await?;
+
+    for (i, spec) in specs.iter().enumerate() {
+        let task_id = ids[&spec.name];
+        let data = CreateSwarmTask {
+            title: spec.title.clone(),
+            description: spec.description.clone(),
+            priority: spec.priority.clone(),
+            depends_on: (!spec.depends_on.is_empty()).then(|| resolve(&spec.depends_on)),
+            tags: Some(spec.tags.clone()),
+            pinned_sandbox_id: None,
+            decompose: None,
+            estimate_minutes: None,
+            secrets: None,
+            write_result_to: None,
+        };
+        let inserted = async {
+            SwarmTask::create(&mut *tx, swarm.id, &data, task_id).await?;
+            if !spec.triggers_after.is_empty() {
+                SwarmTask::set_triggers_after(&mut *tx, task_id, &resolve(&spec.triggers_after))
+                    .await?;
+            }
+            Ok::<_, sqlx::Error>(())
+        }
+        .await;
+
+        // The transaction rolls back when dropped, so nothing is left behind
+        if let Err(e) = inserted {
+            return match item_error_from_db(i, &spec.name, e) {
+                Ok(item_error) => Ok(batch_rejected(vec![item_error])),
+                Err(e) => Err(e),
+            };
+        }
+    }
+
+    tx.commit().await?;
+
+    if Swarm::resume_if_auto_paused(&state.db_pool, swarm.id).await? {
+        tracing::info!("Resumed auto-paused swarm {} for imported tasks", swarm.id);
+    }
+
+    // Return tasks in document order
+    let order: Vec<Uuid> = specs.iter().map(|spec| ids[&spec.name]).collect();
+    let tasks = SwarmTask::find_by_ids_ordered(&state.db_pool, &order).await?;
+
+    tracing::info!("Imported {} tasks into swarm {}", specs.len(), swarm.id);
+
+    Ok((
+        StatusCode::OK,
+        ResponseJson(ApiResponse::success(ImportTasksResponse { tasks, ids })),
+    ))
+}
+
+/// POST /api/swarms/:id/tasks/retry-failed - Requeue every failed task in the swarm
+///
+/// Tasks whose dependencies are still unmet are skipped, since they would only
+/// sit in the queue.
+pub async fn retry_failed_tasks(
+    Extension(swarm): Extension<Swarm>,
+    State(state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<RetryFailedResponse>>, ApiError> {
+    let failed: Vec<SwarmTask> = SwarmTask::find_by_swarm_id(&state.db_pool, swarm.id)
+        .await?
+        .into_iter()
+        .filter(|t| t.status == SwarmTaskStatus::Failed)
+        .collect();
+
+    let mut ready = Vec::new();
+    let mut skipped = Vec::new();
+    for task in failed {
+        if SwarmTask::are_dependencies_complete(&state.db_pool, &task).await? {
+            ready.push(task.id);
+        } else {
+            skipped.push(task.id);
+        }
+    }
+
+    let mut tx = state.db_pool.begin().await?;
+    for task_id in &ready {
+        SwarmTask::retry_task(&mut *tx, *task_id).await?;
+    }
+    tx.commit().await?;
+
+    tracing::info!(
+        "Requeued {} failed tasks in swarm {} ({} skipped for unmet dependencies)",
+        ready.len(),
+        swarm.id,
+        skipped.len()
+    );
+
+    Ok(ResponseJson(ApiResponse::success(RetryFailedResponse {
+        requeued: ready.len(),
+        skipped,
+    })))
+}
+
 pub async fn get_task(
     Extension(swarm): Extension<Swarm>,
     Path((_swarm_id, task_id)): Path<(Uuid, Uuid)>,
     State(state): State<AppState>,
-) -> Result<ResponseJson<ApiResponse<SwarmTask>>, ApiError> {
+    Query(query): Query<TaskQuery>,
+) -> Result<ResponseJson<ApiResponse<SwarmTaskDetail>>, ApiError> {
+    let mut expand_sandbox = false;
+    for field in query.expand.iter().flat_map(|e| e.split(',')).map(str::trim) {
+        match field {
+            "sandbox" => expand_sandbox = true,
+            "" => {}
+            other => {
+                return Err(ApiError::BadRequest(format!("Cannot expand '{}'", other)));
+            }
+        }
+    }
+
     let task = SwarmTask::find_by_id(&state.db_pool, task_id)
         .await
         ?
-        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string()))?;
+        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound))?;
 
     // IDOR protection: verify task belongs to the specified swarm
     if task.swarm_id != swarm.id {
-        return Err(ApiError::BadRequest("Task not found".to_string()));
+        return Err(ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound));
     }
 
-    Ok(ResponseJson(ApiResponse::success(task)))
+    let positions = if task.status == SwarmTaskStatus::Pending {
+        queue_positions(&state, swarm.id).await?
+    } else {
+        HashMap::new()
+    };
+
+    let sandbox = match task.sandbox_id.as_deref() {
+        Some(daytona_id) if expand_sandbox => Sandbox::find_by_daytona_id(&state.db_pool, daytona_id)
+            .await?
+            .filter(|s| s.status != SandboxStatus::Destroyed),
+        _ => None,
+    };
+
+    let mut detail = SwarmTaskDetail::new(task, &positions);
+    detail.sandbox = sandbox;
+    Ok(ResponseJson(ApiResponse::success(detail)))
+}
+
+/// GET /api/swarms/:id/tasks/:task_id/plan - Prompt, skill and CLIs the executor would use
+///
+/// Nothing is run; the skill and CLIs named in the description are checked
+/// against the local skills directory so missing ones show up before dispatch.
+pub async fn get_task_plan(
+    Extension(swarm): Extension<Swarm>,
+    Path((_swarm_id, task_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<TaskPlan>>, ApiError> {
+    let task = SwarmTask::find_by_id(&state.db_pool, task_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound))?;
+
+    if task.swarm_id != swarm.id {
+        return Err(ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound));
+    }
+
+    let config = SwarmConfig::get(&state.db_pool).await?;
+    let timeout = skill_read_timeout(&config);
+    let plan = run_skill_scan(&state, timeout, move || {
+        Ok(plan_task(&config.skills_path, &config.prompt_vars, &swarm, &task))
+    })
+    .await?;
+
+    Ok(ResponseJson(ApiResponse::success(plan)))
 }
 
 pub async fn update_task(
     Extension(swarm): Extension<Swarm>,
     Path((_swarm_id, task_id)): Path<(Uuid, Uuid)>,
     State(state): State<AppState>,
-    Json(payload): Json<UpdateSwarmTask>,
+    StrictJson(payload): StrictJson<UpdateSwarmTask>,
 ) -> Result<ResponseJson<ApiResponse<SwarmTask>>, ApiError> {
+    validate_estimate_minutes(payload.estimate_minutes)?;
+    validate_task_secrets(payload.secrets.as_deref())?;
+    validate_write_result_to(payload.write_result_to.as_deref())?;
+
     // IDOR protection: verify task belongs to the specified swarm before updating
     let existing_task = SwarmTask::find_by_id(&state.db_pool, task_id)
         .await
         ?
-        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string()))?;
+        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound))?;
 
     if existing_task.swarm_id != swarm.id {
-        return Err(ApiError::BadRequest("Task not found".to_string()));
+        return Err(ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound));
+    }
+
+    // New tasks have no downstream edges, so only an update can close a cycle
+    if payload.depends_on.is_some() || payload.triggers_after.is_some() {
+        let new_deps: Vec<Uuid> = payload
+            .depends_on
+            .as_ref()
+            .or(existing_task.depends_on.as_ref())
+            .into_iter()
+            .chain(payload.triggers_after.as_ref().or(existing_task.triggers_after.as_ref()))
+            .flatten()
+            .copied()
+            .collect();
+        if SwarmTask::would_create_cycle(&state.db_pool, swarm.id, task_id, &new_deps).await? {
+            return Err(ApiError::BadRequest("Dependency cycle detected".to_string())
+                .with_code(ErrorCode::DependencyCycle));
+        }
+    }
+
+    // Blocked tasks are released through the approve endpoint so the approver is recorded
+    if existing_task.status == SwarmTaskStatus::Blocked
+        && matches!(
+            payload.status,
+            Some(SwarmTaskStatus::Pending | SwarmTaskStatus::Running | SwarmTaskStatus::Completed)
+        )
+    {
+        return Err(ApiError::BadRequest(
+            "Blocked tasks must be approved before they can run".to_string(),
+        )
+        .with_code(ErrorCode::InvalidTaskState));
     }
 
     let task = SwarmTask::update(&state.db_pool, task_id, &payload)
@@ -115,18 +993,21 @@ pub async fn retry_task(
     let task = SwarmTask::find_by_id(&state.db_pool, task_id)
         .await
         ?
-        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string()))?;
+        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound))?;
 
     // IDOR protection: verify task belongs to the specified swarm
     if task.swarm_id != swarm.id {
-        return Err(ApiError::BadRequest("Task not found".to_string()));
+        return Err(ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound));
     }
 
     // Only allow retry on failed or cancelled tasks
     if !matches!(task.status, SwarmTaskStatus::Failed | SwarmTaskStatus::Cancelled) {
         return Err(ApiError::BadRequest(
             "Can only retry failed or cancelled tasks".to_string(),
-        ));
+        )
+        .with_code(ErrorCode::InvalidTaskState));
     }
 
     // Use the dedicated retry_task method from the model
@@ -145,6 +1026,236 @@ pub async fn retry_task(
     Ok(ResponseJson(ApiResponse::success(updated_task)))
 }
 
+/// Exit code reported to log subscribers when a task is cancelled, as for SIGINT
+const CANCELLED_EXIT_CODE: i32 = 130;
+
+/// POST /api/swarms/:id/tasks/:task_id/cancel - Cancel a task, stopping it if it is running
+///
+/// A running task's processes are killed and its sandbox is returned to the
+/// pool. Tasks that have already finished are rejected with 409.
+pub async fn cancel_task(
+    Extension(swarm): Extension<Swarm>,
+    Path((_swarm_id, task_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<SwarmTask>>, ApiError> {
+    let task = SwarmTask::find_by_id(&state.db_pool, task_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound))?;
+
+    // IDOR protection: verify task belongs to the specified swarm
+    if task.swarm_id != swarm.id {
+        return Err(ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound));
+    }
+
+    if task.status.is_terminal() || !SwarmTask::cancel(&state.db_pool, task_id).await? {
+        let status = SwarmTask::find_by_id(&state.db_pool, task_id)
+            .await?
+            .map_or(task.status, |t| t.status);
+        return Err(ApiError::Conflict(format!("Task is already {}", status))
+            .with_code(ErrorCode::InvalidTaskState));
+    }
+
+    if task.status == SwarmTaskStatus::Running {
+        stop_running_task(&state, &task).await;
+    }
+
+    state
+        .broadcast
+        .logs
+        .publish_log_end(task_id, LogEnd::failure(CANCELLED_EXIT_CODE).with_summary("Task cancelled"))
+        .await;
+
+    let task = SwarmTask::find_by_id(&state.db_pool, task_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound))?;
+
+    tracing::info!("Cancelled swarm task '{}' ({})", task.title, task_id);
+
+    Ok(ResponseJson(ApiResponse::success(task)))
+}
+
+/// Stop the execution of a task that was running when it was cancelled or reassigned
+///
+/// `task` is the record as it was while running. Failures are logged rather
+/// than returned: the task's new status is already stored, and the trigger
+/// engine stops leftover executions of cancelled tasks on its next pass.
+async fn stop_running_task(state: &AppState, task: &SwarmTask) {
+    if let Some(engine) = &state.trigger {
+        if let Err(e) = engine.stop_cancelled_task(task).await {
+            tracing::warn!(task_id = %task.id, error = %e, "Failed to stop cancelled task");
+        }
+        return;
+    }
+
+    // No engine in this process: kill the processes and free the sandbox directly
+    let Some(daytona_id) = task.sandbox_id.as_deref() else {
+        return;
+    };
+    match daytona_client(state).await {
+        Ok(client) => {
+            if let Err(e) = kill_task_processes(&client, daytona_id).await {
+                tracing::warn!(task_id = %task.id, daytona_id = %daytona_id, error = %e, "Failed to kill processes of cancelled task");
+            }
+        }
+        Err(e) => {
+            tracing::warn!(task_id = %task.id, error = %e, "Cannot reach Daytona to stop cancelled task");
+        }
+    }
+
+    let released = async {
+        SwarmTask::release_sandbox_if_assigned(&state.db_pool, task.id, daytona_id).await?;
+        if let Some(sandbox) = Sandbox::find_by_daytona_id(&state.db_pool, daytona_id).await?
+            && sandbox.current_task_id == Some(task.id)
+        {
+            Sandbox::release_task(&state.db_pool, sandbox.id).await?;
+        }
+        Ok::<_, sqlx::Error>(())
+    }
+    .await;
+    if let Err(e) = released {
+        tracing::warn!(task_id = %task.id, error = %e, "Failed to release sandbox of cancelled task");
+    }
+}
+
+/// Longest accepted approver id
+const MAX_APPROVER_ID_LEN: usize = 255;
+
+/// POST /api/swarms/:id/tasks/:task_id/approve - Release a blocked task for dispatch
+///
+/// The approver is taken from the `X-User-Id` header and recorded on the task.
+pub async fn approve_task(
+    Extension(swarm): Extension<Swarm>,
+    Path((_swarm_id, task_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<ResponseJson<ApiResponse<SwarmTask>>, ApiError> {
+    let approved_by = headers
+        .get("x-user-id")
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty());
+    if approved_by.is_some_and(|v| v.len() > MAX_APPROVER_ID_LEN) {
+        return Err(ApiError::BadRequest(format!(
+            "X-User-Id must be at most {} characters",
+            MAX_APPROVER_ID_LEN
+        )));
+    }
+
+    let task = SwarmTask::find_by_id(&state.db_pool, task_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound))?;
+
+    // IDOR protection: verify task belongs to the specified swarm
+    if task.swarm_id != swarm.id {
+        return Err(ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound));
+    }
+
+    if !SwarmTask::approve(&state.db_pool, task_id, approved_by).await? {
+        return Err(ApiError::Conflict(format!(
+            "Task is {}, only blocked tasks can be approved",
+            task.status
+        ))
+        .with_code(ErrorCode::InvalidTaskState));
+    }
+
+    let approved_task = SwarmTask::find_by_id(&state.db_pool, task_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Task disappeared after approval".to_string()))?;
+
+    tracing::info!(
+        "Approved swarm task '{}' ({}) by {}",
+        approved_task.title,
+        task_id,
+        approved_by.unwrap_or("unknown user")
+    );
+
+    if Swarm::resume_if_auto_paused(&state.db_pool, swarm.id).await? {
+        tracing::info!("Resumed auto-paused swarm {} for approved task", swarm.id);
+    }
+
+    Ok(ResponseJson(ApiResponse::success(approved_task)))
+}
+
+/// POST /api/swarms/:id/tasks/:task_id/reassign - Move a running task to another sandbox
+///
+/// The target sandbox is reserved for the task and the old one released in a
+/// single transaction; the task goes back to pending pinned to the new sandbox,
+/// so the trigger engine restarts it there once the old run has been stopped.
+pub async fn reassign_task(
+    Extension(swarm): Extension<Swarm>,
+    Path((_swarm_id, task_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AppState>,
+    StrictJson(payload): StrictJson<ReassignTaskRequest>,
+) -> Result<ResponseJson<ApiResponse<SwarmTask>>, ApiError> {
+    let task = SwarmTask::find_by_id(&state.db_pool, task_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound))?;
+
+    // IDOR protection: verify task belongs to the specified swarm
+    if task.swarm_id != swarm.id {
+        return Err(ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound));
+    }
+
+    if task.status != SwarmTaskStatus::Running {
+        return Err(ApiError::BadRequest("Only running tasks can be reassigned".to_string())
+            .with_code(ErrorCode::InvalidTaskState));
+    }
+
+    let sandbox = Sandbox::find_by_id(&state.db_pool, payload.sandbox_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Sandbox not found".to_string())
+            .with_code(ErrorCode::SandboxNotFound))?;
+
+    if sandbox.swarm_id != Some(swarm.id) {
+        return Err(ApiError::BadRequest("Sandbox does not belong to this swarm".to_string())
+            .with_code(ErrorCode::SandboxNotInSwarm));
+    }
+    if task.sandbox_id.as_deref() == Some(sandbox.daytona_id.as_str()) {
+        return Err(ApiError::BadRequest("Task is already running in this sandbox".to_string())
+            .with_code(ErrorCode::InvalidTaskState));
+    }
+
+    // Dropping the transaction on an early return rolls back the release
+    let mut tx = state.db_pool.begin().await?;
+
+    Sandbox::release_by_task_id(&mut *tx, task_id).await?;
+    if !Sandbox::try_assign_task(&mut *tx, sandbox.id, task_id).await? {
+        return Err(ApiError::Conflict("Sandbox is not idle".to_string())
+            .with_code(ErrorCode::InvalidSandboxState));
+    }
+
+    if !SwarmTask::requeue_on_sandbox(&mut *tx, task_id, sandbox.id).await? {
+        return Err(ApiError::Conflict("Task is no longer running".to_string())
+            .with_code(ErrorCode::InvalidTaskState));
+    }
+
+    tx.commit().await?;
+
+    // The old run would otherwise carry on and overwrite the requeued task when it ends
+    stop_running_task(&state, &task).await;
+
+    let updated = SwarmTask::find_by_id(&state.db_pool, task_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound))?;
+
+    tracing::info!(
+        "Reassigned swarm task '{}' ({}) to sandbox {}",
+        updated.title,
+        task_id,
+        sandbox.id
+    );
+
+    Ok(ResponseJson(ApiResponse::success(updated)))
+}
+
 pub async fn delete_task(
     Extension(swarm): Extension<Swarm>,
     Path((_swarm_id, task_id)): Path<(Uuid, Uuid)>,
@@ -154,10 +1265,12 @@ pub async fn delete_task(
     let task = SwarmTask::find_by_id(&state.db_pool, task_id)
         .await
         ?
-        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string()))?;
+        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound))?;
 
     if task.swarm_id != swarm.id {
-        return Err(ApiError::BadRequest("Task not found".to_string()));
+        return Err(ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound));
     }
 
     let rows = SwarmTask::delete(&state.db_pool, task_id)
@@ -165,7 +1278,8 @@ pub async fn delete_task(
         ?;
 
     if rows == 0 {
-        return Err(ApiError::BadRequest("Task not found".to_string()));
+        return Err(ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound));
     }
 
     tracing::info!("Deleted swarm task {}", task_id);
@@ -173,9 +1287,100 @@ pub async fn delete_task(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
-/// Router for routes with task_id path param (get, update, delete, retry)
+/// GET /api/swarms/:id/tasks/:task_id/comments - Notes on a task, oldest first
+pub async fn list_task_comments(
+    Extension(swarm): Extension<Swarm>,
+    Path((_swarm_id, task_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<SwarmTaskComment>>>, ApiError> {
+    let task = SwarmTask::find_by_id(&state.db_pool, task_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound))?;
+
+    // IDOR protection: verify task belongs to the specified swarm
+    if task.swarm_id != swarm.id {
+        return Err(ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound));
+    }
+
+    let comments = SwarmTaskComment::find_by_task_id(&state.db_pool, task_id).await?;
+
+    Ok(ResponseJson(ApiResponse::success(comments)))
+}
+
+/// GET /api/swarms/:id/tasks/:task_id/logs - Stored logs of each attempt, decompressed
+pub async fn list_task_logs(
+    Extension(swarm): Extension<Swarm>,
+    Path((_swarm_id, task_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<Vec<TaskLog>>>, ApiError> {
+    let task = SwarmTask::find_by_id(&state.db_pool, task_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound))?;
+
+    // IDOR protection: verify task belongs to the specified swarm
+    if task.swarm_id != swarm.id {
+        return Err(ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound));
+    }
+
+    let logs = SwarmTaskLog::find_by_task_id(&state.db_pool, task_id)
+        .await?
+        .into_iter()
+        .map(TaskLog::decode)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ResponseJson(ApiResponse::success(logs)))
+}
+
+/// POST /api/swarms/:id/tasks/:task_id/comment - Leave a note on a task
+///
+/// Comments never affect execution; they are a place for collaborators to
+/// discuss a task outside the swarm chat.
+pub async fn add_task_comment(
+    Extension(swarm): Extension<Swarm>,
+    Path((_swarm_id, task_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AppState>,
+    StrictJson(payload): StrictJson<CreateSwarmTaskComment>,
+) -> Result<ResponseJson<ApiResponse<SwarmTaskComment>>, ApiError> {
+    if payload.author.trim().is_empty() || payload.author.len() > 255 {
+        return Err(ApiError::BadRequest("Author must be 1-255 chars".to_string()));
+    }
+    if payload.body.trim().is_empty() || payload.body.len() > 10000 {
+        return Err(ApiError::BadRequest("Comment must be 1-10000 chars".to_string()));
+    }
+
+    let task = SwarmTask::find_by_id(&state.db_pool, task_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound))?;
+
+    // IDOR protection: verify task belongs to the specified swarm
+    if task.swarm_id != swarm.id {
+        return Err(ApiError::BadRequest("Task not found".to_string())
+            .with_code(ErrorCode::TaskNotFound));
+    }
+
+    let comment =
+        SwarmTaskComment::create(&state.db_pool, task_id, &payload, Uuid::new_v4()).await?;
+
+    tracing::debug!("Added comment {} to swarm task {}", comment.id, task_id);
+
+    Ok(ResponseJson(ApiResponse::success(comment)))
+}
+
+/// Router for routes with task_id path param (get, update, delete, retry, cancel, reassign, comments)
 pub fn task_id_router() -> Router<AppState> {
     Router::new()
         .route("/", get(get_task).patch(update_task).delete(delete_task))
         .route("/retry", post(retry_task))
+        .route("/cancel", post(cancel_task))
+        .route("/approve", post(approve_task))
+        .route("/reassign", post(reassign_task))
+        .route("/comment", post(add_task_comment))
+        .route("/comments", get(list_task_comments))
+        .route("/logs", get(list_task_logs))
+        .route("/plan", get(get_task_plan))
 }
@@ -0,0 +1,36 @@
+//! Trigger Engine Routes
+
+use axum::{Router, extract::State, response::Json as ResponseJson, routing::get};
+use serde::Serialize;
+use services::services::swarm::TriggerStats;
+use ts_rs::TS;
+use utils::response::{ApiResponse, ErrorCode};
+
+use crate::{AppState, error::ApiError};
+
+#[derive(Debug, Serialize, TS)]
+pub struct TriggerStatsResponse {
+    pub current: TriggerStats,
+    /// Samples taken once per trigger pass, oldest first
+    pub history: Vec<TriggerStats>,
+}
+
+/// GET /api/swarms/trigger/stats - Current engine stats and recent samples
+pub async fn get_trigger_stats(
+    State(state): State<AppState>,
+) -> Result<ResponseJson<ApiResponse<TriggerStatsResponse>>, ApiError> {
+    let engine = state
+        .trigger
+        .as_ref()
+        .ok_or_else(|| ApiError::Conflict("Trigger engine is not running".to_string())
+            .with_code(ErrorCode::TriggerEngineNotRunning))?;
+
+    Ok(ResponseJson(ApiResponse::success(TriggerStatsResponse {
+        current: engine.get_stats(false).await,
+        history: engine.stats_history().await,
+    })))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/trigger/stats", get(get_trigger_stats))
+}
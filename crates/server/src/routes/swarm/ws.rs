@@ -1,28 +1,39 @@
 //! WebSocket Routes for Swarm
 //!
 //! Provides real-time streaming of logs, chat messages, and pool status updates
-//! using tokio broadcast channels.
+//! using tokio broadcast channels. Task logs can be followed one task per
+//! connection, or several tasks of a swarm over a single connection.
 
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use axum::{
     Router,
     extract::{
-        Path, State,
-        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade, close_code},
     },
     response::IntoResponse,
     routing::get,
 };
-use futures_util::{SinkExt, StreamExt};
+use futures_util::{SinkExt, StreamExt, stream::SplitSink};
 use serde::{Deserialize, Serialize};
-use services::services::swarm::{BroadcastManager, LogMessage};
+use services::services::swarm::{
+    BroadcastManager, ChatBroadcastMessage, ChatEvent, ChatService, LogEnd, LogEntry, LogLevel,
+    LogMessage, LogSource, LogSubscription, PoolStatusUpdate,
+};
+use sqlx::SqlitePool;
 use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 use ts_rs::TS;
 use uuid::Uuid;
 
+use db::models::sandbox::{Sandbox, SandboxStatus};
 use db::models::swarm::Swarm;
+use db::models::swarm_chat::SwarmChat;
+use db::models::swarm_config::SwarmConfig;
 use db::models::swarm_task::SwarmTask;
 
 use crate::AppState;
@@ -34,33 +45,311 @@ const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
 #[allow(dead_code)]
 const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Number of recent chat messages replayed when a chat client sends `refresh`
+const CHAT_REFRESH_LIMIT: i32 = 50;
+
+/// Most tasks one connection to the swarm log stream may follow at once
+pub const MAX_LOG_SUBSCRIPTIONS: usize = 50;
+
+/// Largest frame accepted from a client
+///
+/// Clients only send small control frames (pong, refresh), so anything
+/// bigger is refused before it is parsed.
+pub const MAX_INBOUND_FRAME_BYTES: usize = 64 * 1024;
+
+/// Hard cap applied by the WebSocket codec, so frames far over the limit are
+/// dropped while reading instead of being buffered in full
+const INBOUND_CODEC_LIMIT: usize = 1024 * 1024;
+
+/// Every frame sent or accepted on the swarm WebSockets
+///
+/// Broadcast payloads are always wrapped in this enum before being sent, so
+/// its serialized (and exported TypeScript) form is exactly the wire format.
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum WsMessage {
-    LogLine { line: String, timestamp: i64 },
-    LogEnd { exit_code: i32 },
-    ChatMessage {
-        id: String,
-        sender_type: String,
-        sender_id: Option<String>,
+    Log(LogEntry),
+    LogEnd(LogEnd),
+    /// Complete chat message
+    Message(ChatBroadcastMessage),
+    /// Chunk of a chat message that is still being generated
+    ChatDelta { message_id: String, delta: String },
+    PoolUpdate(PoolStatusUpdate),
+    Connected {
         message: String,
-        timestamp: i64,
+        /// Minimum level of the log entries sent, on log streams with a level filter
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        level: Option<LogLevel>,
     },
-    PoolUpdate {
-        sandbox_id: String,
-        status: String,
-        task_id: Option<String>,
+    Error {
+        message: String,
+        /// Machine-readable cause, for errors a client can act on
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<WsErrorDetail>,
     },
-    Connected { message: String },
-    Error { message: String },
     Ping { timestamp: i64 },
     Pong { timestamp: i64 },
+    /// Client request to resend current state (pool status / recent chat) on this connection
+    Refresh,
+    /// Client request to only receive log entries at or above `level`; null clears the filter
+    SetLevel {
+        #[serde(default)]
+        level: Option<LogLevel>,
+    },
+    /// Client request to start streaming a task's logs on the swarm log stream
+    Subscribe { task_id: Uuid },
+    /// Client request to stop streaming a task's logs on the swarm log stream
+    Unsubscribe { task_id: Uuid },
+    /// Frame from one task on the swarm log stream
+    Task { task_id: Uuid, frame: Box<WsMessage> },
+}
+
+/// Recovery hint sent with lag errors
+///
+/// Streams have no replay; dropped frames can only be recovered from the REST
+/// endpoints (task logs, chat history, pool status).
+const LAG_RECOVERY_HINT: &str = "refetch missed history over REST; the stream continues with new frames";
+
+/// Close reason sent to a client that keeps lagging
+const PERSISTENT_LAG_REASON: &str = "persistent lag, please reconnect";
+
+/// Counts broadcast lag events on one connection
+///
+/// An occasional lag is reported to the client and otherwise let pass;
+/// `limit` lags within `window` mean the client cannot keep up, so it is
+/// disconnected and reconnects from a fresh buffer position.
+pub(super) struct LagTracker {
+    limit: usize,
+    window: Duration,
+    events: VecDeque<Instant>,
+}
+
+impl LagTracker {
+    /// A `limit` of 0 never disconnects
+    pub(super) fn new(limit: usize, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            events: VecDeque::with_capacity(limit),
+        }
+    }
+
+    fn from_config(config: &SwarmConfig) -> Self {
+        Self::new(
+            usize::try_from(config.ws_lag_limit).unwrap_or(0),
+            Duration::from_secs(u64::try_from(config.ws_lag_window_secs).unwrap_or(1).max(1)),
+        )
+    }
+
+    /// Record a lag at `now`; returns true once the client should be disconnected
+    pub(super) fn record(&mut self, now: Instant) -> bool {
+        if self.limit == 0 {
+            return false;
+        }
+        while self
+            .events
+            .front()
+            .is_some_and(|&first| now.duration_since(first) > self.window)
+        {
+            self.events.pop_front();
+        }
+        self.events.push_back(now);
+        self.events.len() >= self.limit
+    }
+}
+
+/// Close frame for a client disconnected after persistent lag
+pub(super) fn persistent_lag_close() -> CloseFrame {
+    CloseFrame {
+        code: close_code::AGAIN,
+        reason: PERSISTENT_LAG_REASON.into(),
+    }
+}
+
+/// Why the server ended a stream, sent as the close frame reason
+///
+/// Lets clients tell a deliberate end from a dropped connection and decide
+/// whether to reconnect.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum StreamEnd {
+    /// The task's log channel was closed once its run was over
+    TaskComplete,
+    /// The swarm is gone; reconnecting will be refused
+    SwarmDeleted,
+    /// The server is going away; reconnect once it is back
+    ServerShutdown,
+}
+
+impl StreamEnd {
+    pub(super) fn close_frame(self) -> CloseFrame {
+        let (code, reason) = match self {
+            Self::TaskComplete => (close_code::NORMAL, "task_complete"),
+            Self::SwarmDeleted => (close_code::NORMAL, "swarm_deleted"),
+            Self::ServerShutdown => (close_code::AWAY, "server_shutdown"),
+        };
+        CloseFrame {
+            code,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Lag policy for a new connection, from the swarm config
+async fn lag_tracker(db_pool: &SqlitePool) -> Result<LagTracker, axum::response::Response> {
+    SwarmConfig::get(db_pool)
+        .await
+        .map(|config| LagTracker::from_config(&config))
+        .map_err(|e| {
+            tracing::warn!(error = %e, "Database error loading WebSocket lag policy");
+            (axum::http::StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+        })
+}
+
+/// Structured detail of a `WsMessage::Error`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WsErrorDetail {
+    /// The connection fell behind its broadcast channel and frames were dropped
+    Lag {
+        /// Number of dropped frames, saturating at `u32::MAX`
+        skipped: u32,
+        hint: String,
+    },
+}
+
+impl WsMessage {
+    /// Error frame reporting that `skipped` frames of `what` were dropped
+    pub fn lag(what: &str, skipped: u64) -> Self {
+        let skipped = u32::try_from(skipped).unwrap_or(u32::MAX);
+        Self::Error {
+            message: format!("Missed {} {} due to lag", skipped, what),
+            detail: Some(WsErrorDetail::Lag {
+                skipped,
+                hint: LAG_RECOVERY_HINT.to_string(),
+            }),
+        }
+    }
+}
+
+impl From<LogMessage> for WsMessage {
+    fn from(message: LogMessage) -> Self {
+        match message {
+            LogMessage::Entry(entry) => Self::Log(entry),
+            LogMessage::End(end) => Self::LogEnd(end),
+        }
+    }
+}
+
+impl From<ChatEvent> for WsMessage {
+    fn from(event: ChatEvent) -> Self {
+        match event {
+            ChatEvent::Message(message) => Self::Message(message),
+            ChatEvent::Delta(delta) => Self::ChatDelta {
+                message_id: delta.message_id.to_string(),
+                delta: delta.delta,
+            },
+        }
+    }
+}
+
+/// Close frame for a client frame of `len` bytes, if it is over the inbound limit
+pub(super) fn oversized_frame_close(len: usize) -> Option<CloseFrame> {
+    (len > MAX_INBOUND_FRAME_BYTES).then(|| CloseFrame {
+        code: close_code::POLICY,
+        reason: format!("Frame exceeds {} bytes", MAX_INBOUND_FRAME_BYTES).into(),
+    })
+}
+
+/// Close the connection with a policy violation if a client frame is too large
+///
+/// Returns true when the frame was refused and the stream should end.
+async fn reject_oversized_frame(ws_sender: &mut SplitSink<WebSocket, Message>, len: usize) -> bool {
+    let Some(close) = oversized_frame_close(len) else {
+        return false;
+    };
+
+    tracing::warn!(len, limit = MAX_INBOUND_FRAME_BYTES, "Closing WebSocket after oversized client frame");
+    let _ = ws_sender.send(Message::Close(Some(close))).await;
+    true
+}
+
+/// Send each frame as a JSON text message; returns false once the socket is gone
+async fn send_frames(ws_sender: &mut SplitSink<WebSocket, Message>, frames: &[WsMessage]) -> bool {
+    for frame in frames {
+        match serde_json::to_string(frame) {
+            Ok(json) => {
+                if ws_sender.send(Message::Text(json.into())).await.is_err() {
+                    return false;
+                }
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to serialize frame");
+            }
+        }
+    }
+    true
+}
+
+/// Current state of every live sandbox, in the same shape as live pool updates
+async fn pool_snapshot(
+    db_pool: &SqlitePool,
+    swarm_filter: Option<Uuid>,
+) -> Result<Vec<WsMessage>, sqlx::Error> {
+    let sandboxes = Sandbox::find_all(db_pool).await?;
+
+    Ok(sandboxes
+        .into_iter()
+        .filter(|s| s.status != SandboxStatus::Destroyed)
+        .filter(|s| swarm_filter.is_none() || s.swarm_id == swarm_filter)
+        .map(|s| {
+            let mut update = PoolStatusUpdate::new(s.id.to_string(), s.status.to_string());
+            if let Some(task_id) = s.current_task_id {
+                update = update.with_task(task_id.to_string());
+            }
+            if let Some(swarm_id) = s.swarm_id {
+                update = update.with_swarm(swarm_id.to_string());
+            }
+            WsMessage::PoolUpdate(update)
+        })
+        .collect())
+}
+
+/// Most recent chat messages, oldest first, in the same shape as live chat frames
+async fn chat_snapshot(
+    db_pool: &SqlitePool,
+    swarm_id: Uuid,
+) -> Result<Vec<WsMessage>, sqlx::Error> {
+    let mut messages = SwarmChat::find_by_swarm_id(db_pool, swarm_id, Some(CHAT_REFRESH_LIMIT)).await?;
+    messages.reverse();
+
+    Ok(messages
+        .iter()
+        .map(|m| WsMessage::Message(ChatBroadcastMessage::new(ChatService::to_broadcast_data(m))))
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogStreamQuery {
+    /// Only stream entries from this component
+    pub source: Option<LogSource>,
+    /// Only stream entries at or above this level
+    pub level: Option<LogLevel>,
+}
+
+/// Acknowledgement of a `set_level` request
+fn level_set_frame(level: Option<LogLevel>) -> WsMessage {
+    let message = match level {
+        Some(level) => format!("Log level filter set to {}", level),
+        None => "Log level filter cleared".to_string(),
+    };
+    WsMessage::Connected { message, level }
 }
 
 /// WebSocket handler for task log streaming
 pub async fn task_logs_ws(
     ws: WebSocketUpgrade,
     Path((swarm_id, task_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<LogStreamQuery>,
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, axum::response::Response> {
     // IDOR protection: verify task belongs to the specified swarm before allowing WebSocket connection
@@ -85,7 +374,11 @@ pub async fn task_logs_ws(
         return Err((axum::http::StatusCode::NOT_FOUND, "Task not found").into_response());
     }
 
-    Ok(ws.on_upgrade(move |socket| handle_log_stream(socket, swarm_id, task_id, state.broadcast)))
+    let lag = lag_tracker(&state.db_pool).await?;
+
+    Ok(ws.max_message_size(INBOUND_CODEC_LIMIT).on_upgrade(move |socket| {
+        handle_log_stream(socket, swarm_id, task_id, query.source, query.level, lag, state.broadcast)
+    }))
 }
 
 /// Handle the log stream WebSocket connection
@@ -93,6 +386,9 @@ async fn handle_log_stream(
     socket: WebSocket,
     swarm_id: Uuid,
     task_id: Uuid,
+    source: Option<LogSource>,
+    mut min_level: Option<LogLevel>,
+    mut lag: LagTracker,
     broadcast: Arc<BroadcastManager>,
 ) {
     let (mut ws_sender, mut ws_receiver) = socket.split();
@@ -100,6 +396,7 @@ async fn handle_log_stream(
     // Send connected message
     let connected = WsMessage::Connected {
         message: format!("Connected to log stream for task {}", task_id),
+        level: min_level,
     };
     match serde_json::to_string(&connected) {
         Ok(json) => {
@@ -133,6 +430,9 @@ async fn handle_log_stream(
             msg = ws_receiver.next() => {
                 match msg {
                     Some(Ok(Message::Text(text))) => {
+                        if reject_oversized_frame(&mut ws_sender, text.len()).await {
+                            break;
+                        }
                         // Handle client messages (e.g., pong responses)
                         if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
                             match ws_msg {
@@ -140,10 +440,23 @@ async fn handle_log_stream(
                                     // Client responded to ping, connection is alive
                                     tracing::trace!(task_id = %task_id, "Received pong");
                                 }
+                                WsMessage::SetLevel { level } => {
+                                    min_level = level;
+                                    if !send_frames(&mut ws_sender, &[level_set_frame(level)]).await {
+                                        break;
+                                    }
+                                }
                                 _ => {}
                             }
                         }
                     }
+                    Some(Ok(Message::Binary(data))) => {
+                        // Binary frames carry nothing for us; only the size is checked
+                        let refused = reject_oversized_frame(&mut ws_sender, data.len()).await;
+                        if refused {
+                            break;
+                        }
+                    }
                     Some(Ok(Message::Ping(data))) => {
                         // Respond to ping with pong
                         if ws_sender.send(Message::Pong(data)).await.is_err() {
@@ -164,37 +477,22 @@ async fn handle_log_stream(
                     None => {
                         break;
                     }
-                    _ => {}
                 }
             }
 
             // Handle broadcast log messages
             log_result = log_receiver.recv() => {
                 match log_result {
+                    Ok(log_msg) if !log_msg.matches_source(source) || !log_msg.meets_level(min_level) => {}
                     Ok(log_msg) => {
-                        let ws_msg = match log_msg {
-                            LogMessage::Entry(entry) => {
-                                // Send the log entry as JSON directly
-                                serde_json::to_string(&entry).ok()
-                            }
-                            LogMessage::End(end) => {
-                                // Send the log end message
-                                serde_json::to_string(&end).ok()
-                            }
-                        };
-
-                        if let Some(json) = ws_msg {
-                            if ws_sender.send(Message::Text(json.into())).await.is_err() {
-                                break;
-                            }
+                        if !send_frames(&mut ws_sender, &[WsMessage::from(log_msg)]).await {
+                            break;
                         }
                     }
                     Err(RecvError::Lagged(n)) => {
                         // Receiver fell behind, notify client
                         tracing::warn!(task_id = %task_id, skipped = n, "Log receiver lagged");
-                        let error = WsMessage::Error {
-                            message: format!("Missed {} log messages due to lag", n),
-                        };
+                        let error = WsMessage::lag("log messages", n);
                         match serde_json::to_string(&error) {
                             Ok(json) => {
                                 let _ = ws_sender.send(Message::Text(json.into())).await;
@@ -203,9 +501,15 @@ async fn handle_log_stream(
                                 tracing::warn!(task_id = %task_id, error = %e, "Failed to serialize error message");
                             }
                         }
+                        if lag.record(Instant::now()) {
+                            tracing::warn!(task_id = %task_id, "Closing log stream after persistent lag");
+                            let _ = ws_sender.send(Message::Close(Some(persistent_lag_close()))).await;
+                            break;
+                        }
                     }
                     Err(RecvError::Closed) => {
                         tracing::debug!(task_id = %task_id, "Log broadcast channel closed");
+                        let _ = ws_sender.send(Message::Close(Some(StreamEnd::TaskComplete.close_frame()))).await;
                         break;
                     }
                 }
@@ -236,6 +540,300 @@ async fn handle_log_stream(
     tracing::debug!(swarm_id = %swarm_id, task_id = %task_id, "Log stream closed");
 }
 
+/// Log receivers of the tasks followed on one swarm log stream connection
+///
+/// Each subscription runs a forwarder that moves its task's log frames onto a
+/// single channel, tagged with the task id. Dropping a subscription stops the
+/// forwarder and releases its broadcast receiver, so the task's channel is
+/// cleaned up once no other connection follows it.
+pub(super) struct LogSubscriptions {
+    broadcast: Arc<BroadcastManager>,
+    source: Option<LogSource>,
+    forwarders: HashMap<Uuid, JoinHandle<()>>,
+    sender: mpsc::Sender<(Uuid, Result<LogMessage, RecvError>)>,
+    receiver: mpsc::Receiver<(Uuid, Result<LogMessage, RecvError>)>,
+}
+
+impl LogSubscriptions {
+    pub(super) fn new(broadcast: Arc<BroadcastManager>, source: Option<LogSource>) -> Self {
+        let (sender, receiver) = mpsc::channel(64);
+        Self {
+            broadcast,
+            source,
+            forwarders: HashMap::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    pub(super) fn contains(&self, task_id: Uuid) -> bool {
+        self.forwarders.contains_key(&task_id)
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.forwarders.len()
+    }
+
+    /// Start forwarding a task's logs; subscribing twice is a no-op
+    pub(super) async fn subscribe(&mut self, task_id: Uuid) {
+        if self.contains(task_id) {
+            return;
+        }
+        let receiver = self.broadcast.logs.subscribe_logs(task_id).await;
+        let forwarder = tokio::spawn(forward_task_logs(task_id, receiver, self.source, self.sender.clone()));
+        self.forwarders.insert(task_id, forwarder);
+    }
+
+    /// Stop forwarding a task's logs; returns false if it was not subscribed
+    pub(super) async fn unsubscribe(&mut self, task_id: Uuid) -> bool {
+        let Some(forwarder) = self.forwarders.remove(&task_id) else {
+            return false;
+        };
+        // Wait for the forwarder to drop its receiver before cleaning up the channel
+        forwarder.abort();
+        let _ = forwarder.await;
+        self.broadcast.logs.cleanup_channel(task_id).await;
+        true
+    }
+
+    /// Next log frame from any subscribed task, tagged with its task id
+    ///
+    /// Lag is reported as `Err(RecvError::Lagged)`; a task whose channel
+    /// closes simply stops producing frames.
+    pub(super) async fn recv(&mut self) -> (Uuid, Result<LogMessage, RecvError>) {
+        // The struct holds a sender itself, so the channel never closes
+        self.receiver.recv().await.expect("log subscription channel closed")
+    }
+
+    /// Drop every subscription
+    pub(super) async fn clear(&mut self) {
+        let task_ids: Vec<Uuid> = self.forwarders.keys().copied().collect();
+        for task_id in task_ids {
+            self.unsubscribe(task_id).await;
+        }
+    }
+}
+
+/// Move one task's log frames onto a shared channel until either side goes away
+async fn forward_task_logs(
+    task_id: Uuid,
+    mut receiver: LogSubscription,
+    source: Option<LogSource>,
+    sender: mpsc::Sender<(Uuid, Result<LogMessage, RecvError>)>,
+) {
+    loop {
+        let result = match receiver.recv().await {
+            Ok(log_msg) if !log_msg.matches_source(source) => continue,
+            Err(RecvError::Closed) => break,
+            result => result,
+        };
+        if sender.send((task_id, result)).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// WebSocket handler for multiplexed log streaming of a swarm's tasks
+///
+/// Clients send `subscribe` / `unsubscribe` frames naming a task of the swarm;
+/// log frames of every subscribed task arrive wrapped in `task` frames.
+pub async fn swarm_logs_ws(
+    ws: WebSocketUpgrade,
+    Path(swarm_id): Path<Uuid>,
+    Query(query): Query<LogStreamQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, axum::response::Response> {
+    // IDOR protection: verify swarm exists before allowing WebSocket connection
+    let _swarm = Swarm::find_by_id(&state.db_pool, swarm_id)
+        .await
+        .map_err(|e| {
+            tracing::warn!(swarm_id = %swarm_id, error = %e, "Database error checking swarm for log WebSocket");
+            (axum::http::StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+        })?
+        .ok_or_else(|| {
+            tracing::warn!(swarm_id = %swarm_id, "Swarm not found for log WebSocket");
+            (axum::http::StatusCode::NOT_FOUND, "Swarm not found").into_response()
+        })?;
+
+    let lag = lag_tracker(&state.db_pool).await?;
+
+    Ok(ws.max_message_size(INBOUND_CODEC_LIMIT).on_upgrade(move |socket| {
+        handle_swarm_log_stream(socket, swarm_id, query.source, query.level, state.db_pool, lag, state.broadcast)
+    }))
+}
+
+/// Frame answering a `subscribe` request
+///
+/// Tasks of other swarms are reported as not found, like on the per-task stream.
+async fn subscribe_frame(
+    db_pool: &SqlitePool,
+    subscriptions: &mut LogSubscriptions,
+    swarm_id: Uuid,
+    task_id: Uuid,
+) -> WsMessage {
+    let error = |message: String| WsMessage::Error { message, detail: None };
+
+    if !subscriptions.contains(task_id) && subscriptions.len() >= MAX_LOG_SUBSCRIPTIONS {
+        return error(format!("Cannot follow more than {} tasks on one connection", MAX_LOG_SUBSCRIPTIONS));
+    }
+
+    match SwarmTask::find_by_id(db_pool, task_id).await {
+        Ok(Some(task)) if task.swarm_id == swarm_id => {
+            subscriptions.subscribe(task_id).await;
+            WsMessage::Task {
+                task_id,
+                frame: Box::new(WsMessage::Connected {
+                    message: format!("Subscribed to logs for task {}", task_id),
+                    level: None,
+                }),
+            }
+        }
+        Ok(_) => {
+            tracing::warn!(swarm_id = %swarm_id, task_id = %task_id, "Log subscription to task outside swarm");
+            error(format!("Task {} not found", task_id))
+        }
+        Err(e) => {
+            tracing::warn!(swarm_id = %swarm_id, task_id = %task_id, error = %e, "Database error checking task for log subscription");
+            error("Failed to subscribe to task logs".to_string())
+        }
+    }
+}
+
+/// Handle the multiplexed log stream WebSocket connection
+async fn handle_swarm_log_stream(
+    socket: WebSocket,
+    swarm_id: Uuid,
+    source: Option<LogSource>,
+    mut min_level: Option<LogLevel>,
+    db_pool: SqlitePool,
+    mut lag: LagTracker,
+    broadcast: Arc<BroadcastManager>,
+) {
+    let (mut ws_sender, mut ws_receiver) = socket.split();
+
+    let connected = WsMessage::Connected {
+        message: format!("Connected to log stream for swarm {}", swarm_id),
+        level: min_level,
+    };
+    if !send_frames(&mut ws_sender, &[connected]).await {
+        return;
+    }
+
+    let mut subscriptions = LogSubscriptions::new(broadcast, source);
+
+    // Spawn heartbeat task
+    let (heartbeat_tx, mut heartbeat_rx) = tokio::sync::mpsc::channel::<()>(1);
+    let heartbeat_handle = tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+            if heartbeat_tx.send(()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    // Main event loop
+    loop {
+        tokio::select! {
+            // Handle incoming WebSocket messages
+            msg = ws_receiver.next() => {
+                match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        if reject_oversized_frame(&mut ws_sender, text.len()).await {
+                            break;
+                        }
+                        let reply = match serde_json::from_str::<WsMessage>(&text) {
+                            Ok(WsMessage::Subscribe { task_id }) => {
+                                Some(subscribe_frame(&db_pool, &mut subscriptions, swarm_id, task_id).await)
+                            }
+                            Ok(WsMessage::Unsubscribe { task_id }) => {
+                                subscriptions.unsubscribe(task_id).await;
+                                None
+                            }
+                            Ok(WsMessage::SetLevel { level }) => {
+                                min_level = level;
+                                Some(level_set_frame(level))
+                            }
+                            Ok(WsMessage::Pong { .. }) => {
+                                tracing::trace!(swarm_id = %swarm_id, "Received pong");
+                                None
+                            }
+                            _ => None,
+                        };
+                        if let Some(reply) = reply
+                            && !send_frames(&mut ws_sender, &[reply]).await
+                        {
+                            break;
+                        }
+                    }
+                    Some(Ok(Message::Binary(data))) => {
+                        // Binary frames carry nothing for us; only the size is checked
+                        let refused = reject_oversized_frame(&mut ws_sender, data.len()).await;
+                        if refused {
+                            break;
+                        }
+                    }
+                    Some(Ok(Message::Ping(data))) => {
+                        if ws_sender.send(Message::Pong(data)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(Message::Pong(_))) => {}
+                    Some(Ok(Message::Close(_))) => {
+                        tracing::debug!(swarm_id = %swarm_id, "Client closed swarm log stream");
+                        break;
+                    }
+                    Some(Err(e)) => {
+                        tracing::warn!(swarm_id = %swarm_id, error = %e, "WebSocket error");
+                        break;
+                    }
+                    None => {
+                        break;
+                    }
+                }
+            }
+
+            // Handle log messages of subscribed tasks
+            (task_id, log_result) = subscriptions.recv() => {
+                let frame = match log_result {
+                    Ok(log_msg) if !log_msg.meets_level(min_level) => continue,
+                    Ok(log_msg) => WsMessage::from(log_msg),
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!(swarm_id = %swarm_id, task_id = %task_id, skipped = n, "Log receiver lagged");
+                        WsMessage::lag("log messages", n)
+                    }
+                    Err(RecvError::Closed) => continue,
+                };
+                let lagged = matches!(frame, WsMessage::Error { .. });
+                let tagged = WsMessage::Task { task_id, frame: Box::new(frame) };
+                if !send_frames(&mut ws_sender, &[tagged]).await {
+                    break;
+                }
+                if lagged && lag.record(Instant::now()) {
+                    tracing::warn!(swarm_id = %swarm_id, "Closing swarm log stream after persistent lag");
+                    let _ = ws_sender.send(Message::Close(Some(persistent_lag_close()))).await;
+                    break;
+                }
+            }
+
+            // Handle heartbeat
+            _ = heartbeat_rx.recv() => {
+                let ping = WsMessage::Ping {
+                    timestamp: chrono::Utc::now().timestamp_millis(),
+                };
+                if !send_frames(&mut ws_sender, &[ping]).await {
+                    break;
+                }
+            }
+        }
+    }
+
+    // Cleanup
+    heartbeat_handle.abort();
+    subscriptions.clear().await;
+    tracing::debug!(swarm_id = %swarm_id, "Swarm log stream closed");
+}
+
 /// WebSocket handler for chat streaming
 pub async fn chat_ws(
     ws: WebSocketUpgrade,
@@ -254,13 +852,17 @@ pub async fn chat_ws(
             (axum::http::StatusCode::NOT_FOUND, "Swarm not found").into_response()
         })?;
 
-    Ok(ws.on_upgrade(move |socket| handle_chat_stream(socket, swarm_id, state.broadcast)))
+    let lag = lag_tracker(&state.db_pool).await?;
+
+    Ok(ws.max_message_size(INBOUND_CODEC_LIMIT).on_upgrade(move |socket| handle_chat_stream(socket, swarm_id, state.db_pool, lag, state.broadcast)))
 }
 
 /// Handle the chat stream WebSocket connection
 async fn handle_chat_stream(
     socket: WebSocket,
     swarm_id: Uuid,
+    db_pool: SqlitePool,
+    mut lag: LagTracker,
     broadcast: Arc<BroadcastManager>,
 ) {
     let (mut ws_sender, mut ws_receiver) = socket.split();
@@ -268,6 +870,7 @@ async fn handle_chat_stream(
     // Send connected message
     let connected = WsMessage::Connected {
         message: format!("Connected to chat for swarm {}", swarm_id),
+        level: None,
     };
     match serde_json::to_string(&connected) {
         Ok(json) => {
@@ -301,16 +904,42 @@ async fn handle_chat_stream(
             msg = ws_receiver.next() => {
                 match msg {
                     Some(Ok(Message::Text(text))) => {
+                        if reject_oversized_frame(&mut ws_sender, text.len()).await {
+                            break;
+                        }
                         // Handle client messages
                         if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
                             match ws_msg {
                                 WsMessage::Pong { .. } => {
                                     tracing::trace!(swarm_id = %swarm_id, "Received pong");
                                 }
+                                WsMessage::Refresh => {
+                                    let sent = match chat_snapshot(&db_pool, swarm_id).await {
+                                        Ok(messages) => send_frames(&mut ws_sender, &messages).await,
+                                        Err(e) => {
+                                            tracing::warn!(swarm_id = %swarm_id, error = %e, "Failed to load chat snapshot");
+                                            let error = WsMessage::Error {
+                                                message: "Failed to load recent chat".to_string(),
+                                                detail: None,
+                                            };
+                                            send_frames(&mut ws_sender, &[error]).await
+                                        }
+                                    };
+                                    if !sent {
+                                        break;
+                                    }
+                                }
                                 _ => {}
                             }
                         }
                     }
+                    Some(Ok(Message::Binary(data))) => {
+                        // Binary frames carry nothing for us; only the size is checked
+                        let refused = reject_oversized_frame(&mut ws_sender, data.len()).await;
+                        if refused {
+                            break;
+                        }
+                    }
                     Some(Ok(Message::Ping(data))) => {
                         if ws_sender.send(Message::Pong(data)).await.is_err() {
                             break;
@@ -328,31 +957,21 @@ async fn handle_chat_stream(
                     None => {
                         break;
                     }
-                    _ => {}
                 }
             }
 
             // Handle broadcast chat messages
             chat_result = chat_receiver.recv() => {
                 match chat_result {
-                    Ok(chat_msg) => {
-                        // Send the chat message as JSON directly
-                        match serde_json::to_string(&chat_msg) {
-                            Ok(json) => {
-                                if ws_sender.send(Message::Text(json.into())).await.is_err() {
-                                    break;
-                                }
-                            }
-                            Err(e) => {
-                                tracing::warn!(swarm_id = %swarm_id, error = %e, "Failed to serialize chat message");
-                            }
+                    Ok(event) => {
+                        // Complete messages go out as message frames, partials as chat_delta
+                        if !send_frames(&mut ws_sender, &[WsMessage::from(event)]).await {
+                            break;
                         }
                     }
                     Err(RecvError::Lagged(n)) => {
                         tracing::warn!(swarm_id = %swarm_id, skipped = n, "Chat receiver lagged");
-                        let error = WsMessage::Error {
-                            message: format!("Missed {} chat messages due to lag", n),
-                        };
+                        let error = WsMessage::lag("chat messages", n);
                         match serde_json::to_string(&error) {
                             Ok(json) => {
                                 let _ = ws_sender.send(Message::Text(json.into())).await;
@@ -361,9 +980,16 @@ async fn handle_chat_stream(
                                 tracing::warn!(swarm_id = %swarm_id, error = %e, "Failed to serialize error message");
                             }
                         }
+                        if lag.record(Instant::now()) {
+                            tracing::warn!(swarm_id = %swarm_id, "Closing chat stream after persistent lag");
+                            let _ = ws_sender.send(Message::Close(Some(persistent_lag_close()))).await;
+                            break;
+                        }
                     }
                     Err(RecvError::Closed) => {
+                        // Chat channels are only closed under subscribers when the swarm is deleted
                         tracing::debug!(swarm_id = %swarm_id, "Chat broadcast channel closed");
+                        let _ = ws_sender.send(Message::Close(Some(StreamEnd::SwarmDeleted.close_frame()))).await;
                         break;
                     }
                 }
@@ -399,19 +1025,49 @@ pub async fn pool_ws(
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_pool_stream(socket, state.broadcast))
+    ws.max_message_size(INBOUND_CODEC_LIMIT).on_upgrade(move |socket| handle_pool_stream(socket, None, state.db_pool, state.broadcast))
+}
+
+/// WebSocket handler for pool status streaming scoped to a single swarm
+pub async fn swarm_pool_ws(
+    ws: WebSocketUpgrade,
+    Path(swarm_id): Path<Uuid>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, axum::response::Response> {
+    // IDOR protection: verify swarm exists before allowing WebSocket connection
+    let _swarm = Swarm::find_by_id(&state.db_pool, swarm_id)
+        .await
+        .map_err(|e| {
+            tracing::warn!(swarm_id = %swarm_id, error = %e, "Database error checking swarm for pool WebSocket");
+            (axum::http::StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+        })?
+        .ok_or_else(|| {
+            tracing::warn!(swarm_id = %swarm_id, "Swarm not found for pool WebSocket");
+            (axum::http::StatusCode::NOT_FOUND, "Swarm not found").into_response()
+        })?;
+
+    Ok(ws.max_message_size(INBOUND_CODEC_LIMIT).on_upgrade(move |socket| handle_pool_stream(socket, Some(swarm_id), state.db_pool, state.broadcast)))
 }
 
 /// Handle the pool status stream WebSocket connection
+///
+/// When `swarm_filter` is set, only updates for sandboxes belonging to that
+/// swarm are forwarded to the client.
 async fn handle_pool_stream(
     socket: WebSocket,
+    swarm_filter: Option<Uuid>,
+    db_pool: SqlitePool,
     broadcast: Arc<BroadcastManager>,
 ) {
     let (mut ws_sender, mut ws_receiver) = socket.split();
 
     // Send connected message
     let connected = WsMessage::Connected {
-        message: "Connected to pool status stream".to_string(),
+        message: match swarm_filter {
+            Some(swarm_id) => format!("Connected to pool status stream for swarm {}", swarm_id),
+            None => "Connected to pool status stream".to_string(),
+        },
+        level: None,
     };
     match serde_json::to_string(&connected) {
         Ok(json) => {
@@ -445,15 +1101,41 @@ async fn handle_pool_stream(
             msg = ws_receiver.next() => {
                 match msg {
                     Some(Ok(Message::Text(text))) => {
+                        if reject_oversized_frame(&mut ws_sender, text.len()).await {
+                            break;
+                        }
                         if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
                             match ws_msg {
                                 WsMessage::Pong { .. } => {
                                     tracing::trace!("Received pong from pool client");
                                 }
+                                WsMessage::Refresh => {
+                                    let sent = match pool_snapshot(&db_pool, swarm_filter).await {
+                                        Ok(updates) => send_frames(&mut ws_sender, &updates).await,
+                                        Err(e) => {
+                                            tracing::warn!(error = %e, "Failed to load pool snapshot");
+                                            let error = WsMessage::Error {
+                                                message: "Failed to load pool status".to_string(),
+                                                detail: None,
+                                            };
+                                            send_frames(&mut ws_sender, &[error]).await
+                                        }
+                                    };
+                                    if !sent {
+                                        break;
+                                    }
+                                }
                                 _ => {}
                             }
                         }
                     }
+                    Some(Ok(Message::Binary(data))) => {
+                        // Binary frames carry nothing for us; only the size is checked
+                        let refused = reject_oversized_frame(&mut ws_sender, data.len()).await;
+                        if refused {
+                            break;
+                        }
+                    }
                     Some(Ok(Message::Ping(data))) => {
                         if ws_sender.send(Message::Pong(data)).await.is_err() {
                             break;
@@ -471,7 +1153,6 @@ async fn handle_pool_stream(
                     None => {
                         break;
                     }
-                    _ => {}
                 }
             }
 
@@ -479,23 +1160,19 @@ async fn handle_pool_stream(
             pool_result = pool_receiver.recv() => {
                 match pool_result {
                     Ok(pool_update) => {
-                        // Send the pool update as JSON directly
-                        match serde_json::to_string(&pool_update) {
-                            Ok(json) => {
-                                if ws_sender.send(Message::Text(json.into())).await.is_err() {
-                                    break;
-                                }
-                            }
-                            Err(e) => {
-                                tracing::warn!(error = %e, "Failed to serialize pool update");
-                            }
+                        if let Some(swarm_id) = swarm_filter
+                            && !pool_update.belongs_to_swarm(swarm_id)
+                        {
+                            continue;
+                        }
+
+                        if !send_frames(&mut ws_sender, &[WsMessage::PoolUpdate(pool_update)]).await {
+                            break;
                         }
                     }
                     Err(RecvError::Lagged(n)) => {
                         tracing::warn!(skipped = n, "Pool receiver lagged");
-                        let error = WsMessage::Error {
-                            message: format!("Missed {} pool updates due to lag", n),
-                        };
+                        let error = WsMessage::lag("pool updates", n);
                         match serde_json::to_string(&error) {
                             Ok(json) => {
                                 let _ = ws_sender.send(Message::Text(json.into())).await;
@@ -506,7 +1183,9 @@ async fn handle_pool_stream(
                         }
                     }
                     Err(RecvError::Closed) => {
+                        // The pool broadcaster lives as long as the server
                         tracing::debug!("Pool broadcast channel closed");
+                        let _ = ws_sender.send(Message::Close(Some(StreamEnd::ServerShutdown.close_frame()))).await;
                         break;
                     }
                 }
@@ -539,6 +1218,8 @@ async fn handle_pool_stream(
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/ws/swarms/{swarm_id}/tasks/{task_id}/logs", get(task_logs_ws))
+        .route("/ws/swarms/{swarm_id}/logs", get(swarm_logs_ws))
         .route("/ws/swarms/{swarm_id}/chat", get(chat_ws))
+        .route("/ws/swarms/{swarm_id}/pool", get(swarm_pool_ws))
         .route("/ws/pool", get(pool_ws))
 }
@@ -3,11 +3,14 @@
 //! Provides broadcast channels for distributing logs and chat messages
 //! to WebSocket subscribers in real-time.
 
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
 use tokio::sync::{broadcast, RwLock};
 use ts_rs::TS;
 use uuid::Uuid;
@@ -15,13 +18,43 @@ use uuid::Uuid;
 /// Default channel capacity for broadcast channels
 const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
 
-/// Log entry sent via WebSocket
+/// Default number of recent log messages replayed to new subscribers
+const DEFAULT_REPLAY_CAPACITY: usize = 200;
+
+/// Component a log entry came from
+///
+/// Serialized as the same snake_case strings clients have always matched on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, EnumString, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum LogSource {
+    Executor,
+    Trigger,
+    Sandbox,
+    /// The swarm's setup script
+    Setup,
+    Git,
+    System,
+}
+
+/// Severity of a log entry, ordered from least to most severe
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS, EnumString, Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    #[strum(to_string = "warn", serialize = "warning")]
+    Warn,
+    Error,
+}
+
+/// Log entry sent via WebSocket (as a `log` frame)
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "snake_case")]
 pub struct LogEntry {
-    /// Type of message (always "log" for log entries)
-    #[serde(rename = "type")]
-    pub msg_type: String,
     /// Log content
     pub content: String,
     /// ISO 8601 timestamp
@@ -29,23 +62,32 @@ pub struct LogEntry {
     /// Log level (info, warn, error, debug)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub level: Option<String>,
-    /// Source of the log (executor, trigger, sandbox, etc.)
+    /// Component the log came from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<LogSource>,
+    /// Number of held-back repeats of the previous line this entry stands for
+    /// (only set on compaction summaries)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub source: Option<String>,
+    pub repeat_count: Option<u32>,
 }
 
 impl LogEntry {
     /// Create a new log entry
     pub fn new(content: impl Into<String>) -> Self {
         Self {
-            msg_type: "log".to_string(),
             content: content.into(),
             timestamp: Utc::now().to_rfc3339(),
             level: None,
             source: None,
+            repeat_count: None,
         }
     }
 
+    /// Severity of the entry, if it has a recognised level
+    pub fn log_level(&self) -> Option<LogLevel> {
+        self.level.as_deref().and_then(|level| level.parse().ok())
+    }
+
     /// Set the log level
     pub fn with_level(mut self, level: impl Into<String>) -> Self {
         self.level = Some(level.into());
@@ -53,8 +95,8 @@ impl LogEntry {
     }
 
     /// Set the log source
-    pub fn with_source(mut self, source: impl Into<String>) -> Self {
-        self.source = Some(source.into());
+    pub fn with_source(mut self, source: LogSource) -> Self {
+        self.source = Some(source);
         self
     }
 
@@ -79,13 +121,10 @@ impl LogEntry {
     }
 }
 
-/// Log end message sent when task execution completes
+/// Log end message sent when task execution completes (as a `log_end` frame)
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "snake_case")]
 pub struct LogEnd {
-    /// Type of message (always "log_end")
-    #[serde(rename = "type")]
-    pub msg_type: String,
     /// Exit code of the task
     pub exit_code: i32,
     /// Final summary message
@@ -99,7 +138,6 @@ impl LogEnd {
     /// Create a new log end message
     pub fn new(exit_code: i32) -> Self {
         Self {
-            msg_type: "log_end".to_string(),
             exit_code,
             summary: None,
             timestamp: Utc::now().to_rfc3339(),
@@ -131,6 +169,30 @@ pub enum LogMessage {
     End(LogEnd),
 }
 
+impl LogMessage {
+    /// Whether the message passes a source filter
+    ///
+    /// Log ends always pass; entries must carry the requested source.
+    pub fn matches_source(&self, source: Option<LogSource>) -> bool {
+        match (self, source) {
+            (_, None) | (LogMessage::End(_), _) => true,
+            (LogMessage::Entry(entry), Some(source)) => entry.source == Some(source),
+        }
+    }
+
+    /// Whether the message passes a minimum level filter
+    ///
+    /// Log ends and entries without a recognised level always pass.
+    pub fn meets_level(&self, min_level: Option<LogLevel>) -> bool {
+        match (self, min_level) {
+            (LogMessage::Entry(entry), Some(min_level)) => {
+                entry.log_level().is_none_or(|level| level >= min_level)
+            }
+            _ => true,
+        }
+    }
+}
+
 impl From<LogEntry> for LogMessage {
     fn from(entry: LogEntry) -> Self {
         LogMessage::Entry(entry)
@@ -143,13 +205,10 @@ impl From<LogEnd> for LogMessage {
     }
 }
 
-/// Chat message sent via WebSocket
+/// Chat message sent via WebSocket (as a `message` frame)
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "snake_case")]
 pub struct ChatBroadcastMessage {
-    /// Type of message (always "message")
-    #[serde(rename = "type")]
-    pub msg_type: String,
     /// Message data
     pub data: ChatMessageData,
 }
@@ -171,9 +230,131 @@ pub struct ChatMessageData {
 impl ChatBroadcastMessage {
     /// Create a new chat broadcast message
     pub fn new(data: ChatMessageData) -> Self {
+        Self { data }
+    }
+}
+
+/// Partial text of a chat message that is still being generated
+///
+/// Deltas for the same `message_id` are appended in order; the complete text is
+/// persisted and broadcast as a regular message under the same id once done.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ChatDelta {
+    pub swarm_id: Uuid,
+    pub message_id: Uuid,
+    pub delta: String,
+}
+
+/// Union type for chat broadcast messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ChatEvent {
+    Message(ChatBroadcastMessage),
+    Delta(ChatDelta),
+}
+
+impl From<ChatBroadcastMessage> for ChatEvent {
+    fn from(message: ChatBroadcastMessage) -> Self {
+        ChatEvent::Message(message)
+    }
+}
+
+impl From<ChatDelta> for ChatEvent {
+    fn from(delta: ChatDelta) -> Self {
+        ChatEvent::Delta(delta)
+    }
+}
+
+/// Tracks a run of identical consecutive log lines for a task
+#[derive(Debug)]
+struct RepeatRun {
+    entry: LogEntry,
+    count: u32,
+}
+
+impl RepeatRun {
+    /// Summary entry for the run, if any lines were suppressed
+    ///
+    /// The first line of the run was sent as is, so the summary counts only
+    /// the repeats held back after it.
+    fn into_summary(self) -> Option<LogEntry> {
+        let suppressed = self.count.saturating_sub(1);
+        if suppressed == 0 {
+            return None;
+        }
+
+        Some(LogEntry {
+            content: format!("{} (repeated {}×)", self.entry.content, suppressed),
+            timestamp: Utc::now().to_rfc3339(),
+            repeat_count: Some(suppressed),
+            ..self.entry
+        })
+    }
+}
+
+/// A task's log channel and its most recent messages
+#[derive(Debug)]
+struct TaskLogChannel {
+    sender: broadcast::Sender<LogMessage>,
+    /// Replayed to new subscribers, oldest first
+    history: Mutex<VecDeque<LogMessage>>,
+}
+
+impl TaskLogChannel {
+    fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
         Self {
-            msg_type: "message".to_string(),
-            data,
+            sender,
+            history: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Record `message` in the history and send it to current subscribers
+    ///
+    /// The history lock is held across the send so a concurrent subscriber
+    /// gets the message either replayed or live, never both.
+    fn send(&self, message: LogMessage, replay_capacity: usize) -> usize {
+        let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        if replay_capacity > 0 {
+            if history.len() >= replay_capacity {
+                history.pop_front();
+            }
+            history.push_back(message.clone());
+        }
+        self.sender.send(message).unwrap_or(0)
+    }
+
+    fn subscribe(&self) -> LogSubscription {
+        let history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        LogSubscription {
+            history: history.clone(),
+            receiver: self.sender.subscribe(),
+        }
+    }
+}
+
+/// A subscriber's view of a task's logs: recent history, then live messages
+#[derive(Debug)]
+pub struct LogSubscription {
+    history: VecDeque<LogMessage>,
+    receiver: broadcast::Receiver<LogMessage>,
+}
+
+impl LogSubscription {
+    /// Next message, from the replayed history first
+    ///
+    /// Cancel safe, like `broadcast::Receiver::recv`.
+    pub async fn recv(&mut self) -> Result<LogMessage, RecvError> {
+        match self.history.pop_front() {
+            Some(message) => Ok(message),
+            None => self.receiver.recv().await,
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Result<LogMessage, TryRecvError> {
+        match self.history.pop_front() {
+            Some(message) => Ok(message),
+            None => self.receiver.try_recv(),
         }
     }
 }
@@ -181,13 +362,20 @@ impl ChatBroadcastMessage {
 /// Broadcaster for task logs
 ///
 /// Manages broadcast channels for each task, allowing multiple WebSocket
-/// connections to subscribe to log streams.
+/// connections to subscribe to log streams. The last messages of each task
+/// are kept so a subscriber joining mid-run still sees recent output.
 #[derive(Debug)]
 pub struct LogBroadcaster {
-    /// Map of task_id -> broadcast sender
-    channels: Arc<RwLock<HashMap<Uuid, broadcast::Sender<LogMessage>>>>,
+    /// Map of task_id -> channel and recent history
+    channels: Arc<RwLock<HashMap<Uuid, TaskLogChannel>>>,
     /// Channel capacity
     capacity: usize,
+    /// Messages kept per task for replay; 0 disables replay
+    replay_capacity: usize,
+    /// Collapse consecutive identical lines into a single summary entry
+    dedup_consecutive: AtomicBool,
+    /// Map of task_id -> current run of identical lines (dedup only)
+    repeats: Arc<RwLock<HashMap<Uuid, RepeatRun>>>,
 }
 
 impl Default for LogBroadcaster {
@@ -199,80 +387,157 @@ impl Default for LogBroadcaster {
 impl LogBroadcaster {
     /// Create a new LogBroadcaster
     pub fn new() -> Self {
-        Self {
-            channels: Arc::new(RwLock::new(HashMap::new())),
-            capacity: DEFAULT_CHANNEL_CAPACITY,
-        }
+        Self::with_capacity(DEFAULT_CHANNEL_CAPACITY)
     }
 
     /// Create with custom capacity
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacities(capacity, DEFAULT_REPLAY_CAPACITY)
+    }
+
+    /// Create with custom channel and replay capacities
+    ///
+    /// The last `replay_capacity` messages of each task are replayed to new
+    /// subscribers; 0 disables replay.
+    pub fn with_capacities(capacity: usize, replay_capacity: usize) -> Self {
         Self {
             channels: Arc::new(RwLock::new(HashMap::new())),
             capacity,
+            replay_capacity,
+            dedup_consecutive: AtomicBool::new(false),
+            repeats: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// Enable or disable compaction of consecutive identical log lines
+    ///
+    /// When enabled, repeats of the previous line are held back and replaced
+    /// by a single "(repeated N×)" entry once a different line (or the log
+    /// end) arrives. Off by default.
+    pub fn with_dedup(self, enabled: bool) -> Self {
+        self.set_dedup(enabled);
+        self
+    }
+
+    /// Turn compaction of repeated lines on or off, e.g. when the config changes
+    ///
+    /// Runs held back when it is turned off are still summarized at the log end.
+    pub fn set_dedup(&self, enabled: bool) {
+        self.dedup_consecutive.store(enabled, Ordering::Relaxed);
+    }
+
     /// Subscribe to logs for a specific task
     ///
-    /// Returns a receiver that will receive all log messages for the task.
-    /// Creates the channel if it doesn't exist.
-    pub async fn subscribe_logs(&self, task_id: Uuid) -> broadcast::Receiver<LogMessage> {
+    /// The subscription first yields the task's buffered history, then every
+    /// message published after this call. Creates the channel if it doesn't exist.
+    pub async fn subscribe_logs(&self, task_id: Uuid) -> LogSubscription {
         let mut channels = self.channels.write().await;
+        channels
+            .entry(task_id)
+            .or_insert_with(|| TaskLogChannel::new(self.capacity))
+            .subscribe()
+    }
 
-        if let Some(sender) = channels.get(&task_id) {
-            sender.subscribe()
-        } else {
-            let (sender, receiver) = broadcast::channel(self.capacity);
-            channels.insert(task_id, sender);
-            receiver
+    /// Send to a task's subscribers and record the message for replay
+    ///
+    /// Without replay, nothing is kept for tasks nobody is subscribed to.
+    async fn send(&self, task_id: Uuid, message: LogMessage) -> usize {
+        {
+            let channels = self.channels.read().await;
+            if let Some(channel) = channels.get(&task_id) {
+                return channel.send(message, self.replay_capacity);
+            }
+        }
+        if self.replay_capacity == 0 {
+            return 0;
         }
+
+        let mut channels = self.channels.write().await;
+        channels
+            .entry(task_id)
+            .or_insert_with(|| TaskLogChannel::new(self.capacity))
+            .send(message, self.replay_capacity)
     }
 
     /// Publish a log entry to all subscribers
     ///
     /// Returns the number of receivers that received the message.
-    /// Returns 0 if no channel exists for the task (no subscribers).
     pub async fn publish_log(&self, task_id: Uuid, entry: LogEntry) -> usize {
-        let channels = self.channels.read().await;
+        if self.dedup_consecutive.load(Ordering::Relaxed) {
+            return self.publish_log_compacted(task_id, entry).await;
+        }
 
-        if let Some(sender) = channels.get(&task_id) {
-            sender.send(LogMessage::Entry(entry)).unwrap_or(0)
-        } else {
-            0
+        self.send(task_id, LogMessage::Entry(entry)).await
+    }
+
+    /// Publish a log entry, holding back repeats of the previous line
+    async fn publish_log_compacted(&self, task_id: Uuid, entry: LogEntry) -> usize {
+        let summary = {
+            let mut repeats = self.repeats.write().await;
+
+            if let Some(run) = repeats.get_mut(&task_id)
+                && run.entry.content == entry.content
+            {
+                run.count += 1;
+                return 0;
+            }
+
+            let previous = repeats.insert(
+                task_id,
+                RepeatRun {
+                    entry: entry.clone(),
+                    count: 1,
+                },
+            );
+            previous.and_then(RepeatRun::into_summary)
+        };
+
+        if let Some(summary) = summary {
+            self.send(task_id, LogMessage::Entry(summary)).await;
+        }
+        self.send(task_id, LogMessage::Entry(entry)).await
+    }
+
+    /// Emit the summary for any pending run of repeated lines
+    async fn flush_repeats(&self, task_id: Uuid) {
+        let summary = self
+            .repeats
+            .write()
+            .await
+            .remove(&task_id)
+            .and_then(RepeatRun::into_summary);
+
+        if let Some(summary) = summary {
+            self.send(task_id, LogMessage::Entry(summary)).await;
         }
     }
 
     /// Publish a log end message to all subscribers
     ///
-    /// This should be called when task execution completes.
+    /// This should be called when task execution completes. With nobody
+    /// subscribed, the task's history is dropped: finished tasks' logs are
+    /// read from storage instead.
     pub async fn publish_log_end(&self, task_id: Uuid, end: LogEnd) -> usize {
-        let channels = self.channels.read().await;
+        self.flush_repeats(task_id).await;
 
-        if let Some(sender) = channels.get(&task_id) {
-            sender.send(LogMessage::End(end)).unwrap_or(0)
-        } else {
-            0
+        let received = self.send(task_id, LogMessage::End(end)).await;
+        if received == 0 {
+            self.cleanup_channel(task_id).await;
         }
+        received
     }
 
     /// Publish a raw log message
     pub async fn publish(&self, task_id: Uuid, message: LogMessage) -> usize {
-        let channels = self.channels.read().await;
-
-        if let Some(sender) = channels.get(&task_id) {
-            sender.send(message).unwrap_or(0)
-        } else {
-            0
-        }
+        self.send(task_id, message).await
     }
 
     /// Check if a task has any active subscribers
     pub async fn has_subscribers(&self, task_id: Uuid) -> bool {
         let channels = self.channels.read().await;
 
-        if let Some(sender) = channels.get(&task_id) {
-            sender.receiver_count() > 0
+        if let Some(channel) = channels.get(&task_id) {
+            channel.sender.receiver_count() > 0
         } else {
             false
         }
@@ -284,19 +549,20 @@ impl LogBroadcaster {
 
         channels
             .get(&task_id)
-            .map(|sender| sender.receiver_count())
+            .map(|channel| channel.sender.receiver_count())
             .unwrap_or(0)
     }
 
-    /// Remove a channel when task is complete and no subscribers remain
+    /// Remove a channel and its history when no subscribers remain
     ///
     /// This helps prevent memory leaks from accumulating channels.
     pub async fn cleanup_channel(&self, task_id: Uuid) {
         let mut channels = self.channels.write().await;
 
-        if let Some(sender) = channels.get(&task_id) {
-            if sender.receiver_count() == 0 {
+        if let Some(channel) = channels.get(&task_id) {
+            if channel.sender.receiver_count() == 0 {
                 channels.remove(&task_id);
+                self.repeats.write().await.remove(&task_id);
                 tracing::debug!(task_id = %task_id, "Cleaned up log channel");
             }
         }
@@ -308,12 +574,14 @@ impl LogBroadcaster {
 
         let to_remove: Vec<Uuid> = channels
             .iter()
-            .filter(|(_, sender)| sender.receiver_count() == 0)
+            .filter(|(_, channel)| channel.sender.receiver_count() == 0)
             .map(|(id, _)| *id)
             .collect();
 
+        let mut repeats = self.repeats.write().await;
         for task_id in to_remove {
             channels.remove(&task_id);
+            repeats.remove(&task_id);
         }
 
         tracing::debug!(remaining = channels.len(), "Cleaned up log channels");
@@ -332,7 +600,7 @@ impl LogBroadcaster {
 #[derive(Debug)]
 pub struct ChatBroadcaster {
     /// Map of swarm_id -> broadcast sender
-    channels: Arc<RwLock<HashMap<Uuid, broadcast::Sender<ChatBroadcastMessage>>>>,
+    channels: Arc<RwLock<HashMap<Uuid, broadcast::Sender<ChatEvent>>>>,
     /// Channel capacity
     capacity: usize,
 }
@@ -364,7 +632,7 @@ impl ChatBroadcaster {
     ///
     /// Returns a receiver that will receive all chat messages for the swarm.
     /// Creates the channel if it doesn't exist.
-    pub async fn subscribe_chat(&self, swarm_id: Uuid) -> broadcast::Receiver<ChatBroadcastMessage> {
+    pub async fn subscribe_chat(&self, swarm_id: Uuid) -> broadcast::Receiver<ChatEvent> {
         let mut channels = self.channels.write().await;
 
         if let Some(sender) = channels.get(&swarm_id) {
@@ -380,13 +648,7 @@ impl ChatBroadcaster {
     ///
     /// Returns the number of receivers that received the message.
     pub async fn publish_message(&self, swarm_id: Uuid, message: ChatBroadcastMessage) -> usize {
-        let channels = self.channels.read().await;
-
-        if let Some(sender) = channels.get(&swarm_id) {
-            sender.send(message).unwrap_or(0)
-        } else {
-            0
-        }
+        self.publish_event(swarm_id, message.into()).await
     }
 
     /// Publish chat message data directly
@@ -395,6 +657,34 @@ impl ChatBroadcaster {
             .await
     }
 
+    /// Publish a chunk of a message that is still being generated
+    ///
+    /// Partials are not persisted; the complete message must still be stored
+    /// and published under the same `message_id` when generation finishes.
+    pub async fn publish_partial(
+        &self,
+        swarm_id: Uuid,
+        message_id: Uuid,
+        delta: impl Into<String>,
+    ) -> usize {
+        let delta = ChatDelta {
+            swarm_id,
+            message_id,
+            delta: delta.into(),
+        };
+        self.publish_event(swarm_id, delta.into()).await
+    }
+
+    async fn publish_event(&self, swarm_id: Uuid, event: ChatEvent) -> usize {
+        let channels = self.channels.read().await;
+
+        if let Some(sender) = channels.get(&swarm_id) {
+            sender.send(event).unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
     /// Check if a swarm has any active subscribers
     pub async fn has_subscribers(&self, swarm_id: Uuid) -> bool {
         let channels = self.channels.read().await;
@@ -416,6 +706,15 @@ impl ChatBroadcaster {
             .unwrap_or(0)
     }
 
+    /// Remove a swarm's channel even if it has subscribers
+    ///
+    /// Subscribers see the channel close; used once the swarm is deleted.
+    pub async fn close_channel(&self, swarm_id: Uuid) {
+        if self.channels.write().await.remove(&swarm_id).is_some() {
+            tracing::debug!(swarm_id = %swarm_id, "Closed chat channel");
+        }
+    }
+
     /// Remove a channel when no subscribers remain
     pub async fn cleanup_channel(&self, swarm_id: Uuid) {
         let mut channels = self.channels.write().await;
@@ -451,13 +750,10 @@ impl ChatBroadcaster {
     }
 }
 
-/// Pool status update sent via WebSocket
+/// Pool status update sent via WebSocket (as a `pool_update` frame)
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "snake_case")]
 pub struct PoolStatusUpdate {
-    /// Type of message (always "pool_update")
-    #[serde(rename = "type")]
-    pub msg_type: String,
     /// Sandbox ID
     pub sandbox_id: String,
     /// New status
@@ -465,6 +761,9 @@ pub struct PoolStatusUpdate {
     /// Associated task ID (if any)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub task_id: Option<String>,
+    /// Swarm the sandbox belongs to (if any)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swarm_id: Option<String>,
     /// ISO 8601 timestamp
     pub timestamp: String,
 }
@@ -473,10 +772,10 @@ impl PoolStatusUpdate {
     /// Create a new pool status update
     pub fn new(sandbox_id: impl Into<String>, status: impl Into<String>) -> Self {
         Self {
-            msg_type: "pool_update".to_string(),
             sandbox_id: sandbox_id.into(),
             status: status.into(),
             task_id: None,
+            swarm_id: None,
             timestamp: Utc::now().to_rfc3339(),
         }
     }
@@ -486,6 +785,19 @@ impl PoolStatusUpdate {
         self.task_id = Some(task_id.into());
         self
     }
+
+    /// Add the owning swarm ID
+    pub fn with_swarm(mut self, swarm_id: impl Into<String>) -> Self {
+        self.swarm_id = Some(swarm_id.into());
+        self
+    }
+
+    /// Check whether this update belongs to the given swarm
+    pub fn belongs_to_swarm(&self, swarm_id: Uuid) -> bool {
+        self.swarm_id
+            .as_deref()
+            .is_some_and(|id| id == swarm_id.to_string())
+    }
 }
 
 /// Broadcaster for pool status updates
@@ -617,6 +929,82 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_log_broadcaster_dedup_off_by_default() {
+        let broadcaster = LogBroadcaster::new();
+        let task_id = Uuid::new_v4();
+        let mut receiver = broadcaster.subscribe_logs(task_id).await;
+
+        for _ in 0..3 {
+            broadcaster.publish_log(task_id, LogEntry::info("Downloading...")).await;
+        }
+
+        for _ in 0..3 {
+            match receiver.recv().await.unwrap() {
+                LogMessage::Entry(e) => {
+                    assert_eq!(e.content, "Downloading...");
+                    assert_eq!(e.repeat_count, None);
+                }
+                _ => panic!("Expected LogEntry"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_log_broadcaster_dedup_collapses_repeats() {
+        let broadcaster = LogBroadcaster::new().with_dedup(true);
+        let task_id = Uuid::new_v4();
+        let mut receiver = broadcaster.subscribe_logs(task_id).await;
+
+        for _ in 0..12 {
+            broadcaster.publish_log(task_id, LogEntry::info("Downloading...")).await;
+        }
+        broadcaster.publish_log(task_id, LogEntry::info("Done")).await;
+        broadcaster.publish_log_end(task_id, LogEnd::success()).await;
+
+        let mut entries = Vec::new();
+        while let Ok(LogMessage::Entry(e)) = receiver.try_recv() {
+            entries.push(e);
+        }
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].content, "Downloading...");
+        assert_eq!(entries[1].content, "Downloading... (repeated 11×)");
+        assert_eq!(entries[1].repeat_count, Some(11));
+        assert_eq!(entries[1].level, Some("info".to_string()));
+        assert_eq!(entries[2].content, "Done");
+        assert_eq!(entries[2].repeat_count, None);
+    }
+
+    #[tokio::test]
+    async fn test_log_broadcaster_replays_recent_history() {
+        let broadcaster = LogBroadcaster::with_capacities(16, 3);
+        let task_id = Uuid::new_v4();
+
+        // Published before anyone subscribed; only the last three are kept
+        for i in 0..5 {
+            broadcaster.publish_log(task_id, LogEntry::info(format!("line {}", i))).await;
+        }
+
+        let mut receiver = broadcaster.subscribe_logs(task_id).await;
+        broadcaster.publish_log(task_id, LogEntry::info("live")).await;
+
+        let mut contents = Vec::new();
+        while let Ok(LogMessage::Entry(e)) = receiver.try_recv() {
+            contents.push(e.content);
+        }
+        assert_eq!(contents, ["line 2", "line 3", "line 4", "live"]);
+
+        // The end of an unwatched task drops its history
+        drop(receiver);
+        broadcaster.publish_log_end(task_id, LogEnd::success()).await;
+        assert_eq!(broadcaster.channel_count().await, 0);
+
+        let unbuffered = LogBroadcaster::with_capacities(16, 0);
+        assert_eq!(unbuffered.publish_log(task_id, LogEntry::info("dropped")).await, 0);
+        assert_eq!(unbuffered.channel_count().await, 0);
+    }
+
     #[tokio::test]
     async fn test_chat_broadcaster_subscribe_publish() {
         let broadcaster = ChatBroadcaster::new();
@@ -638,8 +1026,36 @@ mod tests {
         assert_eq!(count, 1);
 
         // Receive the message
-        let received = receiver.recv().await.unwrap();
-        assert_eq!(received.data.message, "Hello!");
+        match receiver.recv().await.unwrap() {
+            ChatEvent::Message(received) => assert_eq!(received.data.message, "Hello!"),
+            other => panic!("expected message, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_broadcaster_publish_partial() {
+        let broadcaster = ChatBroadcaster::new();
+        let swarm_id = Uuid::new_v4();
+        let message_id = Uuid::new_v4();
+
+        // No subscribers yet: nothing is delivered
+        assert_eq!(broadcaster.publish_partial(swarm_id, message_id, "ignored").await, 0);
+
+        let mut receiver = broadcaster.subscribe_chat(swarm_id).await;
+        broadcaster.publish_partial(swarm_id, message_id, "Hel").await;
+        broadcaster.publish_partial(swarm_id, message_id, "lo").await;
+
+        let mut text = String::new();
+        for _ in 0..2 {
+            match receiver.recv().await.unwrap() {
+                ChatEvent::Delta(delta) => {
+                    assert_eq!(delta.message_id, message_id);
+                    text.push_str(&delta.delta);
+                }
+                other => panic!("expected delta, got {:?}", other),
+            }
+        }
+        assert_eq!(text, "Hello");
     }
 
     #[tokio::test]
@@ -675,4 +1091,55 @@ mod tests {
         assert_eq!(received.status, "running");
         assert_eq!(received.task_id, Some("task-1".to_string()));
     }
+
+    #[test]
+    fn test_pool_update_belongs_to_swarm() {
+        let swarm_id = Uuid::new_v4();
+
+        let scoped = PoolStatusUpdate::new("sandbox-1", "idle").with_swarm(swarm_id.to_string());
+        assert!(scoped.belongs_to_swarm(swarm_id));
+        assert!(!scoped.belongs_to_swarm(Uuid::new_v4()));
+
+        let unscoped = PoolStatusUpdate::new("sandbox-2", "idle");
+        assert!(!unscoped.belongs_to_swarm(swarm_id));
+    }
+
+    #[test]
+    fn test_log_message_matches_source() {
+        let setup: LogMessage = LogEntry::info("npm install").with_source(LogSource::Setup).into();
+        let untagged: LogMessage = LogEntry::info("plain").into();
+        let end: LogMessage = LogEnd::success().into();
+
+        assert!(setup.matches_source(None));
+        assert!(setup.matches_source(Some(LogSource::Setup)));
+        assert!(!setup.matches_source(Some(LogSource::Executor)));
+        assert!(!untagged.matches_source(Some(LogSource::Setup)));
+        assert!(end.matches_source(Some(LogSource::Git)));
+
+        assert_eq!("sandbox".parse::<LogSource>().unwrap(), LogSource::Sandbox);
+        assert_eq!(serde_json::to_value(LogSource::System).unwrap(), "system");
+    }
+
+    #[test]
+    fn test_log_message_meets_level() {
+        let debug: LogMessage = LogEntry::debug("polling").into();
+        let warn: LogMessage = LogEntry::warn("slow response").into();
+        let error: LogMessage = LogEntry::error("crashed").into();
+        let untagged: LogMessage = LogEntry::new("plain").into();
+        let unknown: LogMessage = LogEntry::new("odd").with_level("verbose").into();
+        let end: LogMessage = LogEnd::failure(1).into();
+
+        assert!(debug.meets_level(None));
+        assert!(!debug.meets_level(Some(LogLevel::Info)));
+        assert!(warn.meets_level(Some(LogLevel::Warn)));
+        assert!(!warn.meets_level(Some(LogLevel::Error)));
+        assert!(error.meets_level(Some(LogLevel::Warn)));
+        assert!(untagged.meets_level(Some(LogLevel::Error)));
+        assert!(unknown.meets_level(Some(LogLevel::Error)));
+        assert!(end.meets_level(Some(LogLevel::Error)));
+
+        assert_eq!("WARNING".parse::<LogLevel>().unwrap(), LogLevel::Warn);
+        assert_eq!(LogLevel::Warn.to_string(), "warn");
+        assert_eq!(serde_json::to_value(LogLevel::Debug).unwrap(), "debug");
+    }
 }
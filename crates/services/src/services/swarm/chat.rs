@@ -3,18 +3,24 @@
 //! Manages chat messages for swarms.
 //! Migrated from ChatService.js
 
+use std::collections::HashSet;
 use std::sync::Arc;
 
+use db::models::sandbox::Sandbox;
 use db::models::swarm_chat::{CreateSwarmChat, SenderType, SwarmChat};
+pub use db::models::swarm_chat::MessageMetadata;
 use once_cell::sync::Lazy;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use sqlx::SqlitePool;
 use thiserror::Error;
-use ts_rs::TS;
+use utils::text::truncate_to_char_boundary;
 use uuid::Uuid;
 
 use super::broadcast::{ChatBroadcaster, ChatMessageData};
 
+/// Longest chat message accepted, in bytes
+pub const MAX_MESSAGE_LEN: usize = 10_000;
+
 static MENTION_REGEX: Lazy<regex::Regex> = Lazy::new(|| {
     regex::Regex::new(r"@(\w+(?:-\w+)*)").unwrap()
 });
@@ -36,66 +42,10 @@ pub type Result<T> = std::result::Result<T, ChatError>;
 pub struct GetMessagesOptions {
     pub limit: Option<i32>,
     pub since: Option<chrono::DateTime<chrono::Utc>>,
-}
-
-/// Metadata attached to chat messages
-#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
-#[ts(export)]
-pub struct MessageMetadata {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub task_id: Option<Uuid>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub sandbox_id: Option<Uuid>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub error: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub typing: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub role: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub agent_response: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub credential_required: Option<String>,
-}
-
-impl MessageMetadata {
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    pub fn with_task(mut self, task_id: Uuid) -> Self {
-        self.task_id = Some(task_id);
-        self
-    }
-
-    pub fn with_sandbox(mut self, sandbox_id: Uuid) -> Self {
-        self.sandbox_id = Some(sandbox_id);
-        self
-    }
-
-    pub fn with_error(mut self, error: String) -> Self {
-        self.error = Some(error);
-        self
-    }
-
-    pub fn with_role(mut self, role: String) -> Self {
-        self.role = Some(role);
-        self
-    }
-
-    pub fn as_typing(mut self) -> Self {
-        self.typing = Some(true);
-        self
-    }
-
-    pub fn as_agent_response(mut self) -> Self {
-        self.agent_response = Some(true);
-        self
-    }
-
-    pub fn to_json(&self) -> Option<String> {
-        serde_json::to_string(self).ok()
-    }
+    /// Only messages older than this one, for paging back through history
+    pub before: Option<Uuid>,
+    /// Only messages newer than this one, for polling for new messages
+    pub after: Option<Uuid>,
 }
 
 /// ChatService handles all chat/messaging operations for swarms
@@ -107,19 +57,26 @@ impl ChatService {
         Self
     }
 
-    /// Get messages for a swarm
+    /// Get messages for a swarm, oldest first
+    ///
+    /// With `after`, or `since` without `before`, these are the oldest `limit`
+    /// messages after them, so polling with `after` set to the last one
+    /// returned misses nothing; otherwise the newest `limit` messages.
     pub async fn get_messages(
         &self,
         pool: &SqlitePool,
         swarm_id: Uuid,
         options: GetMessagesOptions,
     ) -> Result<Vec<SwarmChat>> {
-        let mut messages = SwarmChat::find_by_swarm_id(pool, swarm_id, options.limit).await?;
-
-        if let Some(since) = options.since {
-            messages.retain(|m| m.created_at > since);
+        if options.after.is_some() || (options.since.is_some() && options.before.is_none()) {
+            let (messages, _) =
+                SwarmChat::find_after(pool, swarm_id, options.since, options.after, options.limit).await?;
+            return Ok(messages);
         }
 
+        let (mut messages, _) =
+            SwarmChat::find_page(pool, swarm_id, options.since, options.before, options.limit).await?;
+
         messages.reverse();
 
         Ok(messages)
@@ -138,6 +95,8 @@ impl ChatService {
             GetMessagesOptions {
                 limit: Some(count),
                 since: None,
+                before: None,
+                after: None,
             },
         )
         .await
@@ -153,29 +112,85 @@ impl ChatService {
         message: String,
         metadata: Option<MessageMetadata>,
     ) -> Result<SwarmChat> {
-        let message_id = Uuid::new_v4();
-        let metadata_json = metadata.and_then(|m| m.to_json());
-
         let data = CreateSwarmChat {
             swarm_id,
-            sender_type: sender_type.clone(),
-            sender_id: sender_id.clone(),
-            message: message.clone(),
-            metadata: metadata_json,
+            sender_type,
+            sender_id,
+            message,
+            metadata: metadata.and_then(|m| m.to_json()),
         };
 
-        let chat_message = SwarmChat::create(pool, &data, message_id).await?;
+        let chat_message = self.insert_message(pool, &data, Uuid::new_v4()).await?;
+        self.route_mentions(pool, &chat_message).await?;
+
+        Ok(chat_message)
+    }
+
+    /// Store a message under a caller-chosen id, without routing its mentions
+    async fn insert_message(
+        &self,
+        pool: &SqlitePool,
+        data: &CreateSwarmChat,
+        message_id: Uuid,
+    ) -> Result<SwarmChat> {
+        let chat_message = SwarmChat::create(pool, data, message_id).await?;
 
         tracing::debug!(
-            swarm_id = %swarm_id,
+            swarm_id = %data.swarm_id,
             message_id = %chat_message.id,
-            sender_type = ?sender_type,
+            sender_type = ?data.sender_type,
             "Message posted"
         );
 
         Ok(chat_message)
     }
 
+    /// Deliver a message to the inbox of every sandbox it @mentions
+    ///
+    /// A mention matches the sandbox id or the agent role of the sandbox's
+    /// last task; `@all` reaches every active sandbox in the swarm. A sandbox
+    /// never receives its own message. Returns the sandboxes delivered to.
+    pub async fn route_mentions(&self, pool: &SqlitePool, message: &SwarmChat) -> Result<Vec<Uuid>> {
+        let mentions: HashSet<String> = Self::extract_mentions(&message.message)
+            .iter()
+            .map(|m| m.trim_start_matches('@').to_lowercase())
+            .collect();
+        if mentions.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let sender = match message.sender_type {
+            SenderType::Sandbox => message.sender_id.as_deref(),
+            _ => None,
+        };
+        let targets: Vec<Uuid> = Sandbox::find_active_by_swarm_id(pool, message.swarm_id)
+            .await?
+            .into_iter()
+            .filter(|sandbox| sender != Some(sandbox.id.to_string().as_str()))
+            .filter(|sandbox| {
+                mentions.contains("all")
+                    || mentions.contains(&sandbox.id.to_string())
+                    || sandbox
+                        .last_role
+                        .as_deref()
+                        .is_some_and(|role| mentions.contains(&role.to_lowercase()))
+            })
+            .map(|sandbox| sandbox.id)
+            .collect();
+
+        if !targets.is_empty() {
+            SwarmChat::deliver(pool, message.id, &targets).await?;
+            tracing::debug!(
+                swarm_id = %message.swarm_id,
+                message_id = %message.id,
+                recipients = targets.len(),
+                "Message delivered to mentioned sandboxes"
+            );
+        }
+
+        Ok(targets)
+    }
+
     /// Post a system message
     pub async fn post_system_message(
         &self,
@@ -387,3 +402,86 @@ impl ChatService {
         .await
     }
 }
+
+/// Sandbox reply that is streamed to chat subscribers while it is generated
+///
+/// Each chunk is broadcast as a delta keyed by `message_id`; `finish` stores the
+/// accumulated text as a single chat message under that same id, so clients can
+/// replace the partial text with the persisted message. The stored text is cut
+/// to `MAX_MESSAGE_LEN`, and @mentions in it are not routed: it is agent output,
+/// not a message addressed to other sandboxes.
+#[derive(Debug)]
+pub struct StreamedReply {
+    swarm_id: Uuid,
+    sandbox_id: Uuid,
+    message_id: Uuid,
+    role: Option<String>,
+    buffer: String,
+}
+
+impl StreamedReply {
+    pub fn new(swarm_id: Uuid, sandbox_id: Uuid, role: Option<String>) -> Self {
+        Self {
+            swarm_id,
+            sandbox_id,
+            message_id: Uuid::new_v4(),
+            role,
+            buffer: String::new(),
+        }
+    }
+
+    pub fn message_id(&self) -> Uuid {
+        self.message_id
+    }
+
+    /// Text received so far
+    pub fn text(&self) -> &str {
+        &self.buffer
+    }
+
+    /// Drop the text buffered so far, e.g. before a retry starts over
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    /// Append a chunk and broadcast it as a delta
+    pub async fn push(&mut self, broadcaster: &ChatBroadcaster, delta: &str) -> usize {
+        if delta.is_empty() {
+            return 0;
+        }
+
+        self.buffer.push_str(delta);
+        broadcaster
+            .publish_partial(self.swarm_id, self.message_id, delta)
+            .await
+    }
+
+    /// Persist the buffered reply and broadcast it as a complete message
+    pub async fn finish(
+        mut self,
+        pool: &SqlitePool,
+        broadcaster: &Arc<ChatBroadcaster>,
+    ) -> Result<SwarmChat> {
+        let len = truncate_to_char_boundary(&self.buffer, MAX_MESSAGE_LEN).len();
+        self.buffer.truncate(len);
+        let metadata = self.role.map(|r| MessageMetadata::new().with_role(r).as_agent_response());
+
+        let data = CreateSwarmChat {
+            swarm_id: self.swarm_id,
+            sender_type: SenderType::Sandbox,
+            sender_id: Some(self.sandbox_id.to_string()),
+            message: self.buffer,
+            metadata: metadata.and_then(|m| m.to_json()),
+        };
+
+        let chat_message = ChatService::new()
+            .insert_message(pool, &data, self.message_id)
+            .await?;
+
+        broadcaster
+            .publish(self.swarm_id, ChatService::to_broadcast_data(&chat_message))
+            .await;
+
+        Ok(chat_message)
+    }
+}
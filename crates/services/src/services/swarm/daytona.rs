@@ -7,14 +7,23 @@
 //! - Managing sandbox lifecycle
 
 use std::collections::HashMap;
-use std::time::Duration;
-
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+use backon::{ExponentialBuilder, Retryable};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use chrono::{DateTime, Utc};
 use regex::Regex;
 use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use db::models::sandbox_command::{CreateSandboxCommand, SandboxCommand};
+use db::models::swarm_config::SwarmConfig;
 use tracing::{debug, error, info, warn};
 use shlex;
+use sqlx::SqlitePool;
 use url::Url;
 
 // ============================================================================
@@ -102,6 +111,9 @@ pub enum DaytonaError {
     #[error("sandbox not found: {0}")]
     SandboxNotFound(String),
 
+    #[error("snapshot not found: {0}")]
+    SnapshotNotFound(String),
+
     #[error("command execution failed: {0}")]
     CommandFailed(String),
 
@@ -119,6 +131,10 @@ pub enum DaytonaError {
 
     #[error("command rejected: {0}")]
     CommandRejected(String),
+
+    /// Daytona answered with an error object instead of the expected payload
+    #[error("Daytona error: {0}")]
+    Api(String),
 }
 
 impl DaytonaError {
@@ -191,6 +207,8 @@ pub struct CreateSandboxResponse {
 pub struct Sandbox {
     pub id: String,
     #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
     pub state: Option<String>,
     #[serde(default)]
     pub created_at: Option<String>,
@@ -268,6 +286,24 @@ impl From<ExecuteCommandResponse> for CommandResult {
     }
 }
 
+/// Result of `DaytonaClient::execute_command_binary`, with stdout kept as raw bytes
+#[derive(Debug, Clone)]
+pub struct BinaryCommandResult {
+    pub success: bool,
+    pub stdout: Vec<u8>,
+    pub error: String,
+    pub exit_code: i32,
+}
+
+/// What `DaytonaClient::git_commit_and_push` did
+#[derive(Debug, Clone, Default)]
+pub struct GitCommitOutcome {
+    /// False when the working tree had nothing to commit
+    pub committed: bool,
+    /// Why the push failed; None if it succeeded or was not attempted
+    pub push_error: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WriteFileRequest {
@@ -275,6 +311,194 @@ pub struct WriteFileRequest {
     pub content: String,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSessionRequest {
+    pub session_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionExecuteRequest {
+    pub command: String,
+    pub run_async: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionExecuteResponse {
+    pub cmd_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionCommand {
+    /// Unset while the command is still running
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+}
+
+/// Extra time a streaming command's log request gets beyond the command timeout
+const STREAM_GRACE_SECS: u64 = 30;
+
+/// How long to wait for a finished command's exit code to be reported
+const EXIT_CODE_POLLS: u32 = 10;
+const EXIT_CODE_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Output of a command started with `DaytonaClient::execute_command_streaming`
+pub struct CommandStream<'a> {
+    client: &'a DaytonaClient,
+    sandbox_id: String,
+    session_id: String,
+    command_id: String,
+    safe_command: String,
+    started: Instant,
+    response: reqwest::Response,
+    /// Trailing bytes of a character split across chunks
+    partial: Vec<u8>,
+    output: String,
+    /// Set once `finish` or `abort` has deleted the session
+    closed: bool,
+}
+
+impl CommandStream<'_> {
+    /// Next piece of output; None once the command has finished writing
+    pub async fn next_chunk(&mut self) -> Option<Result<String, DaytonaError>> {
+        loop {
+            let bytes = match self.response.chunk().await {
+                Ok(Some(bytes)) => bytes,
+                Ok(None) if self.partial.is_empty() => return None,
+                Ok(None) => {
+                    let text = String::from_utf8_lossy(&self.partial).into_owned();
+                    self.partial.clear();
+                    self.output.push_str(&text);
+                    return Some(Ok(text));
+                }
+                Err(e) if e.is_timeout() => {
+                    return Some(Err(DaytonaError::Timeout(self.started.elapsed().as_millis() as u64)));
+                }
+                Err(e) => return Some(Err(DaytonaError::Transport(e.to_string()))),
+            };
+
+            self.partial.extend_from_slice(&bytes);
+            let text = drain_utf8(&mut self.partial);
+            if !text.is_empty() {
+                self.output.push_str(&text);
+                return Some(Ok(text));
+            }
+        }
+    }
+
+    /// Collect the exit code and delete the session
+    ///
+    /// Call after `next_chunk` has returned None; the output is everything
+    /// streamed so far.
+    pub async fn finish(mut self) -> Result<CommandResult, DaytonaError> {
+        let exit_code = self.exit_code().await;
+        self.client.delete_session(&self.sandbox_id, &self.session_id).await;
+        self.client.record_command(
+            &self.sandbox_id,
+            std::mem::take(&mut self.safe_command),
+            exit_code.as_ref().ok().copied(),
+            self.started,
+        );
+        self.closed = true;
+
+        let exit_code = exit_code?;
+        debug!(
+            sandbox_id = %self.sandbox_id,
+            exit_code,
+            "Streaming command completed"
+        );
+
+        // Session logs interleave stdout and stderr, so a failure is reported
+        // with the last line of output, where the agent's error usually is
+        let error = if exit_code == 0 {
+            String::new()
+        } else {
+            self.output
+                .lines()
+                .rev()
+                .find(|l| !l.trim().is_empty())
+                .map(|l| l.trim().to_string())
+                .unwrap_or_else(|| format!("Command exited with code {}", exit_code))
+        };
+
+        Ok(CommandResult {
+            success: exit_code == 0,
+            output: std::mem::take(&mut self.output),
+            error,
+            exit_code,
+        })
+    }
+
+    /// Stop the command and delete its session
+    pub async fn abort(mut self) {
+        self.client.delete_session(&self.sandbox_id, &self.session_id).await;
+        self.client
+            .record_command(&self.sandbox_id, std::mem::take(&mut self.safe_command), None, self.started);
+        self.closed = true;
+    }
+
+    async fn exit_code(&self) -> Result<i32, DaytonaError> {
+        let path = format!(
+            "/api/toolbox/{}/toolbox/process/session/{}/command/{}",
+            self.sandbox_id, self.session_id, self.command_id
+        );
+        for _ in 0..EXIT_CODE_POLLS {
+            let command: SessionCommand = self.client.get(&path).await?;
+            if let Some(code) = command.exit_code {
+                return Ok(code);
+            }
+            tokio::time::sleep(EXIT_CODE_POLL_INTERVAL).await;
+        }
+        Err(DaytonaError::CommandFailed(format!(
+            "no exit code reported for command {}",
+            self.command_id
+        )))
+    }
+}
+
+/// A stream dropped before `finish` or `abort`, e.g. because the run reading
+/// it was cancelled or timed out, still has its session deleted so the
+/// command does not keep running in the sandbox
+impl Drop for CommandStream<'_> {
+    fn drop(&mut self) {
+        if self.closed {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+
+        let client = self.client.clone();
+        let sandbox_id = std::mem::take(&mut self.sandbox_id);
+        let session_id = std::mem::take(&mut self.session_id);
+        client.record_command(&sandbox_id, std::mem::take(&mut self.safe_command), None, self.started);
+        runtime.spawn(async move {
+            client.delete_session(&sandbox_id, &session_id).await;
+        });
+    }
+}
+
+/// Take the valid UTF-8 prefix of `buf`, leaving an incomplete trailing character
+///
+/// Invalid sequences are replaced rather than held back.
+fn drain_utf8(buf: &mut Vec<u8>) -> String {
+    let valid = match std::str::from_utf8(buf) {
+        Ok(_) => buf.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => {
+            let text = String::from_utf8_lossy(buf).into_owned();
+            buf.clear();
+            return text;
+        }
+    };
+    let rest = buf.split_off(valid);
+    let text = std::mem::replace(buf, rest);
+    String::from_utf8(text).unwrap_or_default()
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PreviewUrlResponse {
@@ -282,17 +506,98 @@ pub struct PreviewUrlResponse {
     pub port: u16,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedPreviewUrlResponse {
+    pub url: String,
+    pub port: u16,
+}
+
+/// Longest lifetime accepted for a signed preview URL (24 hours)
+pub const MAX_PREVIEW_TTL_SECS: u32 = 86_400;
+
+pub fn validate_preview_ttl(ttl_secs: u32) -> Result<(), DaytonaError> {
+    if ttl_secs == 0 || ttl_secs > MAX_PREVIEW_TTL_SECS {
+        return Err(DaytonaError::Config(format!(
+            "preview ttl must be between 1 and {} seconds",
+            MAX_PREVIEW_TTL_SECS
+        )));
+    }
+    Ok(())
+}
+
+/// Preview URL for a port exposed by a sandbox
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewUrl {
+    pub url: String,
+    pub port: u16,
+    /// True when the URL embeds a Daytona-issued token that expires at `expires_at`.
+    /// Unsigned URLs are reachable by anyone who can guess or obtain them.
+    pub signed: bool,
+    /// True when Daytona did not return a URL and this one was derived from the
+    /// sandbox id; such URLs are never signed and may not resolve
+    pub fallback: bool,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// One entry of a sandbox directory listing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub name: String,
+    /// Size in bytes; not reported for every entry
+    pub size: Option<u64>,
+    pub is_dir: bool,
+}
+
 // ============================================================================
 // Daytona Client
 // ============================================================================
 
+/// Between a sandbox name prefix and the rest of the name
+///
+/// Prefixes can't contain it, so no prefix's namespace covers another's:
+/// `vk` owns `vk.worker` but not `vk-b.worker`.
+const SANDBOX_NAME_SEPARATOR: char = '.';
+
+/// Seconds a git commit or push may take
+const GIT_TIMEOUT_SECS: u32 = 120;
+
+/// Commit identity used when the sandbox's git config has none
+const GIT_FALLBACK_NAME: &str = "Vibe Kanban";
+const GIT_FALLBACK_EMAIL: &str = "noreply@vibekanban.com";
+
+/// Environment variable carrying the push token
+const GIT_TOKEN_ENV: &str = "GIT_TOKEN";
+
+/// Credential helper that answers with the token from `GIT_TOKEN_ENV`
+const GIT_TOKEN_CREDENTIAL_HELPER: &str =
+    r#"!f() { echo username=x-access-token; echo "password=$GIT_TOKEN"; }; f"#;
+
+/// Last non-empty line of a command's stderr, else its stdout
+fn last_output_line(result: &CommandResult) -> &str {
+    [&result.error, &result.output]
+        .into_iter()
+        .find_map(|stream| stream.lines().rev().find(|l| !l.trim().is_empty()))
+        .map(str::trim)
+        .unwrap_or("no output")
+}
+
 #[derive(Debug, Clone)]
 pub struct DaytonaConfig {
     pub api_url: String,
     pub api_key: String,
     pub default_snapshot: Option<String>,
+    /// Tried in order when `default_snapshot` is not found
+    pub fallback_snapshots: Vec<String>,
     pub timeout_ms: u64,
     pub target: Option<String>,
+    /// Prefix applied to the names of sandboxes created by this instance.
+    /// Empty means no namespacing.
+    pub sandbox_name_prefix: String,
+    /// Retries of a failed idempotent request; 0 disables retrying
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled (with jitter) for each further one
+    pub retry_base_delay_ms: u64,
 }
 
 impl Default for DaytonaConfig {
@@ -301,10 +606,160 @@ impl Default for DaytonaConfig {
             api_url: "https://api.daytona.io".to_string(),
             api_key: String::new(),
             default_snapshot: Some("swarm-lite-v1".to_string()),
+            fallback_snapshots: Vec::new(),
             timeout_ms: 30_000,
             target: Some("us".to_string()),
+            sandbox_name_prefix: String::new(),
+            max_retries: 3,
+            retry_base_delay_ms: 500,
+        }
+    }
+}
+
+impl DaytonaConfig {
+    /// Build a client config from the stored swarm configuration.
+    /// Returns `None` when the Daytona URL or API key is not configured.
+    pub fn from_swarm_config(config: &SwarmConfig) -> Option<Self> {
+        let api_url = config.daytona_api_url.clone()?;
+        let api_key = config.daytona_api_key.clone()?;
+
+        Some(Self {
+            api_url,
+            api_key,
+            default_snapshot: Some(config.pool_default_snapshot.clone()),
+            fallback_snapshots: config.pool_fallback_snapshots.clone(),
+            sandbox_name_prefix: config.sandbox_name_prefix.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// Snapshots to create sandboxes from, in the order they are tried
+    ///
+    /// The default snapshot comes first, followed by the fallbacks with
+    /// blanks and duplicates removed.
+    pub fn snapshot_chain(&self) -> Vec<String> {
+        let mut chain: Vec<String> = Vec::new();
+        for snapshot in self.default_snapshot.iter().chain(&self.fallback_snapshots) {
+            let snapshot = snapshot.trim();
+            if !snapshot.is_empty() && !chain.iter().any(|s| s == snapshot) {
+                chain.push(snapshot.to_string());
+            }
         }
+        chain
     }
+
+    /// Apply the configured prefix to a sandbox name.
+    ///
+    /// With an empty prefix the name is passed through unchanged. With a
+    /// prefix, unnamed sandboxes get a generated name so they can still be
+    /// recognised as ours when listing.
+    pub fn prefixed_sandbox_name(&self, name: Option<String>) -> Option<String> {
+        if self.sandbox_name_prefix.is_empty() {
+            return name;
+        }
+
+        let base = name.unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+        Some(format!("{}{}{}", self.sandbox_name_prefix, SANDBOX_NAME_SEPARATOR, base))
+    }
+
+    /// Check whether a remote sandbox belongs to this instance's namespace
+    pub fn owns_sandbox(&self, sandbox: &Sandbox) -> bool {
+        if self.sandbox_name_prefix.is_empty() {
+            return true;
+        }
+
+        let namespace = format!("{}{}", self.sandbox_name_prefix, SANDBOX_NAME_SEPARATOR);
+        sandbox.name.as_deref().is_some_and(|name| name.starts_with(&namespace))
+    }
+}
+
+/// A command executed through [`DaytonaClient::execute_command`]
+///
+/// `command` has already been passed through the secret masker.
+#[derive(Debug, Clone)]
+pub struct CommandRecord {
+    pub sandbox_id: String,
+    pub command: String,
+    /// None when the request never produced an exit code
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+}
+
+/// Optional callback invoked after every executed command
+///
+/// Lets callers persist command history without the client itself touching
+/// the database.
+#[derive(Clone)]
+pub struct CommandHook(Arc<dyn Fn(CommandRecord) + Send + Sync>);
+
+impl CommandHook {
+    pub fn new(hook: impl Fn(CommandRecord) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    /// Hook that writes each record to the `sandbox_commands` table
+    ///
+    /// Inserts run on a spawned task so command execution never waits on the
+    /// database; failures are logged and otherwise ignored.
+    pub fn persist_to(pool: SqlitePool) -> Self {
+        Self::new(move |record| {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                let data = CreateSandboxCommand {
+                    daytona_id: record.sandbox_id,
+                    command: record.command,
+                    exit_code: record.exit_code,
+                    duration_ms: record.duration_ms as i64,
+                };
+                if let Err(e) = SandboxCommand::create(&pool, &data).await {
+                    warn!(sandbox_id = %data.daytona_id, error = %e, "Failed to record sandbox command");
+                }
+            });
+        })
+    }
+}
+
+impl fmt::Debug for CommandHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CommandHook")
+    }
+}
+
+/// Parse and normalize a Daytona API base URL
+///
+/// Requires an http(s) scheme and a host, and ends the path with a slash so
+/// joins append to it. A trailing `/api` segment is dropped because every
+/// request path already starts with it. Query and fragment are dropped.
+fn normalize_base_url(raw: &str) -> Result<Url, DaytonaError> {
+    let mut url = Url::parse(raw.trim()).map_err(|e| DaytonaError::Url(e.to_string()))?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(DaytonaError::Config(format!(
+            "Daytona API URL must use http or https, got '{}'",
+            url.scheme()
+        )));
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err(DaytonaError::Config(format!("Daytona API URL has no host: '{}'", raw)));
+    }
+
+    let trimmed = url.path().trim_end_matches('/');
+    let prefix = trimmed.strip_suffix("/api").unwrap_or(trimmed);
+    let path = format!("{}/", prefix);
+    url.set_path(&path);
+    url.set_query(None);
+    url.set_fragment(None);
+
+    Ok(url)
+}
+
+/// Resolve an API path such as `/api/sandbox` against a normalized base URL
+///
+/// The leading slash is dropped so a path prefix on the base (e.g. a
+/// reverse proxy mount) is kept instead of being replaced.
+fn resolve_api_path(base: &Url, path: &str) -> Result<Url, DaytonaError> {
+    base.join(path.trim_start_matches('/'))
+        .map_err(|e| DaytonaError::Url(e.to_string()))
 }
 
 #[derive(Debug, Clone)]
@@ -312,11 +767,12 @@ pub struct DaytonaClient {
     base: Url,
     http: Client,
     config: DaytonaConfig,
+    command_hook: Option<CommandHook>,
 }
 
 impl DaytonaClient {
     pub fn new(config: DaytonaConfig) -> Result<Self, DaytonaError> {
-        let base = Url::parse(&config.api_url).map_err(|e| DaytonaError::Url(e.to_string()))?;
+        let base = normalize_base_url(&config.api_url)?;
 
         let http = Client::builder()
             .timeout(Duration::from_millis(config.timeout_ms))
@@ -326,7 +782,35 @@ impl DaytonaClient {
 
         info!(api_url = %config.api_url, "Daytona client initialized");
 
-        Ok(Self { base, http, config })
+        Ok(Self {
+            base,
+            http,
+            config,
+            command_hook: None,
+        })
+    }
+
+    /// Invoke `hook` with a masked record of every command this client executes
+    pub fn with_command_hook(mut self, hook: CommandHook) -> Self {
+        self.command_hook = Some(hook);
+        self
+    }
+
+    fn record_command(
+        &self,
+        sandbox_id: &str,
+        command: String,
+        exit_code: Option<i32>,
+        started: Instant,
+    ) {
+        if let Some(hook) = &self.command_hook {
+            (hook.0)(CommandRecord {
+                sandbox_id: sandbox_id.to_string(),
+                command,
+                exit_code,
+                duration_ms: started.elapsed().as_millis() as u64,
+            });
+        }
     }
 
     pub fn from_env() -> Result<Self, DaytonaError> {
@@ -356,18 +840,79 @@ impl DaytonaClient {
     where
         B: Serialize,
     {
-        let url = self
-            .base
-            .join(path)
-            .map_err(|e| DaytonaError::Url(e.to_string()))?;
+        self.send_with_timeout(method, path, body, None).await
+    }
 
-        let timeout_ms = self.config.timeout_ms;
+    /// `send`, with `timeout` replacing the client timeout when given
+    ///
+    /// The timeout covers reading the body too, so long-lived streams need one.
+    /// Idempotent requests (GET, DELETE, ...) that fail with a retryable error
+    /// are retried with jittered exponential backoff. POSTs such as sandbox
+    /// creation or command execution are sent once, since a request that timed
+    /// out may still have taken effect.
+    async fn send_with_timeout<B>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&B>,
+        timeout: Option<Duration>,
+    ) -> Result<reqwest::Response, DaytonaError>
+    where
+        B: Serialize,
+    {
+        let max_retries = if method.is_idempotent() { self.config.max_retries } else { 0 };
+        if max_retries == 0 {
+            return self.send_once(method, path, body, timeout).await;
+        }
+
+        let retry = AtomicU32::new(0);
+        (|| self.send_once(method.clone(), path, body, timeout))
+            .retry(
+                ExponentialBuilder::default()
+                    .with_min_delay(Duration::from_millis(self.config.retry_base_delay_ms))
+                    .with_max_delay(Duration::from_secs(30))
+                    .with_max_times(max_retries as usize)
+                    .with_jitter(),
+            )
+            .when(DaytonaError::should_retry)
+            .notify(|e: &DaytonaError, delay: Duration| {
+                let attempt = retry.fetch_add(1, Ordering::Relaxed) + 1;
+                warn!(
+                    method = %method,
+                    path = %path,
+                    attempt,
+                    max_retries,
+                    delay_ms = delay.as_millis() as u64,
+                    error = %e,
+                    "Daytona request failed, retrying"
+                );
+            })
+            .await
+    }
+
+    /// Make a single request
+    async fn send_once<B>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&B>,
+        timeout: Option<Duration>,
+    ) -> Result<reqwest::Response, DaytonaError>
+    where
+        B: Serialize,
+    {
+        let url = resolve_api_path(&self.base, path)?;
+
+        let timeout_ms = timeout.map_or(self.config.timeout_ms, |t| t.as_millis() as u64);
 
         let mut req = self
             .http
             .request(method.clone(), url.clone())
             .bearer_auth(&self.config.api_key);
 
+        if let Some(t) = timeout {
+            req = req.timeout(t);
+        }
         if let Some(b) = body {
             req = req.json(b);
         }
@@ -430,24 +975,66 @@ impl DaytonaClient {
             "Creating sandbox"
         );
 
-        let response: CreateSandboxResponse = self.post("/api/sandbox", &request).await?;
+        let response: CreateSandboxResponse = self
+            .post("/api/sandbox", &request)
+            .await
+            .map_err(|e| match request.snapshot {
+                Some(ref snapshot) if is_missing_snapshot(&e) => {
+                    DaytonaError::SnapshotNotFound(snapshot.clone())
+                }
+                _ => e,
+            })?;
 
         info!(sandbox_id = %response.id, "Sandbox created");
 
         self.get_sandbox(&response.id).await
     }
 
+    /// Create a sandbox from the first available snapshot in the chain
+    ///
+    /// Falls back to the next snapshot only when one is not found; other
+    /// errors are returned immediately. The returned sandbox's `snapshot` is
+    /// the one it was actually created from.
     pub async fn create_sandbox_from_snapshot(
         &self,
         name: Option<String>,
     ) -> Result<Sandbox, DaytonaError> {
-        let request = CreateSandboxRequest {
-            name,
-            snapshot: self.config.default_snapshot.clone(),
-            target: self.config.target.clone(),
-            ..Default::default()
+        let name = self.config.prefixed_sandbox_name(name);
+        let chain = self.config.snapshot_chain();
+
+        // No snapshot configured: let Daytona use its own default
+        let candidates: Vec<Option<String>> = if chain.is_empty() {
+            vec![None]
+        } else {
+            chain.into_iter().map(Some).collect()
         };
-        self.create_sandbox(request).await
+
+        let mut last_err = None;
+        for snapshot in candidates {
+            if let Some(DaytonaError::SnapshotNotFound(ref missing)) = last_err {
+                warn!(missing = %missing, fallback = ?snapshot, "Snapshot not found, trying fallback");
+            }
+
+            let request = CreateSandboxRequest {
+                name: name.clone(),
+                snapshot: snapshot.clone(),
+                target: self.config.target.clone(),
+                ..Default::default()
+            };
+
+            match self.create_sandbox(request).await {
+                Ok(mut sandbox) => {
+                    if sandbox.snapshot.is_none() {
+                        sandbox.snapshot = snapshot;
+                    }
+                    return Ok(sandbox);
+                }
+                Err(e @ DaytonaError::SnapshotNotFound(_)) => last_err = Some(e),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| DaytonaError::Config("no snapshot to create from".to_string())))
     }
 
     pub async fn get_sandbox(&self, sandbox_id: &str) -> Result<Sandbox, DaytonaError> {
@@ -496,6 +1083,19 @@ impl DaytonaClient {
     ) -> Result<CommandResult, DaytonaError> {
         // SECURITY: Mask sensitive values (API keys, secrets) before logging
         let safe_command = mask_sensitive_command(command);
+        self.execute_masked_command(sandbox_id, command, safe_command, cwd, timeout)
+            .await
+    }
+
+    /// Run `command`, logging and recording `safe_command` in its place
+    async fn execute_masked_command(
+        &self,
+        sandbox_id: &str,
+        command: &str,
+        safe_command: String,
+        cwd: Option<&str>,
+        timeout: Option<u32>,
+    ) -> Result<CommandResult, DaytonaError> {
         debug!(
             sandbox_id = %sandbox_id,
             command = %safe_command,
@@ -538,14 +1138,23 @@ impl DaytonaClient {
             timeout: timeout.unwrap_or(60),
         };
 
-        let response: ExecuteCommandResponse = self
+        let started = Instant::now();
+        let response: ExecuteCommandResponse = match self
             .post(
                 &format!("/api/toolbox/{}/toolbox/process/execute", sandbox_id),
                 &request,
             )
-            .await?;
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                self.record_command(sandbox_id, safe_command, None, started);
+                return Err(e);
+            }
+        };
 
         let result = CommandResult::from(response);
+        self.record_command(sandbox_id, safe_command, Some(result.exit_code), started);
 
         debug!(
             sandbox_id = %sandbox_id,
@@ -578,28 +1187,247 @@ impl DaytonaClient {
         timeout: Option<u32>,
         env: Option<HashMap<String, String>>,
     ) -> Result<CommandResult, DaytonaError> {
-        // Build environment prefix for inline variable injection
-        let env_prefix = env
-            .map(|vars| {
-                vars.iter()
-                    .map(|(k, v)| {
-                        // Use shlex to safely quote the value
-                        let quoted_value = shlex::try_quote(v).unwrap_or_else(|_| v.into());
-                        format!("{}={}", k, quoted_value)
-                    })
-                    .collect::<Vec<_>>()
-                    .join(" ")
-            })
-            .unwrap_or_default();
+        let (full_command, safe_command) = with_env_prefix(command, env);
+        self.execute_masked_command(sandbox_id, &full_command, safe_command, cwd, timeout)
+            .await
+    }
 
-        let full_command = if env_prefix.is_empty() {
-            command.to_string()
-        } else {
-            format!("{} {}", env_prefix, command)
+    /// Run a command and capture its stdout as raw bytes, e.g. `tar -c dist`
+    ///
+    /// The process API returns output as a JSON string, which cannot carry
+    /// arbitrary bytes, so stdout is base64-encoded in the sandbox and decoded
+    /// here. The exit code is the command's own, not the encoder's.
+    pub async fn execute_command_binary(
+        &self,
+        sandbox_id: &str,
+        command: &str,
+        cwd: Option<&str>,
+        timeout: Option<u32>,
+    ) -> Result<BinaryCommandResult, DaytonaError> {
+        let wrapped = format!("set -o pipefail; ( {} ) | base64 -w 0", command);
+        let result = self.execute_command(sandbox_id, &wrapped, cwd, timeout).await?;
+
+        let stdout = match BASE64.decode(result.output.trim()) {
+            Ok(bytes) => bytes,
+            // Output of a failed command may be cut short; its exit code already says so
+            Err(_) if !result.success => Vec::new(),
+            Err(e) => return Err(DaytonaError::Json(format!("Command output is not valid base64: {}", e))),
         };
 
-        self.execute_command(sandbox_id, &full_command, cwd, timeout)
+        Ok(BinaryCommandResult {
+            success: result.success,
+            stdout,
+            error: result.error,
+            exit_code: result.exit_code,
+        })
+    }
+
+    /// Start a command in a new session and stream its output as it is written
+    ///
+    /// Read the output with `CommandStream::next_chunk`, then call `finish` for
+    /// the exit code. The follow request is allowed `timeout` seconds (60 by
+    /// default) plus a grace period; deleting the session stops the command.
+    pub async fn execute_command_streaming(
+        &self,
+        sandbox_id: &str,
+        command: &str,
+        cwd: Option<&str>,
+        timeout: Option<u32>,
+        env: Option<HashMap<String, String>>,
+    ) -> Result<CommandStream<'_>, DaytonaError> {
+        let (full_command, safe_command) = with_env_prefix(command, env);
+        let cwd = shlex::try_quote(cwd.unwrap_or("/home/daytona"))
+            .map_err(|e| DaytonaError::CommandRejected(e.to_string()))?
+            .into_owned();
+
+        debug!(
+            sandbox_id = %sandbox_id,
+            command = %safe_command,
+            "Executing streaming command"
+        );
+
+        let session_id = format!("swarm-{}", uuid::Uuid::new_v4());
+        self.post::<serde_json::Value, _>(
+            &format!("/api/toolbox/{}/toolbox/process/session", sandbox_id),
+            &CreateSessionRequest {
+                session_id: session_id.clone(),
+            },
+        )
+        .await?;
+
+        let started = Instant::now();
+        match self
+            .start_session_command(sandbox_id, &session_id, format!("cd {} && {}", cwd, full_command), timeout)
             .await
+        {
+            Ok((command_id, response)) => Ok(CommandStream {
+                client: self,
+                sandbox_id: sandbox_id.to_string(),
+                session_id,
+                command_id,
+                safe_command,
+                started,
+                response,
+                partial: Vec::new(),
+                output: String::new(),
+                closed: false,
+            }),
+            Err(e) => {
+                self.record_command(sandbox_id, safe_command, None, started);
+                self.delete_session(sandbox_id, &session_id).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Run a command asynchronously in a session and open its log stream
+    async fn start_session_command(
+        &self,
+        sandbox_id: &str,
+        session_id: &str,
+        command: String,
+        timeout: Option<u32>,
+    ) -> Result<(String, reqwest::Response), DaytonaError> {
+        let session_path = format!("/api/toolbox/{}/toolbox/process/session/{}", sandbox_id, session_id);
+        let started: SessionExecuteResponse = self
+            .post(
+                &format!("{}/exec", session_path),
+                &SessionExecuteRequest {
+                    command,
+                    run_async: true,
+                },
+            )
+            .await?;
+
+        let timeout = Duration::from_secs(u64::from(timeout.unwrap_or(60)) + STREAM_GRACE_SECS);
+        let logs = self
+            .send_with_timeout(
+                reqwest::Method::GET,
+                &format!("{}/command/{}/logs?follow=true", session_path, started.cmd_id),
+                None::<&()>,
+                Some(timeout),
+            )
+            .await?;
+
+        Ok((started.cmd_id, logs))
+    }
+
+    /// Delete a command session, stopping anything still running in it
+    async fn delete_session(&self, sandbox_id: &str, session_id: &str) {
+        if let Err(e) = self
+            .delete(&format!("/api/toolbox/{}/toolbox/process/session/{}", sandbox_id, session_id))
+            .await
+        {
+            warn!(sandbox_id = %sandbox_id, session_id = %session_id, error = %e, "Failed to delete command session");
+        }
+    }
+
+    // Git
+
+    /// Commit every change in the repository at `cwd`, then optionally push it
+    ///
+    /// The push goes to `origin`, to `branch` or else the current branch. A
+    /// `token` is passed to git through the environment and a one-off credential
+    /// helper, so it is never written to the sandbox's git config or disk.
+    /// Nothing happens when `cwd` is not inside a git repository, and commits
+    /// fall back to a fixed identity when none is configured. A failed commit
+    /// is an error; a failed push is reported on the outcome.
+    pub async fn git_commit_and_push(
+        &self,
+        sandbox_id: &str,
+        cwd: &str,
+        message: &str,
+        push: bool,
+        branch: Option<&str>,
+        token: Option<&str>,
+    ) -> Result<GitCommitOutcome, DaytonaError> {
+        let repo = self
+            .execute_command(sandbox_id, "git rev-parse --is-inside-work-tree", Some(cwd), Some(GIT_TIMEOUT_SECS))
+            .await?;
+        if !repo.success {
+            debug!(sandbox_id = %sandbox_id, cwd = %cwd, "Not a git repository, skipping commit");
+            return Ok(GitCommitOutcome::default());
+        }
+
+        let quoted_message =
+            shlex::try_quote(message).map_err(|e| DaytonaError::CommandRejected(e.to_string()))?;
+        let commit = self
+            .execute_command(
+                sandbox_id,
+                &format!(
+                    "git add -A && git -c user.name=\"$(git config user.name || echo '{}')\" \
+                     -c user.email=\"$(git config user.email || echo '{}')\" commit -q -m {}",
+                    GIT_FALLBACK_NAME, GIT_FALLBACK_EMAIL, quoted_message
+                ),
+                Some(cwd),
+                Some(GIT_TIMEOUT_SECS),
+            )
+            .await?;
+
+        if !commit.success {
+            if commit.output.contains("nothing to commit") {
+                return Ok(GitCommitOutcome::default());
+            }
+            return Err(DaytonaError::CommandFailed(format!(
+                "git commit exited with {}: {}",
+                commit.exit_code,
+                last_output_line(&commit)
+            )));
+        }
+
+        let mut outcome = GitCommitOutcome {
+            committed: true,
+            push_error: None,
+        };
+        if push {
+            outcome.push_error = self.git_push(sandbox_id, cwd, branch, token).await.err();
+        }
+        Ok(outcome)
+    }
+
+    /// Push HEAD to `origin`; the error is safe to show, with the token masked
+    async fn git_push(
+        &self,
+        sandbox_id: &str,
+        cwd: &str,
+        branch: Option<&str>,
+        token: Option<&str>,
+    ) -> Result<(), String> {
+        let refspec = match branch {
+            Some(branch) => shlex::try_quote(&format!("HEAD:refs/heads/{}", branch))
+                .map_err(|e| format!("Invalid branch name: {}", e))?
+                .into_owned(),
+            None => "HEAD".to_string(),
+        };
+
+        // The empty helper drops configured ones, so the token is not stored
+        let (command, env) = match token {
+            Some(token) => (
+                format!(
+                    "git -c credential.helper= -c credential.helper='{}' push origin {}",
+                    GIT_TOKEN_CREDENTIAL_HELPER, refspec
+                ),
+                Some(HashMap::from([(GIT_TOKEN_ENV.to_string(), token.to_string())])),
+            ),
+            None => (format!("git push origin {}", refspec), None),
+        };
+        let mask = |text: &str| match token {
+            Some(token) if !token.is_empty() => text.replace(token, "***"),
+            _ => text.to_string(),
+        };
+
+        let result = self
+            .execute_command_with_env(sandbox_id, &command, Some(cwd), Some(GIT_TIMEOUT_SECS), env)
+            .await
+            .map_err(|e| mask(&e.to_string()))?;
+        if !result.success {
+            return Err(mask(&format!(
+                "git push exited with {}: {}",
+                result.exit_code,
+                last_output_line(&result)
+            )));
+        }
+        Ok(())
     }
 
     // File Operations
@@ -640,11 +1468,32 @@ impl DaytonaClient {
             .ok_or_else(|| DaytonaError::Json("Missing content field".to_string()))
     }
 
+    /// Read a file as raw bytes, for binary artifacts `read_file` cannot return
+    ///
+    /// Uses the download endpoint, which always sends the file itself, so the
+    /// bytes are returned as is whatever the file contains.
+    pub async fn read_file_bytes(&self, sandbox_id: &str, path: &str) -> Result<Vec<u8>, DaytonaError> {
+        let res = self
+            .send(
+                reqwest::Method::GET,
+                &format!(
+                    "/api/toolbox/{}/toolbox/fs/download?path={}",
+                    sandbox_id,
+                    urlencoding::encode(path)
+                ),
+                None::<&()>,
+            )
+            .await?;
+
+        let body = res.bytes().await.map_err(|e| DaytonaError::Transport(e.to_string()))?;
+        Ok(body.to_vec())
+    }
+
     pub async fn list_files(
         &self,
         sandbox_id: &str,
         path: &str,
-    ) -> Result<Vec<String>, DaytonaError> {
+    ) -> Result<Vec<FileEntry>, DaytonaError> {
         let response: serde_json::Value = self
             .get(&format!(
                 "/api/toolbox/{}/toolbox/fs/list?path={}",
@@ -653,15 +1502,11 @@ impl DaytonaClient {
             ))
             .await?;
 
-        response
-            .as_array()
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.get("name").and_then(|n| n.as_str()))
-                    .map(|s| s.to_string())
-                    .collect()
-            })
-            .ok_or_else(|| DaytonaError::Json("Invalid file list response".to_string()))
+        let (entries, skipped) = parse_file_list(&response)?;
+        if skipped > 0 {
+            warn!(sandbox_id = %sandbox_id, path = %path, skipped, "Skipped file entries without a name");
+        }
+        Ok(entries)
     }
 
     // Preview/Port Exposure
@@ -670,16 +1515,58 @@ impl DaytonaClient {
         &self,
         sandbox_id: &str,
         port: u16,
-    ) -> Result<String, DaytonaError> {
+    ) -> Result<PreviewUrl, DaytonaError> {
         match self
             .get::<PreviewUrlResponse>(&format!("/api/sandbox/{}/preview/{}", sandbox_id, port))
             .await
         {
-            Ok(response) => Ok(response.url),
-            Err(_) => Ok(format!("https://{}-{}.daytona.io", sandbox_id, port)),
+            Ok(response) => Ok(PreviewUrl {
+                url: response.url,
+                port: response.port,
+                signed: false,
+                fallback: false,
+                expires_at: None,
+            }),
+            Err(_) => Ok(PreviewUrl {
+                url: format!("https://{}-{}.daytona.io", sandbox_id, port),
+                port,
+                signed: false,
+                fallback: true,
+                expires_at: None,
+            }),
         }
     }
 
+    /// Get a preview URL carrying a Daytona-issued token that expires after `ttl_secs`
+    ///
+    /// The token is validated by Daytona's preview proxy, so the URL stops working
+    /// once it expires without any further action on our side. Unlike
+    /// [`get_preview_url`](Self::get_preview_url) there is no guessed fallback:
+    /// if Daytona can't sign the URL the error is returned.
+    pub async fn get_signed_preview_url(
+        &self,
+        sandbox_id: &str,
+        port: u16,
+        ttl_secs: u32,
+    ) -> Result<PreviewUrl, DaytonaError> {
+        validate_preview_ttl(ttl_secs)?;
+
+        let response: SignedPreviewUrlResponse = self
+            .get(&format!(
+                "/api/sandbox/{}/ports/{}/signed-preview-url?expiresInSeconds={}",
+                sandbox_id, port, ttl_secs
+            ))
+            .await?;
+
+        Ok(PreviewUrl {
+            url: response.url,
+            port: response.port,
+            signed: true,
+            fallback: false,
+            expires_at: Some(Utc::now() + chrono::Duration::seconds(i64::from(ttl_secs))),
+        })
+    }
+
     // Health Check
 
     pub async fn health_check(&self) -> Result<bool, DaytonaError> {
@@ -699,10 +1586,212 @@ impl DaytonaClient {
 // Tests
 // ============================================================================
 
+/// Whether a sandbox creation error means the requested snapshot doesn't exist
+///
+/// Daytona answers 404 or 400 with a message naming the snapshot.
+fn is_missing_snapshot(err: &DaytonaError) -> bool {
+    match err {
+        DaytonaError::SandboxNotFound(_) => true,
+        DaytonaError::Http { status: 400 | 404, body } => {
+            let body = body.to_lowercase();
+            body.contains("snapshot") && (body.contains("not found") || body.contains("does not exist"))
+        }
+        _ => false,
+    }
+}
+
+/// Parse a `fs/list` response into typed entries, returning how many unnamed
+/// entries were skipped. An error object is surfaced with Daytona's message.
+fn parse_file_list(response: &serde_json::Value) -> Result<(Vec<FileEntry>, usize), DaytonaError> {
+    let Some(items) = response.as_array() else {
+        let message = response
+            .get("message")
+            .or_else(|| response.get("error"))
+            .and_then(|m| m.as_str())
+            .map(str::to_string);
+        return Err(match message {
+            Some(message) => DaytonaError::Api(message),
+            None => DaytonaError::Json(format!("Invalid file list response: {}", response)),
+        });
+    };
+
+    let entries: Vec<FileEntry> = items
+        .iter()
+        .filter_map(|item| {
+            let name = item.get("name").and_then(|n| n.as_str())?;
+            Some(FileEntry {
+                name: name.to_string(),
+                size: item.get("size").and_then(|s| s.as_u64()),
+                is_dir: item
+                    .get("isDir")
+                    .or_else(|| item.get("is_dir"))
+                    .and_then(|d| d.as_bool())
+                    .unwrap_or(false),
+            })
+        })
+        .collect();
+
+    let skipped = items.len() - entries.len();
+    Ok((entries, skipped))
+}
+
+/// Prefix `command` with inline `NAME=value` assignments for `env`
+///
+/// Returns the command to run and a copy safe to log, in which every injected
+/// value is masked.
+fn with_env_prefix(command: &str, env: Option<HashMap<String, String>>) -> (String, String) {
+    let env_keys: Vec<String> = env
+        .as_ref()
+        .map(|vars| vars.keys().cloned().collect())
+        .unwrap_or_default();
+
+    // Build environment prefix for inline variable injection
+    let env_prefix = env
+        .map(|vars| {
+            vars.iter()
+                .map(|(k, v)| {
+                    // Use shlex to safely quote the value
+                    let quoted_value = shlex::try_quote(v).unwrap_or_else(|_| v.into());
+                    format!("{}={}", k, quoted_value)
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+
+    let full_command = if env_prefix.is_empty() {
+        command.to_string()
+    } else {
+        format!("{} {}", env_prefix, command)
+    };
+
+    // Every injected value is masked, not just names matching the sensitive patterns
+    let masked_prefix = env_keys
+        .iter()
+        .map(|k| format!("{}=***", k))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let safe_command = if masked_prefix.is_empty() {
+        mask_sensitive_command(command)
+    } else {
+        format!("{} {}", masked_prefix, mask_sensitive_command(command))
+    };
+
+    (full_command, safe_command)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn remote_sandbox(name: Option<&str>) -> Sandbox {
+        Sandbox {
+            id: "remote-1".to_string(),
+            name: name.map(String::from),
+            state: None,
+            created_at: None,
+            snapshot: None,
+            target: None,
+        }
+    }
+
+    #[test]
+    fn test_drain_utf8_holds_back_split_characters() {
+        let mut buf = "ok é".as_bytes().to_vec();
+        buf.pop();
+        assert_eq!(drain_utf8(&mut buf), "ok ");
+        assert_eq!(buf, [0xC3]);
+
+        buf.extend_from_slice(&[0xA9, b'!']);
+        assert_eq!(drain_utf8(&mut buf), "é!");
+        assert!(buf.is_empty());
+
+        let mut invalid = vec![b'a', 0xFF, b'b'];
+        assert_eq!(drain_utf8(&mut invalid), "a\u{FFFD}b");
+        assert!(invalid.is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_chain_starts_with_default() {
+        let config = DaytonaConfig {
+            default_snapshot: Some("primary".to_string()),
+            fallback_snapshots: vec![
+                "backup".to_string(),
+                " ".to_string(),
+                "primary".to_string(),
+                "backup".to_string(),
+                "last-resort".to_string(),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(config.snapshot_chain(), vec!["primary", "backup", "last-resort"]);
+
+        // Without fallbacks the chain is just today's default
+        assert_eq!(DaytonaConfig::default().snapshot_chain(), vec!["swarm-lite-v1"]);
+    }
+
+    #[test]
+    fn test_missing_snapshot_errors_are_recognised() {
+        assert!(is_missing_snapshot(&DaytonaError::Http {
+            status: 400,
+            body: r#"{"message":"Snapshot swarm-lite-v1 not found"}"#.to_string(),
+        }));
+        assert!(is_missing_snapshot(&DaytonaError::SandboxNotFound("/api/sandbox".to_string())));
+        assert!(!is_missing_snapshot(&DaytonaError::Http {
+            status: 400,
+            body: "invalid target".to_string(),
+        }));
+        assert!(!is_missing_snapshot(&DaytonaError::Http {
+            status: 500,
+            body: "snapshot not found".to_string(),
+        }));
+        assert!(!is_missing_snapshot(&DaytonaError::Timeout(30_000)));
+    }
+
+    #[test]
+    fn test_sandbox_name_prefix_empty_is_passthrough() {
+        let config = DaytonaConfig::default();
+        assert_eq!(config.prefixed_sandbox_name(None), None);
+        assert_eq!(
+            config.prefixed_sandbox_name(Some("worker".to_string())),
+            Some("worker".to_string())
+        );
+        assert!(config.owns_sandbox(&remote_sandbox(None)));
+        assert!(config.owns_sandbox(&remote_sandbox(Some("other-worker"))));
+    }
+
+    #[test]
+    fn test_sandbox_name_prefix_applied_and_filtered() {
+        let config = DaytonaConfig {
+            sandbox_name_prefix: "vk".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.prefixed_sandbox_name(Some("worker".to_string())),
+            Some("vk.worker".to_string())
+        );
+        let generated = config.prefixed_sandbox_name(None).unwrap();
+        assert!(generated.starts_with("vk."));
+
+        assert!(config.owns_sandbox(&remote_sandbox(Some("vk.worker"))));
+        // Another instance whose prefix starts with ours
+        assert!(!config.owns_sandbox(&remote_sandbox(Some("vk-b.worker"))));
+        assert!(!config.owns_sandbox(&remote_sandbox(Some("vkworker"))));
+        assert!(!config.owns_sandbox(&remote_sandbox(None)));
+    }
+
+    #[test]
+    fn test_preview_ttl_bounds() {
+        assert!(validate_preview_ttl(1).is_ok());
+        assert!(validate_preview_ttl(MAX_PREVIEW_TTL_SECS).is_ok());
+        assert!(matches!(validate_preview_ttl(0), Err(DaytonaError::Config(_))));
+        assert!(matches!(
+            validate_preview_ttl(MAX_PREVIEW_TTL_SECS + 1),
+            Err(DaytonaError::Config(_))
+        ));
+    }
+
     #[test]
     fn test_mask_sensitive_command_api_key() {
         let cmd = "ANTHROPIC_API_KEY=sk-ant-api03-secret123 claude --print 'hello'";
@@ -759,4 +1848,85 @@ mod tests {
         assert_eq!(masked.get("PATH").unwrap(), "/usr/bin");
         assert_eq!(masked.get("SECRET_TOKEN").unwrap(), "***");
     }
+
+    #[test]
+    fn test_parse_file_list_reads_metadata_and_skips_unnamed() {
+        let response = serde_json::json!([
+            { "name": "src", "isDir": true, "size": 4096 },
+            { "name": "README.md", "isDir": false, "size": 120 },
+            { "isDir": false, "size": 1 },
+            { "name": "odd" }
+        ]);
+        let (entries, skipped) = parse_file_list(&response).unwrap();
+        assert_eq!(skipped, 1);
+        assert_eq!(
+            entries,
+            vec![
+                FileEntry { name: "src".to_string(), size: Some(4096), is_dir: true },
+                FileEntry { name: "README.md".to_string(), size: Some(120), is_dir: false },
+                FileEntry { name: "odd".to_string(), size: None, is_dir: false },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_file_list_surfaces_error_object() {
+        let response = serde_json::json!({ "statusCode": 404, "message": "directory not found" });
+        match parse_file_list(&response) {
+            Err(DaytonaError::Api(message)) => assert_eq!(message, "directory not found"),
+            other => panic!("expected Api error, got {:?}", other),
+        }
+
+        let response = serde_json::json!({ "unexpected": true });
+        assert!(matches!(parse_file_list(&response), Err(DaytonaError::Json(_))));
+    }
+
+    fn request_url(api_url: &str, path: &str) -> String {
+        let base = normalize_base_url(api_url).unwrap();
+        resolve_api_path(&base, path).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_base_url_with_or_without_trailing_slash_joins_the_same() {
+        for api_url in [
+            "https://api.daytona.io",
+            "https://api.daytona.io/",
+            "https://api.daytona.io/api",
+            "https://api.daytona.io/api/",
+            "  https://api.daytona.io  ",
+        ] {
+            assert_eq!(
+                normalize_base_url(api_url).unwrap().as_str(),
+                "https://api.daytona.io/",
+                "{}",
+                api_url
+            );
+            assert_eq!(
+                request_url(api_url, "/api/sandbox/sb-1"),
+                "https://api.daytona.io/api/sandbox/sb-1",
+                "{}",
+                api_url
+            );
+        }
+    }
+
+    #[test]
+    fn test_base_url_path_prefix_is_kept() {
+        for api_url in ["http://localhost:8080/daytona", "http://localhost:8080/daytona/"] {
+            assert_eq!(
+                request_url(api_url, "/api/sandbox"),
+                "http://localhost:8080/daytona/api/sandbox",
+                "{}",
+                api_url
+            );
+        }
+    }
+
+    #[test]
+    fn test_base_url_rejects_bad_scheme_or_host() {
+        assert!(matches!(normalize_base_url("ftp://api.daytona.io"), Err(DaytonaError::Config(_))));
+        assert!(matches!(normalize_base_url("file:///tmp/daytona"), Err(DaytonaError::Config(_))));
+        assert!(matches!(normalize_base_url("http//api"), Err(DaytonaError::Url(_))));
+        assert!(matches!(normalize_base_url("api.daytona.io"), Err(DaytonaError::Url(_))));
+    }
 }
@@ -0,0 +1,306 @@
+//! Task Decomposition
+//!
+//! Optional planning pass for tasks created with `decompose: true`. The agent
+//! is asked for a JSON plan, each planned subtask becomes a child task, and
+//! the original task turns into a coordinator that completes once all of its
+//! subtasks have.
+
+use std::collections::HashMap;
+
+use db::models::swarm_config::SwarmConfig;
+use db::models::swarm_task::{CreateSwarmTask, SwarmTask, TaskPriority};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::executor::TaskExecutor;
+
+/// Hard ceiling on `decompose_max_subtasks`, whatever the config says
+pub const MAX_DECOMPOSE_SUBTASKS: i32 = 50;
+
+#[derive(Debug, Error)]
+pub enum DecomposeError {
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("Task decomposition is disabled")]
+    Disabled,
+    #[error("Planning run failed: {0}")]
+    Planning(String),
+    #[error("Invalid plan: {0}")]
+    InvalidPlan(String),
+    #[error("Plan would exceed the swarm task limit ({0})")]
+    TaskLimit(i32),
+    #[error("Could not create subtask {index} ('{name}'): {source}")]
+    Subtask {
+        /// Position of the subtask in the plan, starting at 0
+        index: usize,
+        name: String,
+        #[source]
+        source: sqlx::Error,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, DecomposeError>;
+
+/// One subtask as produced by the planning run
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlannedSubtask {
+    /// Plan-local identifier referenced by `depends_on`
+    pub name: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub priority: Option<TaskPriority>,
+    /// Names of subtasks listed earlier in the plan
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Plan {
+    subtasks: Vec<PlannedSubtask>,
+}
+
+/// Build the prompt for a planning run
+pub(crate) fn build_planning_prompt(task: &SwarmTask, max_subtasks: usize) -> String {
+    let mut prompt = String::new();
+
+    prompt.push_str("# Agent: Planner\n\n");
+    prompt.push_str(&format!(
+        "## Task: {}\n\
+         Priority: {} | Tags: {}\n\
+         Mode: PLANNING ONLY - do not modify any files\n\n",
+        task.title,
+        task.priority,
+        task.tags.join(", ")
+    ));
+
+    if let Some(description) = task.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        prompt.push_str(&format!("### Details\n{}\n\n", description));
+    }
+
+    prompt.push_str(&format!(
+        "## Instructions\n\
+         Break this task into at most {} independent, self-contained subtasks.\n\
+         Each subtask will be executed by a separate agent that only sees its own title and description.\n\
+         Inspect the workspace if it helps, but make no changes.\n\n\
+         ## Output\n\
+         Reply with a single fenced ```json block and nothing after it:\n\
+         ```json\n\
+         {{\"subtasks\": [{{\"name\": \"setup-db\", \"title\": \"...\", \"description\": \"...\", \
+         \"priority\": \"medium\", \"depends_on\": []}}]}}\n\
+         ```\n\
+         - `name`: short unique identifier\n\
+         - `priority`: one of low, medium, high, urgent (optional)\n\
+         - `depends_on`: names of subtasks listed *earlier* in the list\n",
+        max_subtasks
+    ));
+
+    prompt
+}
+
+/// Extract the JSON plan from agent output
+///
+/// Prefers the last ```json fenced block and falls back to the outermost
+/// braces, since agents do not always fence their answer.
+fn extract_plan_json(output: &str) -> Option<&str> {
+    if let Some(start) = output.rfind("```json") {
+        let body = &output[start + "```json".len()..];
+        if let Some(end) = body.find("```") {
+            return Some(body[..end].trim());
+        }
+    }
+
+    let start = output.find('{')?;
+    let end = output.rfind('}')?;
+    (start < end).then(|| &output[start..=end])
+}
+
+/// Parse and validate the plan produced by a planning run
+///
+/// Subtask names must be unique and dependencies may only point at subtasks
+/// listed earlier, which keeps the resulting graph acyclic.
+pub fn parse_plan(output: &str, max_subtasks: usize) -> Result<Vec<PlannedSubtask>> {
+    let json = extract_plan_json(output)
+        .ok_or_else(|| DecomposeError::InvalidPlan("no JSON plan found in output".to_string()))?;
+
+    let plan: Plan = serde_json::from_str(json)
+        .map_err(|e| DecomposeError::InvalidPlan(format!("malformed JSON: {}", e)))?;
+
+    if plan.subtasks.is_empty() {
+        return Err(DecomposeError::InvalidPlan("plan has no subtasks".to_string()));
+    }
+    if plan.subtasks.len() > max_subtasks {
+        return Err(DecomposeError::InvalidPlan(format!(
+            "plan has {} subtasks (max {})",
+            plan.subtasks.len(),
+            max_subtasks
+        )));
+    }
+
+    let mut seen: Vec<&str> = Vec::with_capacity(plan.subtasks.len());
+    for subtask in &plan.subtasks {
+        let name = subtask.name.trim();
+        if name.is_empty() {
+            return Err(DecomposeError::InvalidPlan("subtask name is empty".to_string()));
+        }
+        if subtask.title.trim().is_empty() || subtask.title.len() > 500 {
+            return Err(DecomposeError::InvalidPlan(format!(
+                "subtask '{}' needs a title of 1-500 chars",
+                name
+            )));
+        }
+        if seen.contains(&name) {
+            return Err(DecomposeError::InvalidPlan(format!("duplicate subtask name '{}'", name)));
+        }
+        if let Some(dep) = subtask.depends_on.iter().find(|d| !seen.contains(&d.trim())) {
+            return Err(DecomposeError::InvalidPlan(format!(
+                "subtask '{}' depends on '{}', which is not listed before it",
+                name, dep
+            )));
+        }
+        seen.push(name);
+    }
+
+    Ok(plan.subtasks)
+}
+
+/// Create the planned subtasks and turn the parent into their coordinator
+///
+/// Runs in a single transaction so a half-created plan is never left behind;
+/// an insert failure names the subtask that caused it.
+/// Subtasks inherit the parent's tags, and its priority unless they set one.
+pub async fn create_subtasks(
+    pool: &SqlitePool,
+    parent: &SwarmTask,
+    plan: &[PlannedSubtask],
+) -> Result<Vec<SwarmTask>> {
+    let mut tx = pool.begin().await?;
+    let mut ids_by_name: HashMap<&str, Uuid> = HashMap::with_capacity(plan.len());
+    let mut created = Vec::with_capacity(plan.len());
+
+    for (index, subtask) in plan.iter().enumerate() {
+        let depends_on: Vec<Uuid> = subtask
+            .depends_on
+            .iter()
+            .filter_map(|dep| ids_by_name.get(dep.trim()).copied())
+            .collect();
+
+        let data = CreateSwarmTask {
+            title: subtask.title.trim().to_string(),
+            description: subtask.description.clone(),
+            priority: Some(subtask.priority.clone().unwrap_or_else(|| parent.priority.clone())),
+            depends_on: (!depends_on.is_empty()).then_some(depends_on),
+            tags: Some(parent.tags.clone()),
+            pinned_sandbox_id: None,
+            decompose: None,
+            estimate_minutes: None,
+            secrets: Some(parent.secrets.clone()),
+            write_result_to: None,
+        };
+
+        let task_id = Uuid::new_v4();
+        let inserted = async {
+            SwarmTask::create(&mut *tx, parent.swarm_id, &data, task_id).await?;
+            SwarmTask::set_parent_task(&mut *tx, task_id, parent.id).await
+        }
+        .await;
+        if let Err(source) = inserted {
+            return Err(DecomposeError::Subtask {
+                index,
+                name: subtask.name.trim().to_string(),
+                source,
+            });
+        }
+
+        ids_by_name.insert(subtask.name.trim(), task_id);
+        created.push(task_id);
+    }
+
+    SwarmTask::mark_coordinator(&mut *tx, parent.id, &created).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        task_id = %parent.id,
+        subtasks = created.len(),
+        "Task decomposed into subtasks"
+    );
+
+    Ok(SwarmTask::find_by_ids_ordered(pool, &created).await?)
+}
+
+/// Run the planning pass for a task marked `decompose` and create its subtasks
+pub async fn decompose_task(
+    pool: &SqlitePool,
+    executor: &TaskExecutor,
+    task: &SwarmTask,
+    daytona_sandbox_id: &str,
+    timeout_minutes: i32,
+) -> Result<Vec<SwarmTask>> {
+    let config = SwarmConfig::get(pool).await?;
+    if !config.decompose_enabled {
+        return Err(DecomposeError::Disabled);
+    }
+
+    let max_subtasks = config.decompose_max_subtasks.clamp(1, MAX_DECOMPOSE_SUBTASKS) as usize;
+
+    let output = executor
+        .plan(task, daytona_sandbox_id, max_subtasks, timeout_minutes)
+        .await
+        .map_err(|e| DecomposeError::Planning(e.to_string()))?;
+
+    let plan = parse_plan(&output, max_subtasks)?;
+
+    let existing = SwarmTask::count_by_swarm_id(pool, task.swarm_id).await?;
+    if existing + plan.len() as i64 > i64::from(config.max_tasks_per_swarm) {
+        return Err(DecomposeError::TaskLimit(config.max_tasks_per_swarm));
+    }
+
+    create_subtasks(pool, task, &plan).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plan_from_fenced_block() {
+        let output = "I looked around the repo.\n\n```json\n{\"subtasks\": [\
+            {\"name\": \"schema\", \"title\": \"Add schema\"},\
+            {\"name\": \"api\", \"title\": \"Add API\", \"priority\": \"high\", \"depends_on\": [\"schema\"]}\
+            ]}\n```\n";
+
+        let plan = parse_plan(output, 10).unwrap();
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[1].priority, Some(TaskPriority::High));
+        assert_eq!(plan[1].depends_on, vec!["schema".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_plan_without_fence() {
+        let output = "Plan: {\"subtasks\": [{\"name\": \"a\", \"title\": \"Do A\"}]} done";
+        assert_eq!(parse_plan(output, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_plan_rejects_invalid_plans() {
+        // Forward reference
+        let output = "{\"subtasks\": [\
+            {\"name\": \"a\", \"title\": \"A\", \"depends_on\": [\"b\"]},\
+            {\"name\": \"b\", \"title\": \"B\"}]}";
+        assert!(matches!(parse_plan(output, 10), Err(DecomposeError::InvalidPlan(_))));
+
+        // Over the cap
+        let output = "{\"subtasks\": [{\"name\": \"a\", \"title\": \"A\"}, {\"name\": \"b\", \"title\": \"B\"}]}";
+        assert!(matches!(parse_plan(output, 1), Err(DecomposeError::InvalidPlan(_))));
+
+        // Duplicate names
+        let output = "{\"subtasks\": [{\"name\": \"a\", \"title\": \"A\"}, {\"name\": \"a\", \"title\": \"B\"}]}";
+        assert!(matches!(parse_plan(output, 10), Err(DecomposeError::InvalidPlan(_))));
+
+        // No plan at all
+        assert!(matches!(parse_plan("nothing to see", 10), Err(DecomposeError::InvalidPlan(_))));
+    }
+}
@@ -4,19 +4,29 @@
 //! Implements the TaskExecutor pattern from the original Node.js backend.
 
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{anyhow, Result};
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
+use ts_rs::TS;
 use uuid::Uuid;
 
+use db::models::swarm::Swarm;
 use db::models::swarm_task::SwarmTask;
 
-use super::daytona::{CommandResult, DaytonaClient};
+use super::broadcast::{ChatBroadcaster, LogBroadcaster, LogEnd, LogEntry, LogSource};
+use super::chat::StreamedReply;
+use super::daytona::{CommandResult, DaytonaClient, DaytonaError, GitCommitOutcome};
+use super::decompose::build_planning_prompt;
 use super::pool::PoolManager;
+use super::skills::{read_skill_frontmatter, resolve_workdir};
 
 /// Retry configuration for task execution
 #[derive(Debug, Clone)]
@@ -44,6 +54,224 @@ pub struct ExecutionResult {
     pub error: Option<String>,
     pub duration_ms: u64,
     pub attempts: i32,
+    /// Execution was stopped through its cancellation token
+    pub cancelled: bool,
+    /// Problems that did not fail the task, e.g. a failed `write_result_to`
+    /// write or git push; posted to the swarm chat
+    pub warnings: Vec<String>,
+}
+
+/// Flags that may be passed to the Claude CLI via `claude_extra_args`.
+/// `--print`, `--yes` and `--model` are managed by the executor itself.
+const ALLOWED_CLAUDE_FLAGS: &[&str] = &[
+    "--max-turns",
+    "--max-tokens",
+    "--temperature",
+    "--fallback-model",
+    "--output-format",
+    "--append-system-prompt",
+    "--allowedTools",
+    "--disallowedTools",
+    "--verbose",
+];
+
+/// Validate a Claude model name (e.g. `claude-sonnet-4-5`)
+pub fn validate_claude_model(model: &str) -> std::result::Result<(), String> {
+    if model.is_empty() || model.len() > 100 {
+        return Err("Claude model must be 1-100 chars".to_string());
+    }
+    if !model
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+    {
+        return Err("Claude model may only contain letters, digits, '-', '_', '.' and ':'".to_string());
+    }
+    Ok(())
+}
+
+/// Validate extra Claude CLI args against the flag allowlist.
+///
+/// Each arg must be a bare allowlisted flag (`--verbose`) or a flag with an
+/// inline value (`--max-turns=10`). Values are shell-quoted when the command
+/// is built, so they cannot inject further commands.
+pub fn validate_claude_extra_args(args: &[String]) -> std::result::Result<(), String> {
+    if args.len() > 20 {
+        return Err("Too many Claude CLI args (max 20)".to_string());
+    }
+
+    for arg in args {
+        if arg.len() > 500 {
+            return Err("Claude CLI arg too long (max 500 chars)".to_string());
+        }
+        let flag = arg.split_once('=').map(|(f, _)| f).unwrap_or(arg);
+        if !ALLOWED_CLAUDE_FLAGS.contains(&flag) {
+            return Err(format!("Claude CLI flag not allowed: {}", flag));
+        }
+    }
+    Ok(())
+}
+
+/// Validate prompt variables: `{{name}}` placeholders substituted into task prompts.
+///
+/// Names are identifiers so they can be written in a placeholder.
+pub fn validate_prompt_vars(vars: &HashMap<String, String>) -> std::result::Result<(), String> {
+    if vars.len() > 50 {
+        return Err("Too many prompt variables (max 50)".to_string());
+    }
+
+    for (name, value) in vars {
+        if !PROMPT_VAR_NAME_REGEX.is_match(name) {
+            return Err(format!(
+                "Invalid prompt variable name '{}': use letters, digits and '_', not starting with a digit",
+                name
+            ));
+        }
+        if value.len() > 4096 {
+            return Err(format!("Prompt variable '{}' too long (max 4096 chars)", name));
+        }
+    }
+    Ok(())
+}
+
+/// Validate a `write_result_to` path: absolute, inside the workspace and
+/// without `.` or `..` segments.
+pub fn validate_result_path(path: &str) -> std::result::Result<(), String> {
+    validate_workspace_file(path, "write_result_to")
+}
+
+/// Validate a file path that must stay inside the sandbox workspace
+///
+/// The path must be absolute, under the workspace and free of empty, `.` and
+/// `..` segments, so it cannot escape through traversal. `field` names the
+/// value in error messages.
+pub fn validate_workspace_file(path: &str, field: &str) -> std::result::Result<(), String> {
+    if path.len() > 1024 {
+        return Err(format!("{} too long (max 1024 chars)", field));
+    }
+    let Some(relative) = path
+        .strip_prefix(WORKSPACE_PATH)
+        .and_then(|rest| rest.strip_prefix('/'))
+    else {
+        return Err(format!("{} must be a file under {}", field, WORKSPACE_PATH));
+    };
+    if path.contains('\0')
+        || relative
+            .split('/')
+            .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+    {
+        return Err(format!("{} is not a valid file path: {}", field, path));
+    }
+    Ok(())
+}
+
+/// Build the Claude CLI invocation for a prompt file.
+/// `--yes --print` are always present regardless of the configured args.
+fn build_claude_command(prompt_path: &str, model: Option<&str>, extra_args: &[String]) -> String {
+    let mut cmd = String::from("claude --yes --print");
+
+    if let Some(model) = model {
+        cmd.push_str(" --model ");
+        cmd.push_str(&shlex::try_quote(model).unwrap_or_default());
+    }
+
+    for arg in extra_args {
+        cmd.push(' ');
+        cmd.push_str(&shlex::try_quote(arg).unwrap_or_default());
+    }
+
+    cmd.push_str(&format!(" \"$(cat {})\"", prompt_path));
+    cmd
+}
+
+/// Default working directory for tasks inside the sandbox
+const WORKSPACE_PATH: &str = "/workspace";
+
+/// Where CLI docs are installed in the sandbox
+const CLI_DOCS_PATH: &str = "/data/.claude/cli";
+
+/// Where the swarm setup script is written inside the sandbox
+const SETUP_SCRIPT_PATH: &str = "/tmp/swarm_setup.sh";
+
+/// Kills the agent and setup script of a cancelled task
+/// The bracketed first letter keeps `pkill -f` from matching its own shell.
+const KILL_TASK_PROCESSES_COMMAND: &str =
+    "pkill -f '[c]laude --yes --print'; pkill -f '[s]warm_setup.sh'";
+
+/// Kill the agent and setup script of a task running in a sandbox
+pub async fn kill_task_processes(daytona: &DaytonaClient, daytona_sandbox_id: &str) -> Result<(), DaytonaError> {
+    daytona
+        .execute_command(daytona_sandbox_id, KILL_TASK_PROCESSES_COMMAND, None, Some(30))
+        .await?;
+    Ok(())
+}
+
+/// Shell command printing each CLI in `clis` that is not on the sandbox PATH, one per line
+fn build_cli_probe_command(clis: &[String]) -> String {
+    let quoted: Vec<String> = clis
+        .iter()
+        .map(|cli| format!("'{}'", cli.replace('\'', "'\\''")))
+        .collect();
+    format!(
+        "for c in {}; do command -v \"$c\" >/dev/null 2>&1 || echo \"$c\"; done",
+        quoted.join(" ")
+    )
+}
+
+/// CLIs the probe reported missing, in the order they were requested
+fn missing_clis(clis: &[String], probe_output: &str) -> Vec<String> {
+    let missing: Vec<&str> = probe_output.lines().map(str::trim).collect();
+    clis.iter().filter(|cli| missing.contains(&cli.as_str())).cloned().collect()
+}
+
+/// Error reported when a sandbox lacks CLIs the task asked for
+fn missing_clis_message(missing: &[String]) -> String {
+    format!(
+        "Required CLIs not found in sandbox: {}. Install them in the snapshot or the swarm setup script.",
+        missing.join(", ")
+    )
+}
+
+/// Replaces secret values with a placeholder before output leaves the executor
+#[derive(Default)]
+struct SecretMask {
+    /// Longest first, so a value containing another is masked whole
+    values: Vec<String>,
+}
+
+impl SecretMask {
+    const PLACEHOLDER: &'static str = "********";
+
+    fn new(secrets: &HashMap<String, String>) -> Self {
+        let mut values: Vec<String> = secrets.values().filter(|v| !v.is_empty()).cloned().collect();
+        values.sort_by_key(|v| std::cmp::Reverse(v.len()));
+        Self { values }
+    }
+
+    fn apply(&self, text: &str) -> String {
+        self.values
+            .iter()
+            .fold(text.to_string(), |text, value| text.replace(value.as_str(), Self::PLACEHOLDER))
+    }
+}
+
+/// Error reported when the setup script exits non-zero
+fn setup_failure_message(result: &CommandResult) -> String {
+    let detail = [&result.error, &result.output]
+        .into_iter()
+        .find_map(|stream| stream.lines().rev().find(|l| !l.trim().is_empty()))
+        .unwrap_or("no output");
+
+    format!(
+        "Setup script failed with exit code {}: {}",
+        result.exit_code,
+        detail.trim()
+    )
+}
+
+/// Agent reply streamed to the swarm chat while a task runs
+struct ChatReply {
+    broadcaster: Arc<ChatBroadcaster>,
+    reply: Mutex<StreamedReply>,
 }
 
 /// Task Executor for running tasks in sandboxes
@@ -53,6 +281,15 @@ pub struct TaskExecutor {
     retry_config: RetryConfig,
     anthropic_api_key: Option<String>,
     skills_path: String,
+    claude_model: Option<String>,
+    claude_extra_args: Vec<String>,
+    cli_probe: bool,
+    git_auto_commit: bool,
+    git_auto_push: bool,
+    git_token: Option<String>,
+    prompt_vars: HashMap<String, String>,
+    log_broadcaster: Option<Arc<LogBroadcaster>>,
+    chat_reply: Option<ChatReply>,
 }
 
 impl TaskExecutor {
@@ -69,6 +306,15 @@ impl TaskExecutor {
             retry_config: RetryConfig::default(),
             anthropic_api_key,
             skills_path,
+            claude_model: None,
+            claude_extra_args: Vec::new(),
+            cli_probe: true,
+            git_auto_commit: false,
+            git_auto_push: false,
+            git_token: None,
+            prompt_vars: HashMap::new(),
+            log_broadcaster: None,
+            chat_reply: None,
         }
     }
 
@@ -78,31 +324,192 @@ impl TaskExecutor {
         self
     }
 
+    /// Set the Claude model and extra CLI flags.
+    /// Invalid values are dropped with a warning rather than passed to the shell.
+    pub fn with_claude_options(mut self, model: Option<String>, extra_args: Vec<String>) -> Self {
+        self.claude_model = match model {
+            Some(m) => match validate_claude_model(&m) {
+                Ok(()) => Some(m),
+                Err(e) => {
+                    warn!(error = %e, "Ignoring invalid Claude model");
+                    None
+                }
+            },
+            None => None,
+        };
+        self.claude_extra_args = match validate_claude_extra_args(&extra_args) {
+            Ok(()) => extra_args,
+            Err(e) => {
+                warn!(error = %e, "Ignoring invalid Claude CLI args");
+                Vec::new()
+            }
+        };
+        self
+    }
+
+    /// Check a task's `CLI:` tools exist in the sandbox before running it (on by default)
+    pub fn with_cli_probe(mut self, enabled: bool) -> Self {
+        self.cli_probe = enabled;
+        self
+    }
+
+    /// Commit the workspace after each successful task, and push the commit
+    /// with `token` if `auto_push` is set
+    pub fn with_git_options(mut self, auto_commit: bool, auto_push: bool, token: Option<String>) -> Self {
+        self.git_auto_commit = auto_commit;
+        self.git_auto_push = auto_push;
+        self.git_token = token.filter(|t| !t.is_empty());
+        self
+    }
+
+    /// Substitute `{{name}}` placeholders in task prompts with these values
+    pub fn with_prompt_vars(mut self, vars: HashMap<String, String>) -> Self {
+        self.prompt_vars = vars;
+        self
+    }
+
+    /// Publish setup script output to task log subscribers
+    pub fn with_log_broadcaster(mut self, broadcaster: Arc<LogBroadcaster>) -> Self {
+        self.log_broadcaster = Some(broadcaster);
+        self
+    }
+
+    /// Stream the agent's output to chat subscribers as it arrives
+    ///
+    /// Every line is pushed to `reply`, which broadcasts it as a delta. Take
+    /// the reply back with `into_chat_reply` once the run is over and store it
+    /// with `StreamedReply::finish`.
+    pub fn with_chat_reply(mut self, broadcaster: Arc<ChatBroadcaster>, reply: StreamedReply) -> Self {
+        self.chat_reply = Some(ChatReply {
+            broadcaster,
+            reply: Mutex::new(reply),
+        });
+        self
+    }
+
+    /// The chat reply buffered during the run, if one was attached
+    pub fn into_chat_reply(self) -> Option<StreamedReply> {
+        self.chat_reply.map(|chat| chat.reply.into_inner())
+    }
+
     /// Execute a task in a sandbox with retry logic
+    ///
+    /// The swarm's setup script, if any, runs once before the first attempt;
+    /// a non-zero exit fails the task without invoking Claude. The task's
+    /// `CLI:` tools are then probed, and any missing one fails the task too.
+    ///
+    /// Cancelling `cancel` aborts the running command or retry wait, kills the
+    /// task's processes in the sandbox and returns a result with `cancelled` set.
+    ///
+    /// `secrets` holds the values of the swarm secrets the task lists, keyed by
+    /// name. They are added to the environment of every command, and their
+    /// values are masked in published logs and in the returned output and error.
+    /// A listed secret missing from the map fails the task before anything runs.
+    #[allow(clippy::too_many_arguments)]
     pub async fn execute(
         &self,
-        swarm_id: Uuid,
+        swarm: &Swarm,
         task: &SwarmTask,
         daytona_sandbox_id: &str,
+        secrets: &HashMap<String, String>,
         initial_attempt: i32,
         max_retries: i32,
         timeout_minutes: i32,
+        cancel: &CancellationToken,
     ) -> Result<ExecutionResult> {
+        let swarm_id = swarm.id;
         let start_time = std::time::Instant::now();
         let mut attempt = initial_attempt;
 
-        // Build environment variables for Claude credentials (passed securely, not written to disk)
-        let env_vars = self.anthropic_api_key.as_ref().map(|api_key| {
-            HashMap::from([
-                ("ANTHROPIC_API_KEY".to_string(), api_key.clone()),
-                ("CLAUDE_CODE_API_KEY".to_string(), api_key.clone()),
-            ])
-        });
+        if cancel.is_cancelled() {
+            return Ok(self.cancelled(task.id, daytona_sandbox_id, start_time, attempt).await);
+        }
+
+        let missing: Vec<&str> = task
+            .secrets
+            .iter()
+            .filter(|name| !secrets.contains_key(*name))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            let error_msg = format!("Secrets not set for this swarm: {}", missing.join(", "));
+            warn!(task_id = %task.id, error = %error_msg, "Task secrets missing, aborting task");
+
+            return Ok(ExecutionResult {
+                success: false,
+                output: String::new(),
+                error: Some(error_msg),
+                duration_ms: start_time.elapsed().as_millis() as u64,
+                attempts: attempt,
+                cancelled: false,
+                warnings: Vec::new(),
+            });
+        }
+
+        let mask = SecretMask::new(secrets);
+        let env_vars = self.task_env_vars(secrets);
 
         // Build execution prompt
-        let prompt = self.build_task_prompt(task, "/workspace");
+        let workdir = task_workdir(&self.skills_path, swarm, task);
+        let prompt = build_task_prompt(&self.skills_path, &self.prompt_vars, swarm, task, &workdir);
         let timeout_secs = (timeout_minutes * 60) as u64;
 
+        if let Some(script) = swarm.setup_script.as_deref().filter(|s| !s.trim().is_empty()) {
+            let setup = cancel
+                .run_until_cancelled(self.run_setup_script(
+                    task.id,
+                    daytona_sandbox_id,
+                    script,
+                    timeout_secs,
+                    env_vars.clone(),
+                    &mask,
+                ))
+                .await;
+            let Some(setup) = setup else {
+                return Ok(self.cancelled(task.id, daytona_sandbox_id, start_time, attempt).await);
+            };
+            let setup = setup?;
+
+            if !setup.success {
+                let error_msg = mask.apply(&setup_failure_message(&setup));
+                warn!(task_id = %task.id, error = %error_msg, "Setup script failed, aborting task");
+
+                return Ok(ExecutionResult {
+                    success: false,
+                    output: mask.apply(&setup.output),
+                    error: Some(error_msg),
+                    duration_ms: start_time.elapsed().as_millis() as u64,
+                    attempts: attempt,
+                    cancelled: false,
+                    warnings: Vec::new(),
+                });
+            }
+        }
+
+        if self.cli_probe {
+            let probe = cancel
+                .run_until_cancelled(self.probe_required_clis(task, daytona_sandbox_id))
+                .await;
+            let Some(missing) = probe else {
+                return Ok(self.cancelled(task.id, daytona_sandbox_id, start_time, attempt).await);
+            };
+
+            if !missing.is_empty() {
+                let error_msg = missing_clis_message(&missing);
+                warn!(task_id = %task.id, missing = ?missing, "Required CLIs missing, aborting task");
+
+                return Ok(ExecutionResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(error_msg),
+                    duration_ms: start_time.elapsed().as_millis() as u64,
+                    attempts: attempt,
+                    cancelled: false,
+                    warnings: Vec::new(),
+                });
+            }
+        }
+
         loop {
             info!(
                 swarm_id = %swarm_id,
@@ -112,10 +519,24 @@ impl TaskExecutor {
                 "Starting task execution"
             );
 
+            // The chat reply carries only the latest attempt's output
+            self.clear_chat().await;
+
             // Execute Claude Code with env vars passed securely (not written to filesystem)
-            let result = self
-                .run_claude_code(daytona_sandbox_id, &prompt, Some("/workspace"), Some(timeout_secs), env_vars.clone())
+            let result = cancel
+                .run_until_cancelled(self.run_claude_code(
+                    task.id,
+                    daytona_sandbox_id,
+                    &prompt,
+                    Some(&workdir),
+                    Some(timeout_secs),
+                    env_vars.clone(),
+                    &mask,
+                ))
                 .await;
+            let Some(result) = result else {
+                return Ok(self.cancelled(task.id, daytona_sandbox_id, start_time, attempt).await);
+            };
 
             let duration_ms = start_time.elapsed().as_millis() as u64;
 
@@ -127,19 +548,35 @@ impl TaskExecutor {
                         "Task completed successfully"
                     );
 
+                    let output = mask.apply(&exec_result.output);
+                    let mut warnings = Vec::new();
+                    if let Some(path) = task.write_result_to.as_deref()
+                        && let Err(warning) = self.write_result(task.id, daytona_sandbox_id, path, &output).await
+                    {
+                        warnings.push(warning);
+                    }
+                    if self.git_auto_commit
+                        && let Err(warning) = self.commit_changes(task, daytona_sandbox_id, &workdir).await
+                    {
+                        warnings.push(warning);
+                    }
+                    self.publish_end(task.id, LogEnd::success()).await;
+
                     return Ok(ExecutionResult {
                         success: true,
-                        output: exec_result.output,
+                        output,
                         error: None,
                         duration_ms,
                         attempts: attempt,
+                        cancelled: false,
+                        warnings,
                     });
                 }
                 Ok(exec_result) => {
                     let error_msg = if exec_result.error.is_empty() {
                         "Unknown error".to_string()
                     } else {
-                        exec_result.error.clone()
+                        mask.apply(&exec_result.error)
                     };
 
                     warn!(
@@ -159,7 +596,12 @@ impl TaskExecutor {
                             "Will retry task"
                         );
 
-                        tokio::time::sleep(Duration::from_millis(delay)).await;
+                        let slept = cancel
+                            .run_until_cancelled(tokio::time::sleep(Duration::from_millis(delay)))
+                            .await;
+                        if slept.is_none() {
+                            return Ok(self.cancelled(task.id, daytona_sandbox_id, start_time, attempt).await);
+                        }
                         attempt += 1;
                         continue;
                     }
@@ -169,16 +611,24 @@ impl TaskExecutor {
                         attempts = attempt,
                         "Task failed after max retries"
                     );
+                    self.publish_end(
+                        task.id,
+                        LogEnd::failure(exec_result.exit_code).with_summary(error_msg.clone()),
+                    )
+                    .await;
 
                     return Ok(ExecutionResult {
                         success: false,
-                        output: exec_result.output,
+                        output: mask.apply(&exec_result.output),
                         error: Some(error_msg),
                         duration_ms,
                         attempts: attempt,
+                        cancelled: false,
+                        warnings: Vec::new(),
                     });
                 }
                 Err(e) => {
+                    let e = anyhow!(mask.apply(&e.to_string()));
                     error!(
                         task_id = %task.id,
                         attempt = attempt,
@@ -189,26 +639,256 @@ impl TaskExecutor {
                     // Check if we should retry on errors
                     if attempt < max_retries {
                         let delay = self.calculate_retry_delay(attempt);
-                        tokio::time::sleep(Duration::from_millis(delay)).await;
+                        let slept = cancel
+                            .run_until_cancelled(tokio::time::sleep(Duration::from_millis(delay)))
+                            .await;
+                        if slept.is_none() {
+                            return Ok(self.cancelled(task.id, daytona_sandbox_id, start_time, attempt).await);
+                        }
                         attempt += 1;
                         continue;
                     }
 
+                    self.publish_end(task.id, LogEnd::failure(-1).with_summary(e.to_string())).await;
                     return Err(e);
                 }
             }
         }
     }
 
+    /// Kill what a cancelled task left running and report the cancellation
+    ///
+    /// The kill is best effort; the sandbox is released either way.
+    async fn cancelled(
+        &self,
+        task_id: Uuid,
+        daytona_sandbox_id: &str,
+        start_time: std::time::Instant,
+        attempt: i32,
+    ) -> ExecutionResult {
+        info!(task_id = %task_id, daytona_sandbox_id = %daytona_sandbox_id, "Task execution cancelled");
+
+        if let Err(e) = kill_task_processes(&self.daytona, daytona_sandbox_id).await {
+            warn!(task_id = %task_id, error = %e, "Failed to kill processes of cancelled task");
+        }
+
+        ExecutionResult {
+            success: false,
+            output: String::new(),
+            error: Some("Task cancelled".to_string()),
+            duration_ms: start_time.elapsed().as_millis() as u64,
+            attempts: attempt,
+            cancelled: true,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Run a planning pass for a task marked `decompose` and return the agent's output
+    ///
+    /// The plan itself is parsed by the decompose module; this only runs the
+    /// agent once, without retries or the swarm setup script.
+    pub async fn plan(
+        &self,
+        task: &SwarmTask,
+        daytona_sandbox_id: &str,
+        max_subtasks: usize,
+        timeout_minutes: i32,
+    ) -> Result<String> {
+        let prompt = build_planning_prompt(task, max_subtasks);
+        let timeout_secs = (timeout_minutes * 60) as u64;
+
+        info!(task_id = %task.id, daytona_sandbox_id = %daytona_sandbox_id, "Starting planning run");
+
+        let result = self
+            .run_claude_code(
+                task.id,
+                daytona_sandbox_id,
+                &prompt,
+                Some(WORKSPACE_PATH),
+                Some(timeout_secs),
+                self.claude_env_vars(),
+                &SecretMask::default(),
+            )
+            .await?;
+
+        if !result.success {
+            let error = if result.error.is_empty() { "Unknown error" } else { result.error.as_str() };
+            return Err(anyhow!("Planning run exited with code {}: {}", result.exit_code, error));
+        }
+
+        Ok(result.output)
+    }
+
+    /// Environment variables for Claude credentials (passed securely, not written to disk)
+    fn claude_env_vars(&self) -> Option<HashMap<String, String>> {
+        self.anthropic_api_key.as_ref().map(|api_key| {
+            HashMap::from([
+                ("ANTHROPIC_API_KEY".to_string(), api_key.clone()),
+                ("CLAUDE_CODE_API_KEY".to_string(), api_key.clone()),
+            ])
+        })
+    }
+
+    /// Return the task's `CLI:` tools that are not installed in the sandbox
+    ///
+    /// The probe is best effort: if it cannot run, the task proceeds as if
+    /// every CLI were present.
+    async fn probe_required_clis(&self, task: &SwarmTask, sandbox_id: &str) -> Vec<String> {
+        let clis = extract_cli_names(task.description.as_deref());
+        if clis.is_empty() {
+            return Vec::new();
+        }
+
+        match self
+            .daytona
+            .execute_command(sandbox_id, &build_cli_probe_command(&clis), None, Some(30))
+            .await
+        {
+            Ok(result) => missing_clis(&clis, &result.output),
+            Err(e) => {
+                warn!(task_id = %task.id, error = %e, "Failed to probe required CLIs");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Write a successful task's output to its `write_result_to` path
+    ///
+    /// A failed write does not fail the task; the returned warning is
+    /// published to the task log and reported on the execution result.
+    async fn write_result(
+        &self,
+        task_id: Uuid,
+        sandbox_id: &str,
+        path: &str,
+        output: &str,
+    ) -> std::result::Result<(), String> {
+        let Err(e) = self.daytona.write_file(sandbox_id, path, output).await else {
+            debug!(task_id = %task_id, path = %path, "Wrote task result to sandbox");
+            return Ok(());
+        };
+
+        let warning = format!("Failed to write result to {}: {}", path, e);
+        warn!(task_id = %task_id, warning = %warning, "Task result not written");
+        if let Some(ref broadcaster) = self.log_broadcaster {
+            broadcaster.publish_log(task_id, LogEntry::warn(&warning)).await;
+        }
+        Err(warning)
+    }
+
+    /// Commit (and push, if enabled) what a successful task changed
+    ///
+    /// Like `write_result`, a failure does not fail the task; the returned
+    /// warning is published to the task log and reported on the result.
+    async fn commit_changes(
+        &self,
+        task: &SwarmTask,
+        sandbox_id: &str,
+        workdir: &str,
+    ) -> std::result::Result<(), String> {
+        let message = format!("{}\n\nSwarm task {}", task.title, task.id);
+        let warning = match self
+            .daytona
+            .git_commit_and_push(
+                sandbox_id,
+                workdir,
+                &message,
+                self.git_auto_push,
+                None,
+                self.git_token.as_deref(),
+            )
+            .await
+        {
+            Ok(GitCommitOutcome { committed: false, .. }) => {
+                debug!(task_id = %task.id, "No changes to commit");
+                return Ok(());
+            }
+            Ok(GitCommitOutcome { push_error: None, .. }) => {
+                info!(task_id = %task.id, pushed = self.git_auto_push, "Committed task changes");
+                return Ok(());
+            }
+            Ok(GitCommitOutcome { push_error: Some(e), .. }) => {
+                format!("Changes were committed but the push failed: {}", e)
+            }
+            Err(e) => format!("Failed to commit changes: {}", e),
+        };
+
+        warn!(task_id = %task.id, warning = %warning, "Task changes not saved to git");
+        if let Some(ref broadcaster) = self.log_broadcaster {
+            broadcaster.publish_log(task.id, LogEntry::warn(&warning)).await;
+        }
+        Err(warning)
+    }
+
+    /// Task secrets plus Claude credentials; credentials win on a name clash
+    fn task_env_vars(&self, secrets: &HashMap<String, String>) -> Option<HashMap<String, String>> {
+        let mut env_vars = secrets.clone();
+        env_vars.extend(self.claude_env_vars().unwrap_or_default());
+        (!env_vars.is_empty()).then_some(env_vars)
+    }
+
+    /// Run the swarm setup script in the workspace
+    /// Output is published to the task log with `source: "setup"` once the script exits
+    async fn run_setup_script(
+        &self,
+        task_id: Uuid,
+        sandbox_id: &str,
+        script: &str,
+        timeout_secs: u64,
+        env_vars: Option<HashMap<String, String>>,
+        mask: &SecretMask,
+    ) -> Result<CommandResult> {
+        // Written to a file so the script is never re-quoted into a single command line
+        self.daytona
+            .write_file(sandbox_id, SETUP_SCRIPT_PATH, script)
+            .await
+            .map_err(|e| anyhow!("Failed to write setup script: {}", e))?;
+
+        info!(task_id = %task_id, sandbox_id = %sandbox_id, "Running swarm setup script");
+
+        let result = self
+            .daytona
+            .execute_command_with_env(
+                sandbox_id,
+                &format!("bash {}", SETUP_SCRIPT_PATH),
+                Some(WORKSPACE_PATH),
+                Some(timeout_secs as u32),
+                env_vars,
+            )
+            .await
+            .map_err(|e| anyhow!("Setup script execution failed: {}", e))?;
+
+        if let Some(ref broadcaster) = self.log_broadcaster {
+            for line in result.output.lines() {
+                broadcaster
+                    .publish_log(task_id, LogEntry::info(mask.apply(line)).with_source(LogSource::Setup))
+                    .await;
+            }
+            for line in result.error.lines() {
+                broadcaster
+                    .publish_log(task_id, LogEntry::error(mask.apply(line)).with_source(LogSource::Setup))
+                    .await;
+            }
+        }
+
+        Ok(result)
+    }
+
     /// Run Claude Code CLI in sandbox with environment variables passed securely
     /// Note: Credentials are passed via env vars, NOT written to filesystem
+    ///
+    /// Output is published to the task's log subscribers, and to the chat
+    /// reply if one is attached, line by line while the command runs.
+    #[allow(clippy::too_many_arguments)]
     async fn run_claude_code(
         &self,
+        task_id: Uuid,
         sandbox_id: &str,
         prompt: &str,
         cwd: Option<&str>,
         timeout_secs: Option<u64>,
         env_vars: Option<HashMap<String, String>>,
+        mask: &SecretMask,
     ) -> Result<CommandResult> {
         // Write prompt to file (this is safe - no secrets in prompt)
         let prompt_path = "/tmp/claude_prompt.md";
@@ -218,15 +898,12 @@ impl TaskExecutor {
             .map_err(|e| anyhow!("Failed to write prompt: {}", e))?;
 
         // Build command - no longer sources .env file since credentials are passed via env vars
-        let cmd = format!(
-            "claude --yes --print \"$(cat {})\"",
-            prompt_path
-        );
+        let cmd = build_claude_command(prompt_path, self.claude_model.as_deref(), &self.claude_extra_args);
 
         // Execute with env vars passed inline (secure - not written to disk)
-        let result = self
+        let mut stream = self
             .daytona
-            .execute_command_with_env(
+            .execute_command_streaming(
                 sandbox_id,
                 &cmd,
                 cwd,
@@ -236,114 +913,307 @@ impl TaskExecutor {
             .await
             .map_err(|e| anyhow!("Command execution failed: {}", e))?;
 
+        // Only whole lines are published, so a secret split across chunks is still masked
+        let mut pending = String::new();
+        while let Some(chunk) = stream.next_chunk().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    stream.abort().await;
+                    return Err(anyhow!("Command execution failed: {}", e));
+                }
+            };
+            pending.push_str(&chunk);
+            while let Some(end) = pending.find('\n') {
+                let line: String = pending.drain(..=end).collect();
+                self.publish_output(task_id, line.trim_end_matches(['\n', '\r']), mask).await;
+                self.publish_chat(&line, mask).await;
+            }
+        }
+        if !pending.is_empty() {
+            self.publish_output(task_id, &pending, mask).await;
+            self.publish_chat(&pending, mask).await;
+        }
+
+        let result = stream
+            .finish()
+            .await
+            .map_err(|e| anyhow!("Command execution failed: {}", e))?;
+
         Ok(result)
     }
 
-    /// Build the task prompt for Claude Code
-    fn build_task_prompt(&self, task: &SwarmTask, workspace_path: &str) -> String {
-        // Extract skill and CLI from description
-        let skill_name = extract_skill_name(task.description.as_deref());
-        let required_clis = extract_cli_names(task.description.as_deref());
+    /// Publish a line of agent output to the task's log subscribers
+    async fn publish_output(&self, task_id: Uuid, line: &str, mask: &SecretMask) {
+        if let Some(ref broadcaster) = self.log_broadcaster {
+            broadcaster
+                .publish_log(task_id, LogEntry::info(mask.apply(line)).with_source(LogSource::Executor))
+                .await;
+        }
+    }
+
+    /// Append agent output to the chat reply, if one is streaming
+    async fn publish_chat(&self, text: &str, mask: &SecretMask) {
+        if let Some(ref chat) = self.chat_reply {
+            chat.reply.lock().await.push(&chat.broadcaster, &mask.apply(text)).await;
+        }
+    }
 
-        // Clean description
-        let description = task
-            .description
-            .as_deref()
-            .map(clean_description)
-            .unwrap_or_default();
+    /// Drop the chat reply's text from earlier attempts
+    async fn clear_chat(&self) {
+        if let Some(ref chat) = self.chat_reply {
+            chat.reply.lock().await.clear();
+        }
+    }
 
-        let mut prompt = String::new();
+    /// Tell log subscribers the task's run is over
+    async fn publish_end(&self, task_id: Uuid, end: LogEnd) {
+        if let Some(ref broadcaster) = self.log_broadcaster {
+            broadcaster.publish_log_end(task_id, end).await;
+        }
+    }
 
-        // Agent identity
-        prompt.push_str("# Agent: Worker\n\n");
+    /// Calculate retry delay with exponential backoff
+    fn calculate_retry_delay(&self, attempt: i32) -> u64 {
+        let base = self.retry_config.base_delay_ms as f64;
+        let multiplier = self.retry_config.backoff_multiplier;
+        (base * multiplier.powi(attempt - 1)) as u64
+    }
+}
 
-        // Task header
-        prompt.push_str(&format!(
-            "## Task: {}\n\
-             Priority: {} | Tags: {}\n\
-             Workspace: {}\n\
-             Mode: TASK EXECUTION - Complete autonomously\n\n",
-            task.title,
-            task.priority,
-            task.tags.join(", "),
-            workspace_path
-        ));
+/// Working directory for a task: its skill's `workdir`, else the workspace
+fn task_workdir(skills_path: &str, swarm: &Swarm, task: &SwarmTask) -> String {
+    let workdir = extract_skill_name(task.description.as_deref())
+        .filter(|skill| swarm.allows_skill(skill))
+        .and_then(|skill| read_skill_frontmatter(Path::new(skills_path), &skill))
+        .and_then(|frontmatter| frontmatter.workdir);
 
-        // Description section
-        if !description.is_empty() {
-            prompt.push_str(&format!("### Details\n{}\n\n", description));
+    resolve_workdir(WORKSPACE_PATH, workdir.as_deref())
+}
+
+/// Build the task prompt for Claude Code
+///
+/// Only skills in the swarm's allowlist are advertised or loaded. `{{name}}`
+/// placeholders in the template and the description are replaced from
+/// `prompt_vars`; `workspace` and `cli_path` default to the sandbox paths.
+fn build_task_prompt(
+    skills_path: &str,
+    prompt_vars: &HashMap<String, String>,
+    swarm: &Swarm,
+    task: &SwarmTask,
+    workspace_path: &str,
+) -> String {
+    // Extract skill and CLI from description
+    let skill_name = extract_skill_name(task.description.as_deref()).filter(|skill| {
+        let allowed = swarm.allows_skill(skill);
+        if !allowed {
+            warn!(task_id = %task.id, skill = %skill, "Skill not in swarm allowlist, not loading it");
         }
+        allowed
+    });
+    let required_clis = extract_cli_names(task.description.as_deref());
 
-        // Environment setup
-        prompt.push_str(&format!(
-            "## Setup\n\
-             **Tools:** Node.js 22, Python 3, Git, curl, jq. Standard dev environment.\n\
-             **Skills:** `ls {}/` | **CLIs:** `ls /data/.claude/cli/`\n\
-             **Note:** API credentials are automatically available in environment.\n\n",
-            self.skills_path
-        ));
+    // Clean description
+    let description = task
+        .description
+        .as_deref()
+        .map(clean_description)
+        .unwrap_or_default();
 
-        // Skill loading
-        if let Some(skill) = skill_name {
-            prompt.push_str(&format!(
-                "### Load Skill: {}\n\
-                 ```bash\n\
-                 cat {}/{}/SKILL.md\n\
-                 ```\n\
-                 Follow the skill instructions carefully.\n\n",
-                skill, self.skills_path, skill
-            ));
-        }
+    let mut prompt = String::new();
 
-        // CLI loading (for non-secret CLI configs only)
-        if !required_clis.is_empty() {
-            prompt.push_str(&format!(
-                "### Available CLIs: {}\n\
-                 Check CLI documentation at `/data/.claude/cli/<cli-name>/` for usage.\n\n",
-                required_clis.join(", ")
-            ));
-        }
+    // Agent identity
+    prompt.push_str("# Agent: Worker\n\n");
 
-        // Thinking framework
-        prompt.push_str(
-            "## Think First\n\
-             1. **SUCCESS**: What defines \"done\" for this task?\n\
-             2. **STEPS**: What sequence achieves this?\n\
-             3. **RISKS**: What could fail? How to handle?\n\n",
-        );
+    // Task header
+    prompt.push_str(&format!(
+        "## Task: {}\n\
+         Priority: {} | Tags: {}\n\
+         Workspace: {}\n\
+         Mode: TASK EXECUTION - Complete autonomously\n\n",
+        task.title,
+        task.priority,
+        task.tags.join(", "),
+        "{{workspace}}"
+    ));
 
-        // Execution instructions
-        prompt.push_str(
-            "## Execute\n\
-             - Complete autonomously - proceed with reasonable assumptions\n\
-             - Make reasonable assumptions, note them in output\n\
-             - If blocked, try alternative approach before reporting failure\n\n",
-        );
+    // Description section
+    if !description.is_empty() {
+        prompt.push_str(&format!("### Details\n{}\n\n", description));
+    }
 
-        // Output rules
-        prompt.push_str(
-            "## Output Rules\n\
-             **ALWAYS filter outputs to save context:**\n\
-             - `command | head -20` or `| tail -20` for long outputs\n\
-             - `curl ... | jq '.field'` to extract specific data\n\
-             - **Max 50 lines** per command output\n\
-             - Summarize all results concisely\n\n\
-             **Response format:**\n\
-             - SUMMARY: 1-2 sentences of what was done\n\
-             - FILES: Created/modified paths (if any)\n\
-             - ISSUES: Problems encountered (if any)\n\
-             - NEXT: Suggested follow-up (if applicable)\n",
-        );
+    // Environment setup
+    let skills_hint = if swarm.allowed_skills.is_empty() {
+        format!("`ls {}/`", skills_path)
+    } else {
+        format!("{} (in `{}/`)", swarm.allowed_skills.join(", "), skills_path)
+    };
+    prompt.push_str(&format!(
+        "## Setup\n\
+         **Tools:** Node.js 22, Python 3, Git, curl, jq. Standard dev environment.\n\
+         **Skills:** {} | **CLIs:** `ls {}/`\n\
+         **Note:** API credentials are automatically available in environment.\n\n",
+        skills_hint, "{{cli_path}}"
+    ));
 
-        prompt
+    // Skill loading
+    if let Some(skill) = skill_name {
+        prompt.push_str(&format!(
+            "### Load Skill: {}\n\
+             ```bash\n\
+             cat {}/{}/SKILL.md\n\
+             ```\n\
+             Follow the skill instructions carefully.\n\n",
+            skill, skills_path, skill
+        ));
     }
 
-    /// Calculate retry delay with exponential backoff
-    fn calculate_retry_delay(&self, attempt: i32) -> u64 {
-        let base = self.retry_config.base_delay_ms as f64;
-        let multiplier = self.retry_config.backoff_multiplier;
-        (base * multiplier.powi(attempt - 1)) as u64
+    // CLI loading (for non-secret CLI configs only)
+    if !required_clis.is_empty() {
+        prompt.push_str(&format!(
+            "### Available CLIs: {}\n\
+             Check CLI documentation at `{}/<cli-name>/` for usage.\n\n",
+            required_clis.join(", "),
+            "{{cli_path}}"
+        ));
     }
+
+    // Thinking framework
+    prompt.push_str(
+        "## Think First\n\
+         1. **SUCCESS**: What defines \"done\" for this task?\n\
+         2. **STEPS**: What sequence achieves this?\n\
+         3. **RISKS**: What could fail? How to handle?\n\n",
+    );
+
+    // Execution instructions
+    prompt.push_str(
+        "## Execute\n\
+         - Complete autonomously - proceed with reasonable assumptions\n\
+         - Make reasonable assumptions, note them in output\n\
+         - If blocked, try alternative approach before reporting failure\n\n",
+    );
+
+    // Output rules
+    prompt.push_str(
+        "## Output Rules\n\
+         **ALWAYS filter outputs to save context:**\n\
+         - `command | head -20` or `| tail -20` for long outputs\n\
+         - `curl ... | jq '.field'` to extract specific data\n\
+         - **Max 50 lines** per command output\n\
+         - Summarize all results concisely\n\n\
+         **Response format:**\n\
+         - SUMMARY: 1-2 sentences of what was done\n\
+         - FILES: Created/modified paths (if any)\n\
+         - ISSUES: Problems encountered (if any)\n\
+         - NEXT: Suggested follow-up (if applicable)\n",
+    );
+
+    let mut vars = HashMap::from([
+        ("workspace".to_string(), workspace_path.to_string()),
+        ("cli_path".to_string(), CLI_DOCS_PATH.to_string()),
+    ]);
+    vars.extend(prompt_vars.iter().map(|(name, value)| (name.clone(), value.clone())));
+    apply_prompt_vars(&prompt, &vars)
+}
+
+/// Replace each `{{name}}` placeholder with its value in `vars`
+///
+/// Placeholders without a value are left as written, and substituted values
+/// are not scanned again.
+fn apply_prompt_vars(text: &str, vars: &HashMap<String, String>) -> String {
+    PROMPT_VAR_REGEX
+        .replace_all(text, |caps: &regex::Captures| match vars.get(&caps[1]) {
+            Some(value) => value.clone(),
+            None => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+/// A skill referenced by a task and whether the executor can load it
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct PlannedSkill {
+    pub name: String,
+    /// The skill has a SKILL.md in the skills directory
+    pub found: bool,
+    /// The swarm's allowlist permits it; disallowed skills are left out of the prompt
+    pub allowed: bool,
+}
+
+/// A CLI referenced by a task and whether its docs are installed
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct PlannedCli {
+    pub name: String,
+    pub found: bool,
+}
+
+/// What the executor would send for a task, resolved without a sandbox
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TaskPlan {
+    pub prompt: String,
+    pub workdir: String,
+    pub skill: Option<PlannedSkill>,
+    pub clis: Vec<PlannedCli>,
+}
+
+/// Resolve a task's prompt, skill and CLIs against the local skills directory
+///
+/// CLI docs are looked up in the `cli` directory next to the skills
+/// directory, mirroring `/data/.claude/cli` in the sandbox. This reads the
+/// filesystem, so call it off the async runtime.
+pub fn plan_task(
+    skills_path: &str,
+    prompt_vars: &HashMap<String, String>,
+    swarm: &Swarm,
+    task: &SwarmTask,
+) -> TaskPlan {
+    let workdir = task_workdir(skills_path, swarm, task);
+    let prompt = build_task_prompt(skills_path, prompt_vars, swarm, task, &workdir);
+
+    let skills_dir = Path::new(skills_path);
+    let skill = extract_skill_name(task.description.as_deref()).map(|name| PlannedSkill {
+        found: read_skill_frontmatter(skills_dir, &name).is_some(),
+        allowed: swarm.allows_skill(&name),
+        name,
+    });
+
+    let cli_dir = skills_dir.parent().map(|dir| dir.join("cli"));
+    let clis = extract_cli_names(task.description.as_deref())
+        .into_iter()
+        .map(|name| {
+            let safe = !name.contains("..") && !name.contains('/') && !name.contains('\\');
+            let found = safe && cli_dir.as_ref().is_some_and(|dir| dir.join(&name).is_dir());
+            PlannedCli { name, found }
+        })
+        .collect();
+
+    TaskPlan { prompt, workdir, skill, clis }
+}
+
+/// Plan a task that is never stored, to preview the prompt a skill produces
+///
+/// The task references `skill` ahead of `description` and runs under an
+/// unrestricted swarm, so the skill is always loaded.
+pub fn plan_skill_preview(
+    skills_path: &str,
+    prompt_vars: &HashMap<String, String>,
+    skill: &str,
+    title: &str,
+    description: Option<&str>,
+    tags: Vec<String>,
+) -> TaskPlan {
+    let description = match description.filter(|d| !d.trim().is_empty()) {
+        Some(description) => format!("SKILL: {}\n{}", skill, description),
+        None => format!("SKILL: {}", skill),
+    };
+    let task = SwarmTask {
+        title: title.to_string(),
+        description: Some(description),
+        tags,
+        ..Default::default()
+    };
+
+    plan_task(skills_path, prompt_vars, &Swarm::default(), &task)
 }
 
 // Static regex patterns compiled once for performance
@@ -351,6 +1221,11 @@ static SKILL_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"(?im)^SKILL:\s*([^\n]+)").expect("Invalid SKILL regex"));
 static CLI_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"(?im)^CLI:\s*([^\n]+)").expect("Invalid CLI regex"));
+static PROMPT_VAR_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").expect("Invalid PROMPT_VAR regex")
+});
+static PROMPT_VAR_NAME_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").expect("Invalid PROMPT_VAR_NAME regex"));
 static SKILL_CLEAN_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"(?im)^SKILL:\s*[^\n]+\n*").expect("Invalid SKILL_CLEAN regex"));
 static CLI_CLEAN_REGEX: Lazy<Regex> =
@@ -392,6 +1267,44 @@ fn clean_description(description: &str) -> String {
     cleaned.trim().to_string()
 }
 
+/// Sections of the response format requested in the task prompt
+const OUTPUT_SECTIONS: [&str; 4] = ["SUMMARY", "FILES", "ISSUES", "NEXT"];
+
+/// The SUMMARY section of an agent's output, if it followed the response format
+///
+/// The section runs until a blank line or the next section header; markdown
+/// decoration like `- **SUMMARY:**` is tolerated.
+pub(crate) fn extract_output_summary(output: &str) -> Option<String> {
+    let mut lines = output.lines();
+    let mut summary = lines
+        .by_ref()
+        .find_map(|line| section_body(line, "SUMMARY"))?
+        .to_string();
+
+    for line in lines {
+        let line = line.trim();
+        if line.is_empty() || OUTPUT_SECTIONS.iter().any(|name| section_body(line, name).is_some()) {
+            break;
+        }
+        summary.push(' ');
+        summary.push_str(line);
+    }
+
+    let summary = summary.trim();
+    (!summary.is_empty()).then(|| summary.to_string())
+}
+
+/// Text after `NAME:` when `line` starts the named response section
+fn section_body<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let line = line.trim_start().trim_start_matches(['-', '*', '#', ' ']);
+    let head = line.get(..name.len())?;
+    if !head.eq_ignore_ascii_case(name) {
+        return None;
+    }
+    let rest = line[name.len()..].trim_start_matches('*').strip_prefix(':')?;
+    Some(rest.trim_start_matches('*').trim())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -415,9 +1328,170 @@ mod tests {
         assert_eq!(clis, vec!["stripe-cli".to_string(), "vercel".to_string()]);
     }
 
+    #[test]
+    fn test_extract_output_summary() {
+        let output = "Working...\n\n- **SUMMARY:** Added the endpoint\n  and its tests.\n- FILES: src/api.rs\n- NEXT: deploy";
+        assert_eq!(
+            extract_output_summary(output).as_deref(),
+            Some("Added the endpoint and its tests.")
+        );
+
+        assert_eq!(
+            extract_output_summary("Summary: done\n\nmore text").as_deref(),
+            Some("done")
+        );
+        assert_eq!(extract_output_summary("no structure here"), None);
+        assert_eq!(extract_output_summary("SUMMARY:\nFILES: a.rs"), None);
+    }
+
+    #[test]
+    fn test_build_claude_command_default() {
+        let cmd = build_claude_command("/tmp/p.md", None, &[]);
+        assert_eq!(cmd, "claude --yes --print \"$(cat /tmp/p.md)\"");
+    }
+
+    #[test]
+    fn test_build_claude_command_with_model_and_args() {
+        let args = vec!["--max-turns=10".to_string(), "--verbose".to_string()];
+        let cmd = build_claude_command("/tmp/p.md", Some("claude-sonnet-4-5"), &args);
+        assert!(cmd.starts_with("claude --yes --print --model claude-sonnet-4-5"));
+        assert!(cmd.contains("--max-turns=10"));
+        assert!(cmd.contains(" --verbose "));
+        assert!(cmd.ends_with("\"$(cat /tmp/p.md)\""));
+    }
+
+    #[test]
+    fn test_validate_claude_options() {
+        assert!(validate_claude_model("claude-opus-4-1").is_ok());
+        assert!(validate_claude_model("opus; rm -rf /").is_err());
+        assert!(validate_claude_model("").is_err());
+
+        assert!(validate_claude_extra_args(&["--max-turns=5".to_string()]).is_ok());
+        assert!(validate_claude_extra_args(&["--verbose".to_string()]).is_ok());
+        assert!(validate_claude_extra_args(&["--print".to_string()]).is_err());
+        assert!(validate_claude_extra_args(&["--model=other".to_string()]).is_err());
+        assert!(validate_claude_extra_args(&["; curl evil".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_cli_probe() {
+        let clis = vec!["stripe".to_string(), "it's".to_string(), "gh".to_string()];
+        assert_eq!(
+            build_cli_probe_command(&clis),
+            "for c in 'stripe' 'it'\\''s' 'gh'; do command -v \"$c\" >/dev/null 2>&1 || echo \"$c\"; done"
+        );
+
+        assert_eq!(missing_clis(&clis, "gh\nstripe\n"), vec!["stripe", "gh"]);
+        assert!(missing_clis(&clis, "").is_empty());
+        assert_eq!(
+            missing_clis_message(&["stripe".to_string(), "gh".to_string()]),
+            "Required CLIs not found in sandbox: stripe, gh. Install them in the snapshot or the swarm setup script."
+        );
+    }
+
+    #[test]
+    fn test_validate_result_path() {
+        assert!(validate_result_path("/workspace/out/result.md").is_ok());
+        assert!(validate_result_path("/workspace").is_err());
+        assert!(validate_result_path("/workspace/").is_err());
+        assert!(validate_result_path("/workspace-other/x").is_err());
+        assert!(validate_result_path("workspace/x").is_err());
+        assert!(validate_result_path("/workspace/../etc/passwd").is_err());
+        assert!(validate_result_path("/workspace/a//b").is_err());
+        assert!(validate_result_path("/tmp/result.md").is_err());
+    }
+
+    #[test]
+    fn test_secret_mask() {
+        let secrets = HashMap::from([
+            ("SHORT".to_string(), "abc".to_string()),
+            ("LONG".to_string(), "abcdef".to_string()),
+            ("EMPTY".to_string(), String::new()),
+        ]);
+        let mask = SecretMask::new(&secrets);
+        assert_eq!(
+            mask.apply("key=abcdef other=abc plain"),
+            "key=******** other=******** plain"
+        );
+        assert_eq!(SecretMask::new(&HashMap::new()).apply("abc"), "abc");
+    }
+
+    #[test]
+    fn test_setup_failure_message() {
+        let result = CommandResult {
+            success: false,
+            output: "installing deps\n".to_string(),
+            error: "npm ERR! missing script\nnpm ERR! exiting\n\n".to_string(),
+            exit_code: 2,
+        };
+        assert_eq!(
+            setup_failure_message(&result),
+            "Setup script failed with exit code 2: npm ERR! exiting"
+        );
+
+        let silent = CommandResult {
+            success: false,
+            output: String::new(),
+            error: String::new(),
+            exit_code: 1,
+        };
+        assert_eq!(
+            setup_failure_message(&silent),
+            "Setup script failed with exit code 1: no output"
+        );
+    }
+
     #[test]
     fn test_clean_description() {
         let desc = "SKILL: test\nCLI: foo\n\nActual description here";
         assert_eq!(clean_description(desc), "Actual description here");
     }
+
+    #[test]
+    fn test_prompt_vars_substitution() {
+        let task = SwarmTask {
+            title: "Deploy".to_string(),
+            description: Some("CLI: stripe\nPush to {{ registry }}/{{image}} from {{workspace}}".to_string()),
+            ..Default::default()
+        };
+        let vars = HashMap::from([
+            ("registry".to_string(), "ghcr.io/acme".to_string()),
+            ("image".to_string(), "api".to_string()),
+            ("cli_path".to_string(), "/opt/cli".to_string()),
+        ]);
+
+        let prompt = build_task_prompt("/skills", &vars, &Swarm::default(), &task, "/workspace/api");
+        assert!(prompt.contains("Push to ghcr.io/acme/api from /workspace/api"));
+        assert!(prompt.contains("Workspace: /workspace/api\n"));
+        assert!(prompt.contains("`ls /opt/cli/`"));
+        assert!(prompt.contains("`/opt/cli/<cli-name>/`"));
+        assert!(!prompt.contains("/data/.claude/cli"));
+
+        // Substituted values are not expanded again
+        let vars = HashMap::from([("a".to_string(), "{{b}}".to_string()), ("b".to_string(), "x".to_string())]);
+        assert_eq!(apply_prompt_vars("{{a}} {{b}}", &vars), "{{b}} x");
+    }
+
+    #[test]
+    fn test_prompt_vars_missing_variable() {
+        let task = SwarmTask {
+            title: "Report".to_string(),
+            description: Some("Upload to {{bucket}} then {{ also_unset }}; keep {{not a var}}".to_string()),
+            ..Default::default()
+        };
+
+        let prompt = build_task_prompt("/skills", &HashMap::new(), &Swarm::default(), &task, "/workspace");
+        assert!(prompt.contains("Upload to {{bucket}} then {{ also_unset }}; keep {{not a var}}"));
+        // Built-in paths apply without any configured variables
+        assert!(prompt.contains("Workspace: /workspace\n"));
+        assert!(prompt.contains("`ls /data/.claude/cli/`"));
+    }
+
+    #[test]
+    fn test_validate_prompt_vars() {
+        assert!(validate_prompt_vars(&HashMap::from([("REGISTRY_2".to_string(), "x".to_string())])).is_ok());
+        assert!(validate_prompt_vars(&HashMap::from([("2fast".to_string(), "x".to_string())])).is_err());
+        assert!(validate_prompt_vars(&HashMap::from([("a-b".to_string(), "x".to_string())])).is_err());
+        assert!(validate_prompt_vars(&HashMap::from([("a".to_string(), "x".repeat(4097))])).is_err());
+    }
 }
@@ -6,18 +6,46 @@
 mod broadcast;
 mod chat;
 mod daytona;
+mod decompose;
 mod executor;
 mod pool;
+mod pool_history;
+mod skills;
 mod swarm;
+mod task_logs;
 mod trigger;
+mod webhook;
 
 pub use broadcast::{
-    BroadcastManager, BroadcastStats, ChatBroadcastMessage, ChatBroadcaster, ChatMessageData,
-    LogBroadcaster, LogEnd, LogEntry, LogMessage, PoolBroadcaster, PoolStatusUpdate,
+    BroadcastManager, BroadcastStats, ChatBroadcastMessage, ChatBroadcaster, ChatDelta, ChatEvent,
+    ChatMessageData, LogBroadcaster, LogEnd, LogEntry, LogLevel, LogMessage, LogSource,
+    LogSubscription, PoolBroadcaster, PoolStatusUpdate,
+};
+pub use chat::{
+    ChatError, ChatService, GetMessagesOptions, MessageMetadata, StreamedReply, MAX_MESSAGE_LEN,
+};
+pub use daytona::{
+    BinaryCommandResult, CommandHook, CommandRecord, CommandResult, CommandStream, DaytonaClient,
+    DaytonaConfig, DaytonaError, FileEntry, GitCommitOutcome, PreviewUrl,
+};
+pub use decompose::{
+    create_subtasks, decompose_task, parse_plan, DecomposeError, PlannedSubtask,
+    MAX_DECOMPOSE_SUBTASKS,
+};
+pub use executor::{
+    kill_task_processes, plan_skill_preview, plan_task, validate_claude_extra_args,
+    validate_claude_model, validate_prompt_vars, validate_result_path, validate_workspace_file,
+    ExecutionResult, PlannedCli, PlannedSkill, RetryConfig, TaskExecutor, TaskPlan,
+};
+pub use pool::{AgentRole, PoolConfig, PoolManager, PoolStats, PoolStatus, ReconciledSandbox, SandboxInfo};
+pub use pool_history::PoolHistorySampler;
+pub use skills::{
+    read_skill_frontmatter, resolve_workdir, split_skill_frontmatter, SkillFrontmatter, SKILL_FILE,
 };
-pub use chat::{ChatService, GetMessagesOptions, MessageMetadata};
-pub use daytona::{CommandResult, DaytonaClient, DaytonaConfig, DaytonaError};
-pub use executor::{ExecutionResult, RetryConfig, TaskExecutor};
-pub use pool::{AgentRole, PoolConfig, PoolManager, PoolStats, PoolStatus, SandboxInfo};
 pub use swarm::{SwarmService, SwarmServiceError, SwarmStats};
+pub use task_logs::{compress_log, decompress_log, save_task_log, TaskLog};
 pub use trigger::{TriggerConfig, TriggerEngine, TriggerStats};
+pub use webhook::{
+    WebhookDeliverer, WebhookDelivery, WebhookError, WebhookRetryConfig, WEBHOOK_EVENT_HEADER,
+    WEBHOOK_TASK_COMPLETED, WEBHOOK_TASK_FAILED,
+};
@@ -3,20 +3,26 @@
 //! Manages dynamic sandbox creation, pooling, cleanup, and health checks.
 //! Migrated from PoolManager.js
 
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use db::models::sandbox::{CreateSandbox, Sandbox, SandboxStatus};
 use db::models::swarm_config::SwarmConfig;
+use db::models::swarm_task::SwarmTask;
 use serde::Serialize;
 use sqlx::SqlitePool;
 use thiserror::Error;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 use ts_rs::TS;
 use uuid::Uuid;
 
+use super::daytona::{DaytonaClient, DaytonaError, Sandbox as DaytonaSandbox};
+
 #[derive(Debug, Error)]
 pub enum PoolError {
     #[error(transparent)]
@@ -33,6 +39,8 @@ pub enum PoolError {
     CreationFailed(String),
     #[error("Already creating sandbox for task: {0}")]
     AlreadyCreating(Uuid),
+    #[error(transparent)]
+    Daytona(#[from] DaytonaError),
 }
 
 pub type Result<T> = std::result::Result<T, PoolError>;
@@ -52,6 +60,8 @@ pub struct PoolStatus {
 pub struct PoolConfig {
     pub max_sandboxes: i32,
     pub idle_timeout_minutes: i32,
+    /// Idle sandboxes kept warm; these are never reaped for being idle
+    pub min_idle: i32,
     pub default_snapshot: String,
 }
 
@@ -62,6 +72,7 @@ pub struct PoolStats {
     pub total: usize,
     pub busy: usize,
     pub idle: usize,
+    pub draining: usize,
     pub destroyed: usize,
 }
 
@@ -137,10 +148,33 @@ impl AgentRole {
     }
 }
 
+/// Pick the least recently used sandbox, preferring one that last ran `role`
+///
+/// `idle` must be ordered least recently used first, as `Sandbox::find_idle` returns it.
+pub(crate) fn pick_idle_sandbox(idle: Vec<Sandbox>, role: Option<AgentRole>) -> Option<Sandbox> {
+    let index = role
+        .and_then(|role| idle.iter().position(|s| s.last_role.as_deref() == Some(role.as_str())))
+        .unwrap_or(0);
+    idle.into_iter().nth(index)
+}
+
+/// Daytona states of a sandbox that is gone or going away
+const REMOTE_GONE_STATES: [&str; 2] = ["destroyed", "destroying"];
+
+/// A sandbox that no longer exists in Daytona, now marked destroyed
+#[derive(Debug, Clone)]
+pub struct ReconciledSandbox {
+    pub sandbox: Sandbox,
+    /// Task that was running in the sandbox, moved back to pending
+    pub requeued_task_id: Option<Uuid>,
+}
+
 /// PoolManager handles sandbox lifecycle and pooling
 pub struct PoolManager {
     /// Set of task IDs currently being created
     creating_sandboxes: Arc<RwLock<HashSet<Uuid>>>,
+    /// Cancellation tokens of in-flight creations, by task id
+    creation_tokens: Arc<RwLock<HashMap<Uuid, CancellationToken>>>,
 }
 
 impl Default for PoolManager {
@@ -153,6 +187,7 @@ impl PoolManager {
     pub fn new() -> Self {
         Self {
             creating_sandboxes: Arc::new(RwLock::new(HashSet::new())),
+            creation_tokens: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -162,6 +197,7 @@ impl PoolManager {
         Ok(PoolConfig {
             max_sandboxes: config.pool_max_sandboxes,
             idle_timeout_minutes: config.pool_idle_timeout_minutes,
+            min_idle: config.pool_min_idle,
             default_snapshot: config.pool_default_snapshot,
         })
     }
@@ -181,6 +217,7 @@ impl PoolManager {
                 match s.status {
                     SandboxStatus::Busy => stats.busy += 1,
                     SandboxStatus::Idle => stats.idle += 1,
+                    SandboxStatus::Draining => stats.draining += 1,
                     SandboxStatus::Destroyed => stats.destroyed += 1,
                 }
 
@@ -228,17 +265,24 @@ impl PoolManager {
         self.creating_sandboxes.read().await.contains(&task_id)
     }
 
-    /// Find an idle sandbox for a swarm
+    /// Find an idle sandbox for a swarm that no waiting task is pinned to
+    ///
+    /// Only sandboxes owned by the swarm or by no swarm (the warm pool) are
+    /// considered. With a role, a sandbox whose last task had the same role is
+    /// preferred.
     pub async fn find_idle_sandbox(
         &self,
         pool: &SqlitePool,
         swarm_id: Uuid,
+        role: Option<AgentRole>,
     ) -> Result<Option<Sandbox>> {
-        let idle_sandboxes = Sandbox::find_idle(pool).await?;
-
-        let sandbox = idle_sandboxes
+        let idle_sandboxes: Vec<Sandbox> = Sandbox::find_idle_unpinned(pool)
+            .await?
             .into_iter()
-            .find(|s| s.swarm_id == Some(swarm_id));
+            .filter(|s| s.swarm_id.is_none_or(|id| id == swarm_id))
+            .collect();
+
+        let sandbox = pick_idle_sandbox(idle_sandboxes, role);
 
         if let Some(ref s) = sandbox {
             tracing::info!(sandbox_id = %s.id, "Reusing idle sandbox from pool");
@@ -253,11 +297,13 @@ impl PoolManager {
         pool: &SqlitePool,
         daytona_id: String,
         swarm_id: Option<Uuid>,
+        snapshot: Option<String>,
     ) -> Result<Sandbox> {
         let sandbox_id = Uuid::new_v4();
         let data = CreateSandbox {
             daytona_id: daytona_id.clone(),
             swarm_id,
+            snapshot,
         };
 
         let sandbox = Sandbox::create(pool, &data, sandbox_id).await?;
@@ -272,18 +318,108 @@ impl PoolManager {
     }
 
     /// Mark creation as started for a task
-    pub async fn start_creating(&self, task_id: Uuid) -> Result<()> {
+    ///
+    /// The returned token is cancelled by `cancel_creating`.
+    pub async fn start_creating(&self, task_id: Uuid) -> Result<CancellationToken> {
         let mut creating = self.creating_sandboxes.write().await;
         if creating.contains(&task_id) {
             return Err(PoolError::AlreadyCreating(task_id));
         }
         creating.insert(task_id);
-        Ok(())
+
+        let token = CancellationToken::new();
+        self.creation_tokens.write().await.insert(task_id, token.clone());
+        Ok(token)
     }
 
     /// Mark creation as finished for a task
     pub async fn finish_creating(&self, task_id: Uuid) {
         self.creating_sandboxes.write().await.remove(&task_id);
+        self.creation_tokens.write().await.remove(&task_id);
+    }
+
+    /// Abort the in-flight sandbox creation for a task; false if there is none
+    pub async fn cancel_creating(&self, task_id: Uuid) -> bool {
+        match self.creation_tokens.read().await.get(&task_id) {
+            Some(token) => {
+                token.cancel();
+                tracing::info!(task_id = %task_id, "Cancelling sandbox creation");
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Task ids with a sandbox creation in flight
+    pub async fn creating_task_ids(&self) -> Vec<Uuid> {
+        self.creating_sandboxes.read().await.iter().copied().collect()
+    }
+
+    /// Create a Daytona sandbox for a task, abandoning it if the task is cancelled
+    ///
+    /// Returns `Ok(None)` when cancelled. Cancelling drops the pending create
+    /// request; a sandbox that was created before the cancellation was seen is
+    /// deleted right away so it is not left running unregistered.
+    pub async fn create_sandbox_for_task(
+        &self,
+        daytona: &DaytonaClient,
+        task_id: Uuid,
+        name: Option<String>,
+    ) -> Result<Option<DaytonaSandbox>> {
+        let token = self.start_creating(task_id).await?;
+        let outcome = until_cancelled(&token, daytona.create_sandbox_from_snapshot(name)).await;
+        self.finish_creating(task_id).await;
+
+        match outcome {
+            None => {
+                tracing::info!(task_id = %task_id, "Sandbox creation cancelled");
+                Ok(None)
+            }
+            Some(Err(e)) => Err(PoolError::CreationFailed(e.to_string())),
+            Some(Ok(sandbox)) if token.is_cancelled() => {
+                tracing::info!(
+                    task_id = %task_id,
+                    daytona_id = %sandbox.id,
+                    "Task cancelled during sandbox creation, deleting sandbox"
+                );
+                if let Err(e) = daytona.delete_sandbox(&sandbox.id).await {
+                    tracing::warn!(daytona_id = %sandbox.id, error = %e, "Failed to delete abandoned sandbox");
+                }
+                Ok(None)
+            }
+            Some(Ok(sandbox)) => Ok(Some(sandbox)),
+        }
+    }
+
+    /// Create a sandbox for a swarm's task and register it in the pool
+    ///
+    /// Creation is deduplicated and cancellable per task, as in
+    /// `create_sandbox_for_task`; `Ok(None)` means it was cancelled. A sandbox
+    /// that Daytona created but the database could not record is deleted again
+    /// so it is not left running untracked.
+    pub async fn create_sandbox(
+        &self,
+        pool: &SqlitePool,
+        daytona: &DaytonaClient,
+        swarm_id: Uuid,
+        task_id: Uuid,
+    ) -> Result<Option<Sandbox>> {
+        let Some(remote) = self.create_sandbox_for_task(daytona, task_id, None).await? else {
+            return Ok(None);
+        };
+
+        match self
+            .register_sandbox(pool, remote.id.clone(), Some(swarm_id), remote.snapshot.clone())
+            .await
+        {
+            Ok(sandbox) => Ok(Some(sandbox)),
+            Err(e) => {
+                if let Err(delete_err) = daytona.delete_sandbox(&remote.id).await {
+                    tracing::warn!(daytona_id = %remote.id, error = %delete_err, "Failed to delete unregistered sandbox");
+                }
+                Err(e)
+            }
+        }
     }
 
     /// Assign a task to a sandbox
@@ -328,7 +464,10 @@ impl PoolManager {
             .await?
             .ok_or(PoolError::SandboxNotFound(sandbox_id))?;
 
-        if sandbox.status == SandboxStatus::Busy {
+        // A draining sandbox still running its task is as busy as any other
+        if sandbox.status == SandboxStatus::Busy
+            || (sandbox.status == SandboxStatus::Draining && sandbox.current_task_id.is_some())
+        {
             return Err(PoolError::SandboxBusy);
         }
 
@@ -339,29 +478,159 @@ impl PoolManager {
         Ok(())
     }
 
-    /// Cleanup idle sandboxes that have been idle longer than the timeout
-    pub async fn cleanup_idle_sandboxes(&self, pool: &SqlitePool) -> Result<Vec<Uuid>> {
+    /// Mark sandboxes that Daytona no longer has as destroyed
+    ///
+    /// Catches sandboxes removed out of band (auto-stop, crashes, manual
+    /// deletion). A task running in one is moved back to pending. Rows are
+    /// read before Daytona is asked, so a sandbox registered meanwhile is not
+    /// mistaken for a missing one. The full listing is used rather than only
+    /// sandboxes under our name prefix: only our own rows are changed, and a
+    /// sandbox created before the prefix was set is still alive.
+    pub async fn reconcile(
+        &self,
+        pool: &SqlitePool,
+        daytona: &DaytonaClient,
+    ) -> Result<Vec<ReconciledSandbox>> {
+        let local: Vec<Sandbox> = Sandbox::find_all(pool)
+            .await?
+            .into_iter()
+            .filter(|s| s.status != SandboxStatus::Destroyed)
+            .collect();
+        if local.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let remote: HashSet<String> = daytona
+            .list_sandboxes()
+            .await?
+            .into_iter()
+            .filter(|s| !s.state.as_deref().is_some_and(|state| REMOTE_GONE_STATES.contains(&state)))
+            .map(|s| s.id)
+            .collect();
+
+        let mut reconciled = Vec::new();
+        for sandbox in local.into_iter().filter(|s| !remote.contains(&s.daytona_id)) {
+            let mut tx = pool.begin().await?;
+            let requeued_task_id = match sandbox.current_task_id {
+                Some(task_id) if SwarmTask::requeue(&mut *tx, task_id).await? => Some(task_id),
+                _ => None,
+            };
+            Sandbox::mark_destroyed(&mut *tx, sandbox.id).await?;
+            tx.commit().await?;
+
+            tracing::warn!(
+                sandbox_id = %sandbox.id,
+                daytona_id = %sandbox.daytona_id,
+                requeued_task_id = ?requeued_task_id,
+                "Sandbox missing from Daytona, marked destroyed"
+            );
+
+            reconciled.push(ReconciledSandbox {
+                sandbox: Sandbox {
+                    status: SandboxStatus::Destroyed,
+                    current_task_id: None,
+                    ..sandbox
+                },
+                requeued_task_id,
+            });
+        }
+
+        Ok(reconciled)
+    }
+
+    /// Create unassigned idle sandboxes until the pool holds `pool_min_idle` of them
+    ///
+    /// Never grows the pool past `max_sandboxes`. A failed creation stops the
+    /// top-up; the next call tries again. Returns the sandboxes created.
+    pub async fn ensure_warm_pool(
+        &self,
+        pool: &SqlitePool,
+        daytona: &DaytonaClient,
+    ) -> Result<Vec<Sandbox>> {
+        let config = self.get_config(pool).await?;
+        let idle = Sandbox::find_idle(pool).await?.len() as i64;
+        let active = self.get_active_count(pool).await?;
+        let needed = (config.min_idle as i64 - idle).min(config.max_sandboxes as i64 - active);
+
+        let mut created = Vec::new();
+        for _ in 0..needed.max(0) {
+            let remote = match daytona.create_sandbox_from_snapshot(None).await {
+                Ok(remote) => remote,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to create warm sandbox");
+                    break;
+                }
+            };
+
+            match self
+                .register_sandbox(pool, remote.id.clone(), None, remote.snapshot.clone())
+                .await
+            {
+                Ok(sandbox) => created.push(sandbox),
+                Err(e) => {
+                    if let Err(delete_err) = daytona.delete_sandbox(&remote.id).await {
+                        tracing::warn!(daytona_id = %remote.id, error = %delete_err, "Failed to delete unregistered sandbox");
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        if !created.is_empty() {
+            tracing::info!(created = created.len(), min_idle = config.min_idle, "Warm pool topped up");
+        }
+
+        Ok(created)
+    }
+
+    /// Destroy idle sandboxes that have been idle longer than the timeout
+    ///
+    /// The `pool_min_idle` most recently used idle sandboxes are kept warm
+    /// however long they have been idle, and sandboxes a waiting task is pinned
+    /// to are never reaped. A sandbox Daytona fails to delete is left for the
+    /// next cleanup. Returns the destroyed sandboxes.
+    pub async fn cleanup_idle_sandboxes(
+        &self,
+        pool: &SqlitePool,
+        daytona: &DaytonaClient,
+    ) -> Result<Vec<Sandbox>> {
         let config = self.get_config(pool).await?;
         let idle_timeout = Duration::from_secs(config.idle_timeout_minutes as u64 * 60);
         let cutoff = Utc::now()
             - chrono::Duration::from_std(idle_timeout)
                 .expect("idle_timeout should be within chrono::Duration bounds");
 
-        let idle_sandboxes = Sandbox::find_idle(pool).await?;
+        let mut idle_sandboxes = Sandbox::find_idle_unpinned(pool).await?;
+        idle_sandboxes.sort_by_key(|s| Reverse(s.last_used_at.unwrap_or(s.created_at)));
         let mut destroyed = Vec::new();
 
-        for sandbox in idle_sandboxes {
+        for sandbox in idle_sandboxes.into_iter().skip(config.min_idle.max(0) as usize) {
             let last_used = sandbox.last_used_at.unwrap_or(sandbox.created_at);
-            if last_used < cutoff {
-                Sandbox::mark_destroyed(pool, sandbox.id).await?;
-                destroyed.push(sandbox.id);
+            if last_used >= cutoff {
+                continue;
+            }
 
-                tracing::info!(
+            if let Err(e) = daytona.delete_sandbox(&sandbox.daytona_id).await {
+                tracing::warn!(
                     sandbox_id = %sandbox.id,
-                    idle_minutes = config.idle_timeout_minutes,
-                    "Idle sandbox marked for cleanup"
+                    daytona_id = %sandbox.daytona_id,
+                    error = %e,
+                    "Failed to delete idle sandbox from Daytona"
                 );
+                continue;
             }
+            Sandbox::mark_destroyed(pool, sandbox.id).await?;
+
+            tracing::info!(
+                sandbox_id = %sandbox.id,
+                idle_minutes = config.idle_timeout_minutes,
+                "Idle sandbox destroyed"
+            );
+
+            destroyed.push(Sandbox {
+                status: SandboxStatus::Destroyed,
+                ..sandbox
+            });
         }
 
         Sandbox::delete_destroyed(pool).await?;
@@ -390,3 +659,79 @@ impl PoolManager {
         Ok(Sandbox::find_busy(pool).await?)
     }
 }
+
+/// Run `work` unless `token` is cancelled first
+///
+/// Work that is already finished wins over a cancellation arriving at the
+/// same time, so its result can still be cleaned up.
+async fn until_cancelled<F: Future>(token: &CancellationToken, work: F) -> Option<F::Output> {
+    tokio::select! {
+        biased;
+        output = work => Some(output),
+        _ = token.cancelled() => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancel_creating_aborts_pending_work() {
+        let manager = PoolManager::new();
+        let task_id = Uuid::new_v4();
+
+        let token = manager.start_creating(task_id).await.unwrap();
+        assert!(manager.is_creating(task_id).await);
+        assert!(matches!(
+            manager.start_creating(task_id).await,
+            Err(PoolError::AlreadyCreating(_))
+        ));
+        assert_eq!(manager.creating_task_ids().await, vec![task_id]);
+
+        assert!(manager.cancel_creating(task_id).await);
+        assert!(until_cancelled(&token, std::future::pending::<()>()).await.is_none());
+
+        manager.finish_creating(task_id).await;
+        assert!(!manager.is_creating(task_id).await);
+        assert!(!manager.cancel_creating(task_id).await);
+    }
+
+    fn idle_sandbox(last_role: Option<&str>) -> Sandbox {
+        Sandbox {
+            id: Uuid::new_v4(),
+            daytona_id: "daytona".to_string(),
+            swarm_id: None,
+            status: SandboxStatus::Idle,
+            current_task_id: None,
+            snapshot: None,
+            created_at: Utc::now(),
+            last_used_at: None,
+            last_role: last_role.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_pick_idle_sandbox_prefers_role() {
+        let idle = vec![idle_sandbox(Some("frontend")), idle_sandbox(None), idle_sandbox(Some("backend"))];
+        let ids: Vec<Uuid> = idle.iter().map(|s| s.id).collect();
+
+        let picked = pick_idle_sandbox(idle.clone(), Some(AgentRole::Backend)).unwrap();
+        assert_eq!(picked.id, ids[2]);
+
+        // No match, or no role: least recently used
+        let picked = pick_idle_sandbox(idle.clone(), Some(AgentRole::Qa)).unwrap();
+        assert_eq!(picked.id, ids[0]);
+        let picked = pick_idle_sandbox(idle, None).unwrap();
+        assert_eq!(picked.id, ids[0]);
+
+        assert!(pick_idle_sandbox(Vec::new(), Some(AgentRole::Backend)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_finished_work_wins_over_cancellation() {
+        let token = CancellationToken::new();
+        token.cancel();
+        assert_eq!(until_cancelled(&token, async { 7 }).await, Some(7));
+    }
+}
@@ -0,0 +1,87 @@
+//! Pool Utilization History
+//!
+//! Periodically records pool status counts into `pool_status_history` so
+//! utilization can be charted over time, pruning samples past the configured
+//! retention.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use db::models::pool_status_history::{CreatePoolStatusSample, PoolStatusSample};
+use db::models::swarm_config::SwarmConfig;
+use sqlx::SqlitePool;
+use tracing::{debug, error, info};
+
+use super::pool::PoolManager;
+
+/// How often to re-read the config while sampling is disabled or failing
+const DISABLED_RECHECK: Duration = Duration::from_secs(60);
+
+/// Background sampler writing pool status snapshots to the history table
+pub struct PoolHistorySampler {
+    db_pool: SqlitePool,
+    pool_manager: PoolManager,
+}
+
+impl PoolHistorySampler {
+    pub fn new(db_pool: SqlitePool) -> Self {
+        Self {
+            db_pool,
+            pool_manager: PoolManager::new(),
+        }
+    }
+
+    /// Start the sampling loop
+    ///
+    /// The interval and retention are re-read from the config before every
+    /// sample, so changes apply without a restart.
+    pub fn start(self: Arc<Self>) {
+        tokio::spawn(async move {
+            info!("Pool history sampler started");
+
+            loop {
+                let delay = match self.sample_once().await {
+                    Ok(delay) => delay,
+                    Err(e) => {
+                        error!(error = %e, "Error sampling pool status");
+                        DISABLED_RECHECK
+                    }
+                };
+                tokio::time::sleep(delay).await;
+            }
+        });
+    }
+
+    /// Record one sample and prune expired ones
+    ///
+    /// Returns the delay until the next sample is due. Nothing is recorded
+    /// when `pool_history_interval_secs` is 0.
+    pub async fn sample_once(&self) -> Result<Duration> {
+        let config = SwarmConfig::get(&self.db_pool).await?;
+        if config.pool_history_interval_secs <= 0 {
+            return Ok(DISABLED_RECHECK);
+        }
+
+        let stats = self.pool_manager.get_status(&self.db_pool).await?.stats;
+        PoolStatusSample::create(
+            &self.db_pool,
+            &CreatePoolStatusSample {
+                total: (stats.total - stats.destroyed) as i64,
+                idle: stats.idle as i64,
+                busy: stats.busy as i64,
+                draining: stats.draining as i64,
+            },
+        )
+        .await?;
+
+        let cutoff = Utc::now() - chrono::Duration::hours(config.pool_history_retention_hours.into());
+        let pruned = PoolStatusSample::delete_older_than(&self.db_pool, cutoff).await?;
+        if pruned > 0 {
+            debug!(pruned, "Pruned expired pool history samples");
+        }
+
+        Ok(Duration::from_secs(config.pool_history_interval_secs as u64))
+    }
+}
@@ -0,0 +1,202 @@
+//! Skill Metadata
+//!
+//! Skills may start their SKILL.md with a frontmatter block of `key: value`
+//! lines, e.g.
+//!
+//! ```text
+//! ---
+//! name: Backend API
+//! description: Backend API work
+//! version: 1.2.0
+//! tags: [rust, api]
+//! workdir: services/api
+//! ---
+//! ```
+//!
+//! `tags` may also be written as a block list of `- tag` lines. Unknown keys
+//! are ignored so skills can carry metadata for other tools.
+
+use std::path::Path;
+
+/// File describing a skill inside its directory
+pub const SKILL_FILE: &str = "SKILL.md";
+
+/// Metadata declared in a skill's frontmatter
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SkillFrontmatter {
+    /// Display name; the skill is still referred to by its directory name
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub version: Option<String>,
+    pub tags: Vec<String>,
+    /// Directory tasks using the skill run in, relative to the workspace or absolute
+    pub workdir: Option<String>,
+}
+
+/// Split SKILL.md into its frontmatter (if any) and the remaining body
+pub fn split_skill_frontmatter(content: &str) -> (SkillFrontmatter, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (SkillFrontmatter::default(), content);
+    };
+
+    let mut frontmatter = SkillFrontmatter::default();
+    let mut offset = 0;
+    // Set while reading the `- tag` lines of a block list
+    let mut in_tag_list = false;
+
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let line = line.trim();
+        if line == "---" {
+            return (frontmatter, &rest[offset..]);
+        }
+
+        if in_tag_list && let Some(item) = line.strip_prefix('-') {
+            frontmatter.tags.extend(parse_tag(item));
+            continue;
+        }
+        in_tag_list = false;
+
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = unquote(value);
+        if key.trim() == "tags" {
+            match value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                Some(items) => frontmatter.tags = items.split(',').filter_map(parse_tag).collect(),
+                None if value.is_empty() => in_tag_list = true,
+                None => frontmatter.tags = value.split(',').filter_map(parse_tag).collect(),
+            }
+            continue;
+        }
+        if value.is_empty() {
+            continue;
+        }
+        match key.trim() {
+            "name" => frontmatter.name = Some(value.to_string()),
+            "description" => frontmatter.description = Some(value.to_string()),
+            "version" => frontmatter.version = Some(value.to_string()),
+            "workdir" => frontmatter.workdir = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    // Unterminated block: treat the whole file as body
+    (SkillFrontmatter::default(), content)
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches(|c| c == '"' || c == '\'')
+}
+
+fn parse_tag(item: &str) -> Option<String> {
+    Some(unquote(item)).filter(|tag| !tag.is_empty()).map(str::to_string)
+}
+
+/// Read the frontmatter of skill `name` in `skills_dir`
+///
+/// Returns None for invalid names or skills without a readable SKILL.md.
+pub fn read_skill_frontmatter(skills_dir: &Path, name: &str) -> Option<SkillFrontmatter> {
+    if name.is_empty() || name.contains("..") || name.contains('/') || name.contains('\\') {
+        return None;
+    }
+
+    let content = std::fs::read_to_string(skills_dir.join(name).join(SKILL_FILE)).ok()?;
+    Some(split_skill_frontmatter(&content).0)
+}
+
+/// Resolve a skill's `workdir` against the workspace
+///
+/// Relative paths are joined onto `workspace`; anything containing `..`
+/// falls back to the workspace itself.
+pub fn resolve_workdir(workspace: &str, workdir: Option<&str>) -> String {
+    let Some(workdir) = workdir.map(str::trim).filter(|w| !w.is_empty()) else {
+        return workspace.to_string();
+    };
+
+    if workdir.split('/').any(|part| part == "..") {
+        tracing::warn!(workdir = %workdir, "Ignoring skill workdir that leaves its base directory");
+        return workspace.to_string();
+    }
+
+    if workdir.starts_with('/') {
+        workdir.trim_end_matches('/').to_string()
+    } else {
+        format!(
+            "{}/{}",
+            workspace.trim_end_matches('/'),
+            workdir.trim_matches('/')
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_frontmatter_with_workdir() {
+        let content = "---\ndescription: \"API work\"\nworkdir: services/api\nowner: team\n---\n# Backend\n\nBody";
+        let (frontmatter, body) = split_skill_frontmatter(content);
+
+        assert_eq!(frontmatter.description.as_deref(), Some("API work"));
+        assert_eq!(frontmatter.workdir.as_deref(), Some("services/api"));
+        assert_eq!(body, "# Backend\n\nBody");
+    }
+
+    #[test]
+    fn test_parse_frontmatter_name_version_and_tags() {
+        let content = "---\nname: Backend API\nversion: '1.2.0'\ntags: [rust, \"api\", ]\n---\nBody";
+        let (frontmatter, body) = split_skill_frontmatter(content);
+
+        assert_eq!(frontmatter.name.as_deref(), Some("Backend API"));
+        assert_eq!(frontmatter.version.as_deref(), Some("1.2.0"));
+        assert_eq!(frontmatter.tags, ["rust", "api"]);
+        assert_eq!(body, "Body");
+
+        // Block lists end at the next key
+        let content = "---\ntags:\n  - frontend\n  - 'react'\ndescription: UI work\n---\n";
+        let (frontmatter, _) = split_skill_frontmatter(content);
+        assert_eq!(frontmatter.tags, ["frontend", "react"]);
+        assert_eq!(frontmatter.description.as_deref(), Some("UI work"));
+    }
+
+    #[test]
+    fn test_parse_without_frontmatter() {
+        let content = "# Skill\n\nworkdir: not-frontmatter";
+        let (frontmatter, body) = split_skill_frontmatter(content);
+
+        assert_eq!(frontmatter, SkillFrontmatter::default());
+        assert_eq!(body, content);
+    }
+
+    #[test]
+    fn test_read_skill_frontmatter_from_disk() {
+        let dir = std::env::temp_dir().join(format!("skill-meta-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("frontend")).unwrap();
+        std::fs::write(
+            dir.join("frontend").join(SKILL_FILE),
+            "---\nworkdir: apps/web\n---\n# Frontend\n",
+        )
+        .unwrap();
+
+        let frontmatter = read_skill_frontmatter(&dir, "frontend").unwrap();
+        assert_eq!(frontmatter.workdir.as_deref(), Some("apps/web"));
+        assert!(read_skill_frontmatter(&dir, "missing").is_none());
+        assert!(read_skill_frontmatter(&dir, "../frontend").is_none());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_workdir() {
+        assert_eq!(resolve_workdir("/workspace", None), "/workspace");
+        assert_eq!(resolve_workdir("/workspace", Some("apps/web/")), "/workspace/apps/web");
+        assert_eq!(resolve_workdir("/workspace/", Some("/srv/app")), "/srv/app");
+        assert_eq!(resolve_workdir("/workspace", Some("../etc")), "/workspace");
+        assert_eq!(resolve_workdir("/workspace", Some("  ")), "/workspace");
+    }
+}
@@ -3,7 +3,10 @@
 //! Manages swarm lifecycle: create, read, update, delete.
 //! Migrated from SwarmService.js
 
-use db::models::swarm::{CreateSwarm, Swarm, SwarmStatus, UpdateSwarm};
+use db::models::{
+    swarm::{CreateSwarm, Swarm, SwarmStatus, UpdateSwarm},
+    UpdateError,
+};
 use sqlx::{Row, SqlitePool};
 use thiserror::Error;
 use uuid::Uuid;
@@ -18,6 +21,17 @@ pub enum SwarmServiceError {
     NameRequired,
     #[error("Cannot delete swarm with active sandboxes")]
     HasActiveSandboxes,
+    #[error("Swarm was modified since it was read")]
+    Conflict,
+}
+
+impl From<UpdateError> for SwarmServiceError {
+    fn from(err: UpdateError) -> Self {
+        match err {
+            UpdateError::Database(e) => Self::Database(e),
+            UpdateError::Conflict => Self::Conflict,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, SwarmServiceError>;
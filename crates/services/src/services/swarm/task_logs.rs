@@ -0,0 +1,123 @@
+//! Task Log Storage
+//!
+//! Stores each task attempt's log in `swarm_task_logs`. Logs at or above the
+//! configured threshold are compressed with the configured algorithm; smaller
+//! ones are kept as plain text, where compression would cost more than it saves.
+//! Reads decompress transparently.
+
+use std::io::{self, Read, Write};
+
+use chrono::{DateTime, Utc};
+use db::models::swarm_config::{LogCompression, SwarmConfig};
+use db::models::swarm_task_log::{CreateSwarmTaskLog, SwarmTaskLog};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tracing::debug;
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// zstd level; favours speed, as logs are written while tasks finish
+const ZSTD_LEVEL: i32 = 3;
+
+/// A stored task log, decompressed
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TaskLog {
+    pub task_id: Uuid,
+    pub attempt: i32,
+    pub content: String,
+    /// How the log is stored
+    pub encoding: LogCompression,
+    pub byte_size: i64,
+    /// Size on disk after compression
+    pub stored_size: i64,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaskLog {
+    pub fn decode(log: SwarmTaskLog) -> io::Result<Self> {
+        Ok(Self {
+            task_id: log.task_id,
+            attempt: log.attempt,
+            content: decompress_log(log.encoding, &log.content)?,
+            encoding: log.encoding,
+            byte_size: log.byte_size,
+            stored_size: log.content.len() as i64,
+            created_at: log.created_at,
+        })
+    }
+}
+
+/// Encode `content` for storage
+///
+/// Returns the encoding actually used, which is `None` below `threshold_bytes`.
+pub fn compress_log(
+    content: &str,
+    algorithm: LogCompression,
+    threshold_bytes: usize,
+) -> io::Result<(LogCompression, Vec<u8>)> {
+    if content.len() < threshold_bytes {
+        return Ok((LogCompression::None, content.as_bytes().to_vec()));
+    }
+
+    let data = match algorithm {
+        LogCompression::None => content.as_bytes().to_vec(),
+        LogCompression::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(content.as_bytes())?;
+            encoder.finish()?
+        }
+        LogCompression::Zstd => zstd::encode_all(content.as_bytes(), ZSTD_LEVEL)?,
+    };
+    Ok((algorithm, data))
+}
+
+/// Decode a stored log back to text
+pub fn decompress_log(encoding: LogCompression, data: &[u8]) -> io::Result<String> {
+    let bytes = match encoding {
+        LogCompression::None => data.to_vec(),
+        LogCompression::Gzip => {
+            let mut bytes = Vec::new();
+            GzDecoder::new(data).read_to_end(&mut bytes)?;
+            bytes
+        }
+        LogCompression::Zstd => zstd::decode_all(data)?,
+    };
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Store one attempt's log using the configured compression
+pub async fn save_task_log(
+    pool: &SqlitePool,
+    task_id: Uuid,
+    attempt: i32,
+    content: &str,
+) -> anyhow::Result<SwarmTaskLog> {
+    let config = SwarmConfig::get(pool).await?;
+    let threshold = config.log_compression_threshold_bytes.max(0) as usize;
+    let (encoding, data) = compress_log(content, config.log_compression, threshold)?;
+
+    debug!(
+        task_id = %task_id,
+        attempt,
+        encoding = %encoding,
+        byte_size = content.len(),
+        stored_size = data.len(),
+        "Storing task log"
+    );
+
+    let log = SwarmTaskLog::create(
+        pool,
+        &CreateSwarmTaskLog {
+            task_id,
+            attempt,
+            encoding,
+            content: data,
+            byte_size: content.len() as i64,
+        },
+    )
+    .await?;
+
+    Ok(log)
+}
@@ -3,34 +3,51 @@
 //! Automatically monitors pending tasks and dispatches them to available sandboxes.
 //! Implements the TriggerEngine pattern from the original Node.js backend.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use db::models::sandbox::Sandbox;
-use db::models::swarm::Swarm;
-use db::models::swarm_config::SwarmConfig;
-use db::models::swarm_task::SwarmTask;
+use chrono::{DateTime, Utc};
+use db::models::sandbox::{Sandbox, SandboxStatus};
+use db::models::swarm::{Swarm, SwarmStatus};
+use db::models::swarm_chat::MessageMetadata;
+use db::models::swarm_config::{ChatResultMode, SwarmConfig};
+use db::models::swarm_secret::SwarmSecret;
+use db::models::swarm_task::{SwarmTask, SwarmTaskStatus};
+use serde::Serialize;
 use sqlx::SqlitePool;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
+use ts_rs::TS;
 use uuid::Uuid;
 
+use super::broadcast::{ChatBroadcaster, LogBroadcaster, LogEnd, PoolBroadcaster, PoolStatusUpdate};
+use super::chat::{ChatService, StreamedReply};
 use super::daytona::DaytonaClient;
-use super::pool::PoolManager;
+use super::decompose::decompose_task;
+use super::executor::{extract_output_summary, kill_task_processes, TaskExecutor};
+use super::pool::{AgentRole, PoolError, PoolManager};
+use super::task_logs::save_task_log;
+use super::webhook::{WebhookDeliverer, WEBHOOK_TASK_COMPLETED, WEBHOOK_TASK_FAILED};
 
 /// Configuration for the trigger engine
 #[derive(Debug, Clone)]
 pub struct TriggerConfig {
     /// Interval between trigger checks in seconds
     pub check_interval_secs: u64,
-    /// Maximum number of concurrent task executions
+    /// Maximum running tasks per swarm, unless the swarm sets its own limit
     pub max_concurrent: usize,
     /// Maximum retries for failed tasks
     pub max_retries: i32,
     /// Execution timeout in minutes
     pub execution_timeout_minutes: i32,
+    /// Interval between checks of the pool against Daytona in seconds; 0 disables them
+    pub reconcile_interval_secs: u64,
+    /// Interval between idle cleanups and warm pool top-ups in seconds; 0 disables them
+    pub pool_maintenance_interval_secs: u64,
 }
 
 impl Default for TriggerConfig {
@@ -40,10 +57,93 @@ impl Default for TriggerConfig {
             max_concurrent: 5,
             max_retries: 3,
             execution_timeout_minutes: 30,
+            reconcile_interval_secs: 60,
+            pool_maintenance_interval_secs: 60,
         }
     }
 }
 
+/// Stats samples kept for `TriggerEngine::stats_history`, one per loop pass
+const STATS_HISTORY_LEN: usize = 60;
+
+/// Sliding one-minute window of task dispatches per swarm
+#[derive(Debug, Default)]
+struct DispatchRateLimiter {
+    windows: HashMap<Uuid, VecDeque<Instant>>,
+}
+
+impl DispatchRateLimiter {
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    /// Whether the swarm may dispatch another task at `now`
+    /// A rate of 0 (or less) means unlimited.
+    fn allows(&mut self, swarm_id: Uuid, rate_per_minute: i32, now: Instant) -> bool {
+        if rate_per_minute <= 0 {
+            self.windows.remove(&swarm_id);
+            return true;
+        }
+
+        let window = self.windows.entry(swarm_id).or_default();
+        while window
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= Self::WINDOW)
+        {
+            window.pop_front();
+        }
+        window.len() < rate_per_minute as usize
+    }
+
+    /// Count a dispatch for a rate-limited swarm
+    fn record(&mut self, swarm_id: Uuid, rate_per_minute: i32, now: Instant) {
+        if rate_per_minute > 0 {
+            self.windows.entry(swarm_id).or_default().push_back(now);
+        }
+    }
+}
+
+/// Cancellation tokens of in-flight task executions, by task id
+///
+/// Each registration is numbered, so a run that ends after its task was
+/// dispatched again cannot drop the new run's token.
+#[derive(Debug, Default)]
+struct ExecutionTokens {
+    tokens: HashMap<Uuid, (u64, CancellationToken)>,
+    next_run: u64,
+}
+
+impl ExecutionTokens {
+    /// Create the token for a newly dispatched task, with the run's number
+    fn register(&mut self, task_id: Uuid) -> (u64, CancellationToken) {
+        let run = self.next_run;
+        self.next_run += 1;
+        let token = CancellationToken::new();
+        self.tokens.insert(task_id, (run, token.clone()));
+        (run, token)
+    }
+
+    /// Cancel a task's execution; false if it is not running here
+    fn cancel(&mut self, task_id: Uuid) -> bool {
+        match self.tokens.remove(&task_id) {
+            Some((_, token)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Untrack a finished run, unless the task has been registered again since
+    fn finish(&mut self, task_id: Uuid, run: u64) {
+        if self.tokens.get(&task_id).is_some_and(|(current, _)| *current == run) {
+            self.tokens.remove(&task_id);
+        }
+    }
+
+    fn task_ids(&self) -> Vec<Uuid> {
+        self.tokens.keys().copied().collect()
+    }
+}
+
 /// Trigger Engine for automatic task processing
 pub struct TriggerEngine {
     db_pool: SqlitePool,
@@ -52,6 +152,23 @@ pub struct TriggerEngine {
     config: TriggerConfig,
     shutdown: RwLock<bool>,
     processing_tasks: Arc<RwLock<HashMap<Uuid, bool>>>,
+    dispatch_limiter: RwLock<DispatchRateLimiter>,
+    execution_tokens: Arc<RwLock<ExecutionTokens>>,
+    /// Sandboxes created for a single task, destroyed once that task finishes
+    single_use_sandboxes: Arc<RwLock<HashSet<Uuid>>>,
+    pool_broadcaster: Option<Arc<PoolBroadcaster>>,
+    log_broadcaster: Option<Arc<LogBroadcaster>>,
+    chat_broadcaster: Option<Arc<ChatBroadcaster>>,
+    /// Sends task events to the configured `webhook_url`
+    webhooks: Option<Arc<WebhookDeliverer>>,
+    /// Wakes the loop before the next interval, e.g. when chained tasks become ready
+    wake: Arc<Notify>,
+    /// Tasks dispatched since the engine was created
+    tasks_dispatched_total: AtomicU64,
+    /// Tasks failed since the engine was created, by the engine or their executions
+    tasks_failed_total: Arc<AtomicU64>,
+    /// Recent stats samples, oldest first
+    stats_history: RwLock<VecDeque<TriggerStats>>,
 }
 
 impl TriggerEngine {
@@ -69,9 +186,44 @@ impl TriggerEngine {
             config,
             shutdown: RwLock::new(false),
             processing_tasks: Arc::new(RwLock::new(HashMap::new())),
+            dispatch_limiter: RwLock::new(DispatchRateLimiter::default()),
+            execution_tokens: Arc::new(RwLock::new(ExecutionTokens::default())),
+            single_use_sandboxes: Arc::new(RwLock::new(HashSet::new())),
+            pool_broadcaster: None,
+            log_broadcaster: None,
+            chat_broadcaster: None,
+            webhooks: None,
+            wake: Arc::new(Notify::new()),
+            tasks_dispatched_total: AtomicU64::new(0),
+            tasks_failed_total: Arc::new(AtomicU64::new(0)),
+            stats_history: RwLock::new(VecDeque::with_capacity(STATS_HISTORY_LEN)),
         }
     }
 
+    /// Publish sandbox lifecycle changes made by the engine
+    pub fn with_pool_broadcaster(mut self, broadcaster: Arc<PoolBroadcaster>) -> Self {
+        self.pool_broadcaster = Some(broadcaster);
+        self
+    }
+
+    /// Stream task output to log subscribers while tasks run
+    pub fn with_log_broadcaster(mut self, broadcaster: Arc<LogBroadcaster>) -> Self {
+        self.log_broadcaster = Some(broadcaster);
+        self
+    }
+
+    /// Stream agent replies to chat subscribers while tasks run
+    pub fn with_chat_broadcaster(mut self, broadcaster: Arc<ChatBroadcaster>) -> Self {
+        self.chat_broadcaster = Some(broadcaster);
+        self
+    }
+
+    /// POST task completions and failures to the `webhook_url` in the swarm config
+    pub fn with_webhooks(mut self, deliverer: Arc<WebhookDeliverer>) -> Self {
+        self.webhooks = Some(deliverer);
+        self
+    }
+
     /// Start the trigger engine loop
     pub fn start(self: Arc<Self>) {
         let engine = self.clone();
@@ -86,7 +238,10 @@ impl TriggerEngine {
             );
 
             loop {
-                interval.tick().await;
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = engine.wake.notified() => {}
+                }
 
                 if *engine.shutdown.read().await {
                     break;
@@ -95,10 +250,53 @@ impl TriggerEngine {
                 if let Err(e) = engine.check_triggers().await {
                     error!(error = %e, "Error in trigger check");
                 }
+                engine.sample_stats().await;
             }
 
             info!("Trigger engine stopped");
         });
+
+        if self.config.reconcile_interval_secs > 0 {
+            let engine = self.clone();
+            tokio::spawn(async move {
+                let mut interval =
+                    tokio::time::interval(Duration::from_secs(engine.config.reconcile_interval_secs));
+
+                loop {
+                    interval.tick().await;
+
+                    if *engine.shutdown.read().await {
+                        break;
+                    }
+
+                    if let Err(e) = engine.reconcile_sandboxes().await {
+                        error!(error = %e, "Error reconciling sandboxes with Daytona");
+                    }
+                }
+            });
+        }
+
+        // The first tick fires right away, so the warm pool is filled on startup
+        if self.config.pool_maintenance_interval_secs > 0 {
+            let engine = self.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(Duration::from_secs(
+                    engine.config.pool_maintenance_interval_secs,
+                ));
+
+                loop {
+                    interval.tick().await;
+
+                    if *engine.shutdown.read().await {
+                        break;
+                    }
+
+                    if let Err(e) = engine.maintain_pool().await {
+                        error!(error = %e, "Error maintaining sandbox pool");
+                    }
+                }
+            });
+        }
     }
 
     /// Stop the trigger engine
@@ -108,14 +306,120 @@ impl TriggerEngine {
         info!("Trigger engine stop requested");
     }
 
+    /// Stop the in-flight execution of a task
+    ///
+    /// Also aborts a sandbox creation still under way for the task. Returns
+    /// false when the task is neither executing nor waiting on a sandbox in
+    /// this engine. The task's status is left to the caller; the sandbox is
+    /// released once the execution has wound down.
+    pub async fn cancel_task(&self, task_id: Uuid) -> bool {
+        let creating = self.pool_manager.cancel_creating(task_id).await;
+        let cancelled = self.execution_tokens.write().await.cancel(task_id);
+        if cancelled {
+            info!(task_id = %task_id, "Cancelling task execution");
+        }
+        cancelled || creating
+    }
+
+    /// Stop a running task that has been marked cancelled or failed, or reassigned
+    ///
+    /// An execution in flight here is stopped through its token and releases
+    /// its sandbox as it winds down. Otherwise (e.g. it was dispatched before a
+    /// restart) the task's processes are killed and its sandbox released now.
+    pub async fn stop_cancelled_task(&self, task: &SwarmTask) -> Result<()> {
+        if self.cancel_task(task.id).await {
+            return Ok(());
+        }
+
+        if let Some(daytona_id) = task.sandbox_id.as_deref()
+            && let Err(e) = kill_task_processes(&self.daytona, daytona_id).await
+        {
+            warn!(task_id = %task.id, daytona_id = %daytona_id, error = %e, "Failed to kill processes of stopped task");
+        }
+        self.release_task_sandbox(task.id).await
+    }
+
+    /// Stop executions and sandbox creations whose task was cancelled or deleted through the API
+    async fn cancel_aborted_executions(&self) -> Result<()> {
+        let mut task_ids = self.execution_tokens.read().await.task_ids();
+        task_ids.extend(self.pool_manager.creating_task_ids().await);
+        if task_ids.is_empty() {
+            return Ok(());
+        }
+
+        let tasks = SwarmTask::find_by_ids(&self.db_pool, &task_ids).await?;
+        let live: HashSet<Uuid> = tasks
+            .iter()
+            .filter(|t| t.status != SwarmTaskStatus::Cancelled)
+            .map(|t| t.id)
+            .collect();
+        for task_id in task_ids.into_iter().filter(|id| !live.contains(id)) {
+            self.cancel_task(task_id).await;
+        }
+
+        Ok(())
+    }
+
+    /// Fail running tasks that have exceeded `trigger_execution_timeout_minutes`
+    ///
+    /// Works from the task records rather than the execution futures, so a
+    /// task whose execution hangs, or was lost in a restart, still ends. The
+    /// task is failed first so the stopped execution leaves its status alone.
+    async fn fail_timed_out_tasks(&self) -> Result<()> {
+        let config = SwarmConfig::get(&self.db_pool).await?;
+        if config.trigger_execution_timeout_minutes <= 0 {
+            return Ok(());
+        }
+
+        let cutoff =
+            chrono::Utc::now() - chrono::Duration::minutes(i64::from(config.trigger_execution_timeout_minutes));
+        for task in SwarmTask::find_running_started_before(&self.db_pool, cutoff).await? {
+            let error = format!(
+                "Task exceeded timeout of {} minutes",
+                config.trigger_execution_timeout_minutes
+            );
+            if !SwarmTask::fail_if_running(&self.db_pool, task.id, &error).await? {
+                continue;
+            }
+            self.tasks_failed_total.fetch_add(1, Ordering::Relaxed);
+            notify_webhook(&self.db_pool, self.webhooks.as_ref(), task.id, WEBHOOK_TASK_FAILED).await;
+            warn!(task_id = %task.id, error = %error, "Task timed out");
+
+            if let Some(broadcaster) = &self.log_broadcaster {
+                broadcaster
+                    .publish_log_end(task.id, LogEnd::failure(-1).with_summary(error.clone()))
+                    .await;
+            }
+            if let Err(e) = self.stop_cancelled_task(&task).await {
+                warn!(task_id = %task.id, error = %e, "Failed to stop timed out task");
+            }
+        }
+
+        Ok(())
+    }
+
     /// Check if the trigger engine is enabled
     pub async fn is_enabled(&self) -> Result<bool> {
         let config = SwarmConfig::get(&self.db_pool).await?;
         Ok(config.trigger_enabled)
     }
 
-    /// Main trigger check loop
-    async fn check_triggers(&self) -> Result<()> {
+    /// Run one pass of the trigger loop
+    pub async fn check_triggers(&self) -> Result<()> {
+        // Cancellations, timeouts and drained sandboxes are handled even while triggers are paused
+        if let Err(e) = self.cancel_aborted_executions().await {
+            error!(error = %e, "Error cancelling aborted executions");
+        }
+        if let Err(e) = self.fail_timed_out_tasks().await {
+            error!(error = %e, "Error failing timed out tasks");
+        }
+        if let Err(e) = self.destroy_drained_sandboxes().await {
+            error!(error = %e, "Error destroying drained sandboxes");
+        }
+        if let Err(e) = self.auto_pause_idle_swarms().await {
+            error!(error = %e, "Error auto-pausing idle swarms");
+        }
+
         // Check if triggers are enabled
         if !self.is_enabled().await? {
             debug!("Triggers disabled, skipping check");
@@ -134,12 +438,115 @@ impl TriggerEngine {
         Ok(())
     }
 
+    /// Mark sandboxes that vanished from Daytona as destroyed
+    ///
+    /// Executions still running in them are stopped; their tasks are already
+    /// pending again and are picked up on the next pass.
+    pub async fn reconcile_sandboxes(&self) -> Result<()> {
+        let reconciled = self.pool_manager.reconcile(&self.db_pool, &self.daytona).await?;
+
+        let mut requeued = false;
+        for entry in &reconciled {
+            if let Some(task_id) = entry.requeued_task_id {
+                self.execution_tokens.write().await.cancel(task_id);
+                requeued = true;
+            }
+            publish_pool_update(self.pool_broadcaster.as_deref(), &entry.sandbox);
+        }
+
+        if requeued {
+            self.wake.notify_one();
+        }
+        Ok(())
+    }
+
+    /// Destroy sandboxes idle past the timeout, then top the warm pool back up
+    ///
+    /// A cleanup failure does not stop the top-up.
+    pub async fn maintain_pool(&self) -> Result<()> {
+        match self.pool_manager.cleanup_idle_sandboxes(&self.db_pool, &self.daytona).await {
+            Ok(destroyed) => {
+                for sandbox in &destroyed {
+                    publish_pool_update(self.pool_broadcaster.as_deref(), sandbox);
+                }
+            }
+            Err(e) => error!(error = %e, "Error cleaning up idle sandboxes"),
+        }
+
+        let created = self.pool_manager.ensure_warm_pool(&self.db_pool, &self.daytona).await?;
+        for sandbox in &created {
+            publish_pool_update(self.pool_broadcaster.as_deref(), sandbox);
+        }
+        Ok(())
+    }
+
+    /// Destroy draining sandboxes whose last task has been released
+    async fn destroy_drained_sandboxes(&self) -> Result<()> {
+        for sandbox in Sandbox::find_drained(&self.db_pool).await? {
+            if let Err(e) = self.daytona.delete_sandbox(&sandbox.daytona_id).await {
+                warn!(
+                    sandbox_id = %sandbox.id,
+                    daytona_id = %sandbox.daytona_id,
+                    error = %e,
+                    "Failed to delete drained sandbox from Daytona"
+                );
+                continue;
+            }
+
+            Sandbox::mark_destroyed(&self.db_pool, sandbox.id).await?;
+            let destroyed = Sandbox {
+                status: SandboxStatus::Destroyed,
+                ..sandbox
+            };
+            publish_pool_update(self.pool_broadcaster.as_deref(), &destroyed);
+            info!(sandbox_id = %destroyed.id, "Drained sandbox destroyed");
+        }
+
+        Ok(())
+    }
+
+    /// Pause active swarms that have been idle past `auto_pause_after_minutes`
+    async fn auto_pause_idle_swarms(&self) -> Result<()> {
+        let config = SwarmConfig::get(&self.db_pool).await?;
+        if config.auto_pause_after_minutes <= 0 {
+            return Ok(());
+        }
+
+        let cutoff = chrono::Utc::now() - chrono::Duration::minutes(i64::from(config.auto_pause_after_minutes));
+        for swarm in Swarm::find_idle_since(&self.db_pool, cutoff).await? {
+            if !Swarm::auto_pause(&self.db_pool, swarm.id).await? {
+                continue;
+            }
+
+            info!(swarm_id = %swarm.id, "Swarm auto-paused after inactivity");
+            let message = format!(
+                "Swarm paused automatically after {} minutes without tasks or chat activity. \
+                 Create a task or resume the swarm to reactivate it.",
+                config.auto_pause_after_minutes
+            );
+            if let Err(e) = ChatService::new()
+                .post_system_message(&self.db_pool, swarm.id, message, None)
+                .await
+            {
+                warn!(swarm_id = %swarm.id, error = %e, "Failed to post auto-pause message");
+            }
+        }
+
+        Ok(())
+    }
+
     /// Process triggers for a single swarm
     async fn process_swarm_triggers(&self, swarm: &Swarm) -> Result<()> {
         let swarm_id = swarm.id;
 
         // Get pending tasks for this swarm
         let pending_tasks = self.get_pending_tasks(swarm_id).await?;
+        if pending_tasks.is_empty() {
+            return Ok(());
+        }
+
+        let max_concurrent = self.max_concurrent_for(swarm);
+        let mut running = SwarmTask::count_by_status(&self.db_pool, swarm_id).await?.running;
 
         for task in pending_tasks {
             // Atomic check-and-insert to prevent race condition
@@ -154,6 +561,16 @@ impl TriggerEngine {
                 processing.insert(task.id, true);
             }
 
+            // Coordinators never run; they settle from their subtasks
+            if task.is_coordinator {
+                if let Err(e) = self.process_coordinator(&task).await {
+                    error!(task_id = %task.id, error = %e, "Error settling coordinator task");
+                }
+                let mut processing = self.processing_tasks.write().await;
+                processing.remove(&task.id);
+                continue;
+            }
+
             // Check dependencies
             if !self.are_dependencies_complete(&task).await? {
                 debug!(task_id = %task.id, "Task dependencies not complete");
@@ -163,11 +580,39 @@ impl TriggerEngine {
                 continue;
             }
 
+            // Concurrency limit: wait for one of the swarm's running tasks to finish
+            if running >= max_concurrent {
+                debug!(swarm_id = %swarm_id, max_concurrent, "Concurrency limit reached, skipping task");
+                let mut processing = self.processing_tasks.write().await;
+                processing.remove(&task.id);
+                continue;
+            }
+
+            // Throughput limit: skip the swarm's remaining tasks for this window.
+            // Coordinators above are not dispatches and still settle.
+            let rate = swarm.dispatch_rate_per_minute;
+            if !self
+                .dispatch_limiter
+                .write()
+                .await
+                .allows(swarm_id, rate, Instant::now())
+            {
+                debug!(swarm_id = %swarm_id, rate, "Dispatch rate limit reached, skipping task");
+                let mut processing = self.processing_tasks.write().await;
+                processing.remove(&task.id);
+                continue;
+            }
+
             // Find or create sandbox
             match self.process_pending_task(swarm, &task).await {
                 Ok(true) => {
                     // Task was successfully dispatched, processing flag will be
                     // cleared by the spawned execution task
+                    running += 1;
+                    self.dispatch_limiter
+                        .write()
+                        .await
+                        .record(swarm_id, rate, Instant::now());
                 }
                 Ok(false) => {
                     // No sandbox available, remove from processing so it can be retried
@@ -187,6 +632,37 @@ impl TriggerEngine {
         Ok(())
     }
 
+    /// Running tasks allowed for a swarm: its own limit, or the engine default
+    fn max_concurrent_for(&self, swarm: &Swarm) -> usize {
+        if swarm.max_concurrent > 0 {
+            swarm.max_concurrent as usize
+        } else {
+            self.config.max_concurrent
+        }
+    }
+
+    /// Settle a coordinator task from the state of its subtasks
+    /// Completes once every subtask has completed and fails as soon as one
+    /// fails or is cancelled.
+    async fn process_coordinator(&self, task: &SwarmTask) -> Result<()> {
+        let subtask_ids = task.depends_on.clone().unwrap_or_default();
+        let subtasks = SwarmTask::find_by_ids(&self.db_pool, &subtask_ids).await?;
+
+        if let Some(failed) = subtasks.iter().find(|t| {
+            t.status == SwarmTaskStatus::Failed || t.status == SwarmTaskStatus::Cancelled
+        }) {
+            let reason = format!("Subtask '{}' {}", failed.title, failed.status);
+            self.fail_task(task.id, &reason).await?;
+        } else if subtasks.len() == subtask_ids.len()
+            && subtasks.iter().all(|t| t.status == SwarmTaskStatus::Completed)
+        {
+            let summary = format!("All {} subtasks completed", subtasks.len());
+            self.complete_task(task.id, Some(&summary)).await?;
+        }
+
+        Ok(())
+    }
+
     /// Process a pending task - find sandbox and dispatch
     /// Returns Err only on actual failures that should trigger cleanup
     /// Returns Ok(false) when no sandbox available (task should be removed from processing)
@@ -194,11 +670,25 @@ impl TriggerEngine {
     async fn process_pending_task(&self, swarm: &Swarm, task: &SwarmTask) -> Result<bool> {
         let swarm_id = swarm.id;
 
-        // Try to find an idle sandbox first
-        let sandbox = Sandbox::find_idle(&self.db_pool).await?;
+        // Pinned tasks only ever run in their own sandbox
+        if let Some(pinned_id) = task.pinned_sandbox_id {
+            return self.process_pinned_task(task, pinned_id).await;
+        }
+
+        if swarm.fresh_sandbox_per_task {
+            return self.process_fresh_sandbox_task(swarm, task).await;
+        }
+
+        // Try to find an idle sandbox of this swarm or the warm pool first,
+        // preferably one that last ran the same kind of task
+        let role = AgentRole::from_tags(&task.tags);
+        let idle = self
+            .pool_manager
+            .find_idle_sandbox(&self.db_pool, swarm_id, Some(role))
+            .await?;
 
-        let sandbox = if let Some(sb) = sandbox.first() {
-            sb.clone()
+        let sandbox = if let Some(sb) = idle {
+            sb
         } else {
             // Check pool capacity
             let active_count = Sandbox::count_active(&self.db_pool).await?;
@@ -209,14 +699,23 @@ impl TriggerEngine {
                 return Ok(false); // No sandbox available, signal to release from processing
             }
 
-            // Would create new sandbox here via PoolManager
-            // For now, just log
-            info!(
-                swarm_id = %swarm_id,
-                task_id = %task.id,
-                "Would create new sandbox for task"
-            );
-            return Ok(false); // No sandbox available, signal to release from processing
+            match self
+                .pool_manager
+                .create_sandbox(&self.db_pool, &self.daytona, swarm_id, task.id)
+                .await
+            {
+                Ok(Some(sb)) => {
+                    publish_pool_update(self.pool_broadcaster.as_deref(), &sb);
+                    info!(swarm_id = %swarm_id, task_id = %task.id, sandbox_id = %sb.id, "Created sandbox for task");
+                    sb
+                }
+                Ok(None) => return Ok(false),
+                Err(PoolError::AlreadyCreating(_)) => {
+                    debug!(task_id = %task.id, "Sandbox already being created for task");
+                    return Ok(false);
+                }
+                Err(e) => return Err(e.into()),
+            }
         };
 
         // Dispatch the task
@@ -224,12 +723,130 @@ impl TriggerEngine {
         Ok(true)
     }
 
+    /// Create a sandbox for this task alone and dispatch to it
+    ///
+    /// The sandbox is destroyed when the task finishes instead of going back
+    /// to the pool. Returns Ok(false) while the pool is at capacity or if the
+    /// creation was cancelled.
+    async fn process_fresh_sandbox_task(&self, swarm: &Swarm, task: &SwarmTask) -> Result<bool> {
+        let active_count = Sandbox::count_active(&self.db_pool).await?;
+        let config = SwarmConfig::get(&self.db_pool).await?;
+        if active_count >= config.pool_max_sandboxes as i64 {
+            info!(swarm_id = %swarm.id, "Pool at capacity, waiting to create fresh sandbox");
+            return Ok(false);
+        }
+
+        let Some(sandbox) = self
+            .pool_manager
+            .create_sandbox(&self.db_pool, &self.daytona, swarm.id, task.id)
+            .await?
+        else {
+            return Ok(false);
+        };
+        self.single_use_sandboxes.write().await.insert(sandbox.id);
+        publish_pool_update(self.pool_broadcaster.as_deref(), &sandbox);
+        info!(task_id = %task.id, sandbox_id = %sandbox.id, "Created fresh sandbox for task");
+
+        if let Err(e) = self.dispatch_task(task, &sandbox).await {
+            self.finish_sandbox(sandbox.id).await;
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    /// Hand a sandbox back after its task: single-use sandboxes are
+    /// destroyed, everything else returns to the pool
+    async fn finish_sandbox(&self, sandbox_id: Uuid) {
+        finish_sandbox(
+            &self.db_pool,
+            &self.daytona,
+            &self.single_use_sandboxes,
+            self.pool_broadcaster.as_deref(),
+            sandbox_id,
+            None,
+        )
+        .await;
+    }
+
+    /// Dispatch a task pinned to a specific sandbox
+    /// Waits (returns Ok(false)) while the sandbox is busy and fails the task
+    /// if the sandbox no longer exists or has been destroyed.
+    /// A sandbox already reserved for this task (e.g. by a reassign) counts as free.
+    async fn process_pinned_task(&self, task: &SwarmTask, pinned_id: Uuid) -> Result<bool> {
+        let sandbox = Sandbox::find_by_id(&self.db_pool, pinned_id).await?;
+
+        match sandbox {
+            Some(sb)
+                if sb.status == SandboxStatus::Idle
+                    || (sb.status == SandboxStatus::Busy && sb.current_task_id == Some(task.id)) =>
+            {
+                self.dispatch_task(task, &sb).await?;
+                Ok(true)
+            }
+            Some(sb) if sb.status == SandboxStatus::Busy => {
+                debug!(task_id = %task.id, sandbox_id = %pinned_id, "Pinned sandbox busy, waiting");
+                Ok(false)
+            }
+            Some(sb) if sb.status == SandboxStatus::Draining => {
+                let reason = format!("Pinned sandbox {} is draining", pinned_id);
+                self.fail_task(task.id, &reason).await?;
+                Ok(true)
+            }
+            _ => {
+                let reason = format!("Pinned sandbox {} was destroyed", pinned_id);
+                // fail_task also clears the processing flag
+                self.fail_task(task.id, &reason).await?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Build an executor from the current Claude and CLI probe settings
+    async fn task_executor(&self) -> Result<TaskExecutor> {
+        let config = SwarmConfig::get(&self.db_pool).await?;
+        let executor = TaskExecutor::new(
+            self.daytona.clone(),
+            self.pool_manager.clone(),
+            config.anthropic_api_key,
+            config.skills_path,
+        )
+        .with_claude_options(config.claude_model, config.claude_extra_args)
+        .with_cli_probe(config.cli_probe_enabled)
+        .with_git_options(config.git_auto_commit, config.git_auto_push, config.git_token)
+        .with_prompt_vars(config.prompt_vars);
+
+        Ok(match &self.log_broadcaster {
+            Some(broadcaster) => executor.with_log_broadcaster(broadcaster.clone()),
+            None => executor,
+        })
+    }
+
     /// Dispatch a task to a sandbox - update status and start execution
     async fn dispatch_task(&self, task: &SwarmTask, sandbox: &Sandbox) -> Result<()> {
         let task_id = task.id;
         let sandbox_id = sandbox.id;
         let daytona_id = sandbox.daytona_id.clone();
 
+        // Everything the execution needs is loaded before the task is marked
+        // running, so a failure here leaves it pending
+        let swarm = Swarm::find_by_id(&self.db_pool, task.swarm_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Swarm {} not found", task.swarm_id))?;
+        let secrets = SwarmSecret::resolve(&self.db_pool, swarm.id, &task.secrets).await?;
+        let role = AgentRole::from_tags(&task.tags);
+        let executor = self.task_executor().await?;
+        // The agent's output is streamed to chat only when the swarm posts full
+        // results; planning output is a JSON plan, not a reply worth showing
+        let stream_reply = !task.decompose
+            && SwarmConfig::get(&self.db_pool).await?.chat_result_mode == ChatResultMode::Full;
+        let executor = match &self.chat_broadcaster {
+            Some(broadcaster) if stream_reply => executor.with_chat_reply(
+                broadcaster.clone(),
+                StreamedReply::new(swarm.id, sandbox_id, Some(role.as_str().to_string())),
+            ),
+            _ => executor,
+        };
+
         // Note: Task is already marked as processing in process_swarm_triggers
         // via atomic check-and-insert to prevent race conditions
 
@@ -258,6 +875,11 @@ impl TriggerEngine {
             return Err(anyhow::anyhow!("Failed to assign task to sandbox: {}", e));
         }
 
+        if let Err(e) = Sandbox::set_last_role(&self.db_pool, sandbox_id, role.as_str()).await {
+            warn!(sandbox_id = %sandbox_id, error = %e, "Failed to record sandbox role");
+        }
+        self.tasks_dispatched_total.fetch_add(1, Ordering::Relaxed);
+
         info!(
             task_id = %task_id,
             sandbox_id = %sandbox_id,
@@ -267,57 +889,120 @@ impl TriggerEngine {
 
         // Spawn execution task
         let processing_tasks = self.processing_tasks.clone();
+        let execution_tokens = self.execution_tokens.clone();
+        let (run, cancel) = execution_tokens.write().await.register(task_id);
         let db_pool = self.db_pool.clone();
-        let _daytona = self.daytona.clone();
+        let daytona = self.daytona.clone();
+        let single_use_sandboxes = self.single_use_sandboxes.clone();
+        let pool_broadcaster = self.pool_broadcaster.clone();
+        let log_broadcaster = self.log_broadcaster.clone();
+        let chat_broadcaster = self.chat_broadcaster.clone();
+        let webhooks = self.webhooks.clone();
+        let wake = self.wake.clone();
+        let tasks_failed_total = self.tasks_failed_total.clone();
+        let max_retries = self.config.max_retries;
         let timeout_minutes = self.config.execution_timeout_minutes;
+        let task = task.clone();
 
         tokio::spawn(async move {
-            // TODO: Execute task via TaskExecutor
-            // For now, simulate execution with timeout
-            let execution_result = tokio::time::timeout(
-                Duration::from_secs(timeout_minutes as u64 * 60),
-                async {
-                    // Placeholder for actual execution
-                    tokio::time::sleep(Duration::from_secs(5)).await;
-                    Ok::<Option<String>, String>(Some("Task completed successfully".to_string()))
+            if task.decompose {
+                run_decomposition(
+                    &db_pool,
+                    &executor,
+                    &task,
+                    &daytona_id,
+                    timeout_minutes,
+                    &cancel,
+                    &tasks_failed_total,
+                    log_broadcaster.as_deref(),
+                    webhooks.as_ref(),
+                    &wake,
+                )
+                .await;
+            } else {
+                let execution = executor
+                    .execute(&swarm, &task, &daytona_id, &secrets, 1, max_retries, timeout_minutes, &cancel)
+                    .await;
+
+                // The streamed reply is stored as one chat message under the id its deltas used
+                if let (Some(reply), Some(broadcaster)) = (executor.into_chat_reply(), &chat_broadcaster)
+                    && !reply.text().is_empty()
+                    && let Err(e) = reply.finish(&db_pool, broadcaster).await
+                {
+                    warn!(task_id = %task_id, error = %e, "Failed to store streamed chat reply");
                 }
-            ).await;
-
-            // Handle execution result
-            match execution_result {
-                Ok(Ok(result)) => {
-                    // Task completed successfully
-                    if let Err(e) = SwarmTask::complete_task(&db_pool, task_id, result.as_deref()).await {
-                        error!(task_id = %task_id, error = %e, "Failed to mark task as completed");
+
+                // Handle execution result
+                match execution {
+                    Ok(result) if result.cancelled => {
+                        // Whoever cancelled the task has already set its status
+                        info!(task_id = %task_id, "Task execution stopped after cancellation");
                     }
-                    info!(task_id = %task_id, "Task completed successfully");
-                }
-                Ok(Err(error)) => {
-                    // Task failed
-                    if let Err(e) = SwarmTask::fail_task(&db_pool, task_id, &error).await {
-                        error!(task_id = %task_id, error = %e, "Failed to mark task as failed");
+                    Ok(result) => {
+                        record_execution(&db_pool, task_id, result.attempts, &result.output).await;
+                        for warning in &result.warnings {
+                            warn!(task_id = %task_id, warning = %warning, "Task finished with a warning");
+                            if let Err(e) = post_warning_message(&db_pool, &task, warning).await {
+                                warn!(task_id = %task_id, error = %e, "Failed to post warning message");
+                            }
+                        }
+
+                        // The status writes only apply while the task is still running
+                        // here, so a task reassigned or stopped meanwhile keeps its state
+                        if result.success {
+                            let output = Some(result.output.as_str()).filter(|o| !o.is_empty());
+                            match SwarmTask::complete_if_running_on(&db_pool, task_id, &daytona_id, output).await {
+                                Ok(true) => {
+                                    if let Err(e) = post_completion_message(&db_pool, task_id, output).await {
+                                        warn!(task_id = %task_id, error = %e, "Failed to post completion message");
+                                    }
+                                    notify_webhook(&db_pool, webhooks.as_ref(), task_id, WEBHOOK_TASK_COMPLETED).await;
+                                    if let Err(e) = wake_for_triggered_tasks(&db_pool, &wake, task_id).await {
+                                        warn!(task_id = %task_id, error = %e, "Failed to check triggered tasks");
+                                    }
+                                    info!(task_id = %task_id, attempts = result.attempts, "Task completed successfully");
+                                }
+                                Ok(false) => {
+                                    info!(task_id = %task_id, "Task left this sandbox before its run finished, discarding result");
+                                }
+                                Err(e) => {
+                                    error!(task_id = %task_id, error = %e, "Failed to mark task as completed");
+                                }
+                            }
+                        } else {
+                            let error = result.error.unwrap_or_else(|| "Unknown error".to_string());
+                            fail_run(&db_pool, &tasks_failed_total, webhooks.as_ref(), task_id, &daytona_id, &error).await;
+                            warn!(task_id = %task_id, attempts = result.attempts, error = %error, "Task failed");
+                        }
                     }
-                    warn!(task_id = %task_id, error = %error, "Task failed");
-                }
-                Err(_) => {
-                    // Task timed out
-                    let error = format!("Task timed out after {} minutes", timeout_minutes);
-                    if let Err(e) = SwarmTask::fail_task(&db_pool, task_id, &error).await {
-                        error!(task_id = %task_id, error = %e, "Failed to mark task as timed out");
+                    Err(e) => {
+                        // The executor only gives up with an error once its retries are spent
+                        record_execution(&db_pool, task_id, max_retries.max(1), "").await;
+                        let error = e.to_string();
+                        fail_run(&db_pool, &tasks_failed_total, webhooks.as_ref(), task_id, &daytona_id, &error).await;
+                        warn!(task_id = %task_id, error = %error, "Task execution failed");
                     }
-                    warn!(task_id = %task_id, "Task timed out");
                 }
             }
 
-            // Release sandbox
-            if let Err(e) = SwarmTask::release_sandbox(&db_pool, task_id).await {
+            // Release sandbox, unless the task was reassigned away from it
+            if let Err(e) = SwarmTask::release_sandbox_if_assigned(&db_pool, task_id, &daytona_id).await {
                 error!(task_id = %task_id, error = %e, "Failed to release sandbox from task");
             }
-            if let Err(e) = Sandbox::release_task(&db_pool, sandbox_id).await {
-                error!(sandbox_id = %sandbox_id, error = %e, "Failed to release sandbox");
-            }
+            finish_sandbox(
+                &db_pool,
+                &daytona,
+                &single_use_sandboxes,
+                pool_broadcaster.as_deref(),
+                sandbox_id,
+                Some(task_id),
+            )
+            .await;
 
-            // Clear processing flag
+            // Drop the token before clearing the processing flag: once the flag
+            // is gone the task can be dispatched again, and the new run's token
+            // must not be removed by this one
+            execution_tokens.write().await.finish(task_id, run);
             {
                 let mut processing = processing_tasks.write().await;
                 processing.remove(&task_id);
@@ -329,17 +1014,16 @@ impl TriggerEngine {
 
     /// Release sandbox associated with a task
     async fn release_task_sandbox(&self, task_id: Uuid) -> Result<()> {
-        // Release sandbox from task record
-        SwarmTask::release_sandbox(&self.db_pool, task_id)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to release sandbox: {}", e))?;
+        // Look the sandbox up before the task record forgets it
+        let task = SwarmTask::find_by_id(&self.db_pool, task_id).await?;
 
         // Find and release the sandbox
-        if let Some(task) = SwarmTask::find_by_id(&self.db_pool, task_id).await? {
-            if let Some(sandbox_id_str) = &task.sandbox_id {
-                if let Some(sandbox) = Sandbox::find_by_daytona_id(&self.db_pool, sandbox_id_str).await? {
-                    Sandbox::release_task(&self.db_pool, sandbox.id).await?;
-                }
+        if let Some(daytona_id) = task.as_ref().and_then(|t| t.sandbox_id.as_deref()) {
+            SwarmTask::release_sandbox_if_assigned(&self.db_pool, task_id, daytona_id)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to release sandbox: {}", e))?;
+            if let Some(sandbox) = Sandbox::find_by_daytona_id(&self.db_pool, daytona_id).await? {
+                self.finish_sandbox(sandbox.id).await;
             }
         }
 
@@ -359,6 +1043,14 @@ impl TriggerEngine {
             .await
             .map_err(|e| anyhow::anyhow!("Failed to complete task: {}", e))?;
 
+        if let Err(e) = post_completion_message(&self.db_pool, task_id, result).await {
+            warn!(task_id = %task_id, error = %e, "Failed to post completion message");
+        }
+        notify_webhook(&self.db_pool, self.webhooks.as_ref(), task_id, WEBHOOK_TASK_COMPLETED).await;
+        if let Err(e) = wake_for_triggered_tasks(&self.db_pool, &self.wake, task_id).await {
+            warn!(task_id = %task_id, error = %e, "Failed to check triggered tasks");
+        }
+
         // Release sandbox
         self.release_task_sandbox(task_id).await?;
 
@@ -372,6 +1064,8 @@ impl TriggerEngine {
         SwarmTask::fail_task(&self.db_pool, task_id, error)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to fail task: {}", e))?;
+        self.tasks_failed_total.fetch_add(1, Ordering::Relaxed);
+        notify_webhook(&self.db_pool, self.webhooks.as_ref(), task_id, WEBHOOK_TASK_FAILED).await;
 
         // Release sandbox
         self.release_task_sandbox(task_id).await?;
@@ -382,7 +1076,8 @@ impl TriggerEngine {
 
     /// Get pending tasks for a swarm from the database
     async fn get_pending_tasks(&self, swarm_id: Uuid) -> Result<Vec<SwarmTask>> {
-        let tasks = SwarmTask::find_pending_by_swarm_id(&self.db_pool, swarm_id)
+        let config = SwarmConfig::get(&self.db_pool).await?;
+        let tasks = SwarmTask::find_pending_by_swarm_id(&self.db_pool, swarm_id, config.priority_aging_minutes)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to fetch pending tasks: {}", e))?;
         Ok(tasks)
@@ -395,46 +1090,478 @@ impl TriggerEngine {
             .map_err(|e| anyhow::anyhow!("Failed to check dependencies: {}", e))
     }
 
-    /// Get current processing stats
-    pub async fn get_stats(&self) -> TriggerStats {
+    /// Get engine and task statistics
+    ///
+    /// Task counts cover active swarms, or every swarm when `include_inactive`
+    /// is set. Work estimates always cover active swarms only, since the engine
+    /// does not dispatch tasks of paused or stopped swarms.
+    pub async fn get_stats(&self, include_inactive: bool) -> TriggerStats {
         let processing = self.processing_tasks.read().await;
         let is_running = !*self.shutdown.read().await;
 
-        // Get task counts from all active swarms
-        let mut total_pending = 0;
-        let mut total_running = 0;
-        let mut total_completed = 0;
-        let mut total_failed = 0;
+        let mut stats = TriggerStats {
+            sampled_at: Utc::now(),
+            processing_count: processing.len(),
+            is_running,
+            include_inactive,
+            tasks_dispatched_total: self.tasks_dispatched_total.load(Ordering::Relaxed),
+            tasks_failed_total: self.tasks_failed_total.load(Ordering::Relaxed),
+            ..Default::default()
+        };
 
-        if let Ok(swarms) = Swarm::find_active(&self.db_pool).await {
+        let swarms = if include_inactive {
+            Swarm::find_all(&self.db_pool).await
+        } else {
+            Swarm::find_active(&self.db_pool).await
+        };
+
+        if let Ok(swarms) = swarms {
             for swarm in swarms {
-                if let Ok(counts) = SwarmTask::count_by_status(&self.db_pool, swarm.id).await {
-                    total_pending += counts.pending;
-                    total_running += counts.running;
-                    total_completed += counts.completed;
-                    total_failed += counts.failed;
+                let Ok(counts) = SwarmTask::count_by_status(&self.db_pool, swarm.id).await else {
+                    continue;
+                };
+                stats.swarms_included += 1;
+                stats.tasks_blocked += counts.blocked;
+                stats.tasks_pending += counts.pending;
+                stats.tasks_running += counts.running;
+                stats.tasks_completed += counts.completed;
+                stats.tasks_failed += counts.failed;
+                stats.tasks_cancelled += counts.cancelled;
+
+                if swarm.status == SwarmStatus::Active {
+                    stats.tasks_dispatchable += counts.pending;
+                    stats.remaining_estimate_minutes += counts.remaining_estimate_minutes;
                 }
             }
         }
 
-        TriggerStats {
-            processing_count: processing.len(),
-            is_running,
-            tasks_completed: total_completed,
-            tasks_failed: total_failed,
-            tasks_pending: total_pending,
-            tasks_running: total_running,
+        stats
+    }
+
+    /// Record the current stats of active swarms in the history
+    ///
+    /// Called once per loop pass; only the last `STATS_HISTORY_LEN` samples are kept.
+    pub async fn sample_stats(&self) {
+        let stats = self.get_stats(false).await;
+        let mut history = self.stats_history.write().await;
+        if history.len() == STATS_HISTORY_LEN {
+            history.pop_front();
         }
+        history.push_back(stats);
+    }
+
+    /// Recent stats samples, oldest first
+    pub async fn stats_history(&self) -> Vec<TriggerStats> {
+        self.stats_history.read().await.iter().cloned().collect()
+    }
+}
+
+/// Release a sandbox after its task, destroying it if it was single-use
+///
+/// A single-use sandbox that Daytona fails to delete is left draining so the
+/// drained-sandbox sweep retries the delete. With `held_by` set, a sandbox no
+/// longer running that task (it was reassigned away) is left alone.
+async fn finish_sandbox(
+    db_pool: &SqlitePool,
+    daytona: &DaytonaClient,
+    single_use_sandboxes: &RwLock<HashSet<Uuid>>,
+    broadcaster: Option<&PoolBroadcaster>,
+    sandbox_id: Uuid,
+    held_by: Option<Uuid>,
+) {
+    if !single_use_sandboxes.write().await.remove(&sandbox_id) {
+        let released = match held_by {
+            Some(task_id) => Sandbox::release_task_if_current(db_pool, sandbox_id, task_id)
+                .await
+                .map(|_| ()),
+            None => Sandbox::release_task(db_pool, sandbox_id).await,
+        };
+        if let Err(e) = released {
+            error!(sandbox_id = %sandbox_id, error = %e, "Failed to release sandbox");
+        }
+        return;
+    }
+
+    let result = async {
+        let Some(sandbox) = Sandbox::find_by_id(db_pool, sandbox_id).await? else {
+            return Ok(());
+        };
+        if let Some(task_id) = held_by
+            && sandbox.current_task_id != Some(task_id)
+        {
+            // Destroyed once whatever it runs now has finished
+            single_use_sandboxes.write().await.insert(sandbox_id);
+            return Ok(());
+        }
+
+        if let Err(e) = daytona.delete_sandbox(&sandbox.daytona_id).await {
+            warn!(sandbox_id = %sandbox_id, error = %e, "Failed to delete fresh sandbox, draining it");
+            Sandbox::mark_draining(db_pool, sandbox_id).await?;
+            Sandbox::release_task(db_pool, sandbox_id).await?;
+            return Ok(());
+        }
+
+        Sandbox::mark_destroyed(db_pool, sandbox_id).await?;
+        let destroyed = Sandbox {
+            status: SandboxStatus::Destroyed,
+            current_task_id: None,
+            ..sandbox
+        };
+        publish_pool_update(broadcaster, &destroyed);
+        info!(sandbox_id = %sandbox_id, "Fresh sandbox destroyed after task");
+        Ok::<(), anyhow::Error>(())
+    }
+    .await;
+
+    if let Err(e) = result {
+        error!(sandbox_id = %sandbox_id, error = %e, "Failed to destroy fresh sandbox");
     }
 }
 
+/// Run the planning pass of a task created with `decompose`
+///
+/// On success the task has become the coordinator of its new subtasks and the
+/// loop is woken to dispatch them. A planning failure fails the task.
+#[allow(clippy::too_many_arguments)]
+async fn run_decomposition(
+    db_pool: &SqlitePool,
+    executor: &TaskExecutor,
+    task: &SwarmTask,
+    daytona_id: &str,
+    timeout_minutes: i32,
+    cancel: &CancellationToken,
+    tasks_failed_total: &AtomicU64,
+    log_broadcaster: Option<&LogBroadcaster>,
+    webhooks: Option<&Arc<WebhookDeliverer>>,
+    wake: &Notify,
+) {
+    let planned = cancel
+        .run_until_cancelled(decompose_task(db_pool, executor, task, daytona_id, timeout_minutes))
+        .await;
+
+    let end = match planned {
+        None => {
+            info!(task_id = %task.id, "Planning run stopped after cancellation");
+            return;
+        }
+        Some(Ok(subtasks)) => {
+            info!(task_id = %task.id, subtasks = subtasks.len(), "Task decomposed");
+            wake.notify_one();
+            LogEnd::success().with_summary(format!("Planned {} subtasks", subtasks.len()))
+        }
+        Some(Err(e)) => {
+            let error = format!("Decomposition failed: {}", e);
+            fail_run(db_pool, tasks_failed_total, webhooks, task.id, daytona_id, &error).await;
+            warn!(task_id = %task.id, error = %error, "Task decomposition failed");
+            LogEnd::failure(1).with_summary(error)
+        }
+    };
+
+    if let Some(broadcaster) = log_broadcaster {
+        broadcaster.publish_log_end(task.id, end).await;
+    }
+}
+
+/// Fail a task at the end of its run, unless it left the sandbox meanwhile
+async fn fail_run(
+    db_pool: &SqlitePool,
+    tasks_failed_total: &AtomicU64,
+    webhooks: Option<&Arc<WebhookDeliverer>>,
+    task_id: Uuid,
+    daytona_id: &str,
+    error: &str,
+) {
+    match SwarmTask::fail_if_running_on(db_pool, task_id, daytona_id, error).await {
+        Ok(true) => {
+            tasks_failed_total.fetch_add(1, Ordering::Relaxed);
+            notify_webhook(db_pool, webhooks, task_id, WEBHOOK_TASK_FAILED).await;
+        }
+        Ok(false) => {
+            info!(task_id = %task_id, "Task left this sandbox before its run failed, discarding error");
+        }
+        Err(e) => {
+            error!(task_id = %task_id, error = %e, "Failed to mark task as failed");
+        }
+    }
+}
+
+/// Send a task event to the configured webhook, if there is one
+///
+/// Delivery runs in the background so retries never hold up the engine; an
+/// event that still fails is kept in the dead-letter log.
+async fn notify_webhook(
+    db_pool: &SqlitePool,
+    webhooks: Option<&Arc<WebhookDeliverer>>,
+    task_id: Uuid,
+    event: &'static str,
+) {
+    let Some(deliverer) = webhooks else {
+        return;
+    };
+    let url = match SwarmConfig::get(db_pool).await {
+        Ok(config) => config.webhook_url,
+        Err(e) => {
+            warn!(task_id = %task_id, error = %e, "Failed to load webhook config");
+            return;
+        }
+    };
+    let Some(url) = url else {
+        return;
+    };
+    let task = match SwarmTask::find_by_id(db_pool, task_id).await {
+        Ok(Some(task)) => task,
+        Ok(None) => return,
+        Err(e) => {
+            warn!(task_id = %task_id, error = %e, "Failed to load task for webhook");
+            return;
+        }
+    };
+
+    let payload = serde_json::json!({ "event": event, "task": task }).to_string();
+    let deliverer = deliverer.clone();
+    let db_pool = db_pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = deliverer.deliver_or_dead_letter(&db_pool, &url, event, &payload).await {
+            warn!(task_id = %task_id, event, error = %e, "Failed to record undelivered webhook");
+        }
+    });
+}
+
+/// Tell pool subscribers about a sandbox's current state
+fn publish_pool_update(broadcaster: Option<&PoolBroadcaster>, sandbox: &Sandbox) {
+    let Some(broadcaster) = broadcaster else {
+        return;
+    };
+
+    let mut update = PoolStatusUpdate::new(sandbox.id.to_string(), sandbox.status.to_string());
+    if let Some(task_id) = sandbox.current_task_id {
+        update = update.with_task(task_id.to_string());
+    }
+    if let Some(swarm_id) = sandbox.swarm_id {
+        update = update.with_swarm(swarm_id.to_string());
+    }
+    broadcaster.publish(update);
+}
+
+/// Longest result excerpt posted when the output has no SUMMARY section
+const CHAT_RESULT_EXCERPT_CHARS: usize = 500;
+
+/// Chat message announcing a completed task; None when the mode posts nothing
+fn completion_message(mode: ChatResultMode, title: &str, result: Option<&str>) -> Option<String> {
+    let result = result.map(str::trim).filter(|r| !r.is_empty());
+
+    let message = match (mode, result) {
+        (ChatResultMode::None, _) => return None,
+        (_, None) => format!("Task '{}' completed", title),
+        (ChatResultMode::Full, Some(result)) => format!("Task '{}' completed:\n\n{}", title, result),
+        (ChatResultMode::Summary, Some(result)) => {
+            let summary = extract_output_summary(result).unwrap_or_else(|| {
+                let mut excerpt: String = result.chars().take(CHAT_RESULT_EXCERPT_CHARS).collect();
+                if excerpt.len() < result.len() {
+                    excerpt.push('…');
+                }
+                excerpt
+            });
+            format!("Task '{}' completed: {}", title, summary)
+        }
+    };
+
+    Some(message)
+}
+
+/// Wake the engine if completing `task_id` made a task chained after it ready
+///
+/// A task chained after several others is woken for each one that
+/// completes; it is still dispatched only once, as dispatching moves it out of
+/// pending.
+async fn wake_for_triggered_tasks(db_pool: &SqlitePool, wake: &Notify, task_id: Uuid) -> Result<()> {
+    let mut ready = 0;
+    for task in SwarmTask::find_triggered_by(db_pool, task_id).await? {
+        if task.status == SwarmTaskStatus::Pending && SwarmTask::are_dependencies_complete(db_pool, &task).await? {
+            debug!(task_id = %task.id, triggered_by = %task_id, "Chained task ready");
+            ready += 1;
+        }
+    }
+
+    if ready > 0 {
+        info!(task_id = %task_id, ready, "Chained tasks ready, waking trigger engine");
+        wake.notify_one();
+    }
+    Ok(())
+}
+
+/// Persist a finished run's attempt count and output log
+///
+/// Failures are logged; they never change the task's outcome.
+async fn record_execution(db_pool: &SqlitePool, task_id: Uuid, attempts: i32, output: &str) {
+    if let Err(e) = SwarmTask::set_attempts(db_pool, task_id, attempts).await {
+        warn!(task_id = %task_id, error = %e, "Failed to record task attempts");
+    }
+    if !output.is_empty()
+        && let Err(e) = save_task_log(db_pool, task_id, attempts, output).await
+    {
+        warn!(task_id = %task_id, error = %e, "Failed to store task log");
+    }
+}
+
+/// Post a task's completion to its swarm chat according to `chat_result_mode`
+///
+/// The full result always stays on the task.
+async fn post_completion_message(db_pool: &SqlitePool, task_id: Uuid, result: Option<&str>) -> Result<()> {
+    let config = SwarmConfig::get(db_pool).await?;
+    let Some(task) = SwarmTask::find_by_id(db_pool, task_id).await? else {
+        return Ok(());
+    };
+    let Some(message) = completion_message(config.chat_result_mode, &task.title, result) else {
+        return Ok(());
+    };
+
+    ChatService::new()
+        .post_system_message(db_pool, task.swarm_id, message, Some(MessageMetadata::new().with_task(task_id)))
+        .await?;
+    Ok(())
+}
+
+/// Post a problem that did not fail the task to the swarm chat
+async fn post_warning_message(db_pool: &SqlitePool, task: &SwarmTask, warning: &str) -> Result<()> {
+    let message = format!("Task '{}' finished with a warning: {}", task.title, warning);
+    let metadata = MessageMetadata::new()
+        .with_task(task.id)
+        .with_error(warning.to_string());
+    ChatService::new()
+        .post_system_message(db_pool, task.swarm_id, message, Some(metadata))
+        .await?;
+    Ok(())
+}
+
 /// Statistics for the trigger engine
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, TS)]
 pub struct TriggerStats {
+    #[ts(type = "Date")]
+    pub sampled_at: DateTime<Utc>,
     pub processing_count: usize,
     pub is_running: bool,
-    pub tasks_completed: usize,
-    pub tasks_failed: usize,
+    /// Whether paused and stopped swarms are included in the task counts
+    pub include_inactive: bool,
+    /// Number of swarms whose tasks are summed below
+    pub swarms_included: usize,
+    pub tasks_blocked: usize,
     pub tasks_pending: usize,
     pub tasks_running: usize,
+    pub tasks_completed: usize,
+    pub tasks_failed: usize,
+    pub tasks_cancelled: usize,
+    /// Pending tasks in active swarms, i.e. work the engine will dispatch
+    pub tasks_dispatchable: usize,
+    /// Estimated minutes of pending and running work across active swarms
+    pub remaining_estimate_minutes: i64,
+    /// Tasks dispatched since the engine started
+    pub tasks_dispatched_total: u64,
+    /// Tasks failed since the engine started
+    pub tasks_failed_total: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completion_message_modes() {
+        let output = "Did things\nSUMMARY: Fixed the login bug\nFILES: src/auth.rs";
+
+        assert_eq!(
+            completion_message(ChatResultMode::Summary, "Login", Some(output)).as_deref(),
+            Some("Task 'Login' completed: Fixed the login bug")
+        );
+        assert_eq!(
+            completion_message(ChatResultMode::Full, "Login", Some(output)).unwrap(),
+            format!("Task 'Login' completed:\n\n{}", output)
+        );
+        assert_eq!(completion_message(ChatResultMode::None, "Login", Some(output)), None);
+        assert_eq!(
+            completion_message(ChatResultMode::Summary, "Login", None).as_deref(),
+            Some("Task 'Login' completed")
+        );
+
+        // Unstructured output is truncated rather than dumped
+        let long = "x".repeat(CHAT_RESULT_EXCERPT_CHARS + 10);
+        let message = completion_message(ChatResultMode::Summary, "Long", Some(&long)).unwrap();
+        assert!(message.ends_with('…'));
+        assert_eq!(message.chars().filter(|c| *c == 'x').count(), CHAT_RESULT_EXCERPT_CHARS);
+    }
+
+    #[test]
+    fn test_dispatch_pauses_after_rate_limit() {
+        let mut limiter = DispatchRateLimiter::default();
+        let swarm_id = Uuid::new_v4();
+        let start = Instant::now();
+
+        for i in 0..3 {
+            let now = start + Duration::from_secs(i);
+            assert!(limiter.allows(swarm_id, 3, now));
+            limiter.record(swarm_id, 3, now);
+        }
+
+        // Limit reached for the current window
+        assert!(!limiter.allows(swarm_id, 3, start + Duration::from_secs(30)));
+        // Other swarms are unaffected
+        assert!(limiter.allows(Uuid::new_v4(), 3, start + Duration::from_secs(30)));
+
+        // The first dispatch slides out of the window after a minute
+        assert!(limiter.allows(swarm_id, 3, start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_execution_tokens_cancel_registered_task() {
+        let mut tokens = ExecutionTokens::default();
+        let task_id = Uuid::new_v4();
+
+        let (_, token) = tokens.register(task_id);
+        assert_eq!(tokens.task_ids(), vec![task_id]);
+        assert!(!token.is_cancelled());
+
+        assert!(tokens.cancel(task_id));
+        assert!(token.is_cancelled());
+        // Already cancelled and untracked
+        assert!(!tokens.cancel(task_id));
+        assert!(tokens.task_ids().is_empty());
+    }
+
+    #[test]
+    fn test_execution_tokens_finish_untracks_task() {
+        let mut tokens = ExecutionTokens::default();
+        let task_id = Uuid::new_v4();
+
+        let (run, token) = tokens.register(task_id);
+        tokens.finish(task_id, run);
+        assert!(!tokens.cancel(task_id));
+        assert!(!token.is_cancelled());
+    }
+
+    #[test]
+    fn test_execution_tokens_finish_keeps_newer_run() {
+        let mut tokens = ExecutionTokens::default();
+        let task_id = Uuid::new_v4();
+
+        // The task is dispatched again before its first run ends
+        let (old_run, _) = tokens.register(task_id);
+        let (_, token) = tokens.register(task_id);
+        tokens.finish(task_id, old_run);
+
+        assert!(tokens.cancel(task_id));
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn test_dispatch_rate_zero_is_unlimited() {
+        let mut limiter = DispatchRateLimiter::default();
+        let swarm_id = Uuid::new_v4();
+        let now = Instant::now();
+
+        for _ in 0..100 {
+            assert!(limiter.allows(swarm_id, 0, now));
+            limiter.record(swarm_id, 0, now);
+        }
+        assert!(limiter.windows.is_empty());
+    }
 }
@@ -0,0 +1,239 @@
+//! Webhook Delivery
+//!
+//! POSTs event payloads to webhook endpoints, retrying timeouts, network
+//! errors and 5xx responses with exponential backoff. Events that still fail
+//! are kept in `webhook_dead_letters` so they can be inspected and redelivered.
+
+use std::time::Duration;
+
+use db::models::webhook_dead_letter::{CreateWebhookDeadLetter, WebhookDeadLetter};
+use reqwest::Client;
+use sqlx::SqlitePool;
+use thiserror::Error;
+use tracing::{debug, warn};
+
+/// Header carrying the event name
+pub const WEBHOOK_EVENT_HEADER: &str = "X-Swarm-Event";
+
+/// Sent by the trigger engine when a task completes
+pub const WEBHOOK_TASK_COMPLETED: &str = "task.completed";
+/// Sent by the trigger engine when a task fails
+pub const WEBHOOK_TASK_FAILED: &str = "task.failed";
+
+/// Retry policy for webhook delivery
+#[derive(Debug, Clone)]
+pub struct WebhookRetryConfig {
+    /// Attempts per delivery, including the first
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub backoff_multiplier: f64,
+    /// Per-request timeout
+    pub timeout_ms: u64,
+}
+
+impl Default for WebhookRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay_ms: 1000,
+            backoff_multiplier: 2.0,
+            timeout_ms: 10_000,
+        }
+    }
+}
+
+impl WebhookRetryConfig {
+    /// Wait before the attempt after `attempt` (1-based)
+    fn delay_after(&self, attempt: u32) -> Duration {
+        let delay = self.base_delay_ms as f64 * self.backoff_multiplier.powi(attempt as i32 - 1);
+        Duration::from_millis(delay as u64)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("network error: {0}")]
+    Transport(String),
+
+    #[error("request timed out")]
+    Timeout,
+
+    #[error("HTTP {status}: {body}")]
+    Http { status: u16, body: String },
+}
+
+impl WebhookError {
+    /// 4xx responses mean the endpoint rejected the event; sending it again won't help
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            WebhookError::Transport(_) | WebhookError::Timeout => true,
+            WebhookError::Http { status, .. } => *status >= 500,
+        }
+    }
+}
+
+/// Outcome of one delivery, after any retries
+#[derive(Debug)]
+pub struct WebhookDelivery {
+    pub attempts: u32,
+    /// Error of the last attempt; None if the event was delivered
+    pub error: Option<WebhookError>,
+}
+
+impl WebhookDelivery {
+    pub fn delivered(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Sends webhook events with bounded retries
+pub struct WebhookDeliverer {
+    http: Client,
+    retry: WebhookRetryConfig,
+}
+
+impl WebhookDeliverer {
+    pub fn new(retry: WebhookRetryConfig) -> Result<Self, WebhookError> {
+        let http = Client::builder()
+            .timeout(Duration::from_millis(retry.timeout_ms))
+            .build()
+            .map_err(|e| WebhookError::Transport(e.to_string()))?;
+
+        Ok(Self { http, retry })
+    }
+
+    /// POST `payload` (a JSON document) to `url`, retrying transient failures
+    pub async fn deliver(&self, url: &str, event: &str, payload: &str) -> WebhookDelivery {
+        let mut attempt = 1;
+        loop {
+            let error = match self.send(url, event, payload).await {
+                Ok(()) => {
+                    debug!(url = %url, event = %event, attempt, "Webhook delivered");
+                    return WebhookDelivery { attempts: attempt, error: None };
+                }
+                Err(e) => e,
+            };
+
+            if !error.is_retryable() || attempt >= self.retry.max_attempts {
+                warn!(url = %url, event = %event, attempts = attempt, error = %error, "Webhook delivery failed");
+                return WebhookDelivery { attempts: attempt, error: Some(error) };
+            }
+
+            let delay = self.retry.delay_after(attempt);
+            debug!(url = %url, event = %event, attempt, delay_ms = delay.as_millis() as u64, "Retrying webhook");
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// POST `payload` to `url` once, without retrying
+    pub async fn deliver_once(&self, url: &str, event: &str, payload: &str) -> WebhookDelivery {
+        let error = self.send(url, event, payload).await.err();
+        match &error {
+            None => debug!(url = %url, event = %event, "Webhook delivered"),
+            Some(e) => warn!(url = %url, event = %event, error = %e, "Webhook delivery failed"),
+        }
+        WebhookDelivery { attempts: 1, error }
+    }
+
+    /// Deliver an event, recording it as a dead letter if every attempt fails
+    ///
+    /// Returns the dead letter, or None if the event was delivered.
+    pub async fn deliver_or_dead_letter(
+        &self,
+        pool: &SqlitePool,
+        url: &str,
+        event: &str,
+        payload: &str,
+    ) -> Result<Option<WebhookDeadLetter>, sqlx::Error> {
+        let delivery = self.deliver(url, event, payload).await;
+        let Some(error) = delivery.error else {
+            return Ok(None);
+        };
+
+        let letter = WebhookDeadLetter::create(
+            pool,
+            &CreateWebhookDeadLetter {
+                url: url.to_string(),
+                event: event.to_string(),
+                payload: payload.to_string(),
+                attempts: delivery.attempts as i32,
+                last_error: error.to_string(),
+            },
+        )
+        .await?;
+
+        Ok(Some(letter))
+    }
+
+    /// Try a dead letter again with a single attempt; it is removed once delivered
+    ///
+    /// Returns the updated dead letter, or None if the event was delivered.
+    pub async fn redeliver(
+        &self,
+        pool: &SqlitePool,
+        letter: &WebhookDeadLetter,
+    ) -> Result<Option<WebhookDeadLetter>, sqlx::Error> {
+        let delivery = self.deliver_once(&letter.url, &letter.event, &letter.payload).await;
+        match delivery.error {
+            None => {
+                WebhookDeadLetter::delete(pool, letter.id).await?;
+                Ok(None)
+            }
+            Some(error) => {
+                WebhookDeadLetter::record_failure(pool, letter.id, delivery.attempts as i32, &error.to_string())
+                    .await
+            }
+        }
+    }
+
+    async fn send(&self, url: &str, event: &str, payload: &str) -> Result<(), WebhookError> {
+        let response = self
+            .http
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_EVENT_HEADER, event)
+            .body(payload.to_string())
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_timeout() {
+                    WebhookError::Timeout
+                } else {
+                    WebhookError::Transport(e.to_string())
+                }
+            })?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        Err(WebhookError::Http {
+            status: status.as_u16(),
+            body: body.chars().take(500).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        let retry = WebhookRetryConfig::default();
+        assert_eq!(retry.delay_after(1), Duration::from_millis(1000));
+        assert_eq!(retry.delay_after(2), Duration::from_millis(2000));
+        assert_eq!(retry.delay_after(3), Duration::from_millis(4000));
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        assert!(WebhookError::Timeout.is_retryable());
+        assert!(WebhookError::Transport("refused".to_string()).is_retryable());
+        assert!(WebhookError::Http { status: 503, body: String::new() }.is_retryable());
+        assert!(!WebhookError::Http { status: 404, body: String::new() }.is_retryable());
+    }
+}
@@ -1,12 +1,70 @@
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
+/// Machine-readable reason for a failed request, sent next to the message
+///
+/// Errors without a specific code get the generic one for their HTTP status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    // Generic, by HTTP status
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Conflict,
+    Gone,
+    PayloadTooLarge,
+    Timeout,
+    ServiceUnavailable,
+    UpstreamError,
+    InternalError,
+
+    InvalidRequestBody,
+
+    // Missing resources
+    SwarmNotFound,
+    TaskNotFound,
+    SandboxNotFound,
+    SkillNotFound,
+    SkillsDirectoryNotFound,
+    MessageNotFound,
+    SecretNotFound,
+    DeadLetterNotFound,
+
+    // Field validation
+    ValidationNameTooLong,
+    ValidationTitleEmpty,
+    ValidationTitleTooLong,
+    ValidationDescriptionTooLong,
+    ValidationTooManyTags,
+    ValidationTagTooLong,
+    ValidationTooManyDependencies,
+    ValidationInvalidSkillName,
+    ValidationMessageTooLong,
+
+    // State
+    DependencyCycle,
+    InvalidSwarmState,
+    InvalidTaskState,
+    InvalidSandboxState,
+    SandboxNotInSwarm,
+    PoolAtCapacity,
+    TaskLimitReached,
+    SkillAlreadyExists,
+    DaytonaNotConfigured,
+    DecompositionDisabled,
+    TriggerEngineNotRunning,
+}
+
 #[derive(Debug, Serialize, Deserialize, TS)]
 pub struct ApiResponse<T, E = T> {
     success: bool,
     data: Option<T>,
     error_data: Option<E>,
     message: Option<String>,
+    #[serde(default)]
+    code: Option<ErrorCode>,
 }
 
 impl<T, E> ApiResponse<T, E> {
@@ -17,6 +75,7 @@ impl<T, E> ApiResponse<T, E> {
             data: Some(data),
             message: None,
             error_data: None,
+            code: None,
         }
     }
 
@@ -27,6 +86,18 @@ impl<T, E> ApiResponse<T, E> {
             data: None,
             message: Some(message.to_string()),
             error_data: None,
+            code: None,
+        }
+    }
+
+    /// Creates an error response, with `message`, a machine-readable `code` and no data.
+    pub fn error_with_code(message: &str, code: ErrorCode) -> Self {
+        ApiResponse {
+            success: false,
+            data: None,
+            message: Some(message.to_string()),
+            error_data: None,
+            code: Some(code),
         }
     }
     /// Creates an error response, with no `data`, no `message`, but with arbitrary `error_data`.
@@ -36,6 +107,7 @@ impl<T, E> ApiResponse<T, E> {
             data: None,
             error_data: Some(data),
             message: None,
+            code: None,
         }
     }
 
@@ -53,4 +125,9 @@ impl<T, E> ApiResponse<T, E> {
     pub fn message(&self) -> Option<&str> {
         self.message.as_deref()
     }
+
+    /// Returns the error code if present.
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.code
+    }
 }
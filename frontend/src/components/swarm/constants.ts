@@ -5,6 +5,7 @@ import {
   Clock,
   Loader2,
   CheckCircle2,
+  Lock,
 } from 'lucide-react';
 import type { LucideIcon } from 'lucide-react';
 
@@ -37,7 +38,7 @@ export const SWARM_STATUS_CONFIG: Record<string, SwarmStatusConfigItem> = {
   },
 };
 
-// Task status configuration (pending, running, completed, failed, cancelled)
+// Task status configuration (blocked, pending, running, completed, failed, cancelled)
 export interface TaskStatusConfigItem {
   icon: LucideIcon;
   label: string;
@@ -48,6 +49,13 @@ export interface TaskStatusConfigItem {
 }
 
 export const TASK_STATUS_CONFIG: Record<string, TaskStatusConfigItem> = {
+  blocked: {
+    icon: Lock,
+    label: 'Awaiting approval',
+    className: 'text-amber-500',
+    bgClassName: 'bg-amber-500/10',
+    dotClassName: 'bg-amber-500',
+  },
   pending: {
     icon: Clock,
     label: 'Pending',
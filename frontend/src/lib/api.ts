@@ -3,6 +3,7 @@
 import {
   ApprovalStatus,
   ApiResponse,
+  ErrorCode,
   Config,
   CreateFollowUpAttempt,
   EditorType,
@@ -99,12 +100,15 @@ import {
   SwarmConfigWithMaskedSecrets,
   UpdateSwarmConfig,
   SwarmChat,
+  ChatPage,
   SenderType,
   Sandbox,
+  SandboxLive,
   PoolStatus,
   SwarmTask,
   CreateSwarmTask,
   UpdateSwarmTask,
+  TaskStats,
 } from 'shared/types';
 import type { WorkspaceWithSession } from '@/types/attempt';
 import { createWorkspaceWithSession } from '@/types/attempt';
@@ -117,7 +121,9 @@ export class ApiError<E = unknown> extends Error {
     message: string,
     public statusCode?: number,
     public response?: Response,
-    error_data?: E
+    error_data?: E,
+    // Machine-readable reason, e.g. 'TASK_NOT_FOUND'
+    public code?: ErrorCode
   ) {
     super(message);
     this.name = 'ApiError';
@@ -186,12 +192,14 @@ export const handleApiResponse = async <T, E = T>(
 ): Promise<T> => {
   if (!response.ok) {
     let errorMessage = `Request failed with status ${response.status}`;
+    let errorCode: ErrorCode | undefined;
 
     try {
       const errorData = await response.json();
       if (errorData.message) {
         errorMessage = errorData.message;
       }
+      errorCode = errorData.code ?? undefined;
     } catch {
       // Fallback to status text if JSON parsing fails
       errorMessage = response.statusText || errorMessage;
@@ -199,12 +207,19 @@ export const handleApiResponse = async <T, E = T>(
 
     console.error('[API Error]', {
       message: errorMessage,
+      code: errorCode,
       status: response.status,
       response,
       endpoint: response.url,
       timestamp: new Date().toISOString(),
     });
-    throw new ApiError<E>(errorMessage, response.status, response);
+    throw new ApiError<E>(
+      errorMessage,
+      response.status,
+      response,
+      undefined,
+      errorCode
+    );
   }
 
   if (response.status === 204) {
@@ -229,7 +244,8 @@ export const handleApiResponse = async <T, E = T>(
         result.message || 'API request failed',
         response.status,
         response,
-        result.error_data
+        result.error_data,
+        result.code ?? undefined
       );
     }
 
@@ -243,7 +259,9 @@ export const handleApiResponse = async <T, E = T>(
     throw new ApiError<E>(
       result.message || 'API request failed',
       response.status,
-      response
+      response,
+      undefined,
+      result.code ?? undefined
     );
   }
 
@@ -1374,8 +1392,9 @@ export const queueApi = {
 // Swarm API
 export const swarmApi = {
   // Swarm CRUD
-  list: async (): Promise<Swarm[]> => {
-    const response = await makeRequest('/api/swarms');
+  list: async (includeArchived?: boolean): Promise<Swarm[]> => {
+    const query = includeArchived ? '?include_archived=true' : '';
+    const response = await makeRequest(`/api/swarms${query}`);
     return handleApiResponse<Swarm[]>(response);
   },
 
@@ -1422,10 +1441,42 @@ export const swarmApi = {
     return handleApiResponse<Swarm>(response);
   },
 
+  archive: async (id: string): Promise<Swarm> => {
+    const response = await makeRequest(`/api/swarms/${id}/archive`, {
+      method: 'POST',
+    });
+    return handleApiResponse<Swarm>(response);
+  },
+
+  unarchive: async (id: string): Promise<Swarm> => {
+    const response = await makeRequest(`/api/swarms/${id}/unarchive`, {
+      method: 'POST',
+    });
+    return handleApiResponse<Swarm>(response);
+  },
+
   // Chat
-  getMessages: async (swarmId: string, limit?: number): Promise<SwarmChat[]> => {
-    const params = limit ? `?limit=${limit}` : '';
-    const response = await makeRequest(`/api/swarms/${swarmId}/chat${params}`);
+  getMessages: async (
+    swarmId: string,
+    limit?: number,
+    before?: string,
+    since?: string,
+    after?: string
+  ): Promise<ChatPage> => {
+    const params = new URLSearchParams();
+    if (limit) params.set('limit', String(limit));
+    if (before) params.set('before', before);
+    if (since) params.set('since', since);
+    if (after) params.set('after', after);
+    const query = params.toString() ? `?${params}` : '';
+    const response = await makeRequest(`/api/swarms/${swarmId}/chat${query}`);
+    return handleApiResponse<ChatPage>(response);
+  },
+
+  searchMessages: async (swarmId: string, q: string, limit?: number): Promise<SwarmChat[]> => {
+    const params = new URLSearchParams({ q });
+    if (limit) params.set('limit', String(limit));
+    const response = await makeRequest(`/api/swarms/${swarmId}/chat/search?${params}`);
     return handleApiResponse<SwarmChat[]>(response);
   },
 
@@ -1446,6 +1497,11 @@ export const swarmApi = {
     return handleApiResponse<SwarmTask[]>(response);
   },
 
+  getTaskStats: async (swarmId: string): Promise<TaskStats> => {
+    const response = await makeRequest(`/api/swarms/${swarmId}/tasks/stats`);
+    return handleApiResponse<TaskStats>(response);
+  },
+
   createTask: async (swarmId: string, data: CreateSwarmTask): Promise<SwarmTask> => {
     const response = await makeRequest(`/api/swarms/${swarmId}/tasks`, {
       method: 'POST',
@@ -1481,6 +1537,13 @@ export const swarmApi = {
     return handleApiResponse<SwarmTask>(response);
   },
 
+  cancelTask: async (swarmId: string, taskId: string): Promise<SwarmTask> => {
+    const response = await makeRequest(`/api/swarms/${swarmId}/tasks/${taskId}/cancel`, {
+      method: 'POST',
+    });
+    return handleApiResponse<SwarmTask>(response);
+  },
+
   // WebSocket URLs
   getChatStreamUrl: (swarmId: string): string => `/api/ws/swarms/${swarmId}/chat`,
 
@@ -1502,6 +1565,20 @@ export const poolApi = {
     return handleApiResponse<Sandbox>(response);
   },
 
+  getSandboxLive: async (sandboxId: string): Promise<SandboxLive> => {
+    const response = await makeRequest(`/api/pool/${sandboxId}/live`);
+    return handleApiResponse<SandboxLive>(response);
+  },
+
+  getInbox: async (sandboxId: string, since?: string): Promise<SwarmChat[]> => {
+    const params = since ? `?since=${encodeURIComponent(since)}` : '';
+    const response = await makeRequest(`/api/pool/${sandboxId}/inbox${params}`);
+    return handleApiResponse<SwarmChat[]>(response);
+  },
+
+  getFileDownloadUrl: (sandboxId: string, path: string): string =>
+    `/api/pool/${sandboxId}/files/download?path=${encodeURIComponent(path)}`,
+
   destroySandbox: async (sandboxId: string): Promise<void> => {
     const response = await makeRequest(`/api/pool/${sandboxId}`, {
       method: 'DELETE',
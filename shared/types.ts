@@ -122,7 +122,9 @@ additions: number | null, deletions: number | null, repoId: string | null, };
 
 export type DiffChangeKind = "added" | "deleted" | "modified" | "renamed" | "copied" | "permissionChange";
 
-export type ApiResponse<T, E = T> = { success: boolean, data: T | null, error_data: E | null, message: string | null, };
+export type ApiResponse<T, E = T> = { success: boolean, data: T | null, error_data: E | null, message: string | null, code: ErrorCode | null, };
+
+export type ErrorCode = "BAD_REQUEST" | "UNAUTHORIZED" | "FORBIDDEN" | "NOT_FOUND" | "CONFLICT" | "GONE" | "PAYLOAD_TOO_LARGE" | "TIMEOUT" | "SERVICE_UNAVAILABLE" | "UPSTREAM_ERROR" | "INTERNAL_ERROR" | "INVALID_REQUEST_BODY" | "SWARM_NOT_FOUND" | "TASK_NOT_FOUND" | "SANDBOX_NOT_FOUND" | "SKILL_NOT_FOUND" | "SKILLS_DIRECTORY_NOT_FOUND" | "MESSAGE_NOT_FOUND" | "SECRET_NOT_FOUND" | "DEAD_LETTER_NOT_FOUND" | "VALIDATION_NAME_TOO_LONG" | "VALIDATION_TITLE_EMPTY" | "VALIDATION_TITLE_TOO_LONG" | "VALIDATION_DESCRIPTION_TOO_LONG" | "VALIDATION_TOO_MANY_TAGS" | "VALIDATION_TAG_TOO_LONG" | "VALIDATION_TOO_MANY_DEPENDENCIES" | "VALIDATION_INVALID_SKILL_NAME" | "VALIDATION_MESSAGE_TOO_LONG" | "DEPENDENCY_CYCLE" | "INVALID_SWARM_STATE" | "INVALID_TASK_STATE" | "INVALID_SANDBOX_STATE" | "SANDBOX_NOT_IN_SWARM" | "POOL_AT_CAPACITY" | "TASK_LIMIT_REACHED" | "SKILL_ALREADY_EXISTS" | "DAYTONA_NOT_CONFIGURED" | "DECOMPOSITION_DISABLED" | "TRIGGER_ENGINE_NOT_RUNNING";
 
 export type LoginStatus = { "status": "loggedout" } | { "status": "loggedin", profile: ProfileResponse, };
 
@@ -575,53 +577,515 @@ export type PatchType = { "type": "NORMALIZED_ENTRY", "content": NormalizedEntry
 
 export type JsonValue = number | string | boolean | Array<JsonValue> | { [key in string]?: JsonValue } | null;
 
-export type Swarm = { id: string, name: string, description: string | null, status: SwarmStatus, project_id: string | null, created_at: Date, updated_at: Date, };
+export type Swarm = { id: string, name: string, description: string | null, status: SwarmStatus, project_id: string | null, 
+/**
+ * Shell script run in the workspace before each task; empty means none
+ */
+setup_script: string | null, 
+/**
+ * Maximum task dispatches per minute; 0 means unlimited
+ */
+dispatch_rate_per_minute: number, 
+/**
+ * Skills this swarm may discover and use; empty means all
+ */
+allowed_skills: Array<string>, 
+/**
+ * Run each task in a newly created sandbox and destroy it afterwards
+ * instead of reusing pooled sandboxes
+ */
+fresh_sandbox_per_task: boolean, 
+/**
+ * New tasks start blocked and are only dispatched once approved
+ */
+require_approval: boolean, 
+/**
+ * Maximum tasks running at once; 0 uses the trigger engine's default
+ */
+max_concurrent: number, 
+/**
+ * Set while the swarm is paused for inactivity; a new task resumes it
+ */
+auto_paused_at: Date | null, 
+/**
+ * Id from an imported definition; importing it again updates this swarm
+ */
+external_id: string | null, 
+/**
+ * Set while the swarm is archived: hidden from listings and never dispatched
+ */
+archived_at: Date | null, created_at: Date, updated_at: Date, 
+/**
+ * Bumped on every write; pass it back as `expected_version` to detect concurrent edits
+ */
+version: number, };
 
 export type SwarmStatus = "active" | "paused" | "stopped";
 
-export type CreateSwarm = { name: string, description: string | null, project_id: string | null, };
+export type CreateSwarm = { name: string, description: string | null, project_id: string | null, setup_script: string | null, dispatch_rate_per_minute: number | null, allowed_skills: Array<string> | null, fresh_sandbox_per_task: boolean | null, require_approval: boolean | null, max_concurrent: number | null, };
+
+export type UpdateSwarm = { name: string | null, description: string | null, status: SwarmStatus | null, setup_script: string | null, dispatch_rate_per_minute: number | null, allowed_skills: Array<string> | null, fresh_sandbox_per_task: boolean | null, require_approval: boolean | null, max_concurrent: number | null, 
+/**
+ * The `version` the client last saw; the update is refused if the swarm changed since
+ */
+expected_version: number | null, };
+
+export type SwarmConfig = { id: string, daytona_api_url: string | null, daytona_api_key: string | null, pool_max_sandboxes: number, pool_idle_timeout_minutes: number, 
+/**
+ * Idle sandboxes kept warm and spared from idle cleanup; 0 disables the warm pool
+ */
+pool_min_idle: number, pool_default_snapshot: string, 
+/**
+ * Tried in order when the default snapshot is not found
+ */
+pool_fallback_snapshots: Array<string>, sandbox_name_prefix: string, 
+/**
+ * Seconds between pool utilization samples; 0 disables sampling
+ */
+pool_history_interval_secs: number, 
+/**
+ * Hours pool utilization samples are kept
+ */
+pool_history_retention_hours: number, anthropic_api_key: string | null, claude_model: string | null, claude_extra_args: Array<string>, 
+/**
+ * Check the task's `CLI:` tools are installed before running Claude
+ */
+cli_probe_enabled: boolean, 
+/**
+ * Values for `{{name}}` placeholders in task prompts
+ */
+prompt_vars: { [key in string]?: string }, skills_path: string, 
+/**
+ * Seconds a skill scan or read may take; guards against hung network mounts
+ */
+skills_read_timeout_secs: number, git_auto_commit: boolean, git_auto_push: boolean, git_token: string | null, trigger_enabled: boolean, trigger_poll_interval_seconds: number, trigger_execution_timeout_minutes: number, trigger_max_retries: number, 
+/**
+ * Minutes of waiting per one-level priority bump; 0 disables aging
+ */
+priority_aging_minutes: number, 
+/**
+ * Minutes without tasks or chat before an idle swarm is paused; 0 disables
+ */
+auto_pause_after_minutes: number, 
+/**
+ * What the trigger engine posts to chat when a task completes
+ */
+chat_result_mode: ChatResultMode, max_tasks_per_swarm: number, decompose_enabled: boolean, decompose_max_subtasks: number, 
+/**
+ * Lag events within `ws_lag_window_secs` before a WebSocket client is
+ * disconnected; 0 never disconnects
+ */
+ws_lag_limit: number, ws_lag_window_secs: number, 
+/**
+ * Algorithm for stored task logs at or above the threshold
+ */
+log_compression: LogCompression, 
+/**
+ * Smaller task logs are stored uncompressed
+ */
+log_compression_threshold_bytes: number, 
+/**
+ * Collapse runs of identical lines in live log streams into one summary line
+ */
+log_dedup_enabled: boolean, 
+/**
+ * Endpoint POSTed task.completed and task.failed events; None sends none
+ */
+webhook_url: string | null, updated_at: Date, };
 
-export type UpdateSwarm = { name: string | null, description: string | null, status: SwarmStatus | null, };
+export type SwarmConfigWithMaskedSecrets = { has_daytona_api_key: boolean, has_anthropic_api_key: boolean, has_git_token: boolean, id: string, daytona_api_url: string | null, daytona_api_key: string | null, pool_max_sandboxes: number, pool_idle_timeout_minutes: number, 
+/**
+ * Idle sandboxes kept warm and spared from idle cleanup; 0 disables the warm pool
+ */
+pool_min_idle: number, pool_default_snapshot: string, 
+/**
+ * Tried in order when the default snapshot is not found
+ */
+pool_fallback_snapshots: Array<string>, sandbox_name_prefix: string, 
+/**
+ * Seconds between pool utilization samples; 0 disables sampling
+ */
+pool_history_interval_secs: number, 
+/**
+ * Hours pool utilization samples are kept
+ */
+pool_history_retention_hours: number, anthropic_api_key: string | null, claude_model: string | null, claude_extra_args: Array<string>, 
+/**
+ * Check the task's `CLI:` tools are installed before running Claude
+ */
+cli_probe_enabled: boolean, 
+/**
+ * Values for `{{name}}` placeholders in task prompts
+ */
+prompt_vars: { [key in string]?: string }, skills_path: string, 
+/**
+ * Seconds a skill scan or read may take; guards against hung network mounts
+ */
+skills_read_timeout_secs: number, git_auto_commit: boolean, git_auto_push: boolean, git_token: string | null, trigger_enabled: boolean, trigger_poll_interval_seconds: number, trigger_execution_timeout_minutes: number, trigger_max_retries: number, 
+/**
+ * Minutes of waiting per one-level priority bump; 0 disables aging
+ */
+priority_aging_minutes: number, 
+/**
+ * Minutes without tasks or chat before an idle swarm is paused; 0 disables
+ */
+auto_pause_after_minutes: number, 
+/**
+ * What the trigger engine posts to chat when a task completes
+ */
+chat_result_mode: ChatResultMode, max_tasks_per_swarm: number, decompose_enabled: boolean, decompose_max_subtasks: number, 
+/**
+ * Lag events within `ws_lag_window_secs` before a WebSocket client is
+ * disconnected; 0 never disconnects
+ */
+ws_lag_limit: number, ws_lag_window_secs: number, 
+/**
+ * Algorithm for stored task logs at or above the threshold
+ */
+log_compression: LogCompression, 
+/**
+ * Smaller task logs are stored uncompressed
+ */
+log_compression_threshold_bytes: number, 
+/**
+ * Collapse runs of identical lines in live log streams into one summary line
+ */
+log_dedup_enabled: boolean, 
+/**
+ * Endpoint POSTed task.completed and task.failed events; None sends none
+ */
+webhook_url: string | null, updated_at: Date, };
 
-export type SwarmConfig = { id: string, daytona_api_url: string | null, daytona_api_key: string | null, pool_max_sandboxes: number, pool_idle_timeout_minutes: number, pool_default_snapshot: string, anthropic_api_key: string | null, skills_path: string, git_auto_commit: boolean, git_auto_push: boolean, git_token: string | null, trigger_enabled: boolean, trigger_poll_interval_seconds: number, trigger_execution_timeout_minutes: number, trigger_max_retries: number, updated_at: Date, };
+export type UpdateSwarmConfig = { daytona_api_url: string | null, daytona_api_key: string | null, pool_max_sandboxes: number | null, pool_idle_timeout_minutes: number | null, pool_min_idle: number | null, pool_default_snapshot: string | null, pool_fallback_snapshots: Array<string> | null, sandbox_name_prefix: string | null, pool_history_interval_secs: number | null, pool_history_retention_hours: number | null, anthropic_api_key: string | null, 
+/**
+ * An empty string clears the model, so the CLI default is used
+ */
+claude_model: string | null, claude_extra_args: Array<string> | null, cli_probe_enabled: boolean | null, prompt_vars: { [key in string]?: string } | null, skills_path: string | null, skills_read_timeout_secs: number | null, git_auto_commit: boolean | null, git_auto_push: boolean | null, git_token: string | null, trigger_enabled: boolean | null, trigger_poll_interval_seconds: number | null, trigger_execution_timeout_minutes: number | null, trigger_max_retries: number | null, priority_aging_minutes: number | null, auto_pause_after_minutes: number | null, chat_result_mode: ChatResultMode | null, max_tasks_per_swarm: number | null, decompose_enabled: boolean | null, decompose_max_subtasks: number | null, ws_lag_limit: number | null, ws_lag_window_secs: number | null, log_compression: LogCompression | null, log_compression_threshold_bytes: number | null, log_dedup_enabled: boolean | null, 
+/**
+ * An empty string clears the webhook
+ */
+webhook_url: string | null, };
 
-export type SwarmConfigWithMaskedSecrets = { has_daytona_api_key: boolean, has_anthropic_api_key: boolean, has_git_token: boolean, id: string, daytona_api_url: string | null, daytona_api_key: string | null, pool_max_sandboxes: number, pool_idle_timeout_minutes: number, pool_default_snapshot: string, anthropic_api_key: string | null, skills_path: string, git_auto_commit: boolean, git_auto_push: boolean, git_token: string | null, trigger_enabled: boolean, trigger_poll_interval_seconds: number, trigger_execution_timeout_minutes: number, trigger_max_retries: number, updated_at: Date, };
+export type ChatResultMode = "full" | "summary" | "none";
 
-export type UpdateSwarmConfig = { daytona_api_url: string | null, daytona_api_key: string | null, pool_max_sandboxes: number | null, pool_idle_timeout_minutes: number | null, pool_default_snapshot: string | null, anthropic_api_key: string | null, skills_path: string | null, git_auto_commit: boolean | null, git_auto_push: boolean | null, git_token: string | null, trigger_enabled: boolean | null, trigger_poll_interval_seconds: number | null, trigger_execution_timeout_minutes: number | null, trigger_max_retries: number | null, };
+export type LogCompression = "none" | "gzip" | "zstd";
 
-export type SwarmChat = { id: string, swarm_id: string, sender_type: SenderType, sender_id: string | null, message: string, metadata: string | null, created_at: Date, };
+export type SwarmChat = { id: string, swarm_id: string, sender_type: SenderType, sender_id: string | null, message: string, 
+/**
+ * Raw metadata JSON as stored
+ */
+metadata: string | null, 
+/**
+ * `metadata` parsed into its known fields; None when absent or malformed
+ */
+parsed_metadata: MessageMetadata | null, created_at: Date, };
 
 export type SenderType = "system" | "user" | "sandbox";
 
 export type CreateSwarmChat = { swarm_id: string, sender_type: SenderType, sender_id: string | null, message: string, metadata: string | null, };
 
-export type Sandbox = { id: string, daytona_id: string, swarm_id: string | null, status: SandboxStatus, current_task_id: string | null, created_at: Date, last_used_at: Date | null, };
+export type MessageMetadata = { task_id: string | null, sandbox_id: string | null, error: string | null, typing: boolean | null, role: string | null, agent_response: boolean | null, credential_required: string | null, };
 
-export type SandboxStatus = "idle" | "busy" | "destroyed";
+export type Sandbox = { id: string, daytona_id: string, swarm_id: string | null, status: SandboxStatus, current_task_id: string | null, 
+/**
+ * Snapshot the sandbox was created from, if known
+ */
+snapshot: string | null, created_at: Date, last_used_at: Date | null, 
+/**
+ * Agent role of the last task dispatched to the sandbox
+ */
+last_role: string | null, };
+
+export type SandboxStatus = "idle" | "busy" | "draining" | "destroyed";
 
-export type CreateSandbox = { daytona_id: string, swarm_id: string | null, };
+export type CreateSandbox = { daytona_id: string, swarm_id: string | null, snapshot: string | null, };
+
+export type SandboxCommand = { id: string, daytona_id: string, command: string, 
+/**
+ * None when the command never reached the sandbox
+ */
+exit_code: number | null, duration_ms: bigint, created_at: Date, };
 
-export type SwarmTask = { id: string, swarm_id: string, title: string, description: string | null, status: SwarmTaskStatus, priority: TaskPriority, sandbox_id: string | null, depends_on: Array<string> | null, triggers_after: Array<string> | null, result: string | null, error: string | null, tags: Array<string>, started_at: Date | null, completed_at: Date | null, created_at: Date, updated_at: Date, };
+export type PoolStatusSample = { id: bigint, 
+/**
+ * Live (non-destroyed) sandboxes
+ */
+total: bigint, idle: bigint, busy: bigint, draining: bigint, recorded_at: Date, };
+
+export type SwarmTask = { id: string, swarm_id: string, title: string, description: string | null, status: SwarmTaskStatus, priority: TaskPriority, sandbox_id: string | null, depends_on: Array<string> | null, triggers_after: Array<string> | null, result: string | null, error: string | null, tags: Array<string>, started_at: Date | null, completed_at: Date | null, created_at: Date, updated_at: Date, 
+/**
+ * Sandbox this task must run in (waits for it instead of taking any idle one)
+ */
+pinned_sandbox_id: string | null, 
+/**
+ * Run a planning pass that splits this task into subtasks instead of executing it
+ */
+decompose: boolean, 
+/**
+ * Coordinator task this subtask was planned from
+ */
+parent_task_id: string | null, 
+/**
+ * Set once the task has been decomposed; it completes when its subtasks do
+ */
+is_coordinator: boolean, 
+/**
+ * Expected effort in minutes, for planning
+ */
+estimate_minutes: number | null, 
+/**
+ * Who approved the task for dispatch, from the approver's `X-User-Id`
+ */
+approved_by: string | null, approved_at: Date | null, 
+/**
+ * Names of swarm secrets injected into the task's environment
+ */
+secrets: Array<string>, 
+/**
+ * Sandbox path the output is also written to after a successful run
+ */
+write_result_to: string | null, 
+/**
+ * Id from an imported definition; importing it again updates this task
+ */
+external_id: string | null, 
+/**
+ * Execution attempts of the last run, including retries
+ */
+attempts: number, 
+/**
+ * Bumped on every write; pass it back as `expected_version` to detect concurrent edits
+ */
+version: number, };
 
-export type SwarmTaskStatus = "pending" | "running" | "completed" | "failed" | "cancelled";
+export type SwarmTaskStatus = "blocked" | "pending" | "running" | "completed" | "failed" | "cancelled";
 
 export type TaskPriority = "low" | "medium" | "high" | "urgent";
 
-export type CreateSwarmTask = { title: string, description: string | null, priority: TaskPriority | null, depends_on: Array<string> | null, tags: Array<string> | null, };
+export type CreateSwarmTask = { title: string, description: string | null, priority: TaskPriority | null, depends_on: Array<string> | null, tags: Array<string> | null, pinned_sandbox_id: string | null, decompose: boolean | null, estimate_minutes: number | null, secrets: Array<string> | null, write_result_to: string | null, };
 
-export type UpdateSwarmTask = { title: string | null, description: string | null, status: SwarmTaskStatus | null, priority: TaskPriority | null, sandbox_id: string | null, depends_on: Array<string> | null, triggers_after: Array<string> | null, result: string | null, error: string | null, tags: Array<string> | null, };
+export type UpdateSwarmTask = { title: string | null, description: string | null, status: SwarmTaskStatus | null, priority: TaskPriority | null, sandbox_id: string | null, depends_on: Array<string> | null, triggers_after: Array<string> | null, result: string | null, error: string | null, tags: Array<string> | null, estimate_minutes: number | null, secrets: Array<string> | null, write_result_to: string | null, 
+/**
+ * The `version` the client last saw; the update is refused if the task changed since
+ */
+expected_version: number | null, };
 
-export type TaskStatusCounts = { pending: number, running: number, completed: number, failed: number, cancelled: number, };
+export type TaskStatusCounts = { blocked: number, pending: number, running: number, completed: number, failed: number, cancelled: number, 
+/**
+ * Sum of estimates of pending and running tasks; unestimated tasks count as 0
+ */
+remaining_estimate_minutes: bigint, };
+
+export type SwarmTaskComment = { id: string, task_id: string, author: string, body: string, created_at: Date, };
+
+export type CreateSwarmTaskComment = { author: string, body: string, };
+
+export type SwarmSecret = { id: string, swarm_id: string, name: string, created_at: Date, updated_at: Date, };
+
+export type SetSwarmSecret = { value: string, };
+
+export type WebhookDeadLetter = { id: string, url: string, event: string, 
+/**
+ * JSON body that was sent
+ */
+payload: string, 
+/**
+ * Delivery attempts so far, including redeliveries
+ */
+attempts: number, last_error: string, created_at: Date, updated_at: Date, };
 
 export type PoolStatus = { config: PoolConfig, sandboxes: Array<SandboxInfo>, stats: PoolStats, };
 
-export type PoolConfig = { max_sandboxes: number, idle_timeout_minutes: number, default_snapshot: string, };
+export type PoolConfig = { max_sandboxes: number, idle_timeout_minutes: number, 
+/**
+ * Idle sandboxes kept warm; these are never reaped for being idle
+ */
+min_idle: number, default_snapshot: string, };
 
-export type PoolStats = { total: number, busy: number, idle: number, destroyed: number, };
+export type PoolStats = { total: number, busy: number, idle: number, draining: number, destroyed: number, };
 
 export type SandboxInfo = { id: string, daytona_id: string, status: SandboxStatus, swarm_id: string | null, task_id: string | null, idle_time_seconds: bigint, created_at: string, };
 
-export type MessageMetadata = { task_id: string | null, sandbox_id: string | null, error: string | null, typing: boolean | null, role: string | null, agent_response: boolean | null, credential_required: string | null, };
+export type LogSource = "executor" | "trigger" | "sandbox" | "setup" | "git" | "system";
+
+export type LogLevel = "debug" | "info" | "warn" | "error";
+
+export type LogEntry = { 
+/**
+ * Log content
+ */
+content: string, 
+/**
+ * ISO 8601 timestamp
+ */
+timestamp: string, 
+/**
+ * Log level (info, warn, error, debug)
+ */
+level: string | null, 
+/**
+ * Component the log came from
+ */
+source: LogSource | null, 
+/**
+ * Number of held-back repeats of the previous line this entry stands for
+ * (only set on compaction summaries)
+ */
+repeat_count: number | null, };
+
+export type LogEnd = { 
+/**
+ * Exit code of the task
+ */
+exit_code: number, 
+/**
+ * Final summary message
+ */
+summary: string | null, 
+/**
+ * ISO 8601 timestamp
+ */
+timestamp: string, };
+
+export type ChatBroadcastMessage = { 
+/**
+ * Message data
+ */
+data: ChatMessageData, };
+
+export type ChatMessageData = { id: string, swarm_id: string, sender_type: string, sender_id: string | null, message: string, metadata: string | null, created_at: string, };
+
+export type PoolStatusUpdate = { 
+/**
+ * Sandbox ID
+ */
+sandbox_id: string, 
+/**
+ * New status
+ */
+status: string, 
+/**
+ * Associated task ID (if any)
+ */
+task_id: string | null, 
+/**
+ * Swarm the sandbox belongs to (if any)
+ */
+swarm_id: string | null, 
+/**
+ * ISO 8601 timestamp
+ */
+timestamp: string, };
+
+export type TaskPlan = { prompt: string, workdir: string, skill: PlannedSkill | null, clis: Array<PlannedCli>, };
+
+export type PlannedSkill = { name: string, 
+/**
+ * The skill has a SKILL.md in the skills directory
+ */
+found: boolean, 
+/**
+ * The swarm's allowlist permits it; disallowed skills are left out of the prompt
+ */
+allowed: boolean, };
+
+export type PlannedCli = { name: string, found: boolean, };
+
+export type TaskLog = { task_id: string, attempt: number, content: string, 
+/**
+ * How the log is stored
+ */
+encoding: LogCompression, byte_size: bigint, 
+/**
+ * Size on disk after compression
+ */
+stored_size: bigint, created_at: Date, };
+
+export type TriggerStats = { sampled_at: Date, processing_count: number, is_running: boolean, 
+/**
+ * Whether paused and stopped swarms are included in the task counts
+ */
+include_inactive: boolean, 
+/**
+ * Number of swarms whose tasks are summed below
+ */
+swarms_included: number, tasks_blocked: number, tasks_pending: number, tasks_running: number, tasks_completed: number, tasks_failed: number, tasks_cancelled: number, 
+/**
+ * Pending tasks in active swarms, i.e. work the engine will dispatch
+ */
+tasks_dispatchable: number, 
+/**
+ * Estimated minutes of pending and running work across active swarms
+ */
+remaining_estimate_minutes: bigint, 
+/**
+ * Tasks dispatched since the engine started
+ */
+tasks_dispatched_total: bigint, 
+/**
+ * Tasks failed since the engine started
+ */
+tasks_failed_total: bigint, };
+
+export type WsMessage = { "type": "log" } & LogEntry | { "type": "log_end" } & LogEnd | { "type": "message" } & ChatBroadcastMessage | { "type": "chat_delta", message_id: string, delta: string, } | { "type": "pool_update" } & PoolStatusUpdate | { "type": "connected", message: string, 
+/**
+ * Minimum level of the log entries sent, on log streams with a level filter
+ */
+level?: LogLevel | null, } | { "type": "error", message: string, 
+/**
+ * Machine-readable cause, for errors a client can act on
+ */
+detail?: WsErrorDetail | null, } | { "type": "ping", timestamp: bigint, } | { "type": "pong", timestamp: bigint, } | { "type": "refresh" } | { "type": "set_level", level: LogLevel | null, } | { "type": "subscribe", task_id: string, } | { "type": "unsubscribe", task_id: string, } | { "type": "task", task_id: string, frame: WsMessage, };
+
+export type WsErrorDetail = { "kind": "lag", 
+/**
+ * Number of dropped frames, saturating at `u32::MAX`
+ */
+skipped: number, hint: string, };
+
+export type ChatPage = { messages: Array<SwarmChat>, 
+/**
+ * Id to pass as `before` for the next, older page; null on the last page
+ */
+next_cursor: string | null, };
+
+export type TriggerStatsResponse = { current: TriggerStats, 
+/**
+ * Samples taken once per trigger pass, oldest first
+ */
+history: Array<TriggerStats>, };
+
+export type SandboxLive = { 
+/**
+ * Daytona runtime state (e.g. started, stopped, error)
+ */
+state: string | null, 
+/**
+ * Daytona no longer knows the sandbox, though our record says it exists
+ */
+destroyed_remotely: boolean, 
+/**
+ * When Daytona was asked; None for sandboxes destroyed here, which are never looked up
+ */
+fetched_at: Date | null, id: string, daytona_id: string, swarm_id: string | null, status: SandboxStatus, current_task_id: string | null, 
+/**
+ * Snapshot the sandbox was created from, if known
+ */
+snapshot: string | null, created_at: Date, last_used_at: Date | null, 
+/**
+ * Agent role of the last task dispatched to the sandbox
+ */
+last_role: string | null, };
+
+export type TaskStats = { total: number, 
+/**
+ * Completed tasks as a percentage of all tasks; 0 for a swarm without tasks
+ */
+completion_percentage: number, blocked: number, pending: number, running: number, completed: number, failed: number, cancelled: number, 
+/**
+ * Sum of estimates of pending and running tasks; unestimated tasks count as 0
+ */
+remaining_estimate_minutes: bigint, };
 
 export const DEFAULT_PR_DESCRIPTION_PROMPT = `Update the PR that was just created with a better title and description.
 The PR number is #{pr_number} and the URL is {pr_url}.